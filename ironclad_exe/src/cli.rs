@@ -0,0 +1,169 @@
+//! Command line arguments for the `ironclad` binary
+
+use std::path::PathBuf;
+
+/// What the user asked the compiler to do
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CliCommand {
+  /// Parse, verify and type check the inputs, report errors
+  Check,
+  /// Parse the inputs and verify the AST, report errors
+  Parse,
+  /// Run the preprocessor only and print the resulting token stream
+  Preprocess,
+  /// Parse the inputs and print synthesized types for every function
+  Types,
+  /// Parse the inputs and print the cross-reference report for the project
+  Xref,
+}
+
+impl CliCommand {
+  /// Match a subcommand name
+  fn from_name(name: &str) -> Option<Self> {
+    match name {
+      "check" => Some(CliCommand::Check),
+      "parse" => Some(CliCommand::Parse),
+      "preprocess" => Some(CliCommand::Preprocess),
+      "types" => Some(CliCommand::Types),
+      "xref" => Some(CliCommand::Xref),
+      _ => None,
+    }
+  }
+}
+
+/// Parsed command line
+#[derive(Debug)]
+pub struct CliArgs {
+  /// The subcommand
+  pub command: CliCommand,
+  /// Project file given with `--project`. When neither files nor project are given, the
+  /// `ironclad.toml` in the current directory is used.
+  pub project: Option<PathBuf>,
+  /// Individual `.erl` files to process instead of the project inputs
+  pub files: Vec<PathBuf>,
+  /// Extra include directories from `-I DIR`
+  pub include_paths: Vec<String>,
+  /// Extra preprocessor defines from `-D NAME` or `-D NAME=VALUE`
+  pub defines: Vec<String>,
+  /// Override for the error limit per module from `--max-errors N`
+  pub max_errors: Option<usize>,
+  /// File for the xref report from `--output FILE`, instead of the standard output
+  pub output: Option<PathBuf>,
+  /// Print the project, the preprocessing progress and the preprocessed directives to the
+  /// standard error, from `--verbose`
+  pub verbose: bool,
+}
+
+/// Result of command line parsing: either run something or print the help text
+pub enum CliParseResult {
+  /// Run the compiler with these arguments
+  Run(CliArgs),
+  /// `--help` was requested
+  Help,
+  /// `--version` was requested
+  Version,
+}
+
+/// Default project file name, used when no files and no `--project` are given
+pub const DEFAULT_PROJECT_FILE: &str = "ironclad.toml";
+
+/// Help text printed for `--help` and on usage errors
+pub const USAGE: &str = "Usage: ironclad <COMMAND> [OPTIONS] [FILES.erl...]
+
+Commands:
+  check       Parse and type check the inputs, report errors
+  parse       Parse and verify the syntax tree, report errors
+  preprocess  Print the token stream after preprocessing
+  types       Print synthesized types for every function
+  xref        Report undefined calls and unused functions, types, records, imports
+
+Options:
+  -p, --project <FILE>  Project file (default: ironclad.toml, unless FILES are given)
+  -I <DIR>              Add an include search directory (also -IDIR)
+  -D <NAME[=VALUE]>     Define a preprocessor macro (also -DNAME=VALUE)
+  --max-errors <N>      Stop processing a module after N errors
+  -o, --output <FILE>   Write the xref report to FILE instead of the standard output
+  -v, --verbose         Print the preprocessing progress to the standard error
+  -h, --help            Print this help
+  -V, --version         Print version";
+
+impl CliArgs {
+  /// Parse command line arguments, not including the program name.
+  /// Returns a usage error message on failure.
+  pub fn parse<T>(args: T) -> Result<CliParseResult, String>
+  where
+    T: IntoIterator<Item = String>,
+  {
+    // Split `--option=value` into two arguments
+    let mut args = args
+      .into_iter()
+      .flat_map(|arg| match arg.split_once('=') {
+        Some((opt, value)) if opt.starts_with("--") => vec![opt.to_string(), value.to_string()],
+        _ => vec![arg],
+      })
+      .collect::<Vec<_>>()
+      .into_iter();
+    let mut command: Option<CliCommand> = None;
+    let mut result = CliArgs {
+      command: CliCommand::Check,
+      project: None,
+      files: Vec::new(),
+      include_paths: Vec::new(),
+      defines: Vec::new(),
+      max_errors: None,
+      output: None,
+      verbose: false,
+    };
+
+    while let Some(arg) = args.next() {
+      match arg.as_str() {
+        "-h" | "--help" => return Ok(CliParseResult::Help),
+        "-V" | "--version" => return Ok(CliParseResult::Version),
+        "-p" | "--project" => {
+          result.project = Some(PathBuf::from(Self::value_for(&arg, &mut args)?))
+        }
+        "-v" | "--verbose" => result.verbose = true,
+        "-o" | "--output" => result.output = Some(PathBuf::from(Self::value_for(&arg, &mut args)?)),
+        "-I" => result.include_paths.push(Self::value_for(&arg, &mut args)?),
+        "-D" => result.defines.push(Self::value_for(&arg, &mut args)?),
+        "--max-errors" => {
+          let value = Self::value_for(&arg, &mut args)?;
+          match value.parse::<usize>() {
+            Ok(n) if n > 0 => result.max_errors = Some(n),
+            _ => return Err(format!("--max-errors expects a positive number, got '{}'", value)),
+          }
+        }
+        _ if arg.starts_with("-I") => result.include_paths.push(arg[2..].to_string()),
+        _ if arg.starts_with("-D") => result.defines.push(arg[2..].to_string()),
+        _ if arg.starts_with('-') => return Err(format!("Unknown option '{}'", arg)),
+        _ => {
+          if command.is_none() && result.files.is_empty() {
+            if let Some(cmd) = CliCommand::from_name(&arg) {
+              command = Some(cmd);
+              continue;
+            }
+          }
+          if command.is_none() {
+            return Err(format!("Unknown command '{}'", arg));
+          }
+          result.files.push(PathBuf::from(arg));
+        }
+      }
+    }
+
+    match command {
+      Some(cmd) => {
+        result.command = cmd;
+        Ok(CliParseResult::Run(result))
+      }
+      None => Err("No command given".to_string()),
+    }
+  }
+
+  /// Take the value for an option which requires one
+  fn value_for(option: &str, args: &mut impl Iterator<Item = String>) -> Result<String, String> {
+    args
+      .next()
+      .ok_or_else(|| format!("Option '{}' requires a value", option))
+  }
+}
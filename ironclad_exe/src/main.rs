@@ -5,41 +5,131 @@
 extern crate function_name;
 extern crate libironclad_erlang;
 
+use std::panic;
+use std::path::Path;
 use std::process::exit;
 
+use cli::{CliArgs, CliCommand, CliParseResult, DEFAULT_PROJECT_FILE, USAGE};
 use libironclad_erlang::error::ic_error::IcResult;
-use libironclad_erlang::exit_codes::erl_fatal_icerror;
+use libironclad_erlang::exit_codes::{EXIT_ERRORS_FOUND, EXIT_FATAL, EXIT_SUCCESS};
+use libironclad_erlang::project::compiler_opts::CompilerOptsImpl;
 use libironclad_erlang::project::conf::ProjectConf;
+use libironclad_erlang::project::module::module_impl::ErlModule;
 use libironclad_erlang::project::project_impl::ErlProjectImpl;
 use libironclad_erlang::project::ErlProject;
 use stage::stage_parse::ErlParseStage;
+use stage::stage_preprocess::ErlPreprocessStage;
+use stage::stage_types::ErlTypesStage;
+use stage::stage_xref::ErlXrefStage;
 
+pub mod cli;
 pub mod stage;
 
-fn main_do() -> IcResult<()> {
-  let project: ErlProject = match ProjectConf::from_project_file("test_project/ironclad.toml") {
-    Ok(erlp) => ErlProjectImpl::from(erlp).into(),
-    Err(e) => return Err(Box::new(e)),
+/// Load the project file if given, or if no input files were given on the command line. Apply the
+/// command line compiler options on top of the project options.
+fn create_project(args: &CliArgs) -> IcResult<ErlProject> {
+  let mut project = match (&args.project, args.files.is_empty()) {
+    (Some(project_file), _) => load_project_file(project_file)?,
+    (None, true) => load_project_file(Path::new(DEFAULT_PROJECT_FILE))?,
+    (None, false) => ErlProjectImpl::default(),
   };
-  println!("{}", project);
 
-  project.build_file_list()?;
+  let cli_opts = CompilerOptsImpl::new_from_command_line(&args.include_paths, &args.defines);
+  let mut compiler_opts = project.project_inputs.compiler_opts.overlay(&cli_opts);
+  if let Some(max_errors) = args.max_errors {
+    compiler_opts.max_errors_per_module = max_errors;
+  }
+  compiler_opts.quiet = !args.verbose;
+  project.project_inputs.compiler_opts = compiler_opts.into();
 
-  // Parse all ERL files and their included includes
-  match ErlParseStage::run_parse_stage(&project) {
-    Err(e) => {
-      erl_fatal_icerror(e);
+  let project: ErlProject = project.into();
+  if args.files.is_empty() {
+    project.build_file_list()?;
+  } else {
+    project.set_input_files(&args.files)?;
+  }
+  Ok(project)
+}
+
+fn load_project_file(project_file: &Path) -> IcResult<ErlProjectImpl> {
+  match ProjectConf::from_project_file(&project_file.to_string_lossy()) {
+    Ok(conf) => Ok(ErlProjectImpl::from(conf)),
+    Err(e) => Err(Box::new(e)),
+  }
+}
+
+/// Run the stages for the command. Returns the process exit code.
+fn main_do(args: CliArgs) -> IcResult<i32> {
+  let project = create_project(&args)?;
+  if args.verbose {
+    eprintln!("{}", project);
+  }
+
+  let modules: Vec<ErlModule> = match args.command {
+    CliCommand::Preprocess => ErlPreprocessStage::run_preprocess_stage(&project)?,
+    CliCommand::Parse => ErlParseStage::run_parse_stage(&project)?,
+    CliCommand::Check => {
+      let modules = ErlParseStage::run_parse_stage(&project)?;
+      ErlTypesStage::run_check_stage(&project)?;
+      modules
+    }
+    CliCommand::Types => {
+      let modules = ErlParseStage::run_parse_stage(&project)?;
+      ErlTypesStage::run_types_stage(&project)?;
+      modules
     }
-    _ => (),
+    CliCommand::Xref => {
+      let modules = ErlParseStage::run_parse_stage(&project)?;
+      if !ErlXrefStage::run_xref_stage(&project, args.output.as_deref())?.is_empty() {
+        return Ok(EXIT_ERRORS_FOUND);
+      }
+      modules
+    }
+  };
+
+  if modules.iter().any(|m| m.has_errors()) {
+    Ok(EXIT_ERRORS_FOUND)
+  } else {
+    Ok(EXIT_SUCCESS)
   }
-  Ok(())
+}
+
+/// The tokenizer and the parser print a syntax error with the source pointers and then panic.
+/// Catch the panic, so that a syntax error is reported like other errors found in the code.
+fn main_catch_parse_errors(args: CliArgs) -> IcResult<i32> {
+  panic::set_hook(Box::new(|info| {
+    let payload = info.payload();
+    let msg = match (payload.downcast_ref::<&str>(), payload.downcast_ref::<String>()) {
+      (Some(msg), _) => msg.to_string(),
+      (_, Some(msg)) => msg.clone(),
+      _ => "unknown error".to_string(),
+    };
+    eprintln!("ironclad: {}", msg);
+  }));
+  panic::catch_unwind(|| main_do(args)).unwrap_or(Ok(EXIT_ERRORS_FOUND))
 }
 
 fn main() {
-  match main_do() {
-    Ok(_) => {
-      println!("Ironclad finished.");
-      exit(0);
+  let args = match CliArgs::parse(std::env::args().skip(1)) {
+    Ok(CliParseResult::Run(args)) => args,
+    Ok(CliParseResult::Help) => {
+      println!("{}", USAGE);
+      exit(EXIT_SUCCESS);
+    }
+    Ok(CliParseResult::Version) => {
+      println!("ironclad {}", env!("CARGO_PKG_VERSION"));
+      exit(EXIT_SUCCESS);
+    }
+    Err(msg) => {
+      eprintln!("ironclad: {}\n\n{}", msg, USAGE);
+      exit(EXIT_FATAL);
+    }
+  };
+
+  match main_catch_parse_errors(args) {
+    Ok(exit_code) => {
+      eprintln!("Ironclad finished.");
+      exit(exit_code);
     }
     Err(e) => {
       println!("{}", e);
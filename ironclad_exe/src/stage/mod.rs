@@ -3,3 +3,6 @@
 //! A stage outputs something usable by the following stage.
 
 pub mod stage_parse;
+pub mod stage_preprocess;
+pub mod stage_types;
+pub mod stage_xref;
@@ -1,7 +1,7 @@
 //! Parses Erlang source into AST
 
 use libironclad_erlang::error::ic_error::IcResult;
-use libironclad_erlang::project::module::module_impl::ErlModuleImpl;
+use libironclad_erlang::project::module::module_impl::{ErlModule, ErlModuleImpl};
 use libironclad_erlang::project::ErlProject;
 use libironclad_util::stats::time_stats::TimeStatsImpl;
 use std::path::Path;
@@ -13,15 +13,16 @@ impl ErlParseStage {
   /// Files acceptable as translation units (HRL headers are included independently without checking
   /// for file extension)
   #[inline]
-  fn is_acceptable_input(path: &Path) -> bool {
+  pub(crate) fn is_acceptable_input(path: &Path) -> bool {
     path.extension().unwrap_or_default() == "erl"
   }
 
   /// Parse stage
   /// * Parse loaded ERL files as Erlang.
   /// Returns: Collection of AST trees for all affected ERL modules
-  pub fn run_parse_stage(project: &ErlProject) -> IcResult<()> {
+  pub fn run_parse_stage(project: &ErlProject) -> IcResult<Vec<ErlModule>> {
     let mut stage_time = TimeStatsImpl::default();
+    let mut modules = Vec::new();
     let inputs = project.project_inputs.input_paths.clone_contents();
 
     for path in inputs.iter() {
@@ -41,17 +42,18 @@ impl ErlParseStage {
         module.verify_parsed_integrity()?;
 
         operation_timer.stop_timer();
-        println!("FILE {} - {}", operation_timer, source_file.file_name.to_string_lossy());
+        eprintln!("FILE {} - {}", operation_timer, source_file.file_name.to_string_lossy());
 
         if module.has_errors() {
           module.print_errors()
         }
+        modules.push(module);
       }
     }
 
     stage_time.stop_timer();
-    println!("PARSE stage: {}", stage_time);
+    eprintln!("PARSE stage: {}", stage_time);
 
-    Ok(())
+    Ok(modules)
   }
 }
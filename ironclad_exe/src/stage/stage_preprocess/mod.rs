@@ -0,0 +1,53 @@
+//! Runs the preprocessor alone and prints the resulting token stream
+
+use crate::stage::stage_parse::ErlParseStage;
+use libironclad_erlang::error::ic_error::IcResult;
+use libironclad_erlang::project::module::module_impl::{ErlModule, ErlModuleImpl};
+use libironclad_erlang::project::ErlProject;
+
+/// Handles preprocessing of the Erlang files in the project, without parsing
+pub struct ErlPreprocessStage {}
+
+impl ErlPreprocessStage {
+  /// Preprocess stage
+  /// * Tokenize ERL files and interpret the preprocessor directives.
+  /// * Print the resulting tokens, one source line per output line.
+  ///
+  /// Returns: Modules with preprocessor errors collected, but with no AST
+  pub fn run_preprocess_stage(project: &ErlProject) -> IcResult<Vec<ErlModule>> {
+    let mut modules = Vec::new();
+    let inputs = project.project_inputs.input_paths.clone_contents();
+
+    for path in inputs.iter() {
+      if ErlParseStage::is_acceptable_input(path) {
+        let compiler_opts = project.get_compiler_options_for(path);
+        let source_file = project.get_source_file(path)?;
+        let module = ErlModuleImpl::new(compiler_opts, source_file.clone());
+        module.setup_preprocessor();
+        let tokens = ErlModuleImpl::tokenize(project, &module, &source_file)?;
+
+        println!("%% {}", source_file.file_name.to_string_lossy());
+        let mut line = String::new();
+        for tok in tokens.iter() {
+          if tok.is_eol() {
+            if !line.is_empty() {
+              println!("{}", line.trim_start());
+              line.clear();
+            }
+          } else {
+            line.push_str(&format!(" {}", tok));
+          }
+        }
+        if !line.is_empty() {
+          println!("{}", line.trim_start());
+        }
+
+        if module.has_errors() {
+          module.print_errors()
+        }
+        modules.push(module);
+      }
+    }
+    Ok(modules)
+  }
+}
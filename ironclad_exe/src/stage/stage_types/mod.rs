@@ -0,0 +1,85 @@
+//! Prints synthesized types for the parsed modules
+
+use libironclad_erlang::erl_syntax::erl_ast::ast_binding::BindingCheck;
+use libironclad_erlang::error::ic_error::IcResult;
+use libironclad_erlang::project::module::scope::scope_impl::ScopeImpl;
+use libironclad_erlang::project::ErlProject;
+use libironclad_erlang::typing::coverage::CoverageCheck;
+use libironclad_erlang::typing::spec_check::SpecCheck;
+
+/// Handles type synthesis for the functions of already parsed modules
+pub struct ErlTypesStage {}
+
+impl ErlTypesStage {
+  /// Types stage: runs the checks, and prints synthesized function types
+  pub fn run_types_stage(project: &ErlProject) -> IcResult<()> {
+    Self::check_modules(project, true)
+  }
+
+  /// Check stage: runs the same checks as the types stage, but prints only the problems found
+  pub fn run_check_stage(project: &ErlProject) -> IcResult<()> {
+    Self::check_modules(project, false)
+  }
+
+  /// Checks for the types and check stages
+  /// * Visit the project modules in dependency order, so that the called modules are checked
+  ///   before their callers.
+  /// * Check variable bindings. A module with unbound variables is not typed further.
+  /// * Synthesize a function type for every function in every module, print it if `print_types`.
+  /// * Check functions against their `-spec`, check clause coverage and print the problems found.
+  ///   An error in one function is added to the module errors, and does not stop the checks.
+  fn check_modules(project: &ErlProject, print_types: bool) -> IcResult<()> {
+    for module in project.modules_in_dependency_order().iter() {
+      if print_types {
+        println!("%% module {}", module.get_name());
+      }
+
+      let errors_before = module.errors.len();
+      BindingCheck::verify_module(module);
+      if module.errors.len() > errors_before {
+        module.print_errors();
+        continue;
+      }
+
+      let mut fn_defs = if let Ok(r_fn_defs) = module.root_scope.fn_defs.collection.read() {
+        r_fn_defs
+          .iter()
+          .map(|(mfa, ast)| (mfa.clone(), ast.clone()))
+          .collect::<Vec<_>>()
+      } else {
+        panic!("Can't lock module functions for reading")
+      };
+      fn_defs.sort_by_key(|(mfa, _)| mfa.to_string());
+
+      // Print the module errors again, if the checks found something new
+      let reported_before = module.errors.len() + module.warnings.len();
+
+      for (mfa, ast) in fn_defs.iter() {
+        let scope = ScopeImpl::new_root_scope(mfa.to_string());
+        match ast.synthesize(module, &scope) {
+          Ok(fn_type) => {
+            if print_types {
+              println!("{} :: {}", mfa, fn_type)
+            }
+            // The callers in other modules use the stored type
+            module.root_scope.fn_types.add(mfa.clone(), fn_type);
+          }
+          Err(err) => {
+            module.add_ic_error(err);
+          }
+        }
+      }
+
+      if let Err(err) = SpecCheck::verify_module(module) {
+        module.add_ic_error(err);
+      }
+      if let Err(err) = CoverageCheck::verify_module(module) {
+        module.add_ic_error(err);
+      }
+      if module.errors.len() + module.warnings.len() > reported_before {
+        module.print_errors()
+      }
+    }
+    Ok(())
+  }
+}
@@ -0,0 +1,25 @@
+//! Prints the cross-reference report for the parsed project
+
+use libironclad_erlang::error::ic_error::{IcResult, IroncladError};
+use libironclad_erlang::project::xref::XrefReport;
+use libironclad_erlang::project::ErlProject;
+use std::path::Path;
+
+/// Handles the cross-reference analysis of the whole project
+pub struct ErlXrefStage {}
+
+impl ErlXrefStage {
+  /// Xref stage
+  /// * Find calls to undefined functions, and unused functions, types, records and imports.
+  /// * Write one finding per line: `kind <TAB> module <TAB> name <TAB> file:line:column` to the
+  ///   `output` file, or print to the standard output. The summary goes to the standard error.
+  pub fn run_xref_stage(project: &ErlProject, output: Option<&Path>) -> IcResult<XrefReport> {
+    let report = project.xref();
+    match output {
+      Some(path) => std::fs::write(path, report.to_string()).map_err(IroncladError::from)?,
+      None => print!("{}", report),
+    }
+    eprintln!("XREF stage: {} problem(s) found", report.findings.len());
+    Ok(report)
+  }
+}
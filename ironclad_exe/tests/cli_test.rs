@@ -0,0 +1,115 @@
+extern crate function_name;
+extern crate libironclad_erlang;
+
+mod test_util;
+
+use ::function_name::named;
+use libironclad_erlang::exit_codes::{EXIT_ERRORS_FOUND, EXIT_SUCCESS};
+use std::path::PathBuf;
+use std::process::{Command, Output};
+
+/// Write the module source to a temporary `.erl` file and run `ironclad <command>` on it
+fn run_ironclad(function_name: &str, command: &str, input: &str) -> Output {
+  let dir = std::env::temp_dir().join(format!("ironclad_{}_{}", function_name, std::process::id()));
+  std::fs::create_dir_all(&dir).unwrap();
+  let path: PathBuf = dir.join(format!("{}.erl", function_name));
+  std::fs::write(&path, format!("-module({}).\n{}", function_name, input)).unwrap();
+
+  let output = Command::new(env!("CARGO_BIN_EXE_ironclad"))
+    .arg(command)
+    .arg(&path)
+    .output()
+    .unwrap();
+  std::fs::remove_dir_all(&dir).unwrap();
+  println!("{}: stdout=«{}»", function_name, String::from_utf8_lossy(&output.stdout));
+  output
+}
+
+#[named]
+#[test]
+/// `check` runs the type checks and exits with an error code if problems were found
+fn cli_check_reports_errors() {
+  test_util::start(function_name!(), "CLI check command");
+
+  let input = "-export([size/1, sign/1, unused/1]).
+-spec size(list()) -> atom().
+size(L) -> length(L).
+-spec sign(0 | 1 | 2) -> atom().
+sign(0) -> zero;
+sign(1) -> one.
+unused(X) -> Y = 1, ok.";
+  let output = run_ironclad(function_name!(), "check", input);
+  let stdout = String::from_utf8_lossy(&output.stdout);
+
+  assert_eq!(output.status.code(), Some(EXIT_ERRORS_FOUND));
+  let line_with = |text: &str| stdout.lines().find(|line| line.contains(text)).unwrap();
+  assert!(!line_with("Return type mismatch: size/1").starts_with("warning: "));
+  assert!(line_with("Non-exhaustive clauses: sign/1").starts_with("warning: "));
+  assert!(line_with("Variable 'Y' is unused").starts_with("warning: "));
+  // Only the problems are printed, not the synthesized types
+  assert!(!stdout.contains("%% module"));
+}
+
+#[named]
+#[test]
+/// `check` of a module without problems succeeds
+fn cli_check_success() {
+  test_util::start(function_name!(), "CLI check command without problems");
+
+  let input = "-export([inc/1]).
+-spec inc(integer()) -> integer().
+inc(X) -> X + 1.";
+  let output = run_ironclad(function_name!(), "check", input);
+  assert_eq!(output.status.code(), Some(EXIT_SUCCESS));
+  // Without `--verbose` neither the project nor the preprocessed lines are printed
+  assert!(output.stdout.is_empty());
+  assert!(!String::from_utf8_lossy(&output.stderr).contains("LINE "));
+}
+
+#[named]
+#[test]
+/// A syntax error is printed and reported with the exit code for errors found, not as a crash
+fn cli_check_syntax_error() {
+  test_util::start(function_name!(), "CLI check command with a syntax error");
+
+  let input = "-spec apply(fun((integer()) -> integer())) -> integer().
+apply(F) -> F(1).";
+  let output = run_ironclad(function_name!(), "check", input);
+  let stdout = String::from_utf8_lossy(&output.stdout);
+
+  assert_eq!(output.status.code(), Some(EXIT_ERRORS_FOUND));
+  assert!(stdout.contains("Parse error"));
+  assert!(!String::from_utf8_lossy(&output.stderr).contains("panicked"));
+}
+
+#[named]
+#[test]
+/// `xref --output FILE` writes only the report to the file, the summary goes to stderr
+fn cli_xref_output() {
+  test_util::start(function_name!(), "CLI xref command with --output");
+
+  let dir =
+    std::env::temp_dir().join(format!("ironclad_{}_{}", function_name!(), std::process::id()));
+  std::fs::create_dir_all(&dir).unwrap();
+  let input_path = dir.join("cli_xref.erl");
+  let report_path = dir.join("report.tsv");
+  std::fs::write(&input_path, "-module(cli_xref).\n-export([a/0]).\na() -> nope().\n").unwrap();
+
+  let output = Command::new(env!("CARGO_BIN_EXE_ironclad"))
+    .args(["xref", "--output"])
+    .arg(&report_path)
+    .arg(&input_path)
+    .output()
+    .unwrap();
+  let report = std::fs::read_to_string(&report_path).unwrap();
+  std::fs::remove_dir_all(&dir).unwrap();
+  let stdout = String::from_utf8_lossy(&output.stdout);
+  let stderr = String::from_utf8_lossy(&output.stderr);
+  println!("report=«{}»\nstderr=«{}»", report, stderr);
+
+  assert_eq!(output.status.code(), Some(EXIT_ERRORS_FOUND));
+  assert!(report.lines().all(|line| line.split('\t').count() == 4));
+  assert!(report.starts_with("undefined_call\tcli_xref\tnope/0\t"));
+  assert!(stderr.contains("XREF stage: 2 problem(s) found"));
+  assert!(!stdout.contains("undefined_call"));
+}
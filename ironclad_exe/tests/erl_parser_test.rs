@@ -6,9 +6,12 @@ use std::ops::Deref;
 
 use ::function_name::named;
 use libironclad_erlang::erl_syntax::erl_ast::ast_iter::IterableAstNodeT;
+use libironclad_erlang::erl_syntax::erl_ast::node_impl::AstNodeImpl;
 use libironclad_erlang::erl_syntax::erl_ast::node_impl::AstNodeType::{
-  Apply, BinaryOp, ListComprehension, Lit,
+  Apply, BinaryOp, ListComprehension, Lit, MapBuilder, MapComprehension, MapComprehensionGenerator,
+  MaybeExpr, MaybeMatch, Receive, UnaryOp,
 };
+use libironclad_erlang::erl_syntax::erl_op::{ErlBinaryOp, ErlUnaryOp};
 use libironclad_erlang::erl_syntax::parsers::misc::panicking_parser_error_reporter;
 use libironclad_erlang::erl_syntax::parsers::parse_expr::parse_expr_list::parse_list_comprehension;
 use libironclad_erlang::erl_syntax::parsers::parser_input::ParserInput;
@@ -511,6 +514,166 @@ fn parse_fun_with_case() -> IcResult<()> {
   Ok(())
 }
 
+#[named]
+#[test]
+fn parse_expr_receive() -> IcResult<()> {
+  test_util::start(function_name!(), "Parse receive with clauses and after section");
+  let input = "receive
+      {ping, From} when is_pid(From) -> From ! pong, ok;
+      stop -> stopped
+    after Timeout * 1000 ->
+      timeout
+    end";
+  let expr = test_util::parse_expr(function_name!(), input);
+  assert!(expr.is_receive_expr(), "Expected a receive expression, got {}", expr);
+  if let Receive { clauses, after } = &expr.content {
+    assert_eq!(clauses.len(), 2, "Receive must have 2 clauses");
+    assert!(clauses[0].guard.is_some(), "First receive clause must have a guard");
+    assert!(after.is_some(), "Receive must have an after section");
+
+    // The after section gives AST walkers the same nodes as the receive expression does
+    let after_children = after.as_ref().unwrap().children().unwrap();
+    let receive_children = expr.children().unwrap();
+    assert_eq!(after_children.len(), 2, "After section has the timeout and the body");
+    let tail = &receive_children[receive_children.len() - 2..];
+    assert!(after_children
+      .iter()
+      .zip(tail.iter())
+      .all(|(a, b)| std::sync::Arc::ptr_eq(a, b)));
+  }
+  Ok(())
+}
+
+#[named]
+#[test]
+fn parse_expr_receive_after_only() -> IcResult<()> {
+  test_util::start(function_name!(), "Parse receive with only an after section");
+  let expr = test_util::parse_expr(function_name!(), "receive after 100 -> ok end");
+  assert!(
+    matches!(&expr.content, Receive { clauses, after: Some(_) } if clauses.is_empty()),
+    "Expected a receive with no clauses and an after section, got {}",
+    expr
+  );
+  Ok(())
+}
+
+#[should_panic]
+#[named]
+#[test]
+fn parse_expr_receive_empty() {
+  test_util::start(function_name!(), "Receive without clauses and after section must fail");
+  test_util::parse_expr(function_name!(), "receive end");
+}
+
+#[named]
+#[test]
+fn parse_fun_with_maybe() -> IcResult<()> {
+  test_util::start(function_name!(), "Parse a function with maybe-else block");
+  let input = "-feature(maybe_expr, enable).
+f(Pid) ->
+    maybe
+        {ok, A} ?= a(),
+        true = A >= 0,
+        {ok, B} ?= b(),
+        A + B
+    else
+        error -> {error, unknown};
+        {error, _} = Err -> Err
+    end.";
+  let nodes = test_util::parse_module_unwrap(function_name!(), input);
+  let body = &nodes[0].as_fn_def().clauses[0].body;
+  assert!(body.is_maybe_expr(), "Expected a maybe expression, got {}", body);
+  if let MaybeExpr { exprs, else_clauses } = &body.content {
+    assert_eq!(exprs.len(), 4, "Maybe block must have 4 expressions");
+    assert!(
+      matches!(exprs[0].content, MaybeMatch { .. }),
+      "Expected ?= match, got {}",
+      exprs[0]
+    );
+    assert_eq!(else_clauses.as_ref().map(|ec| ec.len()), Some(2), "Expected 2 else clauses");
+  }
+  Ok(())
+}
+
+#[named]
+#[test]
+fn parse_maybe_is_atom_without_feature() -> IcResult<()> {
+  test_util::start(function_name!(), "Without -feature(maybe_expr, enable) maybe is an atom");
+  let nodes = test_util::parse_module_unwrap(function_name!(), "f() -> maybe.");
+  let body = &nodes[0].as_fn_def().clauses[0].body;
+  assert!(body.is_atom_of("maybe"), "Expected atom 'maybe', got {}", body);
+  Ok(())
+}
+
+#[named]
+#[test]
+fn parse_expr_catch() -> IcResult<()> {
+  test_util::start(function_name!(), "Parse catch with the lowest precedence");
+  let expr = test_util::parse_expr(function_name!(), "catch X = foo(1) + 2");
+  if let UnaryOp { unop_expr } = &expr.content {
+    assert_eq!(unop_expr.operator, ErlUnaryOp::Catch);
+    assert!(
+      matches!(&unop_expr.expr.content, BinaryOp { binop_expr } if binop_expr.operator == ErlBinaryOp::Match),
+      "catch must wrap the whole match expression, got {}",
+      expr
+    );
+  } else {
+    panic!("Expected a catch expression, got {}", expr)
+  }
+
+  let nested = test_util::parse_expr(function_name!(), "catch catch throw(x)");
+  assert!(
+    matches!(&nested.content, UnaryOp { unop_expr } if matches!(&unop_expr.expr.content, UnaryOp { .. })),
+    "Expected nested catch, got {}",
+    nested
+  );
+  Ok(())
+}
+
+#[named]
+#[test]
+fn parse_guard_bif_calls() -> IcResult<()> {
+  test_util::start(function_name!(), "Only guard-safe BIFs can be called in a guard");
+  for input in [
+    "length(X) > 1",
+    "is_integer(X) andalso erlang:abs(X) < 10",
+    "element(1, T) =:= ok",
+  ] {
+    let expr = test_util::parse_expr(function_name!(), input);
+    AstNodeImpl::verify_expr_is_guard(&expr)?;
+  }
+  for input in [
+    "foo(X)",
+    "self() =:= lists:last(X)",
+    "is_atom(send(P, X))",
+    "erlang:send(P, X)",
+  ] {
+    let expr = test_util::parse_expr(function_name!(), input);
+    assert!(
+      AstNodeImpl::verify_expr_is_guard(&expr).is_err(),
+      "{} must be rejected in a guard",
+      input
+    );
+  }
+  Ok(())
+}
+
+#[named]
+#[test]
+fn parse_catch_not_allowed_in_guard() -> IcResult<()> {
+  test_util::start(function_name!(), "Catch is not a valid guard or match expression");
+  let expr = test_util::parse_expr(function_name!(), "catch is_atom(X)");
+  assert!(
+    AstNodeImpl::verify_expr_is_guard(&expr).is_err(),
+    "catch must be rejected in a guard"
+  );
+  assert!(
+    AstNodeImpl::verify_expr_is_matchexpr(&expr).is_err(),
+    "catch must be rejected in a pattern"
+  );
+  Ok(())
+}
+
 #[named]
 #[test]
 fn parse_fun_with_lambda() -> IcResult<()> {
@@ -902,6 +1065,74 @@ fn parse_map_builder_no_base() {
   let _m = test_util::parse_module(function_name!(), input);
 }
 
+#[named]
+#[test]
+fn parse_map_pattern_in_fn_head() -> IcResult<()> {
+  test_util::start(function_name!(), "Map patterns in function heads only use :=");
+  let module = test_util::parse_module(function_name!(), "f(#{key := V, 1 := X}) -> {V, X}.");
+  module.verify_parsed_integrity()?;
+  let nodes = module.ast.borrow().children().unwrap_or_default();
+  let arg = &nodes[0].as_fn_def().clauses[0].args[0];
+  assert_eq!(format!("{}", arg), "#{'key' := V, 1 := X}");
+
+  let bad = test_util::parse_module(function_name!(), "f(#{key => V}) -> V.");
+  assert!(bad.verify_parsed_integrity().is_err(), "=> must be rejected in a map pattern");
+
+  let bad_update = test_util::parse_module(function_name!(), "f(M#{key := V}) -> V.");
+  assert!(bad_update.verify_parsed_integrity().is_err(), "Map update is not a pattern");
+  Ok(())
+}
+
+#[named]
+#[test]
+fn parse_map_update_exact() -> IcResult<()> {
+  test_util::start(function_name!(), "Map update with := and =>, and construction with :=");
+  let expr = test_util::parse_expr(function_name!(), "M#{a := 1, b => 2}");
+  if let MapBuilder { base, members } = &expr.content {
+    assert!(base.is_some(), "Map update must have a base");
+    assert!(members[0].is_match(), "a := 1 must be an exact association");
+    assert!(!members[1].is_match(), "b => 2 must be an assignment");
+  } else {
+    panic!("Expected a map update, got {}", expr)
+  }
+
+  let construct = test_util::parse_expr(function_name!(), "#{a := 1}");
+  assert!(
+    AstNodeImpl::verify_expr_is_guard(&construct).is_err(),
+    ":= must be rejected when constructing a map"
+  );
+  Ok(())
+}
+
+#[named]
+#[test]
+fn parse_map_comprehension() -> IcResult<()> {
+  test_util::start(function_name!(), "Parse a map comprehension with a map generator");
+  let expr = test_util::parse_expr(function_name!(), "#{K => V * 2 || K := V <- M, V > 1}");
+  if let MapComprehension { generators, .. } = &expr.content {
+    assert_eq!(generators.len(), 2, "Expected a generator and a filter");
+    assert!(
+      matches!(&generators[0].content, MapComprehensionGenerator { .. }),
+      "Expected a map generator, got {}",
+      generators[0]
+    );
+  } else {
+    panic!("Expected a map comprehension, got {}", expr)
+  }
+
+  let lc = test_util::parse_expr(function_name!(), "[{K, V} || K := V <- M]");
+  if let ListComprehension { generators, .. } = &lc.content {
+    assert!(
+      matches!(&generators[0].content, MapComprehensionGenerator { .. }),
+      "Expected a map generator in a list comprehension, got {}",
+      generators[0]
+    );
+  } else {
+    panic!("Expected a list comprehension, got {}", lc)
+  }
+  Ok(())
+}
+
 #[named]
 #[test]
 fn test_test1() {
@@ -914,3 +1145,30 @@ fn test_test1() {
     io:put_chars(File, lists:join($\n, PP)).";
   let _ = test_util::parse_module(function_name!(), input2);
 }
+
+#[named]
+#[test]
+fn parse_guard_precedence() -> IcResult<()> {
+  test_util::start(function_name!(), "Parse guard sequence, where ; binds looser than ,");
+  let module = test_util::parse_module(function_name!(), "f(A, B, C) when A; B, C -> ok.");
+  let fn_def = module
+    .root_scope
+    .fn_defs
+    .get(&MFArity::new_local("f", 3))
+    .unwrap();
+  let guard = fn_def.as_fn_def().clauses[0].guard_expr.clone().unwrap();
+  println!("Parsed guard: {}", guard);
+
+  if let BinaryOp { binop_expr } = &guard.content {
+    assert_eq!(binop_expr.operator, ErlBinaryOp::Semicolon);
+    assert!(binop_expr.left.is_var(), "Left of ; must be A, got {}", binop_expr.left);
+    assert!(
+      binop_expr.right.is_binop(ErlBinaryOp::Comma),
+      "Right of ; must be B, C; got {}",
+      binop_expr.right
+    );
+  } else {
+    panic!("Expected a ; binary op, got {}", guard)
+  }
+  Ok(())
+}
@@ -0,0 +1,2 @@
+-module(cycle).
+-include("a.hrl").
@@ -0,0 +1,3 @@
+-module(twice).
+-include("once.hrl").
+-include("once.hrl").
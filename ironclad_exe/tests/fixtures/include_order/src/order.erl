@@ -0,0 +1,6 @@
+-module(order).
+-include("first.hrl").
+-include("only_inc.hrl").
+-include("nested/outer.hrl").
+-include("$IRONCLAD_INCLUDE_ORDER_VAR/var.hrl").
+-include("mid/mid.hrl").
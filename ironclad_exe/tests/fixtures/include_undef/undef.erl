@@ -0,0 +1,10 @@
+-module(undef).
+-undef(FOO).
+-undef(OTP_RELEASE).
+-include("after_undef.hrl").
+-ifdef(FOO).
+-record(foo_defined, {value}).
+-endif.
+-ifdef(OTP_RELEASE).
+-record(otp_release_defined, {value}).
+-endif.
@@ -3,8 +3,15 @@ mod test_util;
 use ::function_name::named;
 use libironclad_erlang::erl_syntax::parsers::token_stream::token_type::TokenType;
 use libironclad_erlang::error::ic_error::IcResult;
+use libironclad_erlang::project::compiler_opts::CompilerOptsImpl;
+use libironclad_erlang::project::conf::ProjectConf;
+use libironclad_erlang::project::module::module_impl::{ErlModule, ErlModuleImpl};
+use libironclad_erlang::project::project_impl::ErlProjectImpl;
+use libironclad_erlang::project::ErlProject;
 use libironclad_erlang::typing::erl_integer::ErlInteger;
 use libironclad_util::mfarity::MFArity;
+use libironclad_util::source_file::SourceFileImpl;
+use std::path::{Path, PathBuf};
 
 // #[test]
 // #[named]
@@ -282,22 +289,29 @@ fn test_define_with_dquotes() {
 /// Try parse a define macro where value contains another macro
 fn test_macro_expansion_in_define() {
   test_util::start(function_name!(), "Parse a -define macro with another macro in value");
-  let module =
-    test_util::parse_module(function_name!(), "-define(AAA, test_success).\n-define(BBB, ?AAA).");
+  let module = test_util::parse_module(
+    function_name!(),
+    "-define(AAA, test_success).\n-define(BBB, ?AAA).\nmyfun() -> ?BBB.",
+  );
   let pdef = module
     .root_scope
     .defines
     .get(&MFArity::new_local("BBB", 0))
     .unwrap();
   assert_eq!(pdef.name, "BBB");
-  if let TokenType::Atom(a) = &pdef.tokens[0].content {
-    assert_eq!(
-      a, "test_success",
-      "Macro BBB must expand to 'test_success' and not macro invocation of ?AAA"
-    );
-  } else {
-    panic!("pdef.tokens[0] is not an atom: {}", pdef.tokens[0]);
-  }
+  assert!(
+    pdef.tokens[0].is_tok(TokenType::MacroInvocation("AAA".to_string())),
+    "Macro BBB body is expanded where BBB is used"
+  );
+  let fndef = module
+    .root_scope
+    .fn_defs
+    .get(&MFArity::new_local("myfun", 0))
+    .unwrap();
+  assert!(
+    fndef.as_fn_def().clauses[0].body.is_atom_of("test_success"),
+    "Macro BBB must expand to 'test_success' and not macro invocation of ?AAA"
+  );
 }
 
 #[test]
@@ -371,15 +385,329 @@ myfunction2() ->
 fn test_ast_macro_args_substitution() {
   test_util::start(function_name!(), "Substitute a macro with arguments");
   let input = "-define(M(A,B), A + B).
--define(result, ?M(1,2)).";
+-define(result, ?M(1,2)).
+myfun() -> ?result.";
   let module = test_util::parse_module(function_name!(), input);
   let pdef = module
     .root_scope
     .defines
     .get(&MFArity::new_local("result", 0))
     .unwrap();
-  assert!(pdef.tokens[0].is_tok(TokenType::Integer(ErlInteger::Small(1))));
+  println!("{:?}", pdef);
+  assert_eq!(fn_body_text(&module, "myfun", 0), "(1 + 2)");
+}
+
+/// Print the body of the first clause of a function
+fn fn_body_text(module: &ErlModule, name: &str, arity: usize) -> String {
+  let fndef = module
+    .root_scope
+    .fn_defs
+    .get(&MFArity::new_local(name, arity))
+    .unwrap();
+  format!("{}", fndef.as_fn_def().clauses[0].body)
+}
+
+#[test]
+#[named]
+/// Try defines given on the command line as `-DNAME` and `-DNAME=VALUE`
+fn test_command_line_defines() {
+  test_util::start(function_name!(), "Use -D defines from the command line");
+  let opts =
+    CompilerOptsImpl::new_from_command_line(&[], &["DEBUG".to_string(), "X=40 + 2".to_string()]);
+  let input = format!(
+    "-module({}).\n-ifdef(DEBUG).\n-define(Y, ?X).\n-endif.\nmyfun() -> ?Y.",
+    function_name!()
+  );
+  let source_file = SourceFileImpl::new(&PathBuf::from(function_name!()), input);
+  let project = ErlProjectImpl::default().into();
+  let module =
+    ErlModuleImpl::from_module_source(&project, &source_file, Some(opts.into())).unwrap();
+
+  let debug = module
+    .root_scope
+    .defines
+    .get(&MFArity::new_local("DEBUG", 0))
+    .unwrap();
+  assert!(debug.tokens[0].is_atom_of("true"));
+
+  let pdef = module
+    .root_scope
+    .defines
+    .get(&MFArity::new_local("X", 0))
+    .unwrap();
+  assert_eq!(pdef.tokens.len(), 3);
+  assert!(pdef.tokens[0].is_tok(TokenType::Integer(ErlInteger::Small(40))));
   assert!(pdef.tokens[1].is_tok(TokenType::Plus));
   assert!(pdef.tokens[2].is_tok(TokenType::Integer(ErlInteger::Small(2))));
-  println!("{:?}", pdef);
+  assert_eq!(fn_body_text(&module, "myfun", 0), "(40 + 2)");
+}
+
+#[test]
+#[named]
+/// Macros using other macros, including ones defined later, and `??Arg` stringification
+fn test_nested_macro_expansion() {
+  test_util::start(function_name!(), "Expand macros inside macro bodies and ??Arg");
+  let input = "-define(ADD(A, B), (A + B)).
+-define(DOUBLE(X), ?ADD(X, X)).
+-define(ASSERT(E), {E, ??E}).
+-define(LATER, ?DEFINED_LATER(ok)).
+-define(DEFINED_LATER(V), {later, V}).
+-define(F, myfun).
+double(Y) -> ?DOUBLE(?ADD(Y, 1)).
+check() -> ?ASSERT(lists:reverse([1,2]) =:= \"ab\").
+later() -> ?LATER.
+call() -> ?F(1).";
+  let module = test_util::parse_module(function_name!(), input);
+  assert_eq!(module.errors.len(), 0);
+  assert_eq!(fn_body_text(&module, "double", 1), "((Y + 1) + (Y + 1))");
+  // ??E is the source text of the argument with tokens separated by spaces
+  assert!(
+    fn_body_text(&module, "check", 0).ends_with(", \"lists : reverse ( [ 1 , 2 ] ) =:= \"ab\"\"}")
+  );
+  assert_eq!(fn_body_text(&module, "later", 0), "{'later', 'ok'}");
+  assert_eq!(fn_body_text(&module, "call", 0), "'myfun'(1)");
+}
+
+#[test]
+#[named]
+/// Self-recursive and mutually recursive macros are reported instead of expanding forever
+fn test_recursive_macro() {
+  test_util::start(function_name!(), "Report recursive macro invocations");
+  let input = "-define(SELF, [?SELF]).
+-define(PING, [ping | [?PONG]]).
+-define(PONG, [pong | [?PING]]).
+f() -> ?SELF.
+g() -> ?PING.";
+  let module = test_util::parse_module(function_name!(), input);
+  let errors = module.errors.data.read().unwrap();
+  assert_eq!(errors.len(), 2, "Expected two errors, got {:?}", errors);
+  assert!(errors[0]
+    .to_string()
+    .contains("Recursive macro invocation: ?SELF"));
+  assert!(errors[1]
+    .to_string()
+    .contains("Recursive macro invocation: ?PING (while expanding ?PING, ?PONG)"));
+}
+
+#[test]
+#[named]
+/// Predefined macros get their values from where they are used
+fn test_predefined_macros() {
+  test_util::start(function_name!(), "Substitute predefined macros");
+  let input = "-define(WHERE, {?FUNCTION_NAME, ?FUNCTION_ARITY, ?LINE}).
+-record(r, {f}).
+locate(A, {B, C}) ->
+  ?WHERE.
+names() -> {?MODULE, ?MODULE_STRING, ?FILE}.
+release() -> {?OTP_RELEASE, ?MACHINE}.
+features() -> {?FEATURE_AVAILABLE(maybe_expr), ?FEATURE_ENABLED(maybe_expr)}.
+field(R) -> {R#r.f, ?FUNCTION_NAME}.
+in_section() ->
+-ifdef(MODULE).
+  ?FUNCTION_ARITY.
+-else.
+  none.
+-endif.";
+  let module = test_util::parse_module(function_name!(), input);
+  assert_eq!(module.errors.len(), 0);
+  assert_eq!(fn_body_text(&module, "locate", 2), "{'locate', 2, 5}");
+  assert_eq!(
+    fn_body_text(&module, "names", 0),
+    "{'test_predefined_macros', \"test_predefined_macros\", \"test_predefined_macros\"}"
+  );
+  assert_eq!(fn_body_text(&module, "release", 0), "{25, 'BEAM'}");
+  assert_eq!(fn_body_text(&module, "features", 0), "{'true', 'false'}");
+  assert!(fn_body_text(&module, "field", 1).ends_with(", 'field'}"));
+  assert_eq!(fn_body_text(&module, "in_section", 0), "0");
+}
+
+#[test]
+#[named]
+/// `?OTP_RELEASE` is set in the project file, and `?FEATURE_*` macros only exist since OTP 25
+fn test_otp_release_conf() {
+  test_util::start(function_name!(), "Set ?OTP_RELEASE in the project file");
+  let conf = "[compiler_options]\notp_release = 24\n";
+  let project: ErlProject = ErlProjectImpl::from(ProjectConf::from_string(conf).unwrap()).into();
+  let input = format!(
+    "-module({}).\nrelease() -> ?OTP_RELEASE.\n-ifdef(FEATURE_AVAILABLE).\nfeatures() -> ok.\n-endif.",
+    function_name!()
+  );
+  let path = PathBuf::from(function_name!());
+  let opts = project.get_compiler_options_for(&path);
+  let source_file = SourceFileImpl::new(&path, input);
+  let module = ErlModuleImpl::from_module_source(&project, &source_file, Some(opts)).unwrap();
+  assert_eq!(fn_body_text(&module, "release", 0), "24");
+  assert!(module
+    .root_scope
+    .fn_defs
+    .get(&MFArity::new_local("features", 0))
+    .is_none());
+}
+
+#[test]
+#[named]
+/// Resolve `-include_lib("app/...")` in the project deps, `ERL_LIBS` and the OTP root, choosing
+/// the highest version of a versioned application directory
+fn test_include_lib_app_dirs() {
+  test_util::start(function_name!(), "Resolve -include_lib() via application directories");
+  let fixtures = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/include_lib");
+  let conf = format!(
+    "[compiler_options]\notp_root = {:?}\ndeps = [{:?}]\n",
+    fixtures.join("otp").to_string_lossy(),
+    fixtures.join("deps").to_string_lossy()
+  );
+  let project: ErlProject = ErlProjectImpl::from(ProjectConf::from_string(&conf).unwrap()).into();
+  std::env::set_var("ERL_LIBS", fixtures.join("erl_libs"));
+
+  let input = "-include_lib(\"kernel/include/file.hrl\").
+-include_lib(\"myapp/include/x.hrl\").
+-include_lib(\"otherapp/include/y.hrl\").";
+  let module = test_util::parse_module_in_project(&project, function_name!(), input);
+
+  let records = &module.root_scope.record_defs;
+  assert!(records.contains(&"file_info".to_string()), "kernel from the OTP root");
+  assert!(records.contains(&"myapp_new".to_string()), "myapp-1.10.0 from the project deps");
+  assert!(!records.contains(&"myapp_old".to_string()), "myapp-1.2.3 is older than 1.10.0");
+  assert!(records.contains(&"other".to_string()), "otherapp from ERL_LIBS");
+}
+
+#[test]
+#[named]
+/// Search `-include()` files in the directory of the including file first, then in the module
+/// directory, then in the include paths; directories of the outer includers are not searched.
+/// Substitute `$VAR` from the environment
+fn test_include_search_order() {
+  test_util::start(function_name!(), "Search order and $VAR for -include()");
+  let fixtures = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/include_order");
+  let conf = format!(
+    "[compiler_options]\ninclude_paths = [{:?}]\n",
+    fixtures.join("include").to_string_lossy()
+  );
+  let project: ErlProject = ErlProjectImpl::from(ProjectConf::from_string(&conf).unwrap()).into();
+  std::env::set_var("IRONCLAD_INCLUDE_ORDER_VAR", fixtures.join("var"));
+
+  let module_path = fixtures.join("src/order.erl");
+  let source_file =
+    SourceFileImpl::new(&module_path, std::fs::read_to_string(&module_path).unwrap());
+  let module = ErlModuleImpl::from_module_source(&project, &source_file, None).unwrap();
+
+  let records = &module.root_scope.record_defs;
+  assert!(
+    records.contains(&"first_src".to_string()),
+    "module directory before include paths"
+  );
+  assert!(!records.contains(&"first_inc".to_string()), "shadowed by the module directory");
+  assert!(records.contains(&"only_inc".to_string()), "found in the include paths");
+  assert!(
+    records.contains(&"inner_nested".to_string()),
+    "the including file directory first"
+  );
+  assert!(
+    !records.contains(&"inner_src".to_string()),
+    "shadowed by the including file directory"
+  );
+  assert!(records.contains(&"from_var".to_string()), "$VAR is substituted");
+  assert!(
+    records.contains(&"leaf_src".to_string()),
+    "the module directory after the including file directory"
+  );
+  assert!(
+    !records.contains(&"leaf_mid".to_string()),
+    "the directory of an outer includer is not searched"
+  );
+}
+
+/// Parse a module file from the fixtures directory, with compiler options
+fn parse_fixture_module(path: &Path, opts: CompilerOptsImpl) -> ErlModule {
+  let source_file = SourceFileImpl::new(path, std::fs::read_to_string(path).unwrap());
+  let project = ErlProjectImpl::default().into();
+  ErlModuleImpl::from_module_source(&project, &source_file, Some(opts.into())).unwrap()
+}
+
+#[test]
+#[named]
+/// A header including itself through another header is reported with the include chain
+fn test_include_cycle() {
+  test_util::start(function_name!(), "Report circular -include() with the include chain");
+  let fixtures = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/include_cycle");
+  let module = parse_fixture_module(&fixtures.join("cycle.erl"), CompilerOptsImpl::default());
+
+  let errors = module.errors.data.read().unwrap();
+  assert_eq!(errors.len(), 1, "Expected one error, got {:?}", errors);
+  let msg = errors[0].to_string();
+  println!("{}", msg);
+  let chain: Vec<&str> = msg.lines().skip(1).collect();
+  assert_eq!(chain.len(), 3, "Include chain cycle.erl -> a.hrl -> b.hrl -> a.hrl");
+  assert!(chain[0].contains("cycle.erl:2:") && chain[0].ends_with("a.hrl"));
+  assert!(chain[1].contains("a.hrl:1:") && chain[1].ends_with("b.hrl"));
+  assert!(chain[2].contains("b.hrl:2:") && chain[2].ends_with("a.hrl"));
+
+  // Both headers are still included once
+  assert!(module
+    .root_scope
+    .record_defs
+    .contains(&"from_a".to_string()));
+  assert!(module
+    .root_scope
+    .record_defs
+    .contains(&"from_b".to_string()));
+}
+
+#[test]
+#[named]
+/// Including the same file twice is reported only with the `warn_duplicate_include` option
+fn test_include_duplicate() {
+  test_util::start(function_name!(), "Warn on a duplicate -include()");
+  let fixtures = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/include_cycle");
+  let module_path = fixtures.join("twice.erl");
+
+  let module = parse_fixture_module(&module_path, CompilerOptsImpl::default());
+  assert!(module.warnings.is_empty());
+
+  let opts = CompilerOptsImpl {
+    warn_duplicate_include: true,
+    ..CompilerOptsImpl::default()
+  };
+  let module = parse_fixture_module(&module_path, opts);
+  let warnings = module.warnings.data.read().unwrap();
+  assert_eq!(warnings.len(), 1, "Expected one warning, got {:?}", warnings);
+  assert!(warnings[0]
+    .msg
+    .contains("once.hrl is included again, first included at"));
+  assert!(warnings[0].msg.contains("twice.erl:2:"));
+}
+
+#[test]
+#[named]
+/// An `-undef` of a command line define or a predefined macro holds after an `-include`
+fn test_undef_then_include() {
+  test_util::start(function_name!(), "-undef followed by -include()");
+  let fixtures = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/include_undef");
+  let opts = CompilerOptsImpl::new_from_command_line(&[], &["FOO".to_string()]);
+  let module = parse_fixture_module(&fixtures.join("undef.erl"), opts);
+
+  let records = &module.root_scope.record_defs;
+  assert!(records.contains(&"from_header".to_string()));
+  assert!(!records.contains(&"foo_defined".to_string()), "FOO stays undefined");
+  assert!(
+    !records.contains(&"otp_release_defined".to_string()),
+    "OTP_RELEASE stays undefined"
+  );
+}
+
+#[test]
+#[named]
+/// A missing include file is reported with the location of the directive first
+fn test_include_not_found() {
+  test_util::start(function_name!(), "Report a missing -include() file");
+  let input = format!("-module({}).\n-include(\"missing.hrl\").\n", function_name!());
+  let source_file = SourceFileImpl::new(&PathBuf::from(function_name!()), input);
+  let project = ErlProjectImpl::default().into();
+  let err = ErlModuleImpl::from_module_source(&project, &source_file, None).unwrap_err();
+
+  let msg = err.to_string();
+  println!("{}", msg);
+  assert!(
+    msg.starts_with(&format!("{}:2:1-2:25: File was not found: missing.hrl", function_name!()))
+  );
 }
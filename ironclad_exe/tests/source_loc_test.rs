@@ -0,0 +1,56 @@
+extern crate function_name;
+extern crate libironclad_erlang;
+
+mod test_util;
+
+use ::function_name::named;
+use libironclad_erlang::erl_syntax::erl_ast::node_impl::AstNodeType;
+use libironclad_util::mfarity::MFArity;
+use libironclad_util::source_file::SourceFileImpl;
+use std::path::PathBuf;
+
+#[named]
+#[test]
+fn source_file_line_col() {
+  test_util::start(function_name!(), "Line and column from byte offset");
+  let src = SourceFileImpl::new(&PathBuf::from("test.erl"), "ab\ncd\n\nδx".to_string());
+  assert_eq!(src.line_col(0), (1, 1));
+  assert_eq!(src.line_col(2), (1, 3));
+  assert_eq!(src.line_col(3), (2, 1));
+  assert_eq!(src.line_col(6), (3, 1));
+  // Columns count characters, not bytes: 'δ' takes 2 bytes
+  assert_eq!(src.line_col(9), (4, 2));
+}
+
+#[named]
+#[test]
+fn source_loc_fn_def() {
+  test_util::start(function_name!(), "Spans for a function and its body");
+  let module = test_util::parse_module(function_name!(), "f() ->\n  X = 1,\n  {X, ok}.");
+  let fn_def = module
+    .root_scope
+    .fn_defs
+    .get(&MFArity::new_local("f", 0))
+    .unwrap();
+  assert_eq!(fn_def.location.to_string(), format!("{}:2:1-4:11", function_name!()));
+
+  let body = &fn_def.as_fn_def().clauses[0].body;
+  assert!(matches!(body.content, AstNodeType::CommaExpr { .. }));
+  assert_eq!(body.location.to_string(), format!("{}:3:3-4:10", function_name!()));
+}
+
+#[named]
+#[test]
+fn source_loc_macro_at_end() {
+  test_util::start(function_name!(), "Span of an expression ending in a macro invocation");
+  let module = test_util::parse_module(function_name!(), "-define(X, 1).\nf(A) ->\n  A + ?X.");
+  let fn_def = module
+    .root_scope
+    .fn_defs
+    .get(&MFArity::new_local("f", 1))
+    .unwrap();
+
+  // The pasted macro body points at the -define line, the span ends at the last token after `A`
+  let body = &fn_def.as_fn_def().clauses[0].body;
+  assert_eq!(body.location.to_string(), format!("{}:4:3-4:6", function_name!()));
+}
@@ -7,6 +7,7 @@ use libironclad_erlang::erl_syntax::parsers::token_stream::token::Token;
 use libironclad_erlang::erl_syntax::parsers::token_stream::tokenizer::tokenize_source;
 use libironclad_erlang::project::module::module_impl::{ErlModule, ErlModuleImpl};
 use libironclad_erlang::project::project_impl::ErlProjectImpl;
+use libironclad_erlang::project::ErlProject;
 use libironclad_erlang::typing::erl_type::ErlType;
 use libironclad_util::source_file::SourceFileImpl;
 use std::path::PathBuf;
@@ -46,6 +47,18 @@ pub fn parse_module(function_name: &str, input: &str) -> ErlModule {
   module
 }
 
+/// Parse a module and add it to the project, so that other modules in the project can find it.
+/// Returns `ErlModule`
+pub fn parse_module_in_project(project: &ErlProject, module_name: &str, input: &str) -> ErlModule {
+  let input = format!("-module({}).\n{}", module_name, input);
+  let filename = PathBuf::from(format!("{}.erl", module_name));
+
+  let source_file = SourceFileImpl::new(&filename, input);
+  let module = ErlModuleImpl::from_module_source(project, &source_file, None).unwrap();
+  project.register_new_module(&module);
+  module
+}
+
 pub fn tokenize(input: &str) -> Vec<Token> {
   let project = Arc::new(ErlProjectImpl::default());
   let src_file = SourceFileImpl::new(&PathBuf::from("test"), input.to_string());
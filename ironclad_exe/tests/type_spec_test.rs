@@ -296,3 +296,11 @@ fn parse_int_range_test() {
     .get(&MFArity::new_local("reg_num", 0))
     .unwrap();
 }
+
+#[named]
+#[test]
+fn parse_map_type_mandatory_keys() {
+  test_util::start(function_name!(), "Parse a map type with mandatory and optional keys");
+  let ty = test_util::parse_type(function_name!(), "#{a := integer(), b => atom()}");
+  assert_eq!(format!("{}", ty), "#{'a' := integer(), 'b' => atom()}");
+}
@@ -11,6 +11,7 @@ use libironclad_erlang::erl_syntax::erl_op::ErlBinaryOp;
 use libironclad_erlang::error::ic_error::IcResult;
 use libironclad_erlang::project::module::module_impl::ErlModuleImpl;
 use libironclad_erlang::project::module::scope::scope_impl::ScopeImpl;
+use libironclad_erlang::typing::bif::BifTable;
 use libironclad_erlang::typing::erl_type::ErlTypeImpl;
 use libironclad_util::mfarity::MFArity;
 use std::ops::Deref;
@@ -78,6 +79,99 @@ fn synth_simplefun_addition() -> IcResult<()> {
   Ok(())
 }
 
+#[named]
+#[test]
+fn synth_receive() -> IcResult<()> {
+  test_util::start(function_name!(), "synthesize type for a receive with after section");
+
+  let module = ErlModuleImpl::new_default();
+  let scope1 = ScopeImpl::new_root_scope(function_name!().to_string());
+  let parsed = test_util::parse_expr(
+    function_name!(),
+    "receive {ping, X} -> {pong, X}; stop -> stopped after 100 -> timeout end",
+  );
+  let expr_type = parsed.synthesize(&module, &scope1)?;
+  println!("{}: Inferred {} 🡆 {}", function_name!(), parsed, expr_type);
+
+  if let ErlTypeImpl::Union(u) = expr_type.deref() {
+    assert_eq!(u.types.len(), 3, "Receive type must be a union of 3 types, got {}", expr_type);
+    assert!(
+      u.contains(&ErlTypeImpl::new_atom("stopped")),
+      "Receive type must contain 'stopped' from the clause body: got {}",
+      expr_type
+    );
+    assert!(
+      u.contains(&ErlTypeImpl::new_atom("timeout")),
+      "Receive type must contain 'timeout' from the after body: got {}",
+      expr_type
+    );
+  } else {
+    panic!("Receive type should be a type union, got {}", expr_type)
+  }
+  Ok(())
+}
+
+#[named]
+#[test]
+fn synth_catch() -> IcResult<()> {
+  test_util::start(function_name!(), "synthesize type for catch Expr");
+
+  let module = ErlModuleImpl::new_default();
+  let scope1 = ScopeImpl::new_root_scope(function_name!().to_string());
+  let parsed = test_util::parse_expr(function_name!(), "catch ok");
+  let expr_type = parsed.synthesize(&module, &scope1)?;
+  println!("{}: Inferred {} 🡆 {}", function_name!(), parsed, expr_type);
+
+  if let ErlTypeImpl::Union(u) = expr_type.deref() {
+    assert_eq!(u.types.len(), 3, "Catch type must be a union of 3 types, got {}", expr_type);
+    assert!(
+      u.contains(&ErlTypeImpl::new_atom("ok")),
+      "Catch type must contain the expression type: got {}",
+      expr_type
+    );
+    let exit_tuple = ErlTypeImpl::new_tuple(&[ErlTypeImpl::new_atom("EXIT"), ErlTypeImpl::any()]);
+    assert!(
+      u.contains(&exit_tuple),
+      "Catch type must contain {}: got {}",
+      exit_tuple,
+      expr_type
+    );
+  } else {
+    panic!("Catch type should be a type union, got {}", expr_type)
+  }
+  Ok(())
+}
+
+#[named]
+#[test]
+fn synth_maybe() -> IcResult<()> {
+  test_util::start(function_name!(), "synthesize type for maybe with and without else");
+
+  let module = ErlModuleImpl::new_default();
+  let scope1 = ScopeImpl::new_root_scope(function_name!().to_string());
+  let nodes = test_util::parse_module_unwrap(
+    function_name!(),
+    "-feature(maybe_expr, enable).
+    no_else() -> maybe {ok, X} ?= {ok, 1}, done end.
+    with_else() -> maybe {ok, X} ?= {ok, 1}, done else _ -> failed end.",
+  );
+  let no_else = &nodes[0].as_fn_def().clauses[0].body;
+  let no_else_type = no_else.synthesize(&module, &scope1)?;
+  println!("{}: Inferred {} 🡆 {}", function_name!(), no_else, no_else_type);
+  // `{ok, 1}` always matches `{ok, X}`, so no other value leaves the maybe block
+  assert_eq!(no_else_type, ErlTypeImpl::new_atom("done"), "Maybe type must be 'done'");
+
+  let with_else = &nodes[1].as_fn_def().clauses[0].body;
+  let with_else_type = with_else.synthesize(&module, &scope1)?;
+  println!("{}: Inferred {} 🡆 {}", function_name!(), with_else, with_else_type);
+  let expected = ErlTypeImpl::new_union(&[
+    ErlTypeImpl::new_atom("done"),
+    ErlTypeImpl::new_atom("failed"),
+  ]);
+  assert_eq!(with_else_type, expected, "Maybe-else type must be 'done' | 'failed'");
+  Ok(())
+}
+
 // #[named]
 // #[test]
 // /// Try synthesize type for a function which is a sum of two lists.
@@ -252,3 +346,158 @@ fn synth_multiple_clause_test() -> IcResult<()> {
 
   // TODO: narrowing test, calling 2 clause function with 'one' and with 'two'
 }
+
+#[named]
+#[test]
+fn synth_map_builder() -> IcResult<()> {
+  test_util::start(function_name!(), "synthesize map types with mandatory and optional keys");
+
+  let module = ErlModuleImpl::new_default();
+  let scope1 = ScopeImpl::new_root_scope(function_name!().to_string());
+  let parsed = test_util::parse_expr(function_name!(), "#{a => 1, b => ok}");
+  let map_type = parsed.synthesize(&module, &scope1)?;
+  println!("{}: Inferred {} 🡆 {}", function_name!(), parsed, map_type);
+  if let ErlTypeImpl::Map { members } = map_type.deref() {
+    assert_eq!(members.len(), 2);
+    assert!(members.iter().all(|m| m.mandatory), "Built map keys are always present");
+  } else {
+    panic!("Expected a map type, got {}", map_type)
+  }
+
+  let update = test_util::parse_expr(function_name!(), "#{a => 1}#{a := 2, c => 3}");
+  let update_type = update.synthesize(&module, &scope1)?;
+  println!("{}: Inferred {} 🡆 {}", function_name!(), update, update_type);
+  if let ErlTypeImpl::Map { members } = update_type.deref() {
+    assert_eq!(members.len(), 2, "Key a is updated and key c is added: got {}", update_type);
+  } else {
+    panic!("Expected a map type, got {}", update_type)
+  }
+
+  let comp = test_util::parse_expr(function_name!(), "#{K => {V} || K := V <- #{a => 1}}");
+  let comp_type = comp.synthesize(&module, &scope1)?;
+  println!("{}: Inferred {} 🡆 {}", function_name!(), comp, comp_type);
+  if let ErlTypeImpl::Map { members } = comp_type.deref() {
+    assert_eq!(members.len(), 1);
+    assert!(!members[0].mandatory, "Map comprehension keys are optional");
+  } else {
+    panic!("Expected a map type, got {}", comp_type)
+  }
+  Ok(())
+}
+
+#[named]
+#[test]
+fn synth_all_node_kinds() -> IcResult<()> {
+  test_util::start(function_name!(), "synthesize types for a module using all expression kinds");
+  let input = "-record(point, {x :: integer(), y :: integer()}).
+seq(A) -> B = A + 1, {B, begin C = [B], C end}.
+branch(X) -> if X > 0 -> pos; true -> other end.
+guarded(F) -> try F() of ok -> done catch Class:_Reason -> Class end.
+comprehend(L) -> {[{X} || X <- [1, 2, 3]], << <<Y>> || Y <- L >>}.
+record(P) -> {#point{x = 1, y = 2}, P#point.x, #point.y, P#point{y = 3}}.
+binary(X) -> <<X:8, 1>>.
+logic(A, B) -> {A andalso B, not A, -1, bnot 2, self() ! hello, ok orelse B}.
+funs() -> {fun seq/1, fun lists:map/2}.
+matched({P, Q} = T) -> {P, Q, T}.";
+  let module = test_util::parse_module(function_name!(), input);
+  let ast = module.ast.borrow().clone();
+  let scope = ScopeImpl::new_root_scope(function_name!().to_string());
+  ast.synthesize(&module, &scope)?;
+
+  let synth_fn = |name: &str, arity: usize| -> IcResult<String> {
+    let fn_ast = AstNodeImpl::find_function_def(&ast, &MFArity::new_local(name, arity)).unwrap();
+    let fn_type = fn_ast.synthesize(&module, &scope)?;
+    println!("{}: Synthesized {}/{} 🡆 {}", function_name!(), name, arity, fn_type);
+    Ok(fn_type.to_string())
+  };
+
+  assert_eq!(synth_fn("seq", 1)?, "fun (any()) -> {integer(), strong_list(integer())}");
+  assert_eq!(synth_fn("branch", 1)?, "fun (any()) -> 'pos'|'other'");
+  assert_eq!(synth_fn("guarded", 1)?, "fun (any()) -> 'done'|'error'|'exit'|'throw'");
+  assert_eq!(synth_fn("comprehend", 1)?, "fun (any()) -> {list({1|2|3}), binary()}");
+  assert_eq!(
+    synth_fn("record", 1)?,
+    "fun (any()) -> {#point{x :: 1, y :: 2}, integer(), 3, #point{y :: 3}}"
+  );
+  assert_eq!(synth_fn("binary", 1)?, "fun (any()) -> <<_:16>>");
+  synth_fn("logic", 2)?;
+  synth_fn("funs", 0)?;
+  assert_eq!(
+    synth_fn("matched", 1)?,
+    "fun ({any(), any()}) -> {any(), any(), {any(), any()}}"
+  );
+  Ok(())
+}
+
+#[named]
+#[test]
+/// Without `else`, the values not matching a `?=` pattern are the result of the maybe block
+fn synth_maybe_narrowing() -> IcResult<()> {
+  test_util::start(function_name!(), "synthesize type for maybe without else, narrowed");
+
+  let input = "-feature(maybe_expr, enable).
+found(X) ->
+  maybe
+    {ok, V} ?= if X > 0 -> {ok, X}; true -> error end,
+    {found, V}
+  end.
+inexact(X) ->
+  maybe
+    {ok, X} ?= if X > 0 -> {ok, X}; true -> error end,
+    found
+  end.";
+  let module = test_util::parse_module(function_name!(), input);
+  let ast = module.ast.borrow().clone();
+  let scope = ScopeImpl::new_root_scope(function_name!().to_string());
+
+  let synth_fn = |name: &str| -> IcResult<String> {
+    let fn_ast = AstNodeImpl::find_function_def(&ast, &MFArity::new_local(name, 1)).unwrap();
+    let fn_type = fn_ast.synthesize(&module, &scope)?;
+    println!("{}: Synthesized {}/1 🡆 {}", function_name!(), name, fn_type);
+    Ok(fn_type.to_string())
+  };
+
+  assert_eq!(synth_fn("found")?, "fun (any()) -> 'error'|{'found', any()}");
+  // `X` is bound before the pattern, so `{ok, X}` can fail to match `{ok, X}` values too
+  assert_eq!(synth_fn("inexact")?, "fun (any()) -> {'ok', any()}|'error'|'found'");
+  Ok(())
+}
+
+#[named]
+#[test]
+fn synth_bif_calls() -> IcResult<()> {
+  test_util::start(function_name!(), "synthesize types of calls to erlang BIFs");
+  assert!(!BifTable::erlang().version().is_empty());
+  // Only the auto-imported BIFs are known without the module name
+  let monotonic_time = MFArity::new_local("monotonic_time", 0);
+  assert!(BifTable::erlang().find_spec(&monotonic_time).is_none());
+  assert!(BifTable::erlang()
+    .find_spec(&monotonic_time.clone_with_module("erlang"))
+    .is_some());
+
+  let input = "count(L) -> length(L).
+me() -> self().
+remote(P) -> erlang:send(P, hello).
+name(A) -> atom_to_list(A).
+refs() -> {fun self/0, fun erlang:length/1}.";
+  let module = test_util::parse_module(function_name!(), input);
+  let ast = module.ast.borrow().clone();
+  let scope = ScopeImpl::new_root_scope(function_name!().to_string());
+
+  let synth_fn = |name: &str, arity: usize| -> IcResult<String> {
+    let fn_ast = AstNodeImpl::find_function_def(&ast, &MFArity::new_local(name, arity)).unwrap();
+    let fn_type = fn_ast.synthesize(&module, &scope)?;
+    println!("{}: Synthesized {}/{} 🡆 {}", function_name!(), name, arity, fn_type);
+    Ok(fn_type.to_string())
+  };
+
+  assert_eq!(synth_fn("count", 1)?, "fun (any()) -> non_neg_integer()");
+  assert_eq!(synth_fn("me", 0)?, "fun () -> pid()");
+  assert_eq!(synth_fn("remote", 1)?, "fun (any()) -> any()");
+  assert_eq!(synth_fn("name", 1)?, "fun (any()) -> list(0..1114111)");
+  assert_eq!(
+    synth_fn("refs", 0)?,
+    "fun () -> {fun () -> pid(), fun (list()) -> non_neg_integer()}"
+  );
+  Ok(())
+}
@@ -4,11 +4,19 @@ extern crate libironclad_erlang;
 mod test_util;
 
 use ::function_name::named;
+use libironclad_erlang::erl_syntax::erl_ast::ast_binding::BindingCheck;
+use libironclad_erlang::erl_syntax::erl_ast::node_impl::AstNodeImpl;
 use libironclad_erlang::error::ic_error::IcResult;
-use libironclad_erlang::project::module::module_impl::ErlModuleImpl;
+use libironclad_erlang::project::module::module_impl::{ErlModule, ErlModuleImpl};
 use libironclad_erlang::project::module::scope::scope_impl::ScopeImpl;
+use libironclad_erlang::project::project_impl::ErlProjectImpl;
+use libironclad_erlang::project::ErlProject;
+use libironclad_erlang::source_loc::SourceLoc;
 use libironclad_erlang::typing::check::TypeCheck;
-use libironclad_erlang::typing::erl_type::ErlTypeImpl;
+use libironclad_erlang::typing::coverage::CoverageCheck;
+use libironclad_erlang::typing::erl_type::{ErlType, ErlTypeImpl};
+use libironclad_erlang::typing::spec_check::SpecCheck;
+use libironclad_util::mfarity::MFArity;
 use std::ops::Deref;
 
 #[named]
@@ -185,3 +193,893 @@ fn typing_subtyping_list() -> IcResult<()> {
 
   Ok(())
 }
+
+#[named]
+#[test]
+fn typing_subtyping_structural() -> IcResult<()> {
+  test_util::start(function_name!(), "Typing.Subtyping.Structural");
+  let parse = |t: &str| test_util::parse_type(function_name!(), t);
+
+  // Maps: key and value covariance, mandatory keys must stay mandatory
+  let ab_map = parse("#{a := integer(), b => atom()}");
+  assert!(parse("#{a := 1}").is_subtype_of(&ab_map));
+  assert!(parse("#{a := 1, b := ok}").is_subtype_of(&ab_map));
+  assert!(!parse("#{a => 1}").is_subtype_of(&ab_map)); // a may be missing
+  assert!(!parse("#{a := ok}").is_subtype_of(&ab_map)); // value is not an integer
+  assert!(!parse("#{a := 1, c := 1}").is_subtype_of(&ab_map)); // c is not allowed
+  assert!(parse("#{a := 1}").is_subtype_of(&parse("#{atom() => number()}")));
+  assert!(parse("map()").is_subtype_of(&parse("#{any() => any()}")));
+  assert!(!parse("map()").is_subtype_of(&ab_map));
+
+  // Binaries: `<<_:M, _:_*N>>` sizes must be included in the supertype sizes
+  let bytes = parse("<<_:_*8>>");
+  assert!(parse("<<_:16>>").is_subtype_of(&bytes));
+  assert!(!parse("<<_:12>>").is_subtype_of(&bytes));
+  assert!(parse("<<_:8, _:_*16>>").is_subtype_of(&bytes));
+  assert!(!parse("<<_:8, _:_*4>>").is_subtype_of(&bytes));
+  assert!(bytes.is_subtype_of(&parse("<<_:_*4>>")));
+  assert!(!parse("<<_:0, _:_*8>>").is_subtype_of(&parse("<<_:8, _:_*8>>")));
+  assert!(parse("<<_:16, _:_*8>>").is_subtype_of(&parse("<<_:8, _:_*8>>")));
+  assert!(!parse("binary()").is_subtype_of(&bytes));
+  assert!(bytes.is_subtype_of(&parse("binary()")));
+
+  // Fixed size lists and lists of any length
+  let pair_list: ErlType = ErlTypeImpl::StronglyTypedList {
+    elements: vec![ErlTypeImpl::integer(), parse("atom()")],
+    tail: None,
+  }
+  .into();
+  let empty_list: ErlType = ErlTypeImpl::StronglyTypedList { elements: vec![], tail: None }.into();
+  assert!(pair_list.is_subtype_of(&parse("list(integer() | atom())")));
+  assert!(pair_list.is_subtype_of(&parse("nonempty_list()")));
+  assert!(!empty_list.is_subtype_of(&parse("nonempty_list()")));
+  assert!(!parse("nonempty_list()").is_subtype_of(&pair_list));
+  assert!(!parse("list(integer())").is_subtype_of(&pair_list));
+  assert!(!parse("[]").is_subtype_of(&pair_list));
+  assert!(parse("[]").is_subtype_of(&empty_list));
+  assert!(parse("nonempty_list(integer())").is_subtype_of(&parse("list(number())")));
+  assert!(!parse("list(integer())").is_subtype_of(&parse("nonempty_list(integer())")));
+  assert!(parse("list()").is_subtype_of(&parse("list(any())")));
+
+  Ok(())
+}
+
+#[named]
+#[test]
+fn typing_subtyping_named() -> IcResult<()> {
+  test_util::start(function_name!(), "Typing.Subtyping.Named");
+
+  let input = "-record(point, {x :: integer(), y :: integer()}).
+-record(tagged, {name :: atom(), value}).
+-type coord() :: {integer(), integer()}.
+-type ints() :: [] | {integer(), ints()}.
+-type nums() :: [] | {number(), nums()}.";
+  let module = test_util::parse_module(function_name!(), input);
+  let parse = |t: &str| test_util::parse_type(function_name!(), t);
+
+  // Records and tuples
+  let point = parse("#point{}");
+  assert!(module.is_subtype(&parse("{point, 1, 2}"), &point));
+  assert!(!module.is_subtype(&parse("{point, 1}"), &point));
+  assert!(!module.is_subtype(&parse("{point, 1, ok}"), &point));
+  assert!(!module.is_subtype(&parse("{other, 1, 2}"), &point));
+  assert!(module.is_subtype(&point, &parse("{atom(), integer(), integer()}")));
+  assert!(module.is_subtype(&point, &parse("tuple()")));
+  assert!(module.is_subtype(&parse("#point{x :: 1}"), &point));
+  assert!(!module.is_subtype(&point, &parse("#point{x :: 1}")));
+  assert!(!module.is_subtype(&point, &parse("#tagged{}")));
+  assert!(module.is_subtype(&parse("{tagged, ok, {1, 2}}"), &parse("#tagged{}")));
+  // Without the module the record is unknown
+  assert!(!parse("{point, 1, 2}").is_subtype_of(&point));
+
+  // User-defined types, also nested and recursive
+  assert!(module.is_subtype(&parse("{1, 2}"), &parse("coord()")));
+  assert!(module.is_subtype(&parse("coord()"), &parse("tuple()")));
+  assert!(module.is_subtype(&parse("list(coord())"), &parse("list({number(), number()})")));
+  assert!(!module.is_subtype(&parse("list(coord())"), &parse("list({atom(), number()})")));
+  assert!(module.is_subtype(&parse("ints()"), &parse("nums()")));
+  assert!(!module.is_subtype(&parse("nums()"), &parse("ints()")));
+  assert!(module.is_subtype(&parse("{1, {2, []}}"), &parse("ints()")));
+  Ok(())
+}
+
+#[named]
+#[test]
+fn typing_builtin_types() -> IcResult<()> {
+  test_util::start(function_name!(), "Typing.BuiltinTypes");
+
+  let builtin = |t: &str| test_util::parse_type(function_name!(), t);
+  for name in [
+    "term()",
+    "binary()",
+    "bitstring()",
+    "map()",
+    "string()",
+    "nonempty_string()",
+    "non_neg_integer()",
+    "pos_integer()",
+    "neg_integer()",
+    "byte()",
+    "char()",
+    "iolist()",
+    "iodata()",
+    "mfa()",
+    "module()",
+    "node()",
+    "timeout()",
+    "no_return()",
+    "fun()",
+    "list(atom())",
+    "nonempty_list(atom())",
+    "maybe_improper_list(atom(), pid())",
+    "map(atom(), integer())",
+  ] {
+    let ty = builtin(name);
+    assert!(
+      !matches!(ty.deref(), ErlTypeImpl::UserDefinedType { .. }),
+      "{} must be a built-in type, got {}",
+      name,
+      ty
+    );
+  }
+  // Types with a module prefix are not built-in
+  assert!(matches!(builtin("mymod:byte()").deref(), ErlTypeImpl::UserDefinedType { .. }));
+
+  let byte = builtin("byte()");
+  assert!(builtin("255").is_subtype_of(&byte));
+  assert!(!builtin("256").is_subtype_of(&byte));
+  assert!(builtin("1..10").is_subtype_of(&byte));
+  assert!(byte.is_subtype_of(&builtin("char()")));
+  assert!(byte.is_subtype_of(&builtin("non_neg_integer()")));
+  assert!(!byte.is_subtype_of(&builtin("pos_integer()")));
+  assert!(builtin("pos_integer()").is_subtype_of(&builtin("non_neg_integer()")));
+  assert!(!builtin("-1").is_subtype_of(&builtin("non_neg_integer()")));
+  assert!(builtin("-1").is_subtype_of(&builtin("neg_integer()")));
+  assert!(!builtin("integer()").is_subtype_of(&byte));
+
+  let timeout = builtin("timeout()");
+  assert!(builtin("infinity").is_subtype_of(&timeout));
+  assert!(builtin("1000").is_subtype_of(&timeout));
+  assert!(!builtin("atom()").is_subtype_of(&timeout));
+
+  assert!(builtin("nonempty_string()").is_subtype_of(&builtin("string()")));
+  assert!(!builtin("string()").is_subtype_of(&builtin("nonempty_string()")));
+  assert!(builtin("string()").is_subtype_of(&builtin("list(integer())")));
+  assert!(builtin("binary()").is_subtype_of(&builtin("iodata()")));
+  assert!(builtin("term()").is_subtype_of(&builtin("any()")));
+  Ok(())
+}
+
+#[named]
+#[test]
+fn typing_parametric_types() -> IcResult<()> {
+  test_util::start(function_name!(), "Typing.ParametricTypes");
+
+  let input = "-record(point, {x :: integer(), y :: integer()}).
+-type pair(A, B) :: {A, B}.
+-type option(T) :: T | undefined.
+-type tree(T) :: leaf | {node, tree(T), T, tree(T)}.
+-type points() :: [#point{}].
+-type loop() :: loop2().
+-type loop2() :: loop().";
+  let module = test_util::parse_module(function_name!(), input);
+  let parse = |t: &str| test_util::parse_type(function_name!(), t);
+  let resolve = |t: &str| module.resolve_user_type(&SourceLoc::None, &parse(t));
+  let error_message = |result: IcResult<ErlType>| match result {
+    Ok(ty) => panic!("Expected an error, got {}", ty),
+    Err(e) => e.get_message().to_string(),
+  };
+
+  // Type arguments are substituted for the parameters
+  let pair = resolve("pair(integer(), atom())")?;
+  assert_eq!(pair.to_string(), "{integer(), atom()}");
+  assert!(module.is_subtype(&parse("{1, ok}"), &pair));
+  assert!(!module.is_subtype(&parse("{ok, 1}"), &pair));
+  assert!(module.is_subtype(&parse("pair(1, ok)"), &parse("pair(integer(), atom())")));
+  assert!(module.is_subtype(&parse("undefined"), &parse("option(integer())")));
+  assert!(module.is_subtype(&parse("1..5"), &parse("option(integer())")));
+  assert!(!module.is_subtype(&parse("atom()"), &parse("option(integer())")));
+
+  // Recursive types are expanded as deep as the checked value
+  let int_tree = parse("tree(integer())");
+  assert!(module.is_subtype(&parse("{node, leaf, 1, {node, leaf, 2, leaf}}"), &int_tree));
+  assert!(!module.is_subtype(&parse("{node, leaf, 1, {node, leaf, ok, leaf}}"), &int_tree));
+  assert!(module.is_subtype(&parse("tree(1..3)"), &int_tree));
+  assert!(!module.is_subtype(&int_tree, &parse("tree(1..3)")));
+
+  // Record references use the record definition, pinned fields are narrowed
+  assert!(module.is_subtype(&parse("[{point, 1, 2}]"), &parse("points()")));
+  let point = module.resolve_record_ref(&SourceLoc::None, "point", &[])?;
+  assert!(module.is_subtype(&parse("#point{x :: 0..10}"), &point));
+
+  // Errors
+  assert!(error_message(resolve("pair(integer())"))
+    .starts_with("Type arity mismatch: pair/1 is used, but the type is defined with arity 2"));
+  assert!(
+    error_message(resolve("undefined_type()")).starts_with("Type not found: undefined_type/0")
+  );
+  assert!(
+    error_message(resolve("loop()")).starts_with("Type is defined only through itself: loop/0")
+  );
+  assert!(error_message(module.resolve_record_ref(&SourceLoc::None, "nope", &[]))
+    .starts_with("Record not found: #nope"));
+  let bad_pin = match parse("#point{z :: 1}").deref() {
+    ErlTypeImpl::RecordRef { tag, pins } => module.resolve_record_ref(&SourceLoc::None, tag, pins),
+    other => panic!("Expected a record reference, got {}", other),
+  };
+  assert!(error_message(bad_pin).starts_with("Record field not found: #point has no field z"));
+  Ok(())
+}
+
+#[named]
+#[test]
+/// An opaque type is transparent in its module, and abstract in other modules.
+/// Remote types must be exported.
+fn typing_opaque_remote_type() -> IcResult<()> {
+  test_util::start(function_name!(), "Typing.OpaqueRemoteType");
+
+  let project: ErlProject = ErlProjectImpl::default().into();
+  let mod_a = test_util::parse_module_in_project(
+    &project,
+    "opaque_a",
+    "-export_type([secret/0, plain/0]).
+    -opaque secret() :: {secret, integer()}.
+    -type plain() :: {plain, integer()}.
+    -type hidden() :: atom().",
+  );
+  let mod_b = test_util::parse_module_in_project(&project, "opaque_b", "");
+  let scope = ScopeImpl::new_root_scope(function_name!().to_string());
+
+  let secret_value = test_util::parse_expr(function_name!(), "{secret, 1}");
+  let local_secret = test_util::parse_type(function_name!(), "secret()");
+  assert!(
+    TypeCheck::check(&mod_a, &scope, &secret_value, &local_secret)?,
+    "Opaque type must be transparent in its own module"
+  );
+
+  let remote_secret = test_util::parse_type(function_name!(), "opaque_a:secret()");
+  let err = TypeCheck::check(&mod_b, &scope, &secret_value, &remote_secret)
+    .expect_err("Opaque type structure must not be visible in other modules");
+  println!("Expected error: {}", err);
+
+  let plain_value = test_util::parse_expr(function_name!(), "{plain, 1}");
+  let remote_plain = test_util::parse_type(function_name!(), "opaque_a:plain()");
+  assert!(
+    TypeCheck::check(&mod_b, &scope, &plain_value, &remote_plain)?,
+    "Exported remote type must be resolved"
+  );
+
+  let atom_value = test_util::parse_expr(function_name!(), "hello");
+  let remote_hidden = test_util::parse_type(function_name!(), "opaque_a:hidden()");
+  assert!(
+    TypeCheck::check(&mod_b, &scope, &atom_value, &remote_hidden).is_err(),
+    "Remote type which is not exported must not be resolved"
+  );
+  Ok(())
+}
+
+#[named]
+#[test]
+/// Different nominal types are not compatible, even if their structure is the same
+fn typing_nominal_types() -> IcResult<()> {
+  test_util::start(function_name!(), "Typing.NominalTypes");
+
+  let project: ErlProject = ErlProjectImpl::default().into();
+  let module = test_util::parse_module_in_project(
+    &project,
+    "nominal_a",
+    "-nominal meters() :: integer().
+    -nominal feet() :: integer().
+    -spec to_feet(meters()) -> feet().
+    to_feet(M) -> M * 3.",
+  );
+  let feet = module
+    .root_scope
+    .user_types
+    .get(&MFArity::new_local("feet", 0))
+    .unwrap();
+  assert!(feet.is_nominal(), "feet() must be a nominal type");
+
+  let meters_t = test_util::parse_type(function_name!(), "meters()");
+  let feet_t = test_util::parse_type(function_name!(), "feet()");
+  assert!(module
+    .check_opaque_use(&SourceLoc::None, &meters_t, &feet_t)
+    .is_err());
+  assert!(module
+    .check_opaque_use(&SourceLoc::None, &meters_t, &meters_t)
+    .is_ok());
+  Ok(())
+}
+
+#[named]
+#[test]
+/// Function definitions are checked against their specs
+fn typing_spec_check() -> IcResult<()> {
+  test_util::start(function_name!(), "Typing.SpecCheck");
+
+  let input = "-spec inc(integer()) -> integer().
+inc(X) -> X + 1.
+-spec name(ok | error) -> atom().
+name(ok) -> ok;
+name(5) -> error.
+-spec bad() -> atom().
+bad() -> 42.
+-spec ghost(integer()) -> ok.
+-spec wrong(integer()) -> ok.
+wrong(A, B) -> ok.";
+  let module = test_util::parse_module(function_name!(), input);
+  SpecCheck::verify_module(&module)?;
+
+  let errors: Vec<String> = module
+    .errors
+    .data
+    .read()
+    .unwrap()
+    .iter()
+    .map(|e| e.msg.clone())
+    .collect();
+  let warnings: Vec<String> = module
+    .warnings
+    .data
+    .read()
+    .unwrap()
+    .iter()
+    .map(|e| e.msg.clone())
+    .collect();
+  println!("Errors: {:?}\nWarnings: {:?}", errors, warnings);
+
+  assert_eq!(errors.len(), 3, "Expected 3 errors, got {:?}", errors);
+  assert!(errors[0].starts_with("Return type mismatch: bad/0 returns 42"));
+  assert_eq!(errors[1], "Spec for undefined function: ghost/1");
+  assert!(errors[2].starts_with("Spec arity mismatch: spec for wrong/1"));
+
+  assert_eq!(warnings.len(), 1, "Expected 1 warning, got {:?}", warnings);
+  assert!(warnings[0].starts_with("Clause can never match the spec: name(5)"));
+  Ok(())
+}
+
+#[named]
+#[test]
+/// Results of calls to modules outside of the project are `any()`, and fit any spec they overlap
+fn typing_spec_check_any() -> IcResult<()> {
+  test_util::start(function_name!(), "Typing.SpecCheck with any() results");
+
+  let input = "-spec a(list()) -> integer().
+a(L) -> lists:sum(L).
+-spec m(map()) -> integer().
+m(M) -> maps:get(key, M).
+-spec call(fun()) -> ok.
+call(F) -> F().
+-spec pair(list()) -> {integer(), ok}.
+pair(L) -> {lists:sum(L), ok}.
+-spec wrong(list()) -> atom().
+wrong(L) -> {lists:sum(L), ok}.";
+  let module = test_util::parse_module(function_name!(), input);
+  SpecCheck::verify_module(&module)?;
+
+  let errors: Vec<String> = module
+    .errors
+    .data
+    .read()
+    .unwrap()
+    .iter()
+    .map(|e| e.msg.clone())
+    .collect();
+  println!("Errors: {:?}", errors);
+
+  assert_eq!(errors.len(), 1, "Expected 1 error, got {:?}", errors);
+  assert!(errors[0].starts_with("Return type mismatch: wrong/1 returns {any(), 'ok'}"));
+  Ok(())
+}
+
+#[named]
+#[test]
+/// An error in one spec is reported, and the other specs are still checked
+fn typing_spec_check_continues() -> IcResult<()> {
+  test_util::start(function_name!(), "Typing.SpecCheck continues after an error");
+
+  let input = "-spec b() -> atom().
+b() -> 1.
+-spec c() -> atom().
+c() -> 2.
+-spec g() -> undefined_t().
+g() -> ok.
+-spec e() -> atom().
+e() -> 3.
+-spec k(undefined_t()) -> ok.
+k(a) -> ok.
+-spec n(a | b) -> ok.
+n(a) -> ok.";
+  let module = test_util::parse_module(function_name!(), input);
+  SpecCheck::verify_module(&module)?;
+  CoverageCheck::verify_module(&module)?;
+
+  let errors: Vec<String> = module
+    .errors
+    .data
+    .read()
+    .unwrap()
+    .iter()
+    .map(|e| e.msg.clone())
+    .collect();
+  let warnings: Vec<String> = module
+    .warnings
+    .data
+    .read()
+    .unwrap()
+    .iter()
+    .map(|e| e.msg.clone())
+    .collect();
+  println!("Errors: {:?}\nWarnings: {:?}", errors, warnings);
+
+  let expected = [
+    "Return type mismatch: b/0",
+    "Return type mismatch: c/0",
+    "Return type mismatch: e/0",
+    "Type not found: undefined_t/0",
+    "Type not found: undefined_t/0",
+  ];
+  assert_eq!(errors.len(), expected.len(), "Expected {:?}, got {:?}", expected, errors);
+  for (err, prefix) in errors.iter().zip(expected.iter()) {
+    assert!(err.starts_with(prefix), "Expected {:?}, got {:?}", prefix, err);
+  }
+  assert_eq!(warnings.len(), 1, "Expected 1 warning, got {:?}", warnings);
+  assert!(warnings[0].starts_with("Non-exhaustive clauses: n/1 has no clause matching 'b'"));
+  Ok(())
+}
+
+#[named]
+#[test]
+/// Binaries are checked against `<<_:M, _:_*N>>` specs by their bit sizes
+fn typing_spec_check_binary() -> IcResult<()> {
+  test_util::start(function_name!(), "Typing.SpecCheck binary sizes");
+
+  let input = "-spec i() -> <<_:8, _:_*8>>.
+i() -> <<1>>.
+-spec text() -> <<_:_*8>>.
+text() -> <<\"abc\", 1:16, 2.0/float>>.
+-spec bits(integer()) -> <<_:4, _:_*4>>.
+bits(N) -> <<1:4, 2:N/unit:8>>.
+-spec nibble() -> <<_:_*8>>.
+nibble() -> <<1:4>>.";
+  let module = test_util::parse_module(function_name!(), input);
+  SpecCheck::verify_module(&module)?;
+
+  let errors: Vec<String> = module
+    .errors
+    .data
+    .read()
+    .unwrap()
+    .iter()
+    .map(|e| e.msg.clone())
+    .collect();
+  println!("Errors: {:?}", errors);
+
+  assert_eq!(errors.len(), 1, "Expected 1 error, got {:?}", errors);
+  assert!(errors[0].starts_with("Return type mismatch: nibble/0 returns <<_:4>>"));
+  Ok(())
+}
+
+/// Synthesize the function type for `name/arity` in the module
+fn synthesize_fn(module: &ErlModule, name: &str, arity: usize) -> IcResult<ErlType> {
+  let fn_def = module
+    .root_scope
+    .fn_defs
+    .get(&MFArity::new_local(name, arity))
+    .unwrap();
+  let scope = ScopeImpl::new_root_scope(name.to_string());
+  fn_def.synthesize(module, &scope)
+}
+
+#[named]
+#[test]
+fn typing_narrow_guards() -> IcResult<()> {
+  test_util::start(function_name!(), "Typing.Narrow.Guards");
+
+  let input = "pos(X) when is_integer(X), X > 0 -> X.
+either(X) when is_atom(X); is_float(X) -> X.
+small(X) when is_integer(X) andalso X >= 1 andalso 10 >= X -> X.
+first(T) -> case T of {_A, _B} when element(1, T) =:= ok -> T end.";
+  let module = test_util::parse_module(function_name!(), input);
+
+  let pos_t = synthesize_fn(&module, "pos", 1)?;
+  assert_eq!(pos_t.to_string(), "fun (pos_integer()) -> pos_integer()");
+
+  let either_t = synthesize_fn(&module, "either", 1)?;
+  let expected = test_util::parse_type(function_name!(), "atom() | float()");
+  assert!(
+    if let ErlTypeImpl::Fn(fn_type) = either_t.deref() {
+      fn_type.clause(0).ret_ty().is_subtype_of(&expected)
+        && expected.is_subtype_of(fn_type.clause(0).ret_ty())
+    } else {
+      false
+    },
+    "Expected atom()|float() return, got {}",
+    either_t
+  );
+
+  let small_t = synthesize_fn(&module, "small", 1)?;
+  assert_eq!(small_t.to_string(), "fun (1..10) -> 1..10");
+
+  let first_t = synthesize_fn(&module, "first", 1)?;
+  assert_eq!(first_t.to_string(), "fun (any()) -> {'ok', any()}");
+  Ok(())
+}
+
+#[named]
+#[test]
+fn typing_narrow_patterns() -> IcResult<()> {
+  test_util::start(function_name!(), "Typing.Narrow.Patterns");
+
+  let input = "flip(B) when is_boolean(B) -> case B of true -> 1; Other -> Other end.
+sign(N) when is_integer(N), N >= 0 -> case N of 0 -> zero; M -> M end.
+clauses(0) -> zero;
+clauses(X) when is_integer(X) -> X.
+same(X, Y) -> case Y of X -> same; _ -> other end.";
+  let module = test_util::parse_module(function_name!(), input);
+
+  let flip_t = synthesize_fn(&module, "flip", 1)?;
+  assert_eq!(flip_t.to_string(), "fun (boolean()) -> 1|'false'");
+
+  let sign_t = synthesize_fn(&module, "sign", 1)?;
+  assert_eq!(sign_t.to_string(), "fun (non_neg_integer()) -> 'zero'|pos_integer()");
+
+  let clauses_t = synthesize_fn(&module, "clauses", 1)?;
+  println!("Synthesized clauses/1: {}", clauses_t);
+  assert!(
+    if let ErlTypeImpl::Fn(fn_type) = clauses_t.deref() {
+      fn_type.clause(1).ret_ty().is_integer()
+    } else {
+      false
+    },
+    "Expected the second clause to return integer(), got {}",
+    clauses_t
+  );
+
+  // A variable bound before the case only matches its value, other values reach the next clause
+  let same_t = synthesize_fn(&module, "same", 2)?;
+  assert_eq!(same_t.to_string(), "fun (any(), any()) -> 'same'|'other'");
+  Ok(())
+}
+
+#[named]
+#[test]
+fn typing_destructure() -> IcResult<()> {
+  test_util::start(function_name!(), "Typing.Destructure");
+
+  let input = "-record(point, {x :: integer(), y :: integer()}).
+unpack() -> {A, [B | C]} = {1, [ok, 2]}, {A, B, C}.
+get_x(P) -> #point{x = X} = P, X.
+from_map() -> #{a := V} = #{a => 1}, V.
+bits(B) -> <<X:4, Rest/binary>> = B, {X, Rest}.
+alias({ok, _} = T) -> T.
+bad() -> {A} = {1, 2}, A.";
+  let module = test_util::parse_module(function_name!(), input);
+
+  let unpack_t = synthesize_fn(&module, "unpack", 0)?;
+  assert_eq!(unpack_t.to_string(), "fun () -> {1, 'ok', strong_list(2)}");
+
+  let get_x_t = synthesize_fn(&module, "get_x", 1)?;
+  assert_eq!(get_x_t.to_string(), "fun (any()) -> integer()");
+
+  let from_map_t = synthesize_fn(&module, "from_map", 0)?;
+  assert_eq!(from_map_t.to_string(), "fun () -> 1");
+
+  let bits_t = synthesize_fn(&module, "bits", 1)?;
+  assert_eq!(bits_t.to_string(), "fun (any()) -> {0..15, binary()}");
+
+  let alias_t = synthesize_fn(&module, "alias", 1)?;
+  assert_eq!(alias_t.to_string(), "fun ({'ok', any()}) -> {'ok', any()}");
+
+  match synthesize_fn(&module, "bad", 0) {
+    Ok(t) => panic!("Expected a type error, got {}", t),
+    Err(e) => assert!(
+      e.get_message().starts_with("Pattern can never match: {A}"),
+      "Unexpected error {}",
+      e.get_message()
+    ),
+  }
+  Ok(())
+}
+
+#[named]
+#[test]
+fn typing_integer_arith() -> IcResult<()> {
+  test_util::start(function_name!(), "Typing.IntegerArith");
+
+  let input = "add(X) when is_integer(X), X >= 1, X =< 10 -> X + 5.
+sub(X) when is_integer(X), X >= 1, X =< 10 -> 100 - X.
+mul(X) when is_integer(X), X >= 1, X =< 10 -> X * -2.
+half(X) when is_integer(X), X >= 1, X =< 10 -> X div 2.
+neg(X) when is_integer(X), X >= 1, X =< 10 -> -X.
+rem8(X) when is_integer(X), X >= 0 -> X rem 8.
+big() -> 9223372036854775807 + 1.
+count(0) -> 0;
+count(N) when is_integer(N), N > 0 -> count(N - 1) + 1.
+-spec byte_of(integer()) -> byte().
+byte_of(X) -> X rem 256.
+-spec low(non_neg_integer()) -> byte().
+low(X) -> X band 255.";
+  let module = test_util::parse_module(function_name!(), input);
+
+  let fn_type_str = |name: &str, arity: usize| -> IcResult<String> {
+    let fn_type = synthesize_fn(&module, name, arity)?;
+    println!("Synthesized {}/{}: {}", name, arity, fn_type);
+    Ok(fn_type.to_string())
+  };
+  assert_eq!(fn_type_str("add", 1)?, "fun (1..10) -> 6..15");
+  assert_eq!(fn_type_str("sub", 1)?, "fun (1..10) -> 90..99");
+  assert_eq!(fn_type_str("mul", 1)?, "fun (1..10) -> -20..-2");
+  assert_eq!(fn_type_str("half", 1)?, "fun (1..10) -> 0..5");
+  assert_eq!(fn_type_str("neg", 1)?, "fun (1..10) -> -10..-1");
+  assert_eq!(fn_type_str("rem8", 1)?, "fun (non_neg_integer()) -> 0..7");
+  assert_eq!(fn_type_str("big", 0)?, "fun () -> ↑9223372036854775808");
+  assert_eq!(fn_type_str("count", 1)?, "fun (0) -> 0; (pos_integer()) -> pos_integer()");
+
+  SpecCheck::verify_module(&module)?;
+  let errors: Vec<String> = module
+    .errors
+    .data
+    .read()
+    .unwrap()
+    .iter()
+    .map(|e| e.msg.clone())
+    .collect();
+  println!("Errors: {:?}", errors);
+  assert_eq!(errors.len(), 1, "Expected 1 error, got {:?}", errors);
+  assert!(errors[0].starts_with("Return type mismatch: byte_of/1 returns -255..255"));
+  Ok(())
+}
+
+/// Collect warning messages from the module
+fn module_warnings(module: &ErlModule) -> Vec<String> {
+  module
+    .warnings
+    .data
+    .read()
+    .unwrap()
+    .iter()
+    .map(|e| e.msg.clone())
+    .collect()
+}
+
+#[named]
+#[test]
+fn typing_coverage() -> IcResult<()> {
+  test_util::start(function_name!(), "Typing.Coverage");
+
+  let input = "-record(r, {a :: integer()}).
+-type tree(A) :: leaf | {node, tree(A), A, tree(A)}.
+-spec name(ok | error | timeout) -> atom().
+name(ok) -> good;
+name(error) -> bad.
+-spec sign(integer()) -> atom().
+sign(N) when N > 0 -> pos;
+sign(N) when N < 0 -> neg;
+sign(0) -> zero.
+shadow(X) -> X;
+shadow(0) -> zero.
+flip(B) when is_boolean(B) ->
+  case B of
+    true -> 1;
+    false -> 0;
+    Other -> Other
+  end.
+half(B) when is_boolean(B) ->
+  case B of
+    true -> 1
+  end.
+check(X) when is_integer(X) ->
+  if X > 0 -> pos;
+     X =< 0 -> non_pos
+  end.
+always() ->
+  if true -> a;
+     false -> b
+  end.
+same(X, Y) ->
+  case Y of
+    X -> same;
+    _ -> other
+  end.
+-spec rec(#r{}) -> ok.
+rec(_R) -> ok.
+-spec walk(tree(integer())) -> integer().
+walk(leaf) -> 0;
+walk({node, L, V, R}) -> walk(L) + V + walk(R).";
+  let module = test_util::parse_module(function_name!(), input);
+  CoverageCheck::verify_module(&module)?;
+
+  let warnings = module_warnings(&module);
+  println!("Warnings: {:?}", warnings);
+
+  let expected = [
+    "Redundant clause: 'false' is shadowed by an earlier true clause",
+    "Redundant clause: Other is shadowed by earlier clauses",
+    "Non-exhaustive clauses: case has no clause matching 'false'",
+    "Non-exhaustive clauses: name/1 has no clause matching 'timeout'",
+    "Redundant clause: shadow(0) -> 'zero' is shadowed by earlier clauses",
+  ];
+  assert_eq!(warnings.len(), expected.len(), "Expected {:?}, got {:?}", expected, warnings);
+  for (warning, expected_warning) in warnings.iter().zip(expected.iter()) {
+    assert!(
+      warning.starts_with(expected_warning),
+      "Expected warning {}, got {}",
+      expected_warning,
+      warning
+    );
+  }
+  Ok(())
+}
+
+#[named]
+#[test]
+/// Unused, `_`-prefixed, unsafe, shadowed and unbound variables
+fn typing_variable_bindings() -> IcResult<()> {
+  test_util::start(function_name!(), "Typing.VariableBindings");
+
+  let input = "unused(X, _Y) -> Z = 1, ok.
+underscore(_Value) -> _Value + 1.
+unsafe(X) ->
+  case X of
+    1 -> Y = a;
+    _ -> ok
+  end,
+  Y.
+safe(X) ->
+  case X of
+    1 -> Y = a;
+    _ -> Y = b
+  end,
+  Y.
+shadow(X) ->
+  F = fun(X) -> X end,
+  [X || X <- F(X)].
+unbound() -> Missing.";
+  let module = test_util::parse_module(function_name!(), input);
+  BindingCheck::verify_module(&module);
+
+  let warnings = module_warnings(&module);
+  println!("Warnings: {:?}", warnings);
+  let expected = [
+    "Variable 'X' shadowed in 'fun'",
+    "Variable 'X' shadowed in 'generate'",
+    "Variable '_Value' is used after being bound",
+    "Variable 'Y' unsafe in 'case' (line 5)",
+    "Variable 'X' is unused",
+    "Variable 'Z' is unused",
+  ];
+  assert_eq!(warnings, expected);
+
+  let errors: Vec<String> = module
+    .errors
+    .data
+    .read()
+    .unwrap()
+    .iter()
+    .map(|e| e.msg.clone())
+    .collect();
+  assert_eq!(errors, ["Variable 'Missing' is unbound"]);
+  Ok(())
+}
+
+#[named]
+#[test]
+/// Calls to undefined local functions, and to undefined or not exported `?MODULE:fun()` are errors
+fn typing_local_calls() -> IcResult<()> {
+  test_util::start(function_name!(), "Typing.LocalCalls");
+
+  let input = "-export([exported/0]).
+exported() -> ok.
+hidden() -> ok.
+local() -> hidden().
+own() -> ?MODULE:exported().
+undefined() -> nope().
+own_undefined() -> ?MODULE:nope().
+own_hidden() -> ?MODULE:hidden().
+bifs() -> {monotonic_time(), send(self(), x), garbage_collect()}.";
+  let module = test_util::parse_module(function_name!(), input);
+  let ast = module.ast.borrow().clone();
+  let scope = ScopeImpl::new_root_scope(function_name!().to_string());
+  let synth_fn = |name: &str| -> IcResult<String> {
+    let fn_ast = AstNodeImpl::find_function_def(&ast, &MFArity::new_local(name, 0))?;
+    let fn_type = fn_ast.synthesize(&module, &scope)?;
+    println!("{}: Synthesized {} 🡆 {}", function_name!(), name, fn_type);
+    Ok(fn_type.to_string())
+  };
+
+  assert_eq!(synth_fn("local")?, "fun () -> 'ok'");
+  assert_eq!(synth_fn("own")?, "fun () -> 'ok'");
+
+  let error_message = |name: &str| match synth_fn(name) {
+    Ok(ty) => panic!("Expected an error for {}, got {}", name, ty),
+    Err(e) => e.get_message().to_string(),
+  };
+  assert!(error_message("undefined").contains("Function not found: nope/0"));
+  assert!(error_message("own_undefined").contains("Function not found: typing_local_calls:nope/0"));
+  assert!(
+    error_message("own_hidden").contains("Function is not exported: typing_local_calls:hidden/0")
+  );
+  // Only the auto-imported BIFs are known without the `erlang:` prefix
+  assert!(error_message("bifs").contains("Function not found: monotonic_time/0"));
+  Ok(())
+}
+
+#[named]
+#[test]
+/// Remote and imported calls are resolved with the exports and specs of the called module
+fn typing_remote_calls() -> IcResult<()> {
+  test_util::start(function_name!(), "Typing.RemoteCalls");
+
+  let project: ErlProject = ErlProjectImpl::default().into();
+  let caller = test_util::parse_module_in_project(
+    &project,
+    "remote_b",
+    "-import(remote_a, [double/1]).
+    sum(X) -> remote_a:add(X, 1).
+    twice(X) -> double(X).
+    tagged() -> remote_a:tag().
+    ref() -> fun remote_a:add/2.
+    hidden() -> remote_a:helper().
+    missing() -> remote_a:nothing(1).
+    unknown() -> not_loaded:call().
+    everything() -> remote_c:anything().",
+  );
+  test_util::parse_module_in_project(
+    &project,
+    "remote_a",
+    "-export([add/2, double/1, tag/0]).
+    -spec add(integer(), integer()) -> integer().
+    add(A, B) -> A + B.
+    double(X) -> remote_c:anything() + X.
+    tag() -> {tag, helper()}.
+    helper() -> ok.",
+  );
+  test_util::parse_module_in_project(
+    &project,
+    "remote_c",
+    "-compile([export_all]).
+    anything() -> 42.",
+  );
+  let ast = caller.ast.borrow().clone();
+  let scope = ScopeImpl::new_root_scope(function_name!().to_string());
+  let synth_fn = |name: &str| -> IcResult<String> {
+    let fn_ast = AstNodeImpl::find_function_def(&ast, &MFArity::new_local(name, 0))
+      .or_else(|_| AstNodeImpl::find_function_def(&ast, &MFArity::new_local(name, 1)))?;
+    let fn_type = fn_ast.synthesize(&caller, &scope)?;
+    println!("{}: Synthesized {} 🡆 {}", function_name!(), name, fn_type);
+    Ok(fn_type.to_string())
+  };
+
+  assert_eq!(synth_fn("sum")?, "fun (any()) -> integer()");
+  assert_eq!(synth_fn("twice")?, "fun (any()) -> integer()");
+  assert_eq!(synth_fn("tagged")?, "fun () -> {'tag', 'ok'}");
+  assert_eq!(synth_fn("ref")?, "fun () -> fun (integer(), integer()) -> integer()");
+  assert_eq!(synth_fn("unknown")?, "fun () -> any()");
+  assert_eq!(synth_fn("everything")?, "fun () -> 42");
+
+  let error_message = |name: &str| match synth_fn(name) {
+    Ok(ty) => panic!("Expected an error for {}, got {}", name, ty),
+    Err(e) => e.get_message().to_string(),
+  };
+  assert!(error_message("hidden").contains("Function is not exported: remote_a:helper/0"));
+  assert!(error_message("missing").contains("Function not found: remote_a:nothing/1"));
+
+  // The synthesized type of a remote function is stored in its module, and reused
+  let remote_a = project.modules.get(&"remote_a".to_string()).unwrap();
+  let tag_type = remote_a
+    .root_scope
+    .fn_types
+    .get(&MFArity::new_local("tag", 0))
+    .unwrap();
+  assert_eq!(tag_type.to_string(), "fun () -> {'tag', 'ok'}");
+
+  // Called modules are checked before their callers
+  let order: Vec<String> = project
+    .modules_in_dependency_order()
+    .iter()
+    .map(|m| m.get_name())
+    .collect();
+  assert_eq!(order, vec!["remote_c", "remote_a", "remote_b"]);
+  Ok(())
+}
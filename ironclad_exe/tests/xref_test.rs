@@ -0,0 +1,111 @@
+extern crate function_name;
+extern crate libironclad_erlang;
+
+mod test_util;
+
+use ::function_name::named;
+use libironclad_erlang::error::ic_error::IcResult;
+use libironclad_erlang::project::project_impl::ErlProjectImpl;
+use libironclad_erlang::project::xref::XrefKind;
+use libironclad_erlang::project::ErlProject;
+
+#[named]
+#[test]
+/// Undefined calls, and unused functions, types, records and imports across the project
+fn xref_project() -> IcResult<()> {
+  test_util::start(function_name!(), "Xref.Project");
+
+  let project: ErlProject = ErlProjectImpl::default().into();
+  test_util::parse_module_in_project(
+    &project,
+    "xref_lib",
+    "-export([used/1, unused/0, recursive/1]).
+    -export_type([shape/0]).
+    -record(point, {x :: coord(), y :: coord()}).
+    -record(dead, {a}).
+    -type coord() :: integer().
+    -type shape() :: circle | square.
+    -type tree() :: leaf | {node, tree()}.
+    used(X) -> helper(X).
+    unused() -> #point{x = 1, y = 2}.
+    recursive(0) -> done;
+    recursive(N) -> recursive(N - 1).
+    helper(X) -> X.
+    dead_code() -> dead_code().",
+  );
+  test_util::parse_module_in_project(
+    &project,
+    "xref_app",
+    "-export([start/0]).
+    -import(xref_lib, [used/1, recursive/1]).
+    -import(lists, [reverse/1]).
+    start() ->
+      used(1),
+      xref_lib:helper(2),
+      xref_lib:missing(),
+      not_defined(3),
+      length([1]),
+      other_app:run(),
+      F = fun local/0,
+      F().
+    local() -> ok.",
+  );
+
+  let report = project.xref();
+  println!("{}", report);
+  let names = |kind: XrefKind| report.names_of_kind(kind);
+
+  assert_eq!(
+    names(XrefKind::UndefinedCall),
+    vec!["not_defined/1", "xref_lib:helper/1", "xref_lib:missing/0"]
+  );
+  assert_eq!(
+    names(XrefKind::UnusedExport),
+    vec![
+      "xref_app:start/0",
+      "xref_lib:recursive/1",
+      "xref_lib:unused/0"
+    ]
+  );
+  assert_eq!(names(XrefKind::UnusedFunction), vec!["xref_lib:dead_code/0"]);
+  assert_eq!(names(XrefKind::UnusedType), vec!["tree/0"]);
+  assert_eq!(names(XrefKind::UnusedRecord), vec!["#dead"]);
+  assert_eq!(names(XrefKind::UnusedImport), vec!["lists:reverse/1", "xref_lib:recursive/1"]);
+
+  // One finding per line, with tab separated fields
+  let first_line = report.to_string().lines().next().unwrap().to_string();
+  assert_eq!(
+    first_line.split('\t').take(3).collect::<Vec<_>>(),
+    vec!["undefined_call", "xref_app", "not_defined/1"]
+  );
+  assert!(
+    first_line.ends_with("xref_app.erl:9:7"),
+    "Location expected, got {}",
+    first_line
+  );
+  Ok(())
+}
+
+#[named]
+#[test]
+/// Only the auto-imported BIFs can be called without the `erlang:` prefix
+fn xref_auto_import() -> IcResult<()> {
+  test_util::start(function_name!(), "Xref.AutoImport");
+
+  let project: ErlProject = ErlProjectImpl::default().into();
+  test_util::parse_module_in_project(
+    &project,
+    "xref_bifs",
+    "-export([a/0, b/0]).
+    a() -> {monotonic_time(), send(self(), x), garbage_collect()}.
+    b() -> {erlang:monotonic_time(), erlang:send(self(), x), length([]), self()}.",
+  );
+
+  let report = project.xref();
+  println!("{}", report);
+  assert_eq!(
+    report.names_of_kind(XrefKind::UndefinedCall),
+    vec!["garbage_collect/0", "monotonic_time/0", "send/2"]
+  );
+  Ok(())
+}
@@ -0,0 +1,553 @@
+//! Variable binding analysis. Finds variables which are used but never bound, bound but never
+//! used, `_`-prefixed variables which are used after binding, variables bound only in some
+//! branches of a `case`-like expression and used after it, and variables shadowed by `fun` heads
+//! and comprehension generators.
+
+use crate::erl_syntax::erl_ast::node_impl::AstNodeType;
+use crate::erl_syntax::erl_ast::AstNode;
+use crate::erl_syntax::erl_error::ErlError;
+use crate::erl_syntax::erl_op::ErlBinaryOp;
+use crate::erl_syntax::node::erl_binary_element::ValueWidth;
+use crate::erl_syntax::node::erl_callable_target::CallableTarget;
+use crate::erl_syntax::node::erl_case_clause::ErlCaseClause;
+use crate::erl_syntax::node::erl_fn_clause::ErlFnClause;
+use crate::project::module::module_impl::ErlModule;
+use crate::source_loc::SourceLoc;
+use std::collections::{BTreeMap, BTreeSet};
+
+/// The variable which matches anything and is never bound
+const UNDERSCORE: &str = "_";
+
+/// Contains the variable binding analysis pass. Bindings are collected for one function at a
+/// time, and the unused bindings are reported when the function is done.
+pub struct BindingCheck<'m> {
+  /// The module where the problems are reported
+  module: &'m ErlModule,
+  /// Every binding of a variable found in the current function, in the source order
+  bindings: Vec<Binding>,
+}
+
+/// A place in the source, where a variable gets its value
+struct Binding {
+  /// Variable name
+  name: String,
+  /// Where the variable is bound
+  location: SourceLoc,
+  /// Whether the value is used
+  used: bool,
+}
+
+/// The branching expression, after which a variable is not safe to use
+#[derive(Clone)]
+struct UnsafeIn {
+  /// Keyword of the branching expression: `case`, `if`, `receive`, `try` or `maybe`
+  construct: &'static str,
+  /// Location of the branching expression
+  location: SourceLoc,
+}
+
+/// A variable visible in a scope
+#[derive(Clone)]
+struct BoundVar {
+  /// Indices into `BindingCheck::bindings`. A variable bound in several branches of a `case` has
+  /// one binding per branch.
+  bindings: Vec<usize>,
+  /// Set when the variable is not bound by every branch of a branching expression
+  unsafe_in: Option<UnsafeIn>,
+}
+
+/// Variables bound in a function clause, a `fun` clause or a comprehension. Nested scopes see
+/// the variables of the parent scope. Branches of `case`-like expressions work on copies of the
+/// scope, which are merged when the branching expression is done.
+#[derive(Clone)]
+struct BindingScope<'p> {
+  /// Variables bound in this scope
+  variables: BTreeMap<String, BoundVar>,
+  /// The enclosing scope for the name search
+  parent: Option<&'p BindingScope<'p>>,
+}
+
+impl<'p> BindingScope<'p> {
+  /// Create an empty scope for a function clause
+  fn new_root() -> Self {
+    Self { variables: BTreeMap::new(), parent: None }
+  }
+
+  /// Create an empty scope, which sees the variables of the parent
+  fn new_nested(parent: &'p BindingScope<'p>) -> Self {
+    Self { variables: BTreeMap::new(), parent: Some(parent) }
+  }
+
+  /// Find a variable in this scope, or delegate to the parent scope
+  fn find(&self, name: &str) -> Option<&BoundVar> {
+    match self.variables.get(name) {
+      Some(bound) => Some(bound),
+      None => self.parent.and_then(|parent| parent.find(name)),
+    }
+  }
+}
+
+impl<'m> BindingCheck<'m> {
+  /// Check variable bindings in every function of the module. Unbound variables are added to the
+  /// module errors, other problems are added to the module warnings.
+  pub fn verify_module(module: &'m ErlModule) {
+    let mut fn_defs = if let Ok(r_defs) = module.root_scope.fn_defs.collection.read() {
+      r_defs
+        .iter()
+        .map(|(mfa, fn_def)| (mfa.clone(), fn_def.clone()))
+        .collect::<Vec<_>>()
+    } else {
+      panic!("Can't lock module functions for reading")
+    };
+    fn_defs.sort_by_key(|(mfa, _)| mfa.to_string());
+
+    let mut check = Self { module, bindings: Vec::new() };
+    for (_mfa, fndef_ast) in fn_defs.iter() {
+      for fn_clause in fndef_ast.as_fn_def().clauses.iter() {
+        let mut scope = BindingScope::new_root();
+        check.fn_clause(&mut scope, fn_clause, None);
+      }
+      check.report_unused();
+    }
+  }
+
+  /// Warn about the bindings of the finished function, which were never used
+  fn report_unused(&mut self) {
+    for binding in self.bindings.drain(..) {
+      if !binding.used && !binding.name.starts_with('_') {
+        let msg = format!("Variable '{}' is unused", binding.name);
+        self.module.add_warning(ErlError::new_variable_binding(
+          binding.location,
+          &binding.name,
+          msg,
+        ));
+      }
+    }
+  }
+
+  /// Register a new binding of a variable in the scope
+  fn bind_var(&mut self, scope: &mut BindingScope, name: &str, location: &SourceLoc) {
+    self.bindings.push(Binding {
+      name: name.to_string(),
+      location: location.clone(),
+      used: false,
+    });
+    let bound = BoundVar {
+      bindings: vec![self.bindings.len() - 1],
+      unsafe_in: None,
+    };
+    scope.variables.insert(name.to_string(), bound);
+  }
+
+  /// A variable is read in an expression, or is matched again in a pattern
+  fn use_var(&mut self, scope: &BindingScope, name: &str, location: &SourceLoc) {
+    let bound = match scope.find(name) {
+      Some(bound) => bound,
+      None => {
+        self
+          .module
+          .add_error(ErlError::new_unbound_variable(location.clone(), name));
+        return;
+      }
+    };
+    for index in bound.bindings.iter() {
+      self.bindings[*index].used = true;
+    }
+    if let Some(unsafe_in) = &bound.unsafe_in {
+      let line = match unsafe_in.location.line_col() {
+        Some(((line, _), _)) => format!(" (line {})", line),
+        None => String::default(),
+      };
+      let msg = format!("Variable '{}' unsafe in '{}'{}", name, unsafe_in.construct, line);
+      self
+        .module
+        .add_warning(ErlError::new_variable_binding(location.clone(), name, msg));
+    }
+    if name.starts_with('_') {
+      let msg = format!("Variable '{}' is used after being bound", name);
+      self
+        .module
+        .add_warning(ErlError::new_variable_binding(location.clone(), name, msg));
+    }
+  }
+
+  /// Bind the arguments of a function or `fun` clause, then check the guard and the body. For a
+  /// `fun`, `shadow_in` is set and the arguments bind new variables even if they exist outside.
+  fn fn_clause(
+    &mut self,
+    scope: &mut BindingScope,
+    fn_clause: &ErlFnClause,
+    shadow_in: Option<&'static str>,
+  ) {
+    let mut new_vars = BTreeSet::new();
+    for arg in fn_clause.args.iter() {
+      self.pattern(scope, arg, &mut new_vars, shadow_in);
+    }
+    if let Some(guard) = &fn_clause.guard_expr {
+      self.expr(scope, guard);
+    }
+    self.expr(scope, &fn_clause.body);
+  }
+
+  /// Check a `case`, `receive` or `try ... of` clause on a copy of the scope
+  fn case_clause<'p>(
+    &mut self,
+    scope: &BindingScope<'p>,
+    clause: &ErlCaseClause,
+  ) -> BindingScope<'p> {
+    let mut clause_scope = scope.clone();
+    self.pattern(&mut clause_scope, &clause.pattern, &mut BTreeSet::new(), None);
+    if let Some(guard) = &clause.guard {
+      self.expr(&mut clause_scope, guard);
+    }
+    self.expr(&mut clause_scope, &clause.body);
+    clause_scope
+  }
+
+  /// Merge the scopes of the branches back. Variables bound in every branch stay bound, variables
+  /// bound in some branches are unsafe to use.
+  fn merge_branches<'p>(
+    scope: &mut BindingScope<'p>,
+    branches: &[BindingScope<'p>],
+    construct: &'static str,
+    location: &SourceLoc,
+  ) {
+    let new_names: BTreeSet<&String> = branches
+      .iter()
+      .flat_map(|branch| branch.variables.keys())
+      .filter(|name| !scope.variables.contains_key(*name))
+      .collect();
+    for name in new_names {
+      let found: Vec<&BoundVar> = branches
+        .iter()
+        .filter_map(|branch| branch.variables.get(name))
+        .collect();
+      let mut bindings: Vec<usize> = found
+        .iter()
+        .flat_map(|bound| bound.bindings.iter().copied())
+        .collect();
+      bindings.sort_unstable();
+      bindings.dedup();
+      let unsafe_in = if found.len() < branches.len() {
+        Some(UnsafeIn { construct, location: location.clone() })
+      } else {
+        found.iter().find_map(|bound| bound.unsafe_in.clone())
+      };
+      scope
+        .variables
+        .insert(name.clone(), BoundVar { bindings, unsafe_in });
+    }
+  }
+
+  /// Visit a pattern. New variables are bound in the scope and recorded in `new_vars`, variables
+  /// which are already bound are matched against their value. A variable repeated in the same
+  /// pattern is matched against the first occurrence. With `shadow_in` set, variables from the
+  /// enclosing scopes are shadowed by new bindings.
+  fn pattern(
+    &mut self,
+    scope: &mut BindingScope,
+    node: &AstNode,
+    new_vars: &mut BTreeSet<String>,
+    shadow_in: Option<&'static str>,
+  ) {
+    match &node.content {
+      AstNodeType::Var(v) => {
+        if v.name == UNDERSCORE {
+          return;
+        }
+        if new_vars.contains(&v.name) {
+          return self.use_var(scope, &v.name, &node.location);
+        }
+        match (scope.find(&v.name).is_some(), shadow_in) {
+          (true, Some(construct)) => {
+            let msg = format!("Variable '{}' shadowed in '{}'", v.name, construct);
+            self.module.add_warning(ErlError::new_variable_binding(
+              node.location.clone(),
+              &v.name,
+              msg,
+            ));
+          }
+          (true, None) => return self.use_var(scope, &v.name, &node.location),
+          (false, _) => {}
+        }
+        self.bind_var(scope, &v.name, &node.location);
+        new_vars.insert(v.name.clone());
+      }
+      AstNodeType::BinaryOp { binop_expr } => match binop_expr.operator {
+        ErlBinaryOp::Match => {
+          self.pattern(scope, &binop_expr.left, new_vars, shadow_in);
+          self.pattern(scope, &binop_expr.right, new_vars, shadow_in);
+        }
+        ErlBinaryOp::ListAppend => {
+          self.expr(scope, &binop_expr.left);
+          self.pattern(scope, &binop_expr.right, new_vars, shadow_in);
+        }
+        _ => self.expr(scope, node),
+      },
+      AstNodeType::List { elements, tail } => {
+        for e in elements.iter() {
+          self.pattern(scope, e, new_vars, shadow_in);
+        }
+        if let Some(t) = tail {
+          self.pattern(scope, t, new_vars, shadow_in);
+        }
+      }
+      AstNodeType::Tuple { elements } => {
+        for e in elements.iter() {
+          self.pattern(scope, e, new_vars, shadow_in);
+        }
+      }
+      AstNodeType::MapBuilder { members, .. } => {
+        // Map keys in a pattern are expressions, only the values can bind
+        for member in members.iter() {
+          self.expr(scope, &member.key);
+          self.pattern(scope, &member.expr, new_vars, shadow_in);
+        }
+      }
+      AstNodeType::RecordBuilder { members, .. } => {
+        for member in members.iter() {
+          self.pattern(scope, &member.expr, new_vars, shadow_in);
+        }
+      }
+      AstNodeType::BinaryExpr { elements } => {
+        // A size can use a variable bound by an earlier element: `<<Size:8, Data:Size/binary>>`
+        for element in elements.iter() {
+          if let ValueWidth::Expr(width) = &element.width {
+            self.expr(scope, width);
+          }
+          self.pattern(scope, &element.value, new_vars, shadow_in);
+        }
+      }
+      _ => self.expr(scope, node),
+    }
+  }
+
+  /// Visit an expression. Variables are used, and the matches `Pattern = Expr` bind new variables
+  /// for the expressions which follow.
+  fn expr(&mut self, scope: &mut BindingScope, node: &AstNode) {
+    match &node.content {
+      AstNodeType::Empty { .. }
+      | AstNodeType::Lit { .. }
+      | AstNodeType::MFA { .. }
+      | AstNodeType::FnRef { .. }
+      | AstNodeType::Type { .. } => {}
+      AstNodeType::ModuleForms { forms } => {
+        for f in forms.iter() {
+          self.expr(scope, f);
+        }
+      }
+      AstNodeType::Var(v) => self.use_var(scope, &v.name, &node.location),
+      AstNodeType::FnDef(fndef) => {
+        // Variables bound inside a `fun` are not visible outside
+        for fn_clause in fndef.clauses.iter() {
+          let mut fun_scope = BindingScope::new_nested(scope);
+          self.fn_clause(&mut fun_scope, fn_clause, Some("fun"));
+        }
+      }
+      AstNodeType::CClause(_, clause) => {
+        let clause_scope = self.case_clause(scope, clause);
+        *scope = clause_scope;
+      }
+      AstNodeType::Apply(app) => {
+        match &app.target {
+          CallableTarget::Expr(target) => self.expr(scope, target),
+          CallableTarget::MFArity(_) => {}
+          CallableTarget::MFAExpression { module, function, .. } => {
+            if let Some(m) = module {
+              self.expr(scope, m);
+            }
+            self.expr(scope, function);
+          }
+        }
+        for arg in app.args.iter() {
+          self.expr(scope, arg);
+        }
+      }
+      AstNodeType::CaseExpr { expr, clauses } => {
+        self.expr(scope, expr);
+        let branches: Vec<_> = clauses
+          .iter()
+          .map(|clause| self.case_clause(scope, clause))
+          .collect();
+        Self::merge_branches(scope, &branches, "case", &node.location);
+      }
+      AstNodeType::Receive { clauses, after } => {
+        let mut branches: Vec<_> = clauses
+          .iter()
+          .map(|clause| self.case_clause(scope, clause))
+          .collect();
+        if let Some(after_section) = after {
+          self.expr(scope, &after_section.timeout);
+          let mut after_scope = scope.clone();
+          self.expr(&mut after_scope, &after_section.body);
+          branches.push(after_scope);
+        }
+        Self::merge_branches(scope, &branches, "receive", &node.location);
+      }
+      AstNodeType::MaybeExpr { exprs, else_clauses } => {
+        // The body can stop at any failed `?=` match, so nothing bound in it is safe after
+        let mut body_scope = scope.clone();
+        for e in exprs.iter() {
+          self.expr(&mut body_scope, e);
+        }
+        let mut branches = vec![body_scope, scope.clone()];
+        if let Some(else_clauses) = else_clauses {
+          for clause in else_clauses.iter() {
+            branches.push(self.case_clause(scope, clause));
+          }
+        }
+        Self::merge_branches(scope, &branches, "maybe", &node.location);
+      }
+      AstNodeType::MaybeMatch { pattern, expr } => {
+        self.expr(scope, expr);
+        self.pattern(scope, pattern, &mut BTreeSet::new(), None);
+      }
+      AstNodeType::BinaryOp { binop_expr } => {
+        if binop_expr.operator == ErlBinaryOp::Match {
+          // The value is computed first, then the pattern binds
+          self.expr(scope, &binop_expr.right);
+          self.pattern(scope, &binop_expr.left, &mut BTreeSet::new(), None);
+        } else {
+          self.expr(scope, &binop_expr.left);
+          self.expr(scope, &binop_expr.right);
+        }
+      }
+      AstNodeType::UnaryOp { unop_expr } => self.expr(scope, &unop_expr.expr),
+      AstNodeType::List { elements, tail } => {
+        for e in elements.iter() {
+          self.expr(scope, e);
+        }
+        if let Some(t) = tail {
+          self.expr(scope, t);
+        }
+      }
+      AstNodeType::Tuple { elements } => {
+        for e in elements.iter() {
+          self.expr(scope, e);
+        }
+      }
+      AstNodeType::MapBuilder { base, members } => {
+        if let Some(b) = base {
+          self.expr(scope, b);
+        }
+        for member in members.iter() {
+          self.expr(scope, &member.key);
+          self.expr(scope, &member.expr);
+        }
+      }
+      AstNodeType::RecordBuilder { base, members, .. } => {
+        if let Some(b) = base {
+          self.expr(scope, b);
+        }
+        for member in members.iter() {
+          self.expr(scope, &member.expr);
+        }
+      }
+      AstNodeType::RecordField { base, .. } => {
+        if let Some(b) = base {
+          self.expr(scope, b);
+        }
+      }
+      AstNodeType::CommaExpr { elements } => {
+        for e in elements.iter() {
+          self.expr(scope, e);
+        }
+      }
+      AstNodeType::BeginEnd { exprs } => {
+        for e in exprs.iter() {
+          self.expr(scope, e);
+        }
+      }
+      AstNodeType::ListComprehension { expr, generators }
+      | AstNodeType::BinaryComprehension { expr, generators } => {
+        self.comprehension(scope, generators, &[expr]);
+      }
+      AstNodeType::MapComprehension { key, value, generators } => {
+        self.comprehension(scope, generators, &[key, value]);
+      }
+      AstNodeType::ListComprehensionGenerator { .. }
+      | AstNodeType::MapComprehensionGenerator { .. } => self.generator(scope, node),
+      AstNodeType::TryCatch { body, of_branches, catch_clauses } => {
+        // Any part of `try` can fail, so nothing bound inside is safe after
+        let mut body_scope = scope.clone();
+        self.expr(&mut body_scope, body);
+        let mut branches = vec![scope.clone()];
+        match of_branches {
+          Some(of_clauses) => {
+            for clause in of_clauses.iter() {
+              branches.push(self.case_clause(&body_scope, clause));
+            }
+          }
+          None => branches.push(body_scope),
+        }
+        for catch_clause in catch_clauses.iter() {
+          let exc = &catch_clause.exc_pattern;
+          let mut catch_scope = scope.clone();
+          let mut new_vars = BTreeSet::new();
+          for pattern in [Some(&exc.class), Some(&exc.error), exc.stack.as_ref()]
+            .into_iter()
+            .flatten()
+          {
+            self.pattern(&mut catch_scope, pattern, &mut new_vars, None);
+          }
+          if let Some(guard) = &catch_clause.when_guard {
+            self.expr(&mut catch_scope, guard);
+          }
+          self.expr(&mut catch_scope, &catch_clause.body);
+          branches.push(catch_scope);
+        }
+        Self::merge_branches(scope, &branches, "try", &node.location);
+      }
+      AstNodeType::IfStatement { clauses } => {
+        let branches: Vec<_> = clauses
+          .iter()
+          .map(|clause| {
+            let mut clause_scope = scope.clone();
+            self.expr(&mut clause_scope, &clause.cond);
+            self.expr(&mut clause_scope, &clause.body);
+            clause_scope
+          })
+          .collect();
+        Self::merge_branches(scope, &branches, "if", &node.location);
+      }
+      AstNodeType::BinaryExpr { elements } => {
+        for element in elements.iter() {
+          self.expr(scope, &element.value);
+          if let ValueWidth::Expr(width) = &element.width {
+            self.expr(scope, width);
+          }
+        }
+      }
+    }
+  }
+
+  /// Generators and filters are visited in order in a nested scope, then the result expressions.
+  /// Variables bound by the generators are not visible after the comprehension.
+  fn comprehension(&mut self, scope: &BindingScope, generators: &[AstNode], results: &[&AstNode]) {
+    let mut lc_scope = BindingScope::new_nested(scope);
+    for g in generators.iter() {
+      self.generator(&mut lc_scope, g);
+    }
+    for r in results.iter() {
+      self.expr(&mut lc_scope, r);
+    }
+  }
+
+  /// A generator pattern binds new variables, shadowing the variables with the same name.
+  /// Anything else in the generators list is a filter expression.
+  fn generator(&mut self, scope: &mut BindingScope, node: &AstNode) {
+    match &node.content {
+      AstNodeType::ListComprehensionGenerator { left, right } => {
+        self.expr(scope, right);
+        self.pattern(scope, left, &mut BTreeSet::new(), Some("generate"));
+      }
+      AstNodeType::MapComprehensionGenerator { key, value, right } => {
+        self.expr(scope, right);
+        let mut new_vars = BTreeSet::new();
+        self.pattern(scope, key, &mut new_vars, Some("generate"));
+        self.pattern(scope, value, &mut new_vars, Some("generate"));
+      }
+      _ => self.expr(scope, node),
+    }
+  }
+}
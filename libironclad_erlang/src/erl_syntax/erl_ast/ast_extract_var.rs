@@ -4,6 +4,7 @@ use crate::erl_syntax::erl_ast::ast_iter::IterableAstNodeT;
 use crate::erl_syntax::erl_ast::node_impl::{AstNodeImpl, AstNodeType};
 use crate::erl_syntax::erl_ast::AstNode;
 use crate::erl_syntax::erl_error::ErlError;
+use crate::erl_syntax::erl_op::ErlBinaryOp;
 use crate::erl_syntax::node::erl_binary_element::ValueWidth;
 use crate::error::ic_error::IcResult;
 use crate::typing::erl_type::{ErlType, ErlTypeImpl};
@@ -19,7 +20,10 @@ impl AstNodeImpl {
   ) -> IcResult<()> {
     match &node.content {
       AstNodeType::Var(v) => {
-        variables.insert(v.name.clone(), ErlTypeImpl::any());
+        // A variable which is already bound is matched against its value and keeps its type
+        variables
+          .entry(v.name.clone())
+          .or_insert_with(ErlTypeImpl::any);
         Ok(())
       }
       AstNodeType::BinaryExpr { elements, .. } => {
@@ -55,6 +59,19 @@ impl AstNodeImpl {
         Self::extract_variables(left, variables)?;
         Self::extract_variables(right, variables)
       }
+      AstNodeType::MapComprehension { key, value, generators } => {
+        Self::extract_variables(key, variables)?;
+        Self::extract_variables(value, variables)?;
+        for g in generators {
+          Self::extract_variables(g, variables)?;
+        }
+        Ok(())
+      }
+      AstNodeType::MapComprehensionGenerator { key, value, right } => {
+        Self::extract_variables(key, variables)?;
+        Self::extract_variables(value, variables)?;
+        Self::extract_variables(right, variables)
+      }
       AstNodeType::IfStatement { clauses, .. } => {
         for clause in clauses {
           if let Some(children) = clause.children() {
@@ -133,7 +150,17 @@ impl AstNodeImpl {
         }
         Ok(())
       }
-      AstNodeType::Empty { .. } | AstNodeType::BinaryOp { .. } | AstNodeType::Lit { .. } => {
+      AstNodeType::BinaryOp { binop_expr } => match binop_expr.operator {
+        // `Pattern1 = Pattern2` binds variables on both sides
+        ErlBinaryOp::Match => {
+          Self::extract_variables(&binop_expr.left, variables)?;
+          Self::extract_variables(&binop_expr.right, variables)
+        }
+        // `"prefix" ++ Tail` binds variables in the tail
+        ErlBinaryOp::ListAppend => Self::extract_variables(&binop_expr.right, variables),
+        _ => Ok(()),
+      },
+      AstNodeType::Empty { .. } | AstNodeType::Lit { .. } => {
         Ok(()) // do nothing
       }
       AstNodeType::Type { .. }
@@ -143,6 +170,9 @@ impl AstNodeImpl {
       | AstNodeType::FnDef(_)
       | AstNodeType::CClause(_, _)
       | AstNodeType::CaseExpr { .. }
+      | AstNodeType::Receive { .. }
+      | AstNodeType::MaybeExpr { .. }
+      | AstNodeType::MaybeMatch { .. }
       | AstNodeType::Apply(_)
       | AstNodeType::UnaryOp { .. } => ErlError::unacceptable(
         node.location.clone(),
@@ -80,4 +80,14 @@ impl AstNodeImpl {
   pub fn is_case_expr(&self) -> bool {
     matches!(&self.content, AstNodeType::CaseExpr { .. })
   }
+
+  /// Checks whether an ErlAst node is a Receive Expression
+  pub fn is_receive_expr(&self) -> bool {
+    matches!(&self.content, AstNodeType::Receive { .. })
+  }
+
+  /// Checks whether an ErlAst node is a Maybe Expression
+  pub fn is_maybe_expr(&self) -> bool {
+    matches!(&self.content, AstNodeType::MaybeExpr { .. })
+  }
 }
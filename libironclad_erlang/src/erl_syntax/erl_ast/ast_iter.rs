@@ -49,6 +49,39 @@ impl IterableAstNodeT for AstNodeImpl {
         return_some_vec(r)
       }
 
+      AstNodeType::Receive { clauses, after } => {
+        let mut r: Vec<AstNode> = Vec::default();
+
+        for rc in clauses {
+          r.push(rc.pattern.clone());
+          if let Some(g) = &rc.guard {
+            r.push(g.clone());
+          }
+          r.push(rc.body.clone());
+        }
+        if let Some(a) = after {
+          r.push(a.timeout.clone());
+          r.push(a.body.clone());
+        }
+        return_some_vec(r)
+      }
+
+      AstNodeType::MaybeExpr { exprs, else_clauses } => {
+        let mut r: Vec<AstNode> = exprs.to_vec();
+
+        if let Some(ec) = else_clauses {
+          for cc in ec {
+            r.push(cc.pattern.clone());
+            if let Some(g) = &cc.guard {
+              r.push(g.clone());
+            }
+            r.push(cc.body.clone());
+          }
+        }
+        return_some_vec(r)
+      }
+      AstNodeType::MaybeMatch { pattern, expr } => Some(vec![pattern.clone(), expr.clone()]),
+
       AstNodeType::CClause(_loc, clause) => {
         if let Some(g) = &clause.guard {
           Some(vec![clause.pattern.clone(), g.clone(), clause.body.clone()])
@@ -76,9 +109,17 @@ impl IterableAstNodeT for AstNodeImpl {
       AstNodeType::ListComprehensionGenerator { left, right, .. } => {
         Some(vec![left.clone(), right.clone()])
       }
+      AstNodeType::MapComprehension { key, value, generators } => {
+        let mut result = vec![key.clone(), value.clone()];
+        result.extend(generators.iter().cloned());
+        Some(result)
+      }
+      AstNodeType::MapComprehensionGenerator { key, value, right } => {
+        Some(vec![key.clone(), value.clone(), right.clone()])
+      }
 
       AstNodeType::TryCatch { body, of_branches, catch_clauses, .. } => {
-        let mut r: Vec<AstNode> = body.children().unwrap_or_default();
+        let mut r: Vec<AstNode> = vec![body.clone()];
         // For all of-branches, extend the result with each branch
         if let Some(ofb) = of_branches {
           for cc in ofb {
@@ -94,15 +135,7 @@ impl IterableAstNodeT for AstNodeImpl {
         }
         return_some_vec(r)
       }
-      AstNodeType::CommaExpr { elements, .. } => {
-        let mut r = Vec::default();
-        for e in elements.iter() {
-          if let Some(c) = e.children() {
-            r.extend(c.iter().cloned());
-          }
-        }
-        return_some_vec(r)
-      }
+      AstNodeType::CommaExpr { elements, .. } => return_some_vec(elements.to_vec()),
       AstNodeType::IfStatement { clauses, .. } => {
         let mut r = Vec::default();
         for ifc in clauses.iter() {
@@ -112,26 +145,22 @@ impl IterableAstNodeT for AstNodeImpl {
         }
         return_some_vec(r)
       }
-      AstNodeType::BeginEnd { exprs } => {
-        let mut r = Vec::default();
-        for e in exprs.iter() {
-          if let Some(c) = e.children() {
-            r.extend(c.iter().cloned());
-          }
-        }
-        return_some_vec(r)
-      }
+      AstNodeType::BeginEnd { exprs } => return_some_vec(exprs.to_vec()),
       AstNodeType::BinaryExpr { elements, .. } => {
         let mut r = Vec::default();
         for bel in elements.iter() {
+          r.push(bel.value.clone());
           if let ValueWidth::Expr(expr_width) = &bel.width {
             r.push(expr_width.clone());
           }
         }
         return_some_vec(r)
       }
-      AstNodeType::RecordBuilder { members, .. } => {
+      AstNodeType::RecordBuilder { base, members, .. } => {
         let mut r = Vec::default();
+        if let Some(b) = base {
+          r.push(b.clone());
+        }
         for m in members {
           r.push(m.expr.clone());
         }
@@ -143,9 +172,8 @@ impl IterableAstNodeT for AstNodeImpl {
           r.push(b.clone());
         }
         for m in members {
-          if let Some(c) = m.expr.children() {
-            r.extend(c.iter().cloned());
-          }
+          r.push(m.key.clone());
+          r.push(m.expr.clone());
         }
         return_some_vec(r)
       }
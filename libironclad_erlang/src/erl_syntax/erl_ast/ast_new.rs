@@ -3,7 +3,8 @@
 use crate::erl_syntax::erl_ast::node_impl::AstNodeType::{
   Apply, BeginEnd, BinaryComprehension, BinaryExpr, BinaryOp, CaseExpr, CommaExpr, Empty, FnDef,
   FnRef, IfStatement, List, ListComprehension, ListComprehensionGenerator, Lit, MapBuilder,
-  ModuleForms, RecordBuilder, RecordField, TryCatch, Tuple, Var,
+  MapComprehension, MapComprehensionGenerator, MaybeExpr, MaybeMatch, ModuleForms, Receive,
+  RecordBuilder, RecordField, TryCatch, Tuple, Var,
 };
 use crate::erl_syntax::erl_ast::node_impl::{AstNodeImpl, AstNodeType};
 use crate::erl_syntax::erl_ast::AstNode;
@@ -18,6 +19,7 @@ use crate::erl_syntax::node::erl_fn_clause::ErlFnClause;
 use crate::erl_syntax::node::erl_fn_def::ErlFnDef;
 use crate::erl_syntax::node::erl_if_clause::ErlIfClause;
 use crate::erl_syntax::node::erl_map::MapBuilderMember;
+use crate::erl_syntax::node::erl_receive_after::ErlReceiveAfter;
 use crate::erl_syntax::node::erl_record::RecordBuilderMember;
 use crate::erl_syntax::node::erl_var::ErlVar;
 use crate::literal::Literal;
@@ -213,6 +215,28 @@ impl AstNodeImpl {
     AstNodeImpl::construct_with_location(location, lc_node)
   }
 
+  /// Create a new AST node for a map comprehension `#{ K => V || Generators }`
+  pub(crate) fn new_map_comprehension(
+    location: SourceLoc,
+    key: AstNode,
+    value: AstNode,
+    generators: Vec<AstNode>,
+  ) -> AstNode {
+    let mc_node = MapComprehension { key, value, generators };
+    AstNodeImpl::construct_with_location(location, mc_node)
+  }
+
+  /// Create a new AST node for a map generator `K := V <- Expr`
+  pub(crate) fn new_map_comprehension_generator(
+    location: SourceLoc,
+    key: AstNode,
+    value: AstNode,
+    right: AstNode,
+  ) -> AstNode {
+    let mc_node = MapComprehensionGenerator { key, value, right };
+    AstNodeImpl::construct_with_location(location, mc_node)
+  }
+
   // /// Takes preprocessor group nodes and unfolds them into flat list
   // fn flatten_forms(forms: Vec<AstNode>) -> Vec<AstNode> {
   //   let mut result = Vec::new();
@@ -272,14 +296,37 @@ impl AstNodeImpl {
     AstNodeImpl::construct_with_location(location, CaseExpr { expr, clauses })
   }
 
+  /// Create a new `receive` AST Node for `receive MATCH -> EXPR; ... after TIMEOUT -> EXPR end`
+  pub(crate) fn new_receive(
+    location: SourceLoc,
+    clauses: Vec<ErlCaseClause>,
+    after: Option<ErlReceiveAfter>,
+  ) -> AstNode {
+    AstNodeImpl::construct_with_location(location, Receive { clauses, after })
+  }
+
+  /// Create a new `maybe` AST Node for `maybe EXPR, ... else MATCH -> EXPR; ... end`
+  pub(crate) fn new_maybe(
+    location: SourceLoc,
+    exprs: Vec<AstNode>,
+    else_clauses: Option<Vec<ErlCaseClause>>,
+  ) -> AstNode {
+    AstNodeImpl::construct_with_location(location, MaybeExpr { exprs, else_clauses })
+  }
+
+  /// Create a new conditional match `PATTERN ?= EXPR` for a `maybe` block
+  pub(crate) fn new_maybe_match(location: SourceLoc, pattern: AstNode, expr: AstNode) -> AstNode {
+    AstNodeImpl::construct_with_location(location, MaybeMatch { pattern, expr })
+  }
+
   /// Create a new function AST node, or a lambda AST node.
   pub(crate) fn new_fndef(
     location: SourceLoc,
     funarity: MFArity,
     clauses: Vec<ErlFnClause>,
   ) -> AstNode {
-    let fndef = ErlFnDef { location, funarity, clauses };
-    AstNodeImpl::construct_without_location(FnDef(fndef))
+    let fndef = ErlFnDef { location: location.clone(), funarity, clauses };
+    AstNodeImpl::construct_with_location(location, FnDef(fndef))
   }
 
   /// Create a new binary expression
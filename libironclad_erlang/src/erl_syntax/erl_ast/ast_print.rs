@@ -3,7 +3,8 @@
 use crate::erl_syntax::erl_ast::node_impl::AstNodeType::{
   Apply, BeginEnd, BinaryComprehension, BinaryExpr, BinaryOp, CClause, CaseExpr, CommaExpr, FnDef,
   FnRef, IfStatement, List, ListComprehension, ListComprehensionGenerator, Lit, MapBuilder,
-  ModuleForms, RecordBuilder, RecordField, TryCatch, Tuple, Type, UnaryOp, Var, MFA,
+  MapComprehension, MapComprehensionGenerator, MaybeExpr, MaybeMatch, ModuleForms, Receive,
+  RecordBuilder, RecordField, TryCatch, Tuple, Type, UnaryOp, Var, MFA,
 };
 use crate::erl_syntax::erl_ast::node_impl::{AstNodeImpl, AstNodeType};
 use crate::erl_syntax::erl_op::{ErlBinaryOp, ErlUnaryOp};
@@ -45,6 +46,24 @@ impl std::fmt::Display for AstNodeImpl {
         Pretty::display_semicolon_separated(clauses.iter(), f).unwrap();
         writeln!(f, "end")
       }
+      Receive { clauses, after } => {
+        write!(f, "receive ").unwrap();
+        Pretty::display_semicolon_separated(clauses.iter(), f).unwrap();
+        if let Some(a) = after {
+          write!(f, " {}", a).unwrap();
+        }
+        write!(f, " end")
+      }
+      MaybeExpr { exprs, else_clauses } => {
+        write!(f, "maybe ").unwrap();
+        Pretty::display_comma_separated(exprs.iter(), f).unwrap();
+        if let Some(ec) = else_clauses {
+          write!(f, " else ").unwrap();
+          Pretty::display_semicolon_separated(ec.iter(), f).unwrap();
+        }
+        write!(f, " end")
+      }
+      MaybeMatch { pattern, expr } => write!(f, "{} ?= {}", pattern, expr),
       Lit { value: lit, .. } => lit.fmt(f),
 
       BinaryOp { binop_expr, .. } => {
@@ -103,6 +122,14 @@ impl std::fmt::Display for AstNodeImpl {
       ListComprehensionGenerator { left, right, .. } => {
         write!(f, "{} <- {}", left, right)
       }
+      MapComprehension { key, value, generators } => {
+        write!(f, "#{{{} => {} || ", key, value).unwrap();
+        Pretty::display_comma_separated(generators.iter(), f).unwrap();
+        write!(f, "}}")
+      }
+      MapComprehensionGenerator { key, value, right } => {
+        write!(f, "{} := {} <- {}", key, value, right)
+      }
       TryCatch { body, of_branches, catch_clauses, .. } => {
         write!(f, "try {}", body).unwrap();
         if let Some(ofb) = of_branches {
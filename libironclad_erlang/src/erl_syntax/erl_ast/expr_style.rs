@@ -3,7 +3,10 @@
 use crate::erl_syntax::erl_ast::node_impl::{AstNodeImpl, AstNodeType};
 use crate::erl_syntax::erl_ast::AstNode;
 use crate::erl_syntax::erl_error::ErlError;
+use crate::erl_syntax::erl_op::ErlUnaryOp;
+use crate::erl_syntax::node::erl_map::MapBuilderMember;
 use crate::error::ic_error::IcResult;
+use crate::typing::bif::BifTable;
 use std::fmt::{Display, Formatter};
 
 /// Controls the parser behaviour and allowed operators.
@@ -69,13 +72,29 @@ impl AstNodeImpl {
           );
         }
       }
-      AstNodeType::Apply(_) => {
+      AstNodeType::Apply(app) => {
         if style == ExprStyle::Const {
           return ErlError::unacceptable(
             node.location.clone(),
             format!("Function applications are not allowed in {}", style),
           );
         }
+        if style == ExprStyle::Guard {
+          // Only the BIFs marked as guard-safe can be called from a guard
+          let is_guard_bif = app
+            .target
+            .as_mfarity(app.args.len())
+            .is_some_and(|mfa| BifTable::erlang().is_guard_bif(&mfa));
+          if !is_guard_bif {
+            return ErlError::unacceptable(
+              node.location.clone(),
+              format!("Call to {}/{} is not allowed in {}", app.target, app.args.len(), style),
+            );
+          }
+          for arg in app.args.iter() {
+            Self::verify_expr_style(arg, style)?;
+          }
+        }
       }
       AstNodeType::CaseExpr { .. } => {
         if style == ExprStyle::Const {
@@ -85,11 +104,33 @@ impl AstNodeImpl {
           );
         }
       }
+      AstNodeType::Receive { .. } => {
+        if style != ExprStyle::Full {
+          return ErlError::unacceptable(
+            node.location.clone(),
+            format!("Receive expressions are not allowed in {}", style),
+          );
+        }
+      }
+      AstNodeType::MaybeExpr { .. } => {
+        if style != ExprStyle::Full {
+          return ErlError::unacceptable(
+            node.location.clone(),
+            format!("Maybe expressions are not allowed in {}", style),
+          );
+        }
+      }
       AstNodeType::BinaryOp { binop_expr: expr } => {
         Self::verify_expr_style(&expr.left, style)?;
         Self::verify_expr_style(&expr.right, style)?;
       }
       AstNodeType::UnaryOp { unop_expr: expr } => {
+        if expr.operator == ErlUnaryOp::Catch && style != ExprStyle::Full {
+          return ErlError::unacceptable(
+            node.location.clone(),
+            format!("Catch expressions are not allowed in {}", style),
+          );
+        }
         Self::verify_expr_style(&expr.expr, style)?;
       }
       AstNodeType::List { elements, tail } => {
@@ -105,7 +146,11 @@ impl AstNodeImpl {
           Self::verify_expr_style(e, style)?;
         }
       }
-      AstNodeType::MapBuilder { members, .. } => {
+      AstNodeType::MapBuilder { base, members } => {
+        Self::verify_map_builder_style(node, base, members, style)?;
+        if let Some(b) = base {
+          Self::verify_expr_style(b, style)?;
+        }
         for m in members.iter() {
           Self::verify_expr_style(&m.key, style)?;
           Self::verify_expr_style(&m.expr, style)?;
@@ -136,6 +181,14 @@ impl AstNodeImpl {
           );
         }
       }
+      AstNodeType::MapComprehension { .. } => {
+        if style != ExprStyle::Full {
+          return ErlError::unacceptable(
+            node.location.clone(),
+            format!("Map comprehensions are not allowed in {}", style),
+          );
+        }
+      }
       AstNodeType::BinaryComprehension { .. } => {
         if style == ExprStyle::Const {
           return ErlError::unacceptable(
@@ -177,7 +230,9 @@ impl AstNodeImpl {
       AstNodeType::CClause(_, _)
       | AstNodeType::FnDef(_)
       | AstNodeType::Type { .. }
-      | AstNodeType::ListComprehensionGenerator { .. } => {
+      | AstNodeType::ListComprehensionGenerator { .. }
+      | AstNodeType::MapComprehensionGenerator { .. }
+      | AstNodeType::MaybeMatch { .. } => {
         unreachable!("Node must not occur in the wild: {:?}", &node.content);
       }
       //-----------------------------------
@@ -191,4 +246,36 @@ impl AstNodeImpl {
     }
     Ok(())
   }
+
+  /// Map patterns only use `:=` and can't update a base map. Map construction without a base only
+  /// uses `=>`. A map update `Base#{...}` allows both.
+  fn verify_map_builder_style(
+    node: &AstNode,
+    base: &Option<AstNode>,
+    members: &[MapBuilderMember],
+    style: ExprStyle,
+  ) -> IcResult<()> {
+    if style == ExprStyle::MatchExpr {
+      if base.is_some() {
+        return ErlError::unacceptable(
+          node.location.clone(),
+          format!("Map updates are not allowed in {}", style),
+        );
+      }
+      if let Some(m) = members.iter().find(|m| !m.is_match()) {
+        return ErlError::unacceptable(
+          m.key.location.clone(),
+          format!("Only := associations are allowed in a map pattern, got: {}", m),
+        );
+      }
+    } else if base.is_none() {
+      if let Some(m) = members.iter().find(|m| m.is_match()) {
+        return ErlError::unacceptable(
+          m.key.location.clone(),
+          format!("Only => associations are allowed when constructing a map, got: {}", m),
+        );
+      }
+    }
+    Ok(())
+  }
 }
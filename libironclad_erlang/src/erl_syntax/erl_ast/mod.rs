@@ -3,6 +3,7 @@ use crate::erl_syntax::erl_ast::node_impl::AstNodeImpl;
 use std::sync::Arc;
 
 pub mod ast_as;
+pub mod ast_binding;
 pub mod ast_expr;
 pub mod ast_extract_var;
 pub mod ast_is;
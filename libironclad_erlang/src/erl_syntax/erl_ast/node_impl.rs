@@ -11,6 +11,7 @@ use crate::erl_syntax::node::erl_catch_clause::CatchClause;
 use crate::erl_syntax::node::erl_fn_def::ErlFnDef;
 use crate::erl_syntax::node::erl_if_clause::ErlIfClause;
 use crate::erl_syntax::node::erl_map::MapBuilderMember;
+use crate::erl_syntax::node::erl_receive_after::ErlReceiveAfter;
 use crate::erl_syntax::node::erl_record::RecordBuilderMember;
 use crate::erl_syntax::node::erl_unop::ErlUnaryOperatorExpr;
 use crate::erl_syntax::node::erl_var::ErlVar;
@@ -88,6 +89,31 @@ pub enum AstNodeType {
     clauses: Vec<ErlCaseClause>,
   },
 
+  /// Receive a message matching one of the clauses, with an optional `after` timeout section
+  Receive {
+    /// Receive clauses in order, can be empty if `after` is present
+    clauses: Vec<ErlCaseClause>,
+    /// Optional `after Timeout -> Body` section
+    after: Option<ErlReceiveAfter>,
+  },
+
+  /// A `maybe ... else ... end` block (EEP-49). Evaluates expressions in order, and short-circuits
+  /// when a conditional match `Pattern ?= Expr` fails.
+  MaybeExpr {
+    /// Body expressions, conditional matches are stored as `MaybeMatch` nodes
+    exprs: Vec<AstNode>,
+    /// Optional `else` clauses, matched against the value which did not match
+    else_clauses: Option<Vec<ErlCaseClause>>,
+  },
+
+  /// A conditional match `Pattern ?= Expr`, only valid directly inside a `maybe` block
+  MaybeMatch {
+    /// The pattern on the left side
+    pattern: AstNode,
+    /// The value to be matched
+    expr: AstNode,
+  },
+
   /// A literal value, constant. Type is known via literal.get_type()
   Lit {
     /// The literal value
@@ -174,6 +200,24 @@ pub enum AstNodeType {
     /// The input expression (source of the values)
     right: AstNode,
   },
+  /// A map comprehension expression `#{ K => V || Generators }`
+  MapComprehension {
+    /// The result key expression
+    key: AstNode,
+    /// The result value expression
+    value: AstNode,
+    /// The generators which produce the map comprehension inputs, and the conditions
+    generators: Vec<AstNode>,
+  },
+  /// A map generator expression `KeyPattern := ValuePattern <- MapExpr`, valid in any comprehension
+  MapComprehensionGenerator {
+    /// The match expression for the map key
+    key: AstNode,
+    /// The match expression for the map value
+    value: AstNode,
+    /// The input expression (a map to iterate over)
+    right: AstNode,
+  },
 
   /// Try/Catch block with optional OF... branches and multiple catch clauses
   TryCatch {
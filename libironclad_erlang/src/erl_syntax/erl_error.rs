@@ -1,7 +1,7 @@
 //! Erlang errors
 use crate::error::ic_error::IcResult;
 use crate::error::ic_error_category::IcErrorCategory;
-use crate::error::ic_error_trait::IcErrorT;
+use crate::error::ic_error_trait::{IcError, IcErrorT};
 use crate::source_loc::SourceLoc;
 use crate::typing::type_error::TypeError;
 use libironclad_util::mfarity::MFArity;
@@ -28,6 +28,11 @@ pub enum ErlErrorCategory {
     /// Variable which is not found
     var: String,
   },
+  /// Variable is bound but unused, unsafe to use, or shadowed
+  VariableBinding {
+    /// The variable
+    var: String,
+  },
 }
 
 /// Erlang libironclad errors all gathered together
@@ -91,15 +96,30 @@ impl ErlError {
     Err(Box::new(new_err))
   }
 
-  /// Creates an "TypeError" error
-  pub(crate) fn type_error<T>(loc: SourceLoc, type_err: TypeError) -> IcResult<T> {
-    let new_err = ErlError::new(
+  /// Creates an "TypeError" error value, to be stored in the module errors or warnings
+  pub(crate) fn new_type_error(loc: SourceLoc, type_err: TypeError) -> Self {
+    ErlError::new(
       IcErrorCategory::TypeError,
       ErlErrorCategory::TypeError,
       loc,
       format!("{}", type_err),
-    );
-    Err(Box::new(new_err))
+    )
+  }
+
+  /// Wraps an error returned while checking a function, to be stored in the module errors, so
+  /// that the checks can continue with the other functions
+  pub(crate) fn from_ic_error(err: &IcError) -> Self {
+    let msg = match err.get_category() {
+      IcErrorCategory::VariableNotFound(var) => format!("Variable not found: {}", var),
+      category if err.get_message().is_empty() => category.to_string().to_string(),
+      _ => err.get_message().to_string(),
+    };
+    ErlError::new(IcErrorCategory::TypeError, ErlErrorCategory::TypeError, err.get_location(), msg)
+  }
+
+  /// Creates an "TypeError" error
+  pub(crate) fn type_error<T>(loc: SourceLoc, type_err: TypeError) -> IcResult<T> {
+    Err(Box::new(Self::new_type_error(loc, type_err)))
   }
 
   /// Creates an "Local Function Not Found" error
@@ -136,4 +156,25 @@ impl ErlError {
     );
     Err(Box::new(new_err))
   }
+
+  /// Creates a "Variable Not Found" error value for a variable which is used but never bound, to
+  /// be stored in the module errors
+  pub(crate) fn new_unbound_variable(loc: SourceLoc, var: &str) -> Self {
+    ErlError::new(
+      IcErrorCategory::Erlang,
+      ErlErrorCategory::VariableNotFound { var: var.to_string() },
+      loc,
+      format!("Variable '{}' is unbound", var),
+    )
+  }
+
+  /// Creates a "Variable Binding" warning value, to be stored in the module warnings
+  pub(crate) fn new_variable_binding(loc: SourceLoc, var: &str, msg: String) -> Self {
+    ErlError::new(
+      IcErrorCategory::Erlang,
+      ErlErrorCategory::VariableBinding { var: var.to_string() },
+      loc,
+      msg,
+    )
+  }
 }
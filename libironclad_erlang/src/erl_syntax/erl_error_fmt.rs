@@ -4,7 +4,12 @@ use crate::erl_syntax::erl_error::ErlError;
 use std::fmt::Formatter;
 
 impl std::fmt::Display for ErlError {
+  /// Prints `file.erl:12:5-12:18: message` or only the message if the location is not known
   fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-    write!(f, "{:?}", self)
+    if self.loc.is_none() {
+      write!(f, "{}", self.msg)
+    } else {
+      write!(f, "{}: {}", self.loc, self.msg)
+    }
   }
 }
@@ -4,7 +4,7 @@ use crate::erl_syntax::erl_ast::AstNode;
 use crate::erl_syntax::erl_error::ErlError;
 use crate::erl_syntax::node::erl_callable_target::CallableTarget;
 use crate::error::ic_error::IcResult;
-use crate::project::module::module_impl::ErlModule;
+use crate::project::module::module_impl::{ErlModule, ErlModuleImpl};
 use crate::project::module::scope::scope_impl::Scope;
 use crate::source_loc::SourceLoc;
 use crate::typing::erl_type::{ErlType, ErlTypeImpl};
@@ -53,13 +53,6 @@ impl ErlApply {
     module: &ErlModule,
     scope: &Scope,
   ) -> IcResult<ErlType> {
-    // Synthesize target and check that it is a function type
-    let target_ty = self.target.synthesize(module, scope)?;
-    if !target_ty.is_function() {
-      let msg = format!("Attempt to call a value which is not a function: {}", self.target);
-      return ErlError::type_error(location, TypeError::NotAFunction { msg });
-    }
-
     // Build argument type list and check every argument vs the target (the callee)
     let arg_types_r: IcResult<Vec<ErlType>> = self
       .args
@@ -68,19 +61,28 @@ impl ErlApply {
       .collect();
     let arg_types = arg_types_r?;
 
-    match target_ty.deref() {
-      ErlTypeImpl::Atom => unimplemented!("Callable is a local module function"),
-      ErlTypeImpl::Tuple { .. } => unimplemented!("Callable is a tuple"),
+    // Synthesize target and check that it is a function type
+    let target_ty = match self.target.as_mfarity(self.args.len()) {
+      Some(mfa) => match ErlModuleImpl::find_function_type(module, &location, scope, &mfa)? {
+        Some(fn_ty) => fn_ty,
+        // A function of a module not loaded, or an unknown BIF, we do not know better
+        None => return Ok(ErlTypeImpl::any()),
+      },
+      None => self.target.synthesize(&location, module, scope)?,
+    };
 
-      // AnyFn is always callable and always returns any, for we do not know better
-      ErlTypeImpl::AnyFn => Ok(ErlTypeImpl::any()),
+    match target_ty.deref() {
+      // Unknown values, funs, lambdas and tuple calls return any, for we do not know better
+      ErlTypeImpl::Any
+      | ErlTypeImpl::AnyFn
+      | ErlTypeImpl::FnRef { .. }
+      | ErlTypeImpl::Lambda
+      | ErlTypeImpl::Typevar(_) => Ok(ErlTypeImpl::any()),
 
       ErlTypeImpl::Fn(fn_type) => self.synthesize_call_to_fn(location, fn_type, &arg_types),
-      ErlTypeImpl::FnRef { .. } => unimplemented!("Callable is a fun reference"),
-      ErlTypeImpl::Lambda => unimplemented!("Callable is a lambda"),
 
       other => {
-        let msg = format!("Attempt to call a non-function: {}", other);
+        let msg = format!("Attempt to call a value which is not a function: {}", other);
         ErlError::type_error(location, TypeError::NotAFunction { msg })
       }
     }
@@ -8,7 +8,9 @@ use crate::error::ic_error::IcResult;
 use crate::project::module::module_impl::ErlModule;
 use crate::project::module::scope::scope_impl::Scope;
 use crate::source_loc::SourceLoc;
+use crate::typing::destructure::Destructure;
 use crate::typing::erl_type::{ErlType, ErlTypeImpl};
+use crate::typing::integer_arith::IntegerArith;
 use crate::typing::type_error::TypeError;
 use std::ops::Deref;
 
@@ -79,28 +81,26 @@ impl ErlBinaryOperatorExpr {
     module: &ErlModule,
     scope: &Scope,
   ) -> IcResult<ErlType> {
+    if self.operator == ErlBinaryOp::Match {
+      // The left side is a pattern, which can introduce new variables
+      let right = self.right.synthesize(module, scope)?;
+      let (_, matched) = Destructure::bind_match(module, scope, &location, &self.left, &right)?;
+      return Ok(matched);
+    }
     let left = self.left.synthesize(module, scope)?;
     let right = self.right.synthesize(module, scope)?;
 
     match self.operator {
-      ErlBinaryOp::Add | ErlBinaryOp::Sub | ErlBinaryOp::Mul => {
-        // A binary math operation can only produce a numeric type, integer if both args are integer
-        if !left.is_supertype_of_number() || !right.is_supertype_of_number() {
-          // Either left or right are not compatible with number
-          Ok(ErlTypeImpl::none())
-        // } else if left.is_supertype_of_number() && right.is_supertype_of_number() {
-        //   Ok(ErlType::number())
-        } else if left.is_supertype_of_integer() && right.is_supertype_of_integer() {
-          Ok(ErlTypeImpl::integer())
-        } else {
-          Ok(ErlTypeImpl::float())
-        }
-      }
+      // A binary math operation can only produce a numeric type, integer ranges for integer args
+      ErlBinaryOp::Add
+      | ErlBinaryOp::Sub
+      | ErlBinaryOp::Mul
+      | ErlBinaryOp::IntegerDiv
+      | ErlBinaryOp::Remainder
+      | ErlBinaryOp::BinaryAnd => Ok(IntegerArith::synthesize_binop(self.operator, &left, &right)),
 
       ErlBinaryOp::Div => Ok(ErlTypeImpl::float()),
 
-      ErlBinaryOp::IntegerDiv | ErlBinaryOp::Remainder => Ok(ErlTypeImpl::integer()),
-
       ErlBinaryOp::Less
       | ErlBinaryOp::Greater
       | ErlBinaryOp::LessEq
@@ -115,15 +115,24 @@ impl ErlBinaryOperatorExpr {
         // Type of -- will be left, probably some elements which should be missing, but how do we know?
         Ok(left)
       }
-      ErlBinaryOp::Comma => self.right.synthesize(module, scope),
+      ErlBinaryOp::Comma => Ok(right),
 
-      other => {
-        unimplemented!(
-          "Don't know how to synthesize binary operation type for operation {} on {:?}",
-          other,
-          self
-        )
+      ErlBinaryOp::And | ErlBinaryOp::Or | ErlBinaryOp::Xor | ErlBinaryOp::Semicolon => {
+        Ok(ErlTypeImpl::boolean())
       }
+      // The right side of `andalso` and `orelse` is returned as is, and is not required to be
+      // a boolean
+      ErlBinaryOp::AndAlso => Ok(ErlTypeImpl::new_union(&[ErlTypeImpl::new_atom("false"), right])),
+      ErlBinaryOp::OrElse => Ok(ErlTypeImpl::new_union(&[ErlTypeImpl::new_atom("true"), right])),
+
+      ErlBinaryOp::BinaryOr
+      | ErlBinaryOp::BinaryXor
+      | ErlBinaryOp::BinaryShiftLeft
+      | ErlBinaryOp::BinaryShiftRight => Ok(ErlTypeImpl::integer()),
+
+      // Sending returns the message
+      ErlBinaryOp::Send => Ok(right),
+      ErlBinaryOp::Match => unreachable!("Match is handled above"),
     }
   }
 
@@ -139,10 +148,8 @@ impl ErlBinaryOperatorExpr {
     // Left operand must always be a proper list, right can be any list
     // TODO: AnyList, StronglyTypedList, Nil
     match left.deref() {
-      ErlTypeImpl::AnyList => {
-        // Ok(left.clone()), // anylist makes ++ result anylist too
-        panic!("Internal: Synthesize anylist++any list loses type precision")
-      }
+      // Unknown operands make the result any list
+      ErlTypeImpl::AnyList | ErlTypeImpl::Any => Ok(ErlTypeImpl::any_list()),
 
       ErlTypeImpl::StronglyTypedList { elements: left_elements, tail: left_tail } => {
         Self::synthesize_stronglist_append(location, scope, left, left_elements, left_tail, right)
@@ -184,23 +191,28 @@ impl ErlBinaryOperatorExpr {
     right: &ErlType,
   ) -> IcResult<ErlType> {
     match right.deref() {
-      ErlTypeImpl::AnyList => {
-        panic!("Internal: Synthesize stronglist++anylist loses type precision")
-      }
-      ErlTypeImpl::List { elements: right_elements, tail: right_tail, .. } => {
-        let elements: Vec<ErlType> = left_elements
-          .iter()
-          .map(|l_elem| ErlTypeImpl::new_union(&[l_elem.clone(), right_elements.clone()]))
-          .collect();
-        let result_list = ErlTypeImpl::StronglyTypedList { elements, tail: right_tail.clone() };
-        todo!("support non-empty attribute");
+      ErlTypeImpl::AnyList | ErlTypeImpl::Any => Ok(ErlTypeImpl::any_list()),
+      ErlTypeImpl::List {
+        elements: right_elements,
+        tail: right_tail,
+        is_non_empty: right_non_empty,
+      } => {
+        // The right list can have any length, so the result is a list of all element types
+        let mut element_types = left_elements.to_vec();
+        element_types.push(right_elements.clone());
+        let result_list = ErlTypeImpl::List {
+          elements: ErlTypeImpl::new_union(&element_types),
+          tail: right_tail.clone(),
+          is_non_empty: *right_non_empty || !left_elements.is_empty(),
+        };
         Ok(result_list.into())
       }
       ErlTypeImpl::StronglyTypedList { elements: right_elements, tail: right_tail } => {
+        // Elements of both lists follow each other
         let elements: Vec<ErlType> = left_elements
           .iter()
-          .zip(right_elements.iter())
-          .map(|(l_elem, r_elem)| ErlTypeImpl::new_union(&[l_elem.clone(), r_elem.clone()]))
+          .chain(right_elements.iter())
+          .cloned()
           .collect();
         let result_list = ErlTypeImpl::StronglyTypedList { elements, tail: right_tail.clone() };
         Ok(result_list.into())
@@ -231,9 +243,7 @@ impl ErlBinaryOperatorExpr {
     assert!(left_tail.is_none(), "Left operand for ++ must always be a proper list");
 
     match right.deref() {
-      ErlTypeImpl::AnyList => {
-        panic!("Internal: Synthesize list(T)++anylist loses type precision")
-      }
+      ErlTypeImpl::AnyList | ErlTypeImpl::Any => Ok(ErlTypeImpl::any_list()),
       ErlTypeImpl::List {
         elements: right_elements,
         tail: right_tail,
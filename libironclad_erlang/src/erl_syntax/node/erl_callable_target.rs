@@ -1,11 +1,13 @@
 //! Defines a callable target, to use in function applications
 
 use crate::erl_syntax::erl_ast::ast_iter::IterableAstNodeT;
+use crate::erl_syntax::erl_ast::node_impl::AstNodeImpl;
 use crate::erl_syntax::erl_ast::AstNode;
 use crate::error::ic_error::IcResult;
 use crate::project::module::module_impl::ErlModule;
 use crate::project::module::scope::scope_impl::Scope;
-use crate::typing::erl_type::ErlType;
+use crate::source_loc::SourceLoc;
+use crate::typing::erl_type::{ErlType, ErlTypeImpl};
 use libironclad_util::mfarity::MFArity;
 use std::fmt::Formatter;
 
@@ -75,12 +77,39 @@ impl CallableTarget {
 
   /// Create a type for this callable target
   #[allow(dead_code)]
-  pub(crate) fn synthesize(&self, module: &ErlModule, scope: &Scope) -> IcResult<ErlType> {
+  pub(crate) fn synthesize(
+    &self,
+    location: &SourceLoc,
+    module: &ErlModule,
+    scope: &Scope,
+  ) -> IcResult<ErlType> {
     match self {
       CallableTarget::Expr(e) => e.synthesize(module, scope),
-      CallableTarget::MFArity(_) => todo!("synthesize for mfarity callable target"),
-      CallableTarget::MFAExpression { .. } => {
-        todo!("synthesize for mfa-expression callable target")
+      CallableTarget::MFArity(mfa) => {
+        AstNodeImpl::synthesize_mfa_type(module, location, scope, mfa)
+      }
+      CallableTarget::MFAExpression { arity, .. } => {
+        Ok(ErlTypeImpl::new_fn_type_of_any_args(*arity, &ErlTypeImpl::any()).into())
+      }
+    }
+  }
+
+  /// If the target is a function name `name` or `module:name`, written with atoms, return it with
+  /// the given arity.
+  pub(crate) fn as_mfarity(&self, arity: usize) -> Option<MFArity> {
+    match self {
+      CallableTarget::Expr(e) => e
+        .get_atom_text()
+        .map(|name| MFArity::new_local_from_string(name, arity)),
+      CallableTarget::MFArity(mfa) => Some(mfa.clone()),
+      CallableTarget::MFAExpression { module: None, function, .. } => function
+        .get_atom_text()
+        .map(|name| MFArity::new_local_from_string(name, arity)),
+      CallableTarget::MFAExpression { module: Some(m), function, .. } => {
+        match (m.get_atom_text(), function.get_atom_text()) {
+          (Some(m), Some(name)) => Some(MFArity::new(&m, &name, arity)),
+          _ => None,
+        }
       }
     }
   }
@@ -89,15 +118,12 @@ impl CallableTarget {
 impl IterableAstNodeT for CallableTarget {
   fn children(&self) -> Option<Vec<AstNode>> {
     match self {
-      CallableTarget::Expr(e) => e.children(),
+      CallableTarget::Expr(e) => Some(vec![e.clone()]),
       CallableTarget::MFArity(_) => None,
       CallableTarget::MFAExpression { module, function, .. } => {
-        let mut result = function.children().unwrap_or_default();
+        let mut result = vec![function.clone()];
         if let Some(m) = module {
-          match m.children() {
-            None => {}
-            Some(m_children) => result.extend(m_children.iter().cloned()),
-          }
+          result.push(m.clone());
         }
         Some(result)
       }
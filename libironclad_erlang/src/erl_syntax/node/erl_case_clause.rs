@@ -1,6 +1,13 @@
 //! Declares AST node for a clause in `case of` expression
 use crate::erl_syntax::erl_ast::ast_iter::IterableAstNodeT;
+use crate::erl_syntax::erl_ast::node_impl::AstNodeType;
 use crate::erl_syntax::erl_ast::AstNode;
+use crate::error::ic_error::IcResult;
+use crate::project::module::module_impl::ErlModule;
+use crate::project::module::scope::scope_impl::{Scope, ScopeImpl};
+use crate::typing::destructure::Destructure;
+use crate::typing::erl_type::{ErlType, ErlTypeImpl};
+use crate::typing::type_narrow::TypeNarrow;
 
 /// AST node for a clause in a `case X of` expression.
 #[derive(Debug)]
@@ -18,6 +25,86 @@ impl ErlCaseClause {
   pub(crate) fn new(pattern: AstNode, guard: Option<AstNode>, body: AstNode) -> Self {
     Self { pattern, guard, body }
   }
+
+  /// Synthesize the clause body type. The body sees the variables bound in the pattern, narrowed
+  /// by the guard.
+  pub(crate) fn synthesize_body_type(
+    &self,
+    module: &ErlModule,
+    scope: &Scope,
+  ) -> IcResult<ErlType> {
+    let clause_scope = ScopeImpl::new_with_pattern_vars(scope, "case clause", &self.pattern)?;
+    let clause_scope = self.narrow_by_guard(module, &clause_scope)?;
+    self.body.synthesize(module, &clause_scope)
+  }
+
+  /// Synthesize the clause body type, when the clause is matched against `scrutinee` expression,
+  /// which has values of type `matched` (not yet matched by the previous clauses). If `scrutinee`
+  /// or the pattern are variables, they are narrowed to the values which can match the pattern.
+  pub(crate) fn synthesize_matched_body_type(
+    &self,
+    module: &ErlModule,
+    scope: &Scope,
+    scrutinee: &AstNode,
+    matched: &ErlType,
+  ) -> IcResult<ErlType> {
+    match self.pattern_scope(module, scope, Some(scrutinee), matched)? {
+      Some(clause_scope) => {
+        let clause_scope = self.narrow_by_guard(module, &clause_scope)?;
+        self.body.synthesize(module, &clause_scope)
+      }
+      // The clause can never match, and the body never runs
+      None => Ok(ErlTypeImpl::none()),
+    }
+  }
+
+  /// Create the scope with the variables bound in the pattern, when the clause is matched against
+  /// values of type `matched`. The `scrutinee` and the pattern, if they are variables, are narrowed
+  /// to the values which can match the pattern. Returns `None` if the pattern can never match.
+  pub(crate) fn pattern_scope(
+    &self,
+    module: &ErlModule,
+    scope: &Scope,
+    scrutinee: Option<&AstNode>,
+    matched: &ErlType,
+  ) -> IcResult<Option<Scope>> {
+    let destructure = Destructure::new(module, scope, &self.pattern, matched)?;
+    if destructure.matched.is_none() {
+      return Ok(None);
+    }
+    let mut clause_scope = destructure.bind(scope, "case clause", &self.pattern)?;
+    if let Some(AstNodeType::Var(v)) = scrutinee.map(|s| &s.content) {
+      clause_scope = TypeNarrow::bind_var(&clause_scope, &v.name, &destructure.matched);
+    }
+    Ok(Some(clause_scope))
+  }
+
+  /// Values matched by this clause, which the following clauses will not see. Returns `None` if
+  /// the clause has a guard or the pattern does not match all values of its type.
+  pub(crate) fn synthesize_matched_type(
+    &self,
+    module: &ErlModule,
+    scope: &Scope,
+  ) -> IcResult<Option<ErlType>> {
+    let pattern = std::slice::from_ref(&self.pattern);
+    if self.guard.is_some() || !TypeNarrow::is_exact_pattern(Some(scope), pattern) {
+      return Ok(None);
+    }
+    let clause_scope = ScopeImpl::new_with_pattern_vars(scope, "case clause", &self.pattern)?;
+    Ok(Some(self.pattern.synthesize(module, &clause_scope)?))
+  }
+
+  /// Apply the guard knowledge to the clause scope
+  pub(crate) fn narrow_by_guard(
+    &self,
+    module: &ErlModule,
+    clause_scope: &Scope,
+  ) -> IcResult<Scope> {
+    match &self.guard {
+      Some(guard) => TypeNarrow::narrow_scope_by_guard(module, clause_scope, guard),
+      None => Ok(clause_scope.clone()),
+    }
+  }
 }
 
 impl std::fmt::Display for ErlCaseClause {
@@ -31,16 +118,11 @@ impl std::fmt::Display for ErlCaseClause {
 
 impl IterableAstNodeT for ErlCaseClause {
   fn children(&self) -> Option<Vec<AstNode>> {
-    let mut r = self.pattern.children().unwrap_or_default();
+    let mut r = vec![self.pattern.clone()];
     if let Some(g) = &self.guard {
-      if let Some(g_children) = g.children() {
-        r.extend(g_children.iter().cloned());
-      }
-    }
-    if r.is_empty() {
-      None
-    } else {
-      Some(r)
+      r.push(g.clone());
     }
+    r.push(self.body.clone());
+    Some(r)
   }
 }
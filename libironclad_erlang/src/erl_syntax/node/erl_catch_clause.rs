@@ -1,8 +1,14 @@
 //! Catch clauses for try-catch block
 
 use crate::erl_syntax::erl_ast::ast_iter::IterableAstNodeT;
+use crate::erl_syntax::erl_ast::node_impl::AstNodeType;
 use crate::erl_syntax::erl_ast::AstNode;
 use crate::erl_syntax::node::erl_exception_pattern::ExceptionPattern;
+use crate::error::ic_error::IcResult;
+use crate::project::module::module_impl::ErlModule;
+use crate::project::module::scope::scope_impl::{Scope, ScopeImpl};
+use crate::typing::erl_type::{ErlType, ErlTypeImpl};
+use crate::typing::type_narrow::TypeNarrow;
 use std::fmt::Formatter;
 
 /// Catch clause for a try-catch block
@@ -25,6 +31,35 @@ impl CatchClause {
   ) -> Self {
     Self { exc_pattern, when_guard, body }
   }
+
+  /// Synthesize the clause body type. The body sees the variables bound in the exception pattern,
+  /// the class variable can only be `error`, `exit` or `throw`.
+  pub(crate) fn synthesize_body_type(
+    &self,
+    module: &ErlModule,
+    scope: &Scope,
+  ) -> IcResult<ErlType> {
+    let exc = &self.exc_pattern;
+    let mut clause_scope = scope.clone();
+    for pattern in [Some(&exc.class), Some(&exc.error), exc.stack.as_ref()]
+      .into_iter()
+      .flatten()
+    {
+      clause_scope = ScopeImpl::new_with_pattern_vars(&clause_scope, "catch clause", pattern)?;
+    }
+    if let AstNodeType::Var(v) = &exc.class.content {
+      let classes: Vec<ErlType> = ["error", "exit", "throw"]
+        .iter()
+        .map(|c| ErlTypeImpl::new_atom(c))
+        .collect();
+      clause_scope =
+        TypeNarrow::bind_var(&clause_scope, &v.name, &ErlTypeImpl::new_union(&classes));
+    }
+    if let Some(guard) = &self.when_guard {
+      clause_scope = TypeNarrow::narrow_scope_by_guard(module, &clause_scope, guard)?;
+    }
+    self.body.synthesize(module, &clause_scope)
+  }
 }
 
 impl std::fmt::Display for CatchClause {
@@ -48,17 +83,9 @@ impl IterableAstNodeT for CatchClause {
       r.extend(c.iter().cloned());
     }
     if let Some(wheng) = &self.when_guard {
-      if let Some(wheng_children) = wheng.children() {
-        r.extend(wheng_children.iter().cloned());
-      }
-    }
-    if let Some(b_children) = self.body.children() {
-      r.extend(b_children.iter().cloned());
-    }
-    if r.is_empty() {
-      None
-    } else {
-      Some(r)
+      r.push(wheng.clone());
     }
+    r.push(self.body.clone());
+    Some(r)
   }
 }
@@ -31,22 +31,10 @@ impl ExceptionPattern {
 
 impl IterableAstNodeT for ExceptionPattern {
   fn children(&self) -> Option<Vec<AstNode>> {
-    let mut r = Vec::default();
-    if let Some(c) = self.class.children() {
-      r.extend(c.iter().cloned());
-    }
-    if let Some(c) = self.error.children() {
-      r.extend(c.iter().cloned());
-    }
+    let mut r = vec![self.class.clone(), self.error.clone()];
     if let Some(stack) = &self.stack {
-      if let Some(c) = stack.children() {
-        r.extend(c.iter().cloned());
-      }
-    }
-    if r.is_empty() {
-      None
-    } else {
-      Some(r)
+      r.push(stack.clone());
     }
+    Some(r)
   }
 }
@@ -6,8 +6,10 @@ use crate::erl_syntax::erl_ast::AstNode;
 use crate::error::ic_error::IcResult;
 use crate::project::module::module_impl::ErlModule;
 use crate::project::module::scope::scope_impl::{Scope, ScopeImpl};
+use crate::typing::destructure::Destructure;
 use crate::typing::erl_type::ErlType;
 use crate::typing::fn_clause_type::FnClauseType;
+use crate::typing::type_narrow::TypeNarrow;
 use crate::typing::typevar::Typevar;
 use libironclad_util::pretty::Pretty;
 use std::sync::Weak;
@@ -86,6 +88,31 @@ impl ErlFnClause {
     Ok(synthesized_t)
   }
 
+  /// Create the clause scope, where the variables in the argument patterns have the types
+  /// destructured from the given argument types
+  pub(crate) fn args_scope(&self, module: &ErlModule, arg_types: &[ErlType]) -> IcResult<Scope> {
+    let mut args_scope = self.scope.clone();
+    for (arg, arg_type) in self.args.iter().zip(arg_types.iter()) {
+      let destructure = Destructure::new(module, &args_scope, arg, arg_type)?;
+      args_scope = destructure.bind(&args_scope, &self.scope.name, arg)?;
+    }
+    Ok(args_scope)
+  }
+
+  /// Create the scope for the clause body, where the argument patterns are destructured from the
+  /// given types, and then are narrowed by the guard.
+  pub(crate) fn narrowed_body_scope(
+    &self,
+    module: &ErlModule,
+    arg_types: &[ErlType],
+  ) -> IcResult<Scope> {
+    let body_scope = self.args_scope(module, arg_types)?;
+    match &self.guard_expr {
+      Some(guard) => TypeNarrow::narrow_scope_by_guard(module, &body_scope, guard),
+      None => Ok(body_scope),
+    }
+  }
+
   /// Return type from the body AST
   #[allow(dead_code)]
   pub(crate) fn synthesize_clause_return_type(
@@ -7,10 +7,13 @@ use crate::project::module::module_impl::ErlModule;
 use crate::project::module::scope::scope_impl::Scope;
 use crate::source_loc::SourceLoc;
 use crate::typing::erl_type::{ErlType, ErlTypeImpl};
-use crate::typing::fn_clause_type::FnClauseType;
 use crate::typing::fn_type::FnType;
+use crate::typing::type_narrow::TypeNarrow;
 use libironclad_util::mfarity::MFArity;
 
+/// Limit for the repeated synthesis of a recursive function
+const MAX_FIXPOINT_ITERATIONS: usize = 8;
+
 /// AST node which declares a new function. Contains function clauses. Names and arities on
 /// all clauses must be equal and same as the function name.
 #[derive(Debug)]
@@ -33,19 +36,67 @@ impl ErlFnDef {
     Self { location, funarity, clauses }
   }
 
-  /// Produce `ErlType` for this function definition, with all clauses and their return types
-  #[allow(dead_code)]
+  /// Produce `ErlType` for this function definition, with all clauses and their return types.
+  /// Argument variables are narrowed by the guards, and the values matched by a clause are removed
+  /// from the argument types of the following clauses.
+  ///
+  /// A recursive function is synthesized repeatedly: the recursive calls first return `none()`,
+  /// and then the type from the previous iteration, widened, until it does not change.
   pub(crate) fn synthesize_function_type(
     &self,
     module: &ErlModule,
-    _scope: &Scope,
+    scope: &Scope,
   ) -> IcResult<ErlType> {
-    let clauses_r: IcResult<Vec<FnClauseType>> = self
-      .clauses
-      .iter()
-      .map(|fnc| fnc.synthesize_clause_type(module, &fnc.scope))
-      .collect();
-    let clauses = clauses_r?;
+    let in_progress = &module.root_scope.fn_synth_in_progress;
+    let recursive = &module.root_scope.fn_synth_recursive;
+    if let Some(assumed) = in_progress.get(&self.funarity) {
+      recursive.add(self.funarity.clone());
+      return Ok(assumed);
+    }
+
+    let mut assumed: ErlType =
+      ErlTypeImpl::new_fn_type_of_any_args(self.funarity.arity, &ErlTypeImpl::none()).into();
+    let mut is_first_iteration = true;
+    let mut result = None;
+    for _ in 0..MAX_FIXPOINT_ITERATIONS {
+      in_progress.add(self.funarity.clone(), assumed.clone());
+      recursive.remove(&self.funarity);
+      let fn_type = match self.synthesize_clauses_type(module, scope) {
+        Ok(fn_type) => fn_type,
+        Err(e) => {
+          result = Some(Err(e));
+          break;
+        }
+      };
+      if !recursive.contains(&self.funarity) {
+        result = Some(Ok(fn_type));
+        break;
+      }
+      let widened = if is_first_iteration { fn_type.clone() } else { assumed.widen(&fn_type) };
+      if widened == assumed {
+        result = Some(Ok(fn_type));
+        break;
+      }
+      assumed = widened;
+      is_first_iteration = false;
+    }
+    in_progress.delete_if(|mfa, _| *mfa == self.funarity);
+    recursive.remove(&self.funarity);
+    // When the iteration does not converge, nothing is known about the return type
+    result.unwrap_or_else(|| {
+      Ok(ErlTypeImpl::new_fn_type_of_any_args(self.funarity.arity, &ErlTypeImpl::any()).into())
+    })
+  }
+
+  /// Synthesize the function type clause by clause
+  fn synthesize_clauses_type(&self, module: &ErlModule, _scope: &Scope) -> IcResult<ErlType> {
+    let mut remaining_args = vec![ErlTypeImpl::any(); self.funarity.arity];
+    let mut clauses = Vec::with_capacity(self.clauses.len());
+    for fnc in self.clauses.iter() {
+      let body_scope = fnc.narrowed_body_scope(module, &remaining_args)?;
+      clauses.push(fnc.synthesize_clause_type(module, &body_scope)?);
+      remaining_args = TypeNarrow::subtract_fn_clause(module, fnc, &remaining_args)?;
+    }
 
     let fn_type = FnType::new(self.funarity.arity, &clauses);
     let synthesized_t = ErlTypeImpl::Fn(fn_type.into()).into();
@@ -72,11 +123,14 @@ impl ErlFnDef {
 
 impl IterableAstNodeT for ErlFnDef {
   fn children(&self) -> Option<Vec<AstNode>> {
-    let r: Vec<AstNode> = self
-      .clauses
-      .iter()
-      .map(|fclause| fclause.body.clone())
-      .collect();
+    let mut r: Vec<AstNode> = Vec::default();
+    for fclause in self.clauses.iter() {
+      r.extend(fclause.args.iter().cloned());
+      if let Some(g) = &fclause.guard_expr {
+        r.push(g.clone());
+      }
+      r.push(fclause.body.clone());
+    }
     Some(r)
   }
 }
@@ -27,14 +27,6 @@ impl std::fmt::Display for ErlIfClause {
 
 impl IterableAstNodeT for ErlIfClause {
   fn children(&self) -> Option<Vec<AstNode>> {
-    let mut r = self.cond.children().unwrap_or_default();
-    if let Some(body_children) = self.body.children() {
-      r.extend(body_children.iter().cloned());
-    }
-    if r.is_empty() {
-      None
-    } else {
-      Some(r)
-    }
+    Some(vec![self.cond.clone(), self.body.clone()])
   }
 }
@@ -4,7 +4,7 @@ use crate::erl_syntax::erl_ast::AstNode;
 use std::fmt::Formatter;
 
 /// Operation for a map member
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
 pub enum MapMemberOperation {
   /// Value goes into the map member: `Key => Value`
   Assign,
@@ -33,10 +33,24 @@ impl MapBuilderMember {
   pub fn new_match(key: AstNode, expr: AstNode) -> Self {
     MapBuilderMember { operation: MapMemberOperation::Match, key, expr }
   }
+
+  /// Whether the member uses the exact association `:=` syntax
+  pub fn is_match(&self) -> bool {
+    self.operation == MapMemberOperation::Match
+  }
+}
+
+impl std::fmt::Display for MapMemberOperation {
+  fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+    match self {
+      MapMemberOperation::Assign => write!(f, "=>"),
+      MapMemberOperation::Match => write!(f, ":="),
+    }
+  }
 }
 
 impl std::fmt::Display for MapBuilderMember {
   fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-    write!(f, "{} => {}", self.key, self.expr)
+    write!(f, "{} {} {}", self.key, self.operation, self.expr)
   }
 }
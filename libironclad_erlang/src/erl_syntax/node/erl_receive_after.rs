@@ -0,0 +1,32 @@
+//! The `after Timeout -> Body` section of a `receive` expression
+
+use crate::erl_syntax::erl_ast::ast_iter::IterableAstNodeT;
+use crate::erl_syntax::erl_ast::AstNode;
+
+/// AST node for the `after` section in `receive ... after Timeout -> Body end`
+#[derive(Debug)]
+pub struct ErlReceiveAfter {
+  /// Timeout expression, must evaluate to a non-negative integer or `infinity`
+  pub timeout: AstNode,
+  /// Body expression evaluated when the timeout fires
+  pub body: AstNode,
+}
+
+impl ErlReceiveAfter {
+  /// Create a new `after` section
+  pub(crate) fn new(timeout: AstNode, body: AstNode) -> Self {
+    Self { timeout, body }
+  }
+}
+
+impl std::fmt::Display for ErlReceiveAfter {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    write!(f, "after {} -> {}", self.timeout, self.body)
+  }
+}
+
+impl IterableAstNodeT for ErlReceiveAfter {
+  fn children(&self) -> Option<Vec<AstNode>> {
+    Some(vec![self.timeout.clone(), self.body.clone()])
+  }
+}
@@ -4,7 +4,12 @@ use crate::erl_syntax::erl_ast::node_impl::AstNodeType::UnaryOp;
 use crate::erl_syntax::erl_ast::AstNode;
 use crate::erl_syntax::erl_op::ErlUnaryOp;
 use crate::erl_syntax::literal_bool::LiteralBool;
+use crate::error::ic_error::IcResult;
+use crate::project::module::module_impl::ErlModule;
+use crate::project::module::scope::scope_impl::Scope;
 use crate::source_loc::SourceLoc;
+use crate::typing::erl_type::{ErlType, ErlTypeImpl};
+use crate::typing::integer_arith::IntegerArith;
 
 /// Unary operator is right-associative operation such as `not A` or `+A`
 #[derive(Debug)]
@@ -30,4 +35,30 @@ impl ErlUnaryOperatorExpr {
       _ => LiteralBool::NotABoolean,
     }
   }
+
+  /// Gets the result type of a unary operation
+  pub(crate) fn synthesize_unop_type(
+    &self,
+    module: &ErlModule,
+    scope: &Scope,
+  ) -> IcResult<ErlType> {
+    let operand = self.expr.synthesize(module, scope)?;
+
+    match self.operator {
+      // `catch Expr` returns the value, or `{'EXIT', Reason}` for errors, or the thrown term
+      ErlUnaryOp::Catch => {
+        let exit_tuple =
+          ErlTypeImpl::new_tuple(&[ErlTypeImpl::new_atom("EXIT"), ErlTypeImpl::any()]);
+        Ok(ErlTypeImpl::new_union_skip_normalize(&[
+          operand,
+          exit_tuple,
+          ErlTypeImpl::any(),
+        ]))
+      }
+      ErlUnaryOp::Not => Ok(ErlTypeImpl::boolean()),
+      ErlUnaryOp::BinaryNot | ErlUnaryOp::Negative | ErlUnaryOp::Positive => {
+        Ok(IntegerArith::synthesize_unop(&self.operator, &operand))
+      }
+    }
+  }
 }
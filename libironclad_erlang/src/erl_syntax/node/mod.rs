@@ -10,6 +10,7 @@ pub mod erl_fn_clause;
 pub mod erl_fn_def;
 pub mod erl_if_clause;
 pub mod erl_map;
+pub mod erl_receive_after;
 pub mod erl_record;
 pub mod erl_unop;
 pub mod erl_var;
@@ -16,6 +16,8 @@ pub enum LangConstruct {
   TryCatch,
   IfExpression,
   CaseExpression,
+  ReceiveExpression,
+  MaybeExpression,
   List,
   Tuple,
   FunctionReference,
@@ -23,6 +25,8 @@ pub enum LangConstruct {
   Record,
   RecordField,
   ListComprehension,
+  /// A map comprehension `#{ K => V || Generators }`
+  MapComprehension,
   BinaryComprehension,
   Binary,
   //------------------
@@ -36,6 +40,7 @@ pub enum LangConstruct {
   RecordRefType,
   IntegerLiteralType,
   AtomLiteralType,
+  TypeVariable,
   BinaryType,
 }
 
@@ -51,6 +56,8 @@ impl Display for LangConstruct {
       LangConstruct::TryCatch => write!(f, "try-catch expression"),
       LangConstruct::IfExpression => write!(f, "if expression"),
       LangConstruct::CaseExpression => write!(f, "case expression"),
+      LangConstruct::ReceiveExpression => write!(f, "receive expression"),
+      LangConstruct::MaybeExpression => write!(f, "maybe expression"),
       LangConstruct::List => write!(f, "list"),
       LangConstruct::Tuple => write!(f, "tuple"),
       LangConstruct::FunctionReference => write!(f, "function reference"),
@@ -58,6 +65,7 @@ impl Display for LangConstruct {
       LangConstruct::Record => write!(f, "record"),
       LangConstruct::RecordField => write!(f, "record field"),
       LangConstruct::ListComprehension => write!(f, "list comprehension"),
+      LangConstruct::MapComprehension => write!(f, "map comprehension"),
       LangConstruct::BinaryComprehension => write!(f, "binary comprehension"),
       LangConstruct::Binary => write!(f, "binary"),
       //------------------
@@ -71,6 +79,7 @@ impl Display for LangConstruct {
       LangConstruct::RecordRefType => write!(f, "record reference type"),
       LangConstruct::IntegerLiteralType => write!(f, "integer literal type"),
       LangConstruct::AtomLiteralType => write!(f, "atom literal type"),
+      LangConstruct::TypeVariable => write!(f, "type variable"),
       LangConstruct::BinaryType => {
         write!(f, "binary type spec (head element, repeat element or both)")
       }
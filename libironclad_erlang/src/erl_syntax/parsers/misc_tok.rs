@@ -82,9 +82,10 @@ make_tok_fn!(tok_angle_close, TokenType::AngleClose);
 make_tok_fn!(tok_less_eq, TokenType::LessThanEq);
 make_tok_fn!(tok_greater_eq, TokenType::GreaterEq);
 
-// =/= =:= .. => :=
+// =/= =:= ?= .. => :=
 make_tok_fn!(tok_hard_not_equal, TokenType::HardNotEq);
 make_tok_fn!(tok_hard_equal, TokenType::HardEq);
+make_tok_fn!(tok_maybe_match, TokenType::MaybeMatch);
 make_tok_fn!(tok_double_period, TokenType::PeriodPeriod);
 make_tok_fn!(tok_right_darr, TokenType::RightDoubleArr);
 make_tok_fn!(tok_assign, TokenType::Assign);
@@ -125,6 +126,7 @@ macro_rules! make_keyword_fn {
   };
 }
 
+make_keyword_fn!(keyword_after, Keyword::After);
 make_keyword_fn!(keyword_and, Keyword::And);
 make_keyword_fn!(keyword_andalso, Keyword::AndAlso);
 make_keyword_fn!(keyword_band, Keyword::BinaryAnd);
@@ -141,10 +143,12 @@ make_keyword_fn!(keyword_end, Keyword::End);
 make_keyword_fn!(keyword_fun, Keyword::Fun);
 make_keyword_fn!(keyword_if, Keyword::If);
 make_keyword_fn!(keyword_integerdiv, Keyword::IntegerDiv);
+make_keyword_fn!(keyword_maybe, Keyword::Maybe);
 make_keyword_fn!(keyword_not, Keyword::Not);
 make_keyword_fn!(keyword_of, Keyword::Of);
 make_keyword_fn!(keyword_or, Keyword::Or);
 make_keyword_fn!(keyword_orelse, Keyword::OrElse);
+make_keyword_fn!(keyword_receive, Keyword::Receive);
 make_keyword_fn!(keyword_rem, Keyword::Rem);
 make_keyword_fn!(keyword_try, Keyword::Try);
 make_keyword_fn!(keyword_when, Keyword::When);
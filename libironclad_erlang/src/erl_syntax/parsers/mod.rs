@@ -21,6 +21,8 @@ pub mod parse_expr_op;
 pub mod parse_fn;
 pub mod parse_if;
 pub mod parse_lit;
+pub mod parse_maybe;
+pub mod parse_receive;
 pub mod parse_try_catch;
 pub mod parse_type;
 pub mod parser_error;
@@ -17,7 +17,7 @@ use crate::literal::Literal;
 use crate::source_loc::SourceLoc;
 use crate::typing::erl_integer::ErlInteger;
 use nom::branch::alt;
-use nom::combinator::{cut, map, opt, value};
+use nom::combinator::{consumed, cut, map, opt, value};
 use nom::error::context;
 use nom::multi::{separated_list0, separated_list1};
 use nom::sequence::{preceded, terminated, tuple};
@@ -189,17 +189,19 @@ fn bin_element(input: ParserInput) -> ParserResult<BinaryElement> {
 
 /// Parse a binary or binary builder expression
 pub(crate) fn parse_binary(input: ParserInput) -> ParserResult<AstNode> {
-  preceded(
-    tok_double_angle_open,
-    context(
-      "binary expression",
-      cut(terminated(
-        map(
+  map(
+    consumed(preceded(
+      tok_double_angle_open,
+      context(
+        "binary expression",
+        cut(terminated(
           separated_list0(tok_comma, context("binary expression element", bin_element)),
-          |bin_exprs| AstNodeImpl::new_binary_expr(SourceLoc::new(&input), bin_exprs),
-        ),
-        tok_double_angle_close,
-      )),
-    ),
-  )(input.clone())
+          tok_double_angle_close,
+        )),
+      ),
+    )),
+    |(consumed_input, bin_exprs)| {
+      AstNodeImpl::new_binary_expr(SourceLoc::new_span(&consumed_input), bin_exprs)
+    },
+  )(input)
 }
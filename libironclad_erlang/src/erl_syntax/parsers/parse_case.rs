@@ -9,7 +9,7 @@ use crate::erl_syntax::parsers::parse_expr::{
 };
 use crate::erl_syntax::parsers::parser_input::ParserInput;
 use crate::source_loc::SourceLoc;
-use nom::combinator::{cut, map, opt};
+use nom::combinator::{consumed, cut, map, opt};
 use nom::error::context;
 use nom::multi::separated_list1;
 use nom::sequence::{pair, preceded, terminated, tuple};
@@ -24,29 +24,32 @@ pub(crate) fn parse_case_clause(input: ParserInput) -> ParserResult<ErlCaseClaus
         context("case clause guard expression", cut(parse_guardexpr)),
       )),
       // The body after ->
-      preceded(tok_right_arrow, context("case clause body", cut(parse_comma_sep_exprs1))),
+      preceded(
+        tok_right_arrow,
+        context("case clause body", cut(consumed(parse_comma_sep_exprs1))),
+      ),
     )),
-    |(pattern, maybe_when, body)| {
+    |(pattern, maybe_when, (consumed_body, body))| {
       ErlCaseClause::new(
         pattern,
         maybe_when,
-        AstNodeImpl::new_comma_expr(SourceLoc::new(&input), body),
+        AstNodeImpl::new_comma_expr(SourceLoc::new_span(&consumed_body), body),
       )
     },
-  )(input.clone())
+  )(input)
 }
 
 /// Parses `case EXPR of MATCH -> EXPR; ... end`
 pub(crate) fn parse_case_expression(input: ParserInput) -> ParserResult<AstNode> {
-  let map_fn = |(expr, clauses): (AstNode, Vec<ErlCaseClause>)| -> AstNode {
-    AstNodeImpl::new_case_statement(SourceLoc::new(&input), expr, clauses)
+  let map_fn = |(consumed_input, (expr, clauses)): (ParserInput, (AstNode, Vec<ErlCaseClause>))| {
+    AstNodeImpl::new_case_statement(SourceLoc::new_span(&consumed_input), expr, clauses)
   };
-  preceded(
-    keyword_case,
-    context(
-      "case-of-end expression",
-      cut(map(
-        terminated(
+  map(
+    consumed(preceded(
+      keyword_case,
+      context(
+        "case-of-end expression",
+        cut(terminated(
           pair(
             terminated(context("case block expression", cut(parse_expr)), keyword_of),
             context(
@@ -55,9 +58,9 @@ pub(crate) fn parse_case_expression(input: ParserInput) -> ParserResult<AstNode>
             ),
           ),
           keyword_end,
-        ),
-        map_fn,
-      )),
-    ),
-  )(input.clone())
+        )),
+      ),
+    )),
+    map_fn,
+  )(input)
 }
@@ -19,11 +19,11 @@ use nom::branch::alt;
 use nom::combinator::{consumed, cut, map, opt};
 use nom::error::context;
 use nom::multi::{separated_list0, separated_list1};
-use nom::sequence::{delimited, pair, preceded, separated_pair, terminated};
+use nom::sequence::{delimited, pair, preceded, separated_pair, terminated, tuple};
 
 fn parse_var(input: ParserInput) -> ParserResult<AstNode> {
   let mk_var = |(consumed_input, n): (ParserInput, String)| -> AstNode {
-    AstNodeImpl::construct_with_location(SourceLoc::new(&consumed_input), Var(ErlVar::new(&n)))
+    AstNodeImpl::construct_with_location(SourceLoc::new_span(&consumed_input), Var(ErlVar::new(&n)))
   };
 
   map(consumed(tok_var), mk_var)(input)
@@ -40,15 +40,15 @@ pub fn parse_parenthesized_list_of_exprs(input: ParserInput) -> ParserResult<Vec
 
 fn parse_binary_comprehension_1(input: ParserInput) -> ParserResult<AstNode> {
   map(
-    separated_pair(
+    consumed(separated_pair(
       context("binary comprehension output expression", parse_expr),
       tok_double_vertical_bar,
       context("binary comprehension generators", cut(parse_comprehension_exprs_and_generators)),
-    ),
-    |(expr, generators): (AstNode, Vec<AstNode>)| -> AstNode {
-      AstNodeImpl::new_binary_comprehension(SourceLoc::new(&input), expr, generators)
+    )),
+    |(consumed_input, (expr, generators)): (ParserInput, (AstNode, Vec<AstNode>))| -> AstNode {
+      AstNodeImpl::new_binary_comprehension(SourceLoc::new_span(&consumed_input), expr, generators)
     },
-  )(input.clone())
+  )(input)
 }
 
 /// Parses a binary comprehension syntax `<< OUTPUT || VAR1 <- GENERATOR1, COND1 ... >>`
@@ -61,9 +61,12 @@ fn parse_binary_comprehension(input: ParserInput) -> ParserResult<AstNode> {
 
 /// Parse a sequence of curly braced expressions `"{" EXPR1 "," EXPR2 "," ... "}"`
 fn parse_tuple_builder(input: ParserInput) -> ParserResult<AstNode> {
-  map(delimited(tok_curly_open, parse_comma_sep_exprs0, tok_curly_close), |elements| {
-    AstNodeImpl::new_tuple(SourceLoc::new(&input), elements)
-  })(input.clone())
+  map(
+    consumed(delimited(tok_curly_open, parse_comma_sep_exprs0, tok_curly_close)),
+    |(consumed_input, elements)| {
+      AstNodeImpl::new_tuple(SourceLoc::new_span(&consumed_input), elements)
+    },
+  )(input)
 }
 
 /// Parses comma separated sequence of expressions
@@ -87,19 +90,20 @@ pub fn parenthesized_expr(input: ParserInput) -> ParserResult<AstNode> {
 
 /// Parses a `begin-end` grouping
 pub(crate) fn parse_begin_end(input: ParserInput) -> ParserResult<AstNode> {
-  let map_fn =
-    |exprs: Vec<AstNode>| -> AstNode { AstNodeImpl::new_begin_end(SourceLoc::new(&input), exprs) };
+  let map_fn = |(consumed_input, exprs): (ParserInput, Vec<AstNode>)| -> AstNode {
+    AstNodeImpl::new_begin_end(SourceLoc::new_span(&consumed_input), exprs)
+  };
   map(
-    delimited(
+    consumed(delimited(
       keyword_begin,
       context(
         "contents of a begin-end expression",
         cut(separated_list1(tok_comma, parse_expr)),
       ),
       keyword_end,
-    ),
+    )),
     map_fn,
-  )(input.clone())
+  )(input)
 }
 
 /// Parses `fun [module :] function / arity`
@@ -114,7 +118,7 @@ fn parse_fn_reference(input: ParserInput) -> ParserResult<AstNode> {
     )),
     |(consumed_input, (module, (function, arity)))| {
       AstNodeImpl::new_fn_ref(
-        SourceLoc::new(&consumed_input),
+        SourceLoc::new_span(&consumed_input),
         module,
         function,
         arity.as_usize().unwrap(),
@@ -157,18 +161,37 @@ pub fn parse_constant_expr(input: ParserInput) -> ParserResult<AstNode> {
 /// Parses a `Expr <- Expr` generator for a list or binary comprehension
 fn parse_comprehension_generator(input: ParserInput) -> ParserResult<AstNode> {
   let make_comp_gen = |(consumed_input, (a, b)): (ParserInput, (AstNode, AstNode))| -> AstNode {
-    AstNodeImpl::new_list_comprehension_generator(SourceLoc::new(&consumed_input), a, b)
+    AstNodeImpl::new_list_comprehension_generator(SourceLoc::new_span(&consumed_input), a, b)
   };
   map(consumed(separated_pair(parse_expr, tok_left_arrow, parse_expr)), make_comp_gen)(
     input.clone(),
   )
 }
 
-/// Parses mix of generators and conditions for a list or binary comprehension
+/// Parses a `KeyExpr := ValueExpr <- MapExpr` map generator for a comprehension
+fn parse_map_comprehension_generator(input: ParserInput) -> ParserResult<AstNode> {
+  map(
+    consumed(tuple((
+      terminated(parse_expr, tok_assign),
+      terminated(parse_expr, tok_left_arrow),
+      parse_expr,
+    ))),
+    |(consumed_input, (key, value, right))| {
+      AstNodeImpl::new_map_comprehension_generator(
+        SourceLoc::new_span(&consumed_input),
+        key,
+        value,
+        right,
+      )
+    },
+  )(input)
+}
+
+/// Parses mix of generators and conditions for a list, binary or map comprehension
 pub fn parse_comprehension_exprs_and_generators(input: ParserInput) -> ParserResult<Vec<AstNode>> {
   separated_list1(
     tok_comma,
     // descend into precedence 11 instead of parse_expr, to ignore comma and semicolon
-    alt((parse_comprehension_generator, parse_expr)),
+    alt((parse_map_comprehension_generator, parse_comprehension_generator, parse_expr)),
   )(input)
 }
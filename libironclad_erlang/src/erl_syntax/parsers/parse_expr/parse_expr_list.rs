@@ -24,7 +24,7 @@ pub fn parse_list_builder(input: ParserInput) -> ParserResult<AstNode> {
   )|
    -> AstNode {
     elements.insert(0, head);
-    AstNodeImpl::new_list(SourceLoc::new(&consumed_input), elements, maybe_tail)
+    AstNodeImpl::new_list(SourceLoc::new_span(&consumed_input), elements, maybe_tail)
   };
 
   // A square bracket delimited sequence, where
@@ -53,7 +53,7 @@ pub fn parse_list_builder(input: ParserInput) -> ParserResult<AstNode> {
 //     (Vec<AstNode>, Option<AstNode>),
 //   )|
 //    -> AstNode {
-//     AstNodeImpl::new_list(SourceLoc::new(&consumed_input), elements, maybe_tail)
+//     AstNodeImpl::new_list(SourceLoc::new_span(&consumed_input), elements, maybe_tail)
 //   };
 //
 //   map(
@@ -72,7 +72,7 @@ pub fn parse_list_builder(input: ParserInput) -> ParserResult<AstNode> {
 fn parse_list_comprehension_1(input: ParserInput) -> ParserResult<AstNode> {
   let mk_list_comp =
     |(consumed_input, (expr, generators)): (ParserInput, (AstNode, Vec<AstNode>))| -> AstNode {
-      AstNodeImpl::new_list_comprehension(SourceLoc::new(&consumed_input), expr, generators)
+      AstNodeImpl::new_list_comprehension(SourceLoc::new_span(&consumed_input), expr, generators)
     };
 
   map(
@@ -9,9 +9,10 @@ use crate::erl_syntax::parsers::parse_expr;
 use crate::erl_syntax::parsers::parser_input::ParserInput;
 use crate::source_loc::SourceLoc;
 use nom::branch::alt;
-use nom::combinator::map;
+use nom::combinator::{consumed, cut, map};
+use nom::error::context;
 use nom::multi::separated_list0;
-use nom::sequence::{delimited, pair, separated_pair};
+use nom::sequence::{delimited, pair, preceded, separated_pair};
 
 /// Parse assignment in a map builder `keyExpr "=>" valueExpr`
 fn map_builder_assign(input: ParserInput) -> ParserResult<MapBuilderMember> {
@@ -33,23 +34,41 @@ fn map_builder_match(input: ParserInput) -> ParserResult<MapBuilderMember> {
 /// Contrary to a map matcher, which would use `:=`.
 pub fn parse_map_builder_no_base(input: ParserInput) -> ParserResult<AstNode> {
   map(
-    delimited(
+    consumed(delimited(
       pair(tok_hash, tok_curly_open),
       separated_list0(tok_comma, alt((map_builder_assign, map_builder_match))),
       tok_curly_close,
-    ),
-    |members| AstNodeImpl::new_map_builder(SourceLoc::new(&input), None, members),
-  )(input.clone())
+    )),
+    |(consumed_input, members)| {
+      AstNodeImpl::new_map_builder(SourceLoc::new_span(&consumed_input), None, members)
+    },
+  )(input)
 }
 
-// /// Parse a map builder expression
-// pub fn parse_map_builder_with_base(input: ParserInput) -> ParserResult<AstNode> {
-//   map(
-//     consumed(tuple((
-//       parse_expr_prec01,
-//       preceded(tok_hash, tok_atom),
-//       preceded(tok_period, tok_atom),
-//     ))),
-//     mk_record_access_op,
-//   )(input)
-// }
+/// Parses a map comprehension syntax `#{ KEY => VALUE || GENERATORS }`
+pub fn parse_map_comprehension(input: ParserInput) -> ParserResult<AstNode> {
+  map(
+    consumed(delimited(
+      pair(tok_hash, tok_curly_open),
+      pair(
+        separated_pair(parse_expr::parse_expr, tok_right_darr, parse_expr::parse_expr),
+        preceded(
+          tok_double_vertical_bar,
+          context(
+            "map comprehension generators",
+            cut(parse_expr::parse_comprehension_exprs_and_generators),
+          ),
+        ),
+      ),
+      tok_curly_close,
+    )),
+    |(consumed_input, ((key, value), generators))| {
+      AstNodeImpl::new_map_comprehension(
+        SourceLoc::new_span(&consumed_input),
+        key,
+        value,
+        generators,
+      )
+    },
+  )(input)
+}
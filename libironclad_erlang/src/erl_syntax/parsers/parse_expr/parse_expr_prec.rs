@@ -16,7 +16,9 @@ use crate::erl_syntax::parsers::parse_case::parse_case_expression;
 use crate::erl_syntax::parsers::parse_expr::parse_expr_list::{
   parse_list_builder, parse_list_comprehension,
 };
-use crate::erl_syntax::parsers::parse_expr::parse_expr_map::parse_map_builder_no_base;
+use crate::erl_syntax::parsers::parse_expr::parse_expr_map::{
+  parse_map_builder_no_base, parse_map_comprehension,
+};
 use crate::erl_syntax::parsers::parse_expr::parse_expr_record::{
   parse_record_builder_no_base, parse_record_field_access_no_base,
 };
@@ -35,11 +37,13 @@ use crate::erl_syntax::parsers::parse_expr_op::{
 use crate::erl_syntax::parsers::parse_fn::parse_lambda;
 use crate::erl_syntax::parsers::parse_if::parse_if_expression;
 use crate::erl_syntax::parsers::parse_lit::parse_erl_literal;
+use crate::erl_syntax::parsers::parse_maybe::parse_maybe_expression;
+use crate::erl_syntax::parsers::parse_receive::parse_receive_expression;
 use crate::erl_syntax::parsers::parse_try_catch::parse_try_catch_expression;
 use crate::erl_syntax::parsers::parser_input::ParserInput;
 use crate::source_loc::SourceLoc;
 use nom::branch::alt;
-use nom::combinator::{consumed, map, opt};
+use nom::combinator::{consumed, cut, map, opt};
 use nom::error::context;
 use nom::multi::many0;
 use nom::sequence::{pair, preceded, tuple};
@@ -63,7 +67,10 @@ fn parse_expr_prec_primary<'a>(input: ParserInput<'a>) -> ParserResult<AstNode>
         LangConstruct::ListComprehension,
         LangConstruct::Literal,
         LangConstruct::Map,
+        LangConstruct::MapComprehension,
+        LangConstruct::MaybeExpression,
         LangConstruct::ParenthesizedExpression,
+        LangConstruct::ReceiveExpression,
         LangConstruct::Record,
         LangConstruct::TryCatch,
         LangConstruct::Tuple,
@@ -80,12 +87,15 @@ fn parse_expr_prec_primary<'a>(input: ParserInput<'a>) -> ParserResult<AstNode>
         parse_try_catch_expression,
         parse_if_expression,
         parse_case_expression,
+        parse_receive_expression,
+        parse_maybe_expression,
         parenthesized_expr,
         parse_list_builder,
         parse_tuple_builder,
       )),
       alt((
         parse_fn_reference,
+        parse_map_comprehension,
         parse_map_builder_no_base,
         parse_record_builder_no_base,
         parse_record_field_access_no_base,
@@ -105,7 +115,7 @@ fn parse_expr_prec_primary<'a>(input: ParserInput<'a>) -> ParserResult<AstNode>
 /// Parse expr followed by a parentheses with 0 or more args, to become a function call
 fn parse_expr_prec01(input: ParserInput) -> ParserResult<AstNode> {
   map(
-    tuple((
+    consumed(tuple((
       parse_expr_prec_primary,
       // An optional second expression after a ':', MUST be followed by parentheses with args
       opt(
@@ -115,18 +125,18 @@ fn parse_expr_prec01(input: ParserInput) -> ParserResult<AstNode> {
           parse_parenthesized_list_of_exprs,
         ),
       ),
-    )),
-    |(expr1, maybe_expr2_args)| {
+    ))),
+    |(consumed_input, (expr1, maybe_expr2_args))| {
       if let Some((maybe_expr2, args)) = maybe_expr2_args {
         match maybe_expr2 {
           Some(expr2) => {
             // TODO: merge match clause 2 and 3 as new_mfa_expr should be doing job of both?
             let target = CallableTarget::new_mfa_expr(Some(expr1), expr2, args.len());
-            AstNodeImpl::new_application(SourceLoc::new(&input), target, args)
+            AstNodeImpl::new_application(SourceLoc::new_span(&consumed_input), target, args)
           }
           None => {
             let target = CallableTarget::new_expr(expr1);
-            AstNodeImpl::new_application(SourceLoc::new(&input), target, args)
+            AstNodeImpl::new_application(SourceLoc::new_span(&consumed_input), target, args)
           }
         }
       } else {
@@ -270,7 +280,7 @@ fn parse_expr_prec08(input: ParserInput) -> ParserResult<AstNode> {
 fn mk_binop_left_assoc(
   (consumed_input, (left, tail)): (ParserInput, (AstNode, Vec<(ErlBinaryOp, AstNode)>)),
 ) -> AstNode {
-  ErlBinaryOperatorExpr::new_left_assoc(SourceLoc::new(&consumed_input), left, &tail)
+  ErlBinaryOperatorExpr::new_left_assoc(SourceLoc::new_span(&consumed_input), left, &tail)
 }
 
 /// Precedence 9: orelse
@@ -286,7 +296,7 @@ fn parse_expr_prec09(input: ParserInput) -> ParserResult<AstNode> {
 fn mk_binop_right_assoc(
   (consumed_input, (left, tail)): (ParserInput, (AstNode, Vec<(ErlBinaryOp, AstNode)>)),
 ) -> AstNode {
-  ErlBinaryOperatorExpr::new_right_assoc(SourceLoc::new(&consumed_input), left, &tail)
+  ErlBinaryOperatorExpr::new_right_assoc(SourceLoc::new_span(&consumed_input), left, &tail)
 }
 
 /// Precedence 10: assignment/match = operator, and send operator "!", right associative
@@ -304,30 +314,43 @@ fn parse_expr_prec10(input: ParserInput) -> ParserResult<AstNode> {
 #[inline]
 /// Wrap values passed here with `consumed()` nom combinator
 fn mk_unary((consumed_input, (catch_op, expr)): (ParserInput, (ErlUnaryOp, AstNode))) -> AstNode {
-  ErlUnaryOperatorExpr::new_ast(SourceLoc::new(&consumed_input), catch_op, expr)
+  ErlUnaryOperatorExpr::new_ast(SourceLoc::new_span(&consumed_input), catch_op, expr)
 }
 
-/// Precedence 11: Catch operator, then continue to higher precedences
+/// Precedence 11: Catch operator, then continue to higher precedences.
+/// `catch` is a prefix operator with the lowest precedence, so `catch X = f()` catches the whole
+/// match, and `catch` can be nested. `X = catch f()` is not valid Erlang without the parentheses.
 /// This is also entry point to parse expression when you don't want to recognize comma and semicolon
 fn parse_expr_prec11(input: ParserInput) -> ParserResult<AstNode> {
-  // Try parse (catch Expr) otherwise try next precedence level
-  map(consumed(pair(unop_catch, parse_expr_prec10)), mk_unary)(input.clone())
-    .or_else(|_err| parse_expr_prec10(input.clone()))
+  alt((
+    map(
+      consumed(pair(unop_catch, context("catch expression", cut(parse_expr_prec11)))),
+      mk_unary,
+    ),
+    parse_expr_prec10,
+  ))(input)
+}
+
+/// Precedence 12: comma-separated guard tests, all must succeed.
+fn parse_expr_prec12(input: ParserInput) -> ParserResult<AstNode> {
+  map(
+    // Higher precedence expr, followed by 0 or more commas and higher prec exprs
+    consumed(pair(parse_expr_prec11, many0(pair(binop_comma, parse_expr_prec11)))),
+    mk_binop_left_assoc,
+  )(input)
 }
 
 /// Lowest precedence 13, where we handle comma and semicolon as binary ops.
 /// Note that semicolon is not valid for regular code only allowed in guards.
+/// A guard sequence `A, B; C` is a semicolon-separated list of comma-separated guards, so comma
+/// binds tighter than semicolon.
 fn parse_expr_prec13(style: ExprStyle, input: ParserInput) -> ParserResult<AstNode> {
   // Only guard style expressions allow comma/semicolon as binary ops
   if style == ExprStyle::Guard {
-    // Guard-style expressions allow both comma and semicolons
     map(
-      // Higher precedence expr, followed by 0 or more binary operators and higher prec exprs
-      pair(
-        parse_expr_prec11,
-        many0(pair(alt((binop_semicolon, binop_comma)), parse_expr_prec11)),
-      ),
-      |(left, tail)| ErlBinaryOperatorExpr::new_left_assoc(SourceLoc::None, left, &tail),
+      // Higher precedence expr, followed by 0 or more semicolons and higher prec exprs
+      consumed(pair(parse_expr_prec12, many0(pair(binop_semicolon, parse_expr_prec12)))),
+      mk_binop_left_assoc,
     )(input)
   } else {
     parse_expr_prec11(input)
@@ -337,17 +360,17 @@ fn parse_expr_prec13(style: ExprStyle, input: ParserInput) -> ParserResult<AstNo
 /// Parse an expression from the beginning of precedence ladder
 pub fn parse_expr_lowest_precedence(style: ExprStyle, input: ParserInput) -> ParserResult<AstNode> {
   map(
-    context(
+    consumed(context(
       "expression",
       tuple((|i| parse_expr_prec13(style, i), opt(parse_parenthesized_list_of_exprs))),
-    ),
-    |(expr, maybe_args): (AstNode, Option<Vec<AstNode>>)| -> AstNode {
+    )),
+    |(consumed_input, (expr, maybe_args)): (ParserInput, (AstNode, Option<Vec<AstNode>>))| {
       if let Some(args) = maybe_args {
         let target = CallableTarget::new_expr(expr);
-        AstNodeImpl::new_application(SourceLoc::new(&input), target, args)
+        AstNodeImpl::new_application(SourceLoc::new_span(&consumed_input), target, args)
       } else {
         expr
       }
     },
-  )(input.clone())
+  )(input)
 }
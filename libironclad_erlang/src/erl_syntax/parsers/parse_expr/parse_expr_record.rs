@@ -33,16 +33,16 @@ fn record_builder_tag_body(input: ParserInput) -> ParserResult<(String, Vec<Reco
 
 /// Parse a record builder expression without a prefix expression just `# RECORDTAG { FIELDS }`
 pub fn parse_record_builder_no_base(input: ParserInput) -> ParserResult<AstNode> {
-  map(record_builder_tag_body, |(tag, record_fields)| {
-    AstNodeImpl::new_record_builder(SourceLoc::new(&input), None, tag, record_fields)
-  })(input.clone())
+  map(consumed(record_builder_tag_body), |(consumed_input, (tag, record_fields))| {
+    AstNodeImpl::new_record_builder(SourceLoc::new_span(&consumed_input), None, tag, record_fields)
+  })(input)
 }
 
 /// Parse a record field access expression, without a base, evaluates to record field index
 pub(crate) fn parse_record_field_access_no_base(input: ParserInput) -> ParserResult<AstNode> {
   let mk_field_index =
     |(consumed_input, (tag, field)): (ParserInput, (String, String))| -> AstNode {
-      AstNodeImpl::new_record_field(SourceLoc::new(&consumed_input), None, tag, field)
+      AstNodeImpl::new_record_field(SourceLoc::new_span(&consumed_input), None, tag, field)
     };
   map(
     consumed(tuple((preceded(tok_hash, tok_atom), preceded(tok_period, tok_atom)))),
@@ -54,7 +54,7 @@ pub(crate) fn parse_record_field_access_no_base(input: ParserInput) -> ParserRes
 // pub fn parse_record_field_access_with_base(input: ParserInput) -> ParserResult<AstNode> {
 //   let mk_field_access =
 //     |(consumed_input, (left, tag, field)): (ParserInput, (AstNode, String, String))| -> AstNode {
-//       AstNodeImpl::new_record_field(SourceLoc::new(&consumed_input), Some(left), tag, field)
+//       AstNodeImpl::new_record_field(SourceLoc::new_span(&consumed_input), Some(left), tag, field)
 //     };
 //   map(
 //     consumed(tuple((
@@ -12,7 +12,7 @@ use crate::erl_syntax::parsers::parse_expr::{
 use crate::erl_syntax::parsers::parser_input::ParserInput;
 use crate::source_loc::SourceLoc;
 use libironclad_util::mfarity::MFArity;
-use nom::combinator::{cut, map, not, opt, peek};
+use nom::combinator::{consumed, cut, map, not, opt, peek};
 use nom::error::context;
 use nom::multi::separated_list1;
 use nom::sequence::{delimited, preceded, terminated, tuple};
@@ -55,18 +55,21 @@ fn parse_fnclause<const REQUIRE_FN_NAME: bool>(input: ParserInput) -> ParserResu
       preceded(
         tok_right_arrow,
         // Body as list of exprs
-        context("function clause body of a function definition", cut(parse_comma_sep_exprs1)),
+        context(
+          "function clause body of a function definition",
+          cut(consumed(parse_comma_sep_exprs1)),
+        ),
       ),
     )),
-    |(maybe_name, args, when_expr, body)| {
+    |(maybe_name, args, when_expr, (consumed_body, body))| {
       ErlFnClause::new(
         maybe_name,
         args,
-        AstNodeImpl::new_comma_expr(SourceLoc::new(&input), body),
+        AstNodeImpl::new_comma_expr(SourceLoc::new_span(&consumed_body), body),
         when_expr,
       )
     },
-  )(input.clone())
+  )(input)
 }
 
 /// Builds a function definition from multiple parsed clauses
@@ -92,7 +95,7 @@ fn _construct_fndef(location: SourceLoc, fnclauses: Vec<ErlFnClause>) -> AstNode
 /// Parse function definition
 pub fn parse_fndef(input: ParserInput) -> ParserResult<AstNode> {
   map(
-    delimited(
+    consumed(delimited(
       // does not begin with - (that would be a mis-parsed attribute)
       not(peek(tok_minus)),
       separated_list1(
@@ -101,22 +104,22 @@ pub fn parse_fndef(input: ParserInput) -> ParserResult<AstNode> {
         context("function clause of a function definition", parse_fnclause::<true>),
       ),
       tok_period,
-    ),
-    |t| _construct_fndef(SourceLoc::new(&input), t),
-  )(input.clone())
+    )),
+    |(consumed_input, t)| _construct_fndef(SourceLoc::new_span(&consumed_input), t),
+  )(input)
 }
 
 /// Lambda is an inline function definition
 pub(crate) fn parse_lambda(input: ParserInput) -> ParserResult<AstNode> {
   // Lambda is made of "fun" keyword, followed by multiple ";" separated clauses
   map(
-    preceded(
+    consumed(preceded(
       keyword_fun,
       terminated(
         context("", separated_list1(tok_semicolon, parse_fnclause::<false>)),
         keyword_end,
       ),
-    ),
-    |t| _construct_fndef(SourceLoc::new(&input), t),
-  )(input.clone())
+    )),
+    |(consumed_input, t)| _construct_fndef(SourceLoc::new_span(&consumed_input), t),
+  )(input)
 }
@@ -25,24 +25,29 @@ fn parse_if_clause(input: ParserInput) -> ParserResult<ErlIfClause> {
       parse_comma_sep_exprs1,
     )),
     |(consumed_input, (cond, body))| {
-      ErlIfClause::new(cond, AstNodeImpl::new_comma_expr(SourceLoc::new(&consumed_input), body))
+      ErlIfClause::new(
+        cond,
+        AstNodeImpl::new_comma_expr(SourceLoc::new_span(&consumed_input), body),
+      )
     },
   )(input)
 }
 
 /// Parses `if COND -> EXPR; ... end`
 pub(crate) fn parse_if_expression(input: ParserInput) -> ParserResult<AstNode> {
-  context(
-    "if-end expression",
-    preceded(
-      keyword_if,
-      cut(map(
-        terminated(
+  map(
+    consumed(context(
+      "if-end expression",
+      preceded(
+        keyword_if,
+        cut(terminated(
           separated_list1(tok_semicolon, context("if block clause", parse_if_clause)),
           keyword_end,
-        ),
-        |clauses| AstNodeImpl::new_if_statement(SourceLoc::new(&input), clauses),
-      )),
-    ),
-  )(input.clone())
+        )),
+      ),
+    )),
+    |(consumed_input, clauses)| {
+      AstNodeImpl::new_if_statement(SourceLoc::new_span(&consumed_input), clauses)
+    },
+  )(input)
 }
@@ -52,14 +52,14 @@ fn parse_int_to_ast(input: ParserInput) -> ParserResult<AstNode> {
 
 fn parse_nil(input: ParserInput) -> ParserResult<AstNode> {
   let make_nil = |(consumed_input, ((), ())): (ParserInput, ((), ()))| -> AstNode {
-    AstNodeImpl::new_nil(SourceLoc::new(&consumed_input))
+    AstNodeImpl::new_nil(SourceLoc::new_span(&consumed_input))
   };
   map(consumed(pair(tok_square_open, tok_square_close)), make_nil)(input.clone())
 }
 
 fn parse_empty_binary(input: ParserInput) -> ParserResult<AstNode> {
   let make_empty = |(consumed_input, ((), ())): (ParserInput, ((), ()))| -> AstNode {
-    AstNodeImpl::new_empty_binary(SourceLoc::new(&consumed_input))
+    AstNodeImpl::new_empty_binary(SourceLoc::new_span(&consumed_input))
   };
   map(consumed(pair(tok_double_angle_open, tok_double_angle_close)), make_empty)(input.clone())
 }
@@ -0,0 +1,68 @@
+//! Parses `maybe EXPR, ... else MATCH -> EXPR; ... end` and conditional matches `PATTERN ?= EXPR`
+use crate::erl_syntax::erl_ast::node_impl::AstNodeImpl;
+use crate::erl_syntax::erl_ast::AstNode;
+use crate::erl_syntax::node::erl_case_clause::ErlCaseClause;
+use crate::erl_syntax::parsers::defs::ParserResult;
+use crate::erl_syntax::parsers::misc_tok::*;
+use crate::erl_syntax::parsers::parse_case::parse_case_clause;
+use crate::erl_syntax::parsers::parse_expr::{parse_expr, parse_matchexpr};
+use crate::erl_syntax::parsers::parser_input::ParserInput;
+use crate::source_loc::SourceLoc;
+use nom::branch::alt;
+use nom::combinator::{consumed, cut, map, opt};
+use nom::error::context;
+use nom::multi::separated_list1;
+use nom::sequence::{pair, preceded, separated_pair, terminated};
+
+/// Parses a conditional match `PATTERN ?= EXPR`
+fn parse_maybe_match(input: ParserInput) -> ParserResult<AstNode> {
+  map(
+    consumed(separated_pair(
+      parse_matchexpr,
+      tok_maybe_match,
+      context("conditional match expression", cut(parse_expr)),
+    )),
+    |(consumed_input, (pattern, expr))| {
+      AstNodeImpl::new_maybe_match(SourceLoc::new_span(&consumed_input), pattern, expr)
+    },
+  )(input)
+}
+
+/// Parses the `else MATCH -> EXPR; ...` section of a `maybe` block
+fn parse_maybe_else(input: ParserInput) -> ParserResult<Vec<ErlCaseClause>> {
+  preceded(
+    keyword_else,
+    context(
+      "maybe-else clauses",
+      cut(separated_list1(tok_semicolon, context("maybe-else clause", parse_case_clause))),
+    ),
+  )(input)
+}
+
+/// Parses `maybe EXPR, ... end` where expressions can be conditional matches `PATTERN ?= EXPR`,
+/// with an optional `else MATCH -> EXPR; ...` section.
+pub(crate) fn parse_maybe_expression(input: ParserInput) -> ParserResult<AstNode> {
+  type MaybeParts = (Vec<AstNode>, Option<Vec<ErlCaseClause>>);
+  let map_fn = |(consumed_input, (exprs, else_clauses)): (ParserInput, MaybeParts)| {
+    AstNodeImpl::new_maybe(SourceLoc::new_span(&consumed_input), exprs, else_clauses)
+  };
+  map(
+    consumed(preceded(
+      keyword_maybe,
+      context(
+        "maybe-end expression",
+        cut(terminated(
+          pair(
+            separated_list1(
+              tok_comma,
+              context("maybe block expression", alt((parse_maybe_match, parse_expr))),
+            ),
+            opt(parse_maybe_else),
+          ),
+          keyword_end,
+        )),
+      ),
+    )),
+    map_fn,
+  )(input)
+}
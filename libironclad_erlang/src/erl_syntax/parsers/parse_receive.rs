@@ -0,0 +1,67 @@
+//! Parses `receive MATCH -> EXPR; ... after TIMEOUT -> EXPR end`
+use crate::erl_syntax::erl_ast::node_impl::AstNodeImpl;
+use crate::erl_syntax::erl_ast::AstNode;
+use crate::erl_syntax::node::erl_case_clause::ErlCaseClause;
+use crate::erl_syntax::node::erl_receive_after::ErlReceiveAfter;
+use crate::erl_syntax::parsers::defs::ParserResult;
+use crate::erl_syntax::parsers::misc_tok::*;
+use crate::erl_syntax::parsers::parse_case::parse_case_clause;
+use crate::erl_syntax::parsers::parse_expr::{parse_comma_sep_exprs1, parse_expr};
+use crate::erl_syntax::parsers::parser_input::ParserInput;
+use crate::source_loc::SourceLoc;
+use nom::branch::alt;
+use nom::combinator::{consumed, cut, map, opt};
+use nom::error::context;
+use nom::multi::separated_list1;
+use nom::sequence::{pair, preceded, separated_pair, terminated};
+
+/// Parses the `after TIMEOUT -> EXPR` section of a `receive`
+fn parse_receive_after(input: ParserInput) -> ParserResult<ErlReceiveAfter> {
+  map(
+    preceded(
+      keyword_after,
+      context(
+        "receive-after section",
+        cut(separated_pair(
+          context("receive timeout expression", parse_expr),
+          tok_right_arrow,
+          context("receive-after body", consumed(parse_comma_sep_exprs1)),
+        )),
+      ),
+    ),
+    |(timeout, (consumed_body, body))| {
+      ErlReceiveAfter::new(
+        timeout,
+        AstNodeImpl::new_comma_expr(SourceLoc::new_span(&consumed_body), body),
+      )
+    },
+  )(input)
+}
+
+/// Parses `receive MATCH -> EXPR; ... end` with optional `after TIMEOUT -> EXPR` section.
+/// Clauses can be omitted only if `after` is present.
+pub(crate) fn parse_receive_expression(input: ParserInput) -> ParserResult<AstNode> {
+  type ReceiveParts = (Vec<ErlCaseClause>, Option<ErlReceiveAfter>);
+  let map_fn = |(consumed_input, (clauses, after)): (ParserInput, ReceiveParts)| {
+    AstNodeImpl::new_receive(SourceLoc::new_span(&consumed_input), clauses, after)
+  };
+  map(
+    consumed(preceded(
+      keyword_receive,
+      context(
+        "receive-end expression",
+        cut(terminated(
+          alt((
+            pair(
+              separated_list1(tok_semicolon, context("receive clause", parse_case_clause)),
+              opt(parse_receive_after),
+            ),
+            map(parse_receive_after, |after| (Vec::new(), Some(after))),
+          )),
+          keyword_end,
+        )),
+      ),
+    )),
+    map_fn,
+  )(input)
+}
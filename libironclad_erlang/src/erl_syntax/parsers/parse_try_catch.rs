@@ -2,6 +2,7 @@
 
 use crate::erl_syntax::erl_ast::node_impl::AstNodeImpl;
 use crate::erl_syntax::erl_ast::AstNode;
+use crate::erl_syntax::node::erl_case_clause::ErlCaseClause;
 use crate::erl_syntax::node::erl_catch_clause::CatchClause;
 use crate::erl_syntax::node::erl_exception_pattern::ExceptionPattern;
 use crate::erl_syntax::parsers::defs::ParserResult;
@@ -12,7 +13,7 @@ use crate::erl_syntax::parsers::parse_expr::{
 };
 use crate::erl_syntax::parsers::parser_input::ParserInput;
 use crate::source_loc::SourceLoc;
-use nom::combinator::{cut, map, opt};
+use nom::combinator::{consumed, cut, map, opt};
 use nom::error::context;
 use nom::multi::{many1, separated_list1};
 use nom::sequence::{preceded, terminated, tuple};
@@ -40,22 +41,26 @@ pub fn parse_catch_clause(input: ParserInput) -> ParserResult<CatchClause> {
       // when <Expression>
       opt(preceded(keyword_when, parse_guardexpr)),
       // -> Expression
-      preceded(tok_right_arrow, parse_comma_sep_exprs1),
+      preceded(tok_right_arrow, consumed(parse_comma_sep_exprs1)),
     )),
-    |(exc_pattern, maybe_when, body)| {
+    |(exc_pattern, maybe_when, (consumed_body, body))| {
       CatchClause::new(
         exc_pattern,
         maybe_when,
-        AstNodeImpl::new_comma_expr(SourceLoc::new(&input), body),
+        AstNodeImpl::new_comma_expr(SourceLoc::new_span(&consumed_body), body),
       )
     },
-  )(input.clone())
+  )(input)
 }
 
-fn parse_try_catch_inner(input: ParserInput) -> ParserResult<AstNode> {
+/// Parses the `try` block contents: body, optional `of` clauses and `catch` clauses
+#[allow(clippy::type_complexity)]
+fn parse_try_catch_inner(
+  input: ParserInput,
+) -> ParserResult<(AstNode, Option<Vec<ErlCaseClause>>, Vec<CatchClause>)> {
   map(
     tuple((
-      context("try-catch block trial expression", cut(parse_comma_sep_exprs1)),
+      context("try-catch block trial expression", cut(consumed(parse_comma_sep_exprs1))),
       // Optional OF followed by match clauses
       opt(preceded(
         keyword_of,
@@ -70,25 +75,30 @@ fn parse_try_catch_inner(input: ParserInput) -> ParserResult<AstNode> {
         ),
       ),
     )),
-    |(body, of_branches, catch_clauses)| {
-      let loc = SourceLoc::new(&input);
-      AstNodeImpl::new_try_catch(
-        loc.clone(),
-        AstNodeImpl::new_comma_expr(loc, body),
-        of_branches,
-        catch_clauses,
-      )
+    |((consumed_body, body), of_branches, catch_clauses)| {
+      let body = AstNodeImpl::new_comma_expr(SourceLoc::new_span(&consumed_body), body);
+      (body, of_branches, catch_clauses)
     },
-  )(input.clone())
+  )(input)
 }
 
 /// Parses a `try-catch` or a `try-of-catch` block
 pub(crate) fn parse_try_catch_expression(input: ParserInput) -> ParserResult<AstNode> {
-  preceded(
-    keyword_try,
-    context(
-      "try-end or try-of-end block",
-      cut(terminated(parse_try_catch_inner, keyword_end)),
-    ),
+  map(
+    consumed(preceded(
+      keyword_try,
+      context(
+        "try-end or try-of-end block",
+        cut(terminated(parse_try_catch_inner, keyword_end)),
+      ),
+    )),
+    |(consumed_input, (body, of_branches, catch_clauses))| {
+      AstNodeImpl::new_try_catch(
+        SourceLoc::new_span(&consumed_input),
+        body,
+        of_branches,
+        catch_clauses,
+      )
+    },
   )(input)
 }
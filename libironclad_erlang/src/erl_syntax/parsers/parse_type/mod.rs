@@ -10,7 +10,7 @@ use crate::erl_syntax::erl_ast::AstNode;
 use crate::erl_syntax::parsers::defs::ParserResult;
 use crate::erl_syntax::parsers::lang_construct::LangConstruct;
 use crate::erl_syntax::parsers::misc;
-use crate::erl_syntax::parsers::misc::{tok_atom, tok_integer};
+use crate::erl_syntax::parsers::misc::{tok_atom, tok_integer, tok_var};
 use crate::erl_syntax::parsers::misc_tok::*;
 use crate::erl_syntax::parsers::parse_type::parse_binary_t::binary_type;
 use crate::erl_syntax::parsers::parse_type::parse_container_t::{
@@ -22,6 +22,7 @@ use crate::source_loc::SourceLoc;
 use crate::typing::erl_integer::ErlInteger;
 use crate::typing::erl_type::{ErlType, ErlTypeImpl};
 use crate::typing::record_field_type::RecordFieldType;
+use crate::typing::typevar::Typevar;
 use nom::branch::alt;
 use nom::combinator::{map, opt};
 use nom::error::context;
@@ -51,6 +52,12 @@ fn parse_user_type(input: ParserInput) -> ParserResult<ErlType> {
   )(input)
 }
 
+/// Parse `fun()` type, which matches any function. `fun` is a keyword and can't be parsed as
+/// an user type name.
+fn any_fun_type(input: ParserInput) -> ParserResult<ErlType> {
+  map(tuple((keyword_fun, tok_par_open, tok_par_close)), |_| ErlTypeImpl::any_fun())(input)
+}
+
 /// Parse one record reference element, pinning field to a type, subtype of the parent's field.
 fn record_field_pin(input: ParserInput) -> ParserResult<RecordFieldType> {
   map(separated_pair(tok_atom, tok_double_colon, parse_type), |(field, ty)| {
@@ -76,9 +83,17 @@ fn record_ref(input: ParserInput) -> ParserResult<ErlType> {
   )(input)
 }
 
+/// Parse an integer with an optional minus sign, types don't allow arbitrary expressions
+fn type_integer(input: ParserInput) -> ParserResult<ErlInteger> {
+  map(
+    pair(opt(tok_minus), tok_integer),
+    |(minus, i)| if minus.is_some() { -i } else { i },
+  )(input)
+}
+
 /// Parse an integer and produce a literal integer type
 fn int_literal_type(input: ParserInput) -> ParserResult<ErlType> {
-  map(tok_integer, |i: ErlInteger| {
+  map(type_integer, |i: ErlInteger| {
     // TODO: Can a parsed integer parse with an error?
     ErlTypeImpl::new_singleton(&Literal::Integer(i).into())
   })(input)
@@ -86,9 +101,9 @@ fn int_literal_type(input: ParserInput) -> ParserResult<ErlType> {
 
 /// Parse an integer range
 fn int_range_type(input: ParserInput) -> ParserResult<ErlType> {
-  map(separated_pair(tok_integer, tok_double_period, tok_integer), |(a, b)| {
+  map(separated_pair(type_integer, tok_double_period, type_integer), |(a, b)| {
     // TODO: Can a parsed integer parse with an error?
-    ErlTypeImpl::new_range(a, b)
+    ErlTypeImpl::new_range(Some(a), Some(b))
   })(input)
 }
 
@@ -97,6 +112,11 @@ fn atom_literal_type(input: ParserInput) -> ParserResult<ErlType> {
   map(tok_atom, |a_str| ErlTypeImpl::new_singleton(&Literal::Atom(a_str).into()))(input)
 }
 
+/// Parse a type variable `T` in a type definition, like in `-type option(T) :: T | undefined.`
+fn type_variable(input: ParserInput) -> ParserResult<ErlType> {
+  map(tok_var, |name| ErlTypeImpl::new_typevar(Typevar::new(Some(name), None)))(input)
+}
+
 /// Parse any simple Erlang type without union. To parse unions use `parse_type`.
 fn parse_nonunion_type<'a>(input: ParserInput<'a>) -> ParserResult<ErlType> {
   let alt_failed = |i: ParserInput<'a>| -> ParserResult<ErlType> {
@@ -112,6 +132,7 @@ fn parse_nonunion_type<'a>(input: ParserInput<'a>) -> ParserResult<ErlType> {
         LangConstruct::RecordRefType,
         LangConstruct::IntegerLiteralType,
         LangConstruct::AtomLiteralType,
+        LangConstruct::TypeVariable,
       ],
     )
   };
@@ -119,6 +140,7 @@ fn parse_nonunion_type<'a>(input: ParserInput<'a>) -> ParserResult<ErlType> {
     "non-union type parser",
     alt((
       context("user defined type", parse_user_type),
+      context("any function type", any_fun_type),
       binary_type,
       context("integer range", int_range_type),
       context("non-empty list type", type_of_nonempty_list),
@@ -128,6 +150,7 @@ fn parse_nonunion_type<'a>(input: ParserInput<'a>) -> ParserResult<ErlType> {
       context("record reference type", record_ref),
       context("integer literal type", int_literal_type),
       context("atom literal type", atom_literal_type),
+      context("type variable", type_variable),
     ))
     .or(alt_failed),
   )(input.clone())
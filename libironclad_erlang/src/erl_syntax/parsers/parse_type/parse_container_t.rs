@@ -13,7 +13,7 @@ use nom::branch::alt;
 use nom::combinator::map;
 use nom::error::context;
 use nom::multi::separated_list0;
-use nom::sequence::{delimited, pair, separated_pair, terminated};
+use nom::sequence::{delimited, pair, terminated, tuple};
 
 /// Parse a list of types, returns a temporary list-type
 pub fn type_of_list(input: ParserInput) -> ParserResult<ErlType> {
@@ -62,10 +62,15 @@ pub fn type_of_tuple(input: ParserInput) -> ParserResult<ErlType> {
 
 fn map_member_type(input: ParserInput) -> ParserResult<MapMemberType> {
   map(
-    separated_pair(parse_typevar_or_type, alt((tok_assign, tok_right_darr)), parse_typevar_or_type),
-    |(key, value)| MapMemberType {
+    tuple((
+      parse_typevar_or_type,
+      alt((map(tok_assign, |_| true), map(tok_right_darr, |_| false))),
+      parse_typevar_or_type,
+    )),
+    |(key, mandatory, value)| MapMemberType {
       key: ErlTypeImpl::new_typevar(key),
       value: ErlTypeImpl::new_typevar(value),
+      mandatory,
     },
   )(input)
 }
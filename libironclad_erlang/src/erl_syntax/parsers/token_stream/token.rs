@@ -11,6 +11,9 @@ use std::ptr::null;
 pub struct Token {
   /// Pointer to source
   pub offset: *const u8,
+  /// Pointer to source, after the last character of the token. Null for tokens not coming from
+  /// the source text.
+  pub end: *const u8,
   /// The token itself
   pub content: TokenType,
   // /// True for the last item in line before `\n`. Field updated during preprocessing.
@@ -23,6 +26,7 @@ impl Token {
   pub fn new_keyword(offset: *const u8, k: Keyword) -> Self {
     Self {
       offset,
+      end: null(),
       content: TokenType::Keyword(k),
       // last_in_line: false,
     }
@@ -31,13 +35,17 @@ impl Token {
   /// Create a new symbol token
   #[inline]
   pub fn new(offset: *const u8, tt: TokenType) -> Self {
-    Self { offset, content: tt }
+    Self { offset, end: null(), content: tt }
   }
 
   /// Create a new End of Line
   #[inline]
   pub fn new_eol() -> Self {
-    Self { offset: null(), content: TokenType::EOL }
+    Self {
+      offset: null(),
+      end: null(),
+      content: TokenType::EOL,
+    }
   }
 
   /// Create a new token for small integer
@@ -45,6 +53,7 @@ impl Token {
   pub fn new_small(i: i64) -> Self {
     Self {
       offset: null(),
+      end: null(),
       content: TokenType::Integer(ErlInteger::Small(i)),
     }
   }
@@ -52,13 +61,21 @@ impl Token {
   /// Create a new token for string
   #[inline]
   pub fn new_string(s: String) -> Self {
-    Self { offset: null(), content: TokenType::Str(s.into()) }
+    Self {
+      offset: null(),
+      end: null(),
+      content: TokenType::Str(s.into()),
+    }
   }
 
   /// Create a new token for atom
   #[inline]
   pub fn new_atom(s: String) -> Self {
-    Self { offset: null(), content: TokenType::Atom(s) }
+    Self {
+      offset: null(),
+      end: null(),
+      content: TokenType::Atom(s),
+    }
   }
 
   /// Check whether the token is a newline token
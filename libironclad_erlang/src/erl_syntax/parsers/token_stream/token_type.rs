@@ -58,6 +58,8 @@ pub enum TokenType {
   HardNotEq,
   /// `=` equals symbol
   EqualSymbol,
+  /// `?=` conditional match in a `maybe` block
+  MaybeMatch,
   /// For typespecs `...` is used for non-empty lists and for any-arity functions
   Ellipsis,
   /// `=>` double right arrow
@@ -165,6 +167,7 @@ impl TokenType {
       TokenType::ListSubtract => "double minus",
       TokenType::MacroInvocation(_) => "a macro invocation",
       TokenType::MacroStringifyArg(_) => "a macro argument pasted as a string",
+      TokenType::MaybeMatch => "conditional match operator ?=",
       TokenType::Minus => "minus",
       TokenType::NotEq => "not equal",
       TokenType::ParClose => "closing parenthesis",
@@ -230,6 +233,7 @@ impl std::fmt::Display for TokenType {
       TokenType::ListSubtract => write!(f, "⊖"),
       TokenType::MacroInvocation(macro_name) => write!(f, "?{}", macro_name),
       TokenType::MacroStringifyArg(macro_arg) => write!(f, "⁇{}", macro_arg),
+      TokenType::MaybeMatch => write!(f, "?="),
       TokenType::Minus => write!(f, "-"),
       TokenType::NotEq => write!(f, "≠"),
       TokenType::ParClose => write!(f, ")"),
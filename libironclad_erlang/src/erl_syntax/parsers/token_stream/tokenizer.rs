@@ -327,6 +327,12 @@ fn tokenize_macro_stringify_arg(input: TokenizerInput) -> TokensResult<Token> {
   )(input)
 }
 
+/// Must be tried before a macro invocation, as both begin with `?`
+#[inline]
+fn symbol_maybe_match(input: TokenizerInput) -> TokensResult<Token> {
+  map(tag("?="), |_| Token::new(input.as_ptr(), TokenType::MaybeMatch))(input)
+}
+
 #[inline]
 fn tokenize_macro_invocation(input: TokenizerInput) -> TokensResult<Token> {
   let map_fn = |m| Token::new(input.as_ptr(), TokenType::MacroInvocation(m));
@@ -595,14 +601,29 @@ fn tokenize_keyword(input: TokenizerInput) -> TokensResult<Token> {
   )(input)
 }
 
+/// Wraps a token parser and records in the token where its source text ends
+fn with_token_end<'a, InnerFn>(
+  mut inner: InnerFn,
+) -> impl FnMut(TokenizerInput<'a>) -> TokensResult<Token>
+where
+  InnerFn: FnMut(TokenizerInput<'a>) -> TokensResult<Token>,
+{
+  move |input: TokenizerInput<'a>| {
+    let (tail, mut token) = inner(input)?;
+    token.end = tail.as_ptr();
+    Ok((tail, token))
+  }
+}
+
 /// Break module source into tokens
 pub fn tokenize_source(input: TokenizerInput) -> TokensResult<Vec<Token>> {
   // Comments after the code are consumed by the outer ws_mut
   // Comments and spaces between the tokens are consumed by the inner ws_before_mut
-  complete(ws_mut(bigcapacity_many0(ws_before_mut(alt((
+  complete(ws_mut(bigcapacity_many0(ws_before_mut(with_token_end(alt((
     tokenize_line_comment,
     tokenize_newline,
     tokenize_macro_stringify_arg,
+    symbol_maybe_match,
     tokenize_macro_invocation,
     tokenize_dollar_character,
     tokenize_string,
@@ -613,5 +634,5 @@ pub fn tokenize_source(input: TokenizerInput) -> TokensResult<Vec<Token>> {
     tokenize_integer,
     tokenize_float,
     tokenize_other_symbols,
-  ))))))(input)
+  )))))))(input)
 }
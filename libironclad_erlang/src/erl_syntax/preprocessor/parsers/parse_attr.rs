@@ -1,4 +1,5 @@
 //! Use nom parser to parse a generic module attribute from a wall of text.
+use crate::erl_syntax::erl_ast::node_impl::AstNodeImpl;
 use crate::erl_syntax::erl_ast::AstNode;
 use crate::erl_syntax::parsers::defs::ParserResult;
 use crate::erl_syntax::parsers::misc::{
@@ -13,12 +14,13 @@ use crate::erl_syntax::preprocessor::parsers::parse_record::parse_record_def;
 use crate::erl_syntax::preprocessor::pp_node::pp_impl::PreprocessorNodeImpl;
 use crate::erl_syntax::preprocessor::pp_node::PreprocessorNode;
 use crate::source_loc::SourceLoc;
+use crate::typing::user_type::UserTypeKind;
 use libironclad_util::mfarity::MFArity;
 use nom::branch::alt;
-use nom::combinator::{cut, map};
+use nom::combinator::{consumed, cut, map};
 use nom::error::context;
 use nom::multi::{separated_list0, separated_list1};
-use nom::sequence::{delimited, pair, preceded, separated_pair, tuple};
+use nom::sequence::{delimited, pair, preceded, separated_pair, terminated, tuple};
 
 /// Parse a `()` for a generic attribute `-<atom>().` and return empty `ErlAst`
 fn attr_body_empty_parens(input: ParserInput) -> ParserResult<Option<AstNode>> {
@@ -64,6 +66,34 @@ pub(crate) fn parse_generic_attr_no_parentheses(
   })(input.clone())
 }
 
+/// Parses a `-feature(NAME, enable | disable).` attribute. The two arguments are stored as a tuple
+/// in a generic attribute, and are checked by the preprocessor.
+pub(crate) fn feature_attr(input: ParserInput) -> ParserResult<PreprocessorNode> {
+  map(
+    delimited(
+      |i1| dash_atom(i1, "feature"),
+      context(
+        "feature name and action in a -feature() attribute",
+        cut(delimited(
+          tok_par_open,
+          consumed(separated_pair(parse_expr, tok_comma, parse_expr)),
+          tok_par_close,
+        )),
+      ),
+      period_eol_eof,
+    ),
+    |(consumed_input, (feature, action))| {
+      let term =
+        AstNodeImpl::new_tuple(SourceLoc::new_span(&consumed_input), vec![feature, action]);
+      PreprocessorNodeImpl::new_generic_attr(
+        SourceLoc::new(&input),
+        "feature".to_string(),
+        Some(term),
+      )
+    },
+  )(input.clone())
+}
+
 /// Parses a `fun/arity` atom with an integer.
 pub fn parse_funarity(input: ParserInput) -> ParserResult<MFArity> {
   map(tuple((tok_atom, tok_forward_slash, tok_integer)), |(name, _slash, erl_int)| {
@@ -138,27 +168,44 @@ pub(crate) fn parse_parenthesized_list_of_vars(input: ParserInput) -> ParserResu
   )(input)
 }
 
-/// Parses a `-type IDENT(ARG, ...) :: TYPE.` attribute.
+/// Parses the `-type`, `-opaque` or `-nominal` attribute name
+fn user_type_kind(input: ParserInput) -> ParserResult<UserTypeKind> {
+  alt((
+    map(|i1| dash_atom(i1, "type"), |_| UserTypeKind::Type),
+    map(|i1| dash_atom(i1, "opaque"), |_| UserTypeKind::Opaque),
+    map(|i1| dash_atom(i1, "nominal"), |_| UserTypeKind::Nominal),
+  ))(input)
+}
+
+/// Parses a `-type IDENT(ARG, ...) :: TYPE.` attribute, also `-opaque` and `-nominal`.
 /// Dash `-` and trailing `.` are matched outside by the caller.
 pub fn parse_new_type_attr(input: ParserInput) -> ParserResult<PreprocessorNode> {
   map(
-    delimited(
-      |i1| dash_atom(i1, "type"),
-      context(
-        "new type definition",
-        cut(tuple((
-          tok_atom,
-          parse_parenthesized_list_of_vars,
-          preceded(
-            tok_double_colon,
-            context("type in a -type() definition attribute", cut(parse_type)),
-          ),
-        ))),
+    terminated(
+      pair(
+        user_type_kind,
+        context(
+          "new type definition",
+          cut(tuple((
+            tok_atom,
+            parse_parenthesized_list_of_vars,
+            preceded(
+              tok_double_colon,
+              context("type in a -type() definition attribute", cut(parse_type)),
+            ),
+          ))),
+        ),
       ),
       period_eol_eof,
     ),
-    |(type_name, type_args, new_type)| {
-      PreprocessorNodeImpl::new_type_attr(SourceLoc::new(&input), type_name, type_args, new_type)
+    |(kind, (type_name, type_args, new_type))| {
+      PreprocessorNodeImpl::new_type_attr(
+        SourceLoc::new(&input),
+        kind,
+        type_name,
+        type_args,
+        new_type,
+      )
     },
   )(input.clone())
 }
@@ -172,6 +219,7 @@ pub(crate) fn parse_any_module_attr(input: ParserInput) -> ParserResult<Preproce
       export_type_attr,
       export_attr,
       import_attr,
+      feature_attr,
       context("new type definition", parse_new_type_attr),
       parse_fn_spec,
       // Generic parser will try consume any `-IDENT(EXPR).`
@@ -2,9 +2,7 @@
 use crate::erl_syntax::parsers::defs::ParserResult;
 use crate::erl_syntax::parsers::misc::{dash_atom, period_eol_eof, tok_atom, tok_string};
 use crate::erl_syntax::parsers::misc_tok::*;
-use crate::erl_syntax::parsers::parse_expr::parse_expr;
 use crate::erl_syntax::parsers::parser_input::ParserInput;
-use crate::erl_syntax::parsers::token_stream::token::Token;
 use crate::erl_syntax::preprocessor::parsers::parse_attr::parse_any_module_attr;
 use crate::erl_syntax::preprocessor::parsers::parse_def_undef::{
   define_directive, undef_directive,
@@ -17,7 +15,7 @@ use crate::erl_syntax::preprocessor::pp_node::pp_impl::PreprocessorNodeImpl;
 use crate::erl_syntax::preprocessor::pp_node::PreprocessorNode;
 use crate::source_loc::SourceLoc;
 use nom::branch::alt;
-use nom::combinator::{cut, map, opt, recognize};
+use nom::combinator::{consumed, cut, map};
 use nom::error::context;
 use nom::multi::separated_list0;
 use nom::sequence::delimited;
@@ -104,46 +102,38 @@ pub(crate) fn module_start_attr(input: ParserInput) -> ParserResult<Preprocessor
 //   map(peek(not(tok_par_open)), |_| Vec::default())(input)
 // }
 
-/// Parses `(EXPR, EXPR, ...)` which follow a macro invocation token `?MACRONAME`, or `()` or
-/// nothing. If an opening parenthesis is not found, then return value is empty vector.
-/// Returns `Vec<AstNode>` but it is ignored by the caller and "recognized" to `Vec<Token>`.
-pub(crate) fn parse_macro_invocation_args(input: ParserInput) -> ParserResult<Vec<Vec<Token>>> {
-  map(
-    opt(delimited(
-      tok_par_open,
-      // Do we allow ?MACRO() with 0 args in parentheses?
-      separated_list0(tok_comma, recognize(parse_expr)),
-      tok_par_close,
-    )),
-    |maybe_args| match maybe_args {
-      Some(args) => args.into_iter().map(|arg| arg.tokens.into()).collect(),
-      None => Vec::default(),
-    },
-  )(input)
-}
-
 /// Parse one of supported preprocessor directives
 pub(crate) fn parse_preproc_directive(input: ParserInput) -> ParserResult<PreprocessorNode> {
-  alt((
-    alt((
-      context("'-module()' opening attribute", module_start_attr),
-      // -define is special, it needs closing ).\n to consume the content
-      context("'-define()' directive", define_directive),
-      context("'-undef()' directive", undef_directive),
-      // temporary nodes used by parse_if_block
-      context("'-endif()' directive", endif_directive),
-      context("'-elif()' directive", elif_directive),
-      context("'-else()' directive", else_directive),
-      context("'-ifdef()' directive", ifdef_directive),
-      context("'-ifndef()' directive", ifndef_directive),
-      context("'-if()' directive", if_directive),
-    )),
-    alt((
-      context("'-warning()' directive", warning_directive),
-      context("'-error()' directive", error_directive),
-      context("'-include_lib()' directive", include_lib_directive),
-      context("'-include()' directive", include_directive),
-      parse_any_module_attr,
-    )),
-  ))(input)
+  // The directive nodes only know their start, give them the location of the whole directive
+  let with_span = |(consumed_input, ppnode): (ParserInput, PreprocessorNode)| {
+    PreprocessorNodeImpl::new_with_location(
+      SourceLoc::new_span(&consumed_input),
+      ppnode.content.clone(),
+    )
+  };
+  map(
+    consumed(alt((
+      alt((
+        context("'-module()' opening attribute", module_start_attr),
+        // -define is special, it needs closing ).\n to consume the content
+        context("'-define()' directive", define_directive),
+        context("'-undef()' directive", undef_directive),
+        // temporary nodes used by parse_if_block
+        context("'-endif()' directive", endif_directive),
+        context("'-elif()' directive", elif_directive),
+        context("'-else()' directive", else_directive),
+        context("'-ifdef()' directive", ifdef_directive),
+        context("'-ifndef()' directive", ifndef_directive),
+        context("'-if()' directive", if_directive),
+      )),
+      alt((
+        context("'-warning()' directive", warning_directive),
+        context("'-error()' directive", error_directive),
+        context("'-include_lib()' directive", include_lib_directive),
+        context("'-include()' directive", include_directive),
+        parse_any_module_attr,
+      )),
+    ))),
+    with_span,
+  )(input)
 }
@@ -1,8 +1,11 @@
 //! Preprocessor definition, sometimes with args
 
 use crate::erl_syntax::parsers::token_stream::token::{format_tok_stream, Token};
+use crate::erl_syntax::parsers::token_stream::tokenizer::tokenize_source;
 use libironclad_util::mfarity::MFArity;
 use libironclad_util::pretty::Pretty;
+use nom::Finish;
+use std::ptr::null;
 use std::sync::Arc;
 
 /// A preprocessor definition created by `-define(X...)` or from the project settings or
@@ -36,9 +39,23 @@ impl PreprocessorDefineImpl {
 
   /// Given NAME=VALUE or NAME style option, convert it into a record in preprocessor definition
   /// symbols table. This will be passed then to preprocessor parser.
+  /// Like `erlc`, a bare `NAME` is defined as the atom `true`.
   pub(crate) fn new_from_command_line(key_value: &str) -> PreprocessorDefine {
-    println!("TODO: new preproc-define from: {}", key_value);
-    Self::new(key_value.to_string(), &Vec::default(), &[])
+    match key_value.split_once('=') {
+      None => Self::new(key_value.trim().to_string(), &[], &[Token::new_atom("true".to_string())]),
+      Some((name, value)) => {
+        let tokens = match tokenize_source(value).finish() {
+          Ok((_tail, tokens)) => tokens
+            .into_iter()
+            .filter(|t| !t.is_eol())
+            // The value string does not outlive this call, drop pointers into it
+            .map(|t| Token::new(null(), t.content))
+            .collect(),
+          Err(_) => vec![Token::new_atom(value.to_string())],
+        };
+        Self::new(name.trim().to_string(), &[], &tokens)
+      }
+    }
   }
 
   /// Return the name/arity pair for this macro
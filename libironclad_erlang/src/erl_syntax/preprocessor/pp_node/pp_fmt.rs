@@ -62,8 +62,8 @@ impl std::fmt::Display for PreprocessorNodeImpl {
         Pretty::display_square_list(fun_arities.iter(), f)?;
         writeln!(f, ").")
       }
-      PreprocessorNodeType::NewType { name, vars, ty, .. } => {
-        write!(f, "-type {}", name)?;
+      PreprocessorNodeType::NewType { kind, name, vars, ty } => {
+        write!(f, "-{} {}", kind.attr_name(), name)?;
         Pretty::display_paren_list(vars.iter(), f)?;
         write!(f, " :: {}", ty)?;
         writeln!(f, ".")
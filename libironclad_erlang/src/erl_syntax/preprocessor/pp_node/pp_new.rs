@@ -8,6 +8,7 @@ use crate::erl_syntax::preprocessor::pp_node::pp_type::PreprocessorNodeType;
 use crate::erl_syntax::preprocessor::pp_node::PreprocessorNode;
 use crate::source_loc::SourceLoc;
 use crate::typing::erl_type::ErlType;
+use crate::typing::user_type::UserTypeKind;
 use libironclad_util::mfarity::MFArity;
 
 impl PreprocessorNodeImpl {
@@ -178,15 +179,16 @@ impl PreprocessorNodeImpl {
     Self::new_with_location(location, PreprocessorNodeType::ExportType { type_arities })
   }
 
-  /// Create a new `-type IDENT(ARG1, ...) :: TYPE.` module attr.
+  /// Create a new `-type IDENT(ARG1, ...) :: TYPE.` module attr, or `-opaque` or `-nominal`.
   #[inline]
   pub(crate) fn new_type_attr(
     location: SourceLoc,
+    kind: UserTypeKind,
     name: String,
     vars: Vec<String>,
     ty: ErlType,
   ) -> PreprocessorNode {
-    Self::new_with_location(location, PreprocessorNodeType::NewType { name, vars, ty })
+    Self::new_with_location(location, PreprocessorNodeType::NewType { kind, name, vars, ty })
   }
 
   /// Create a new `-import(modulename, [...]).` module attr.
@@ -4,6 +4,7 @@ use crate::erl_syntax::erl_ast::AstNode;
 use crate::erl_syntax::node::erl_record::RecordField;
 use crate::erl_syntax::parsers::token_stream::token::Token;
 use crate::typing::erl_type::ErlType;
+use crate::typing::user_type::UserTypeKind;
 use libironclad_util::mfarity::MFArity;
 
 /// Preprocessor data nodes, are produced during the `ErlModule::stage_preprocess` stage.
@@ -89,6 +90,8 @@ pub enum PreprocessorNodeType {
   },
   /// Defines a new type, globally available in the module
   NewType {
+    /// Which attribute created the type: `-type`, `-opaque` or `-nominal`
+    kind: UserTypeKind,
     /// Custom type name
     name: String,
     /// Type parameter variables
@@ -11,33 +11,38 @@ impl std::fmt::Debug for IroncladError {
 }
 
 impl std::fmt::Display for IroncladError {
+  /// Prints `file.erl:12:5-12:18: Category: message`, without the location if it is not known
   fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-    writeln!(f, "{}:", self.get_category().to_string())?;
+    let location = self.get_location();
+    if !location.is_none() {
+      write!(f, "{}: ", location)?;
+    }
+    write!(f, "{}:", self.get_category().to_string())?;
 
     match self.get_category() {
       // IcErrorType::Interrupted(s) => write!(f, "Processing interrupted: {}", s),
       IcErrorCategory::Multiple(errs) => {
         for err in errs.iter() {
-          writeln!(f, "{}", err)?;
+          write!(f, "\n{}", err)?;
         }
         Ok(())
       }
       IcErrorCategory::FileNotFound { file, while_verb } => {
-        writeln!(f, "file: {} while {}", file.to_string_lossy(), while_verb)
+        write!(f, " {} while {}", file.to_string_lossy(), while_verb)
       }
-      IcErrorCategory::StdIoError(ioerr) => writeln!(f, "{}", ioerr),
-      IcErrorCategory::Glob(gerr) => gerr.fmt(f),
-      IcErrorCategory::GlobPattern(gperr) => gperr.fmt(f),
-      IcErrorCategory::Config(cfgerr) => cfgerr.fmt(f),
+      IcErrorCategory::StdIoError(ioerr) => write!(f, " {}", ioerr),
+      IcErrorCategory::IcFileError(ferr) => write!(f, " {}", ferr),
+      IcErrorCategory::Glob(gerr) => write!(f, " {}", gerr),
+      IcErrorCategory::GlobPattern(gperr) => write!(f, " {}", gperr),
+      IcErrorCategory::Config(cfgerr) => write!(f, " {}", cfgerr),
+      IcErrorCategory::VariableNotFound(var) => write!(f, " {}", var),
       IcErrorCategory::Preprocessor
       | IcErrorCategory::PreprocessorParse
       | IcErrorCategory::ParserInternal
       | IcErrorCategory::Internal
       | IcErrorCategory::TypeError
-      | IcErrorCategory::ErlangParse => {
-        write!(f, "{} (at {})", self.get_message(), self.get_location())
-      }
-      _ => unimplemented!("Format is not impl for {:?}", self.get_category()),
+      | IcErrorCategory::Erlang
+      | IcErrorCategory::ErlangParse => write!(f, " {}", self.get_message()),
     }
   }
 }
@@ -15,6 +15,22 @@ pub struct CompilerOptsImpl {
 
   /// Tries to break the operations when this many errors found in 1 module
   pub max_errors_per_module: usize,
+
+  /// OTP installation directory, `<otp_root>/lib` contains the OTP applications
+  pub otp_root: Option<String>,
+
+  /// Directories containing dependency applications, searched before `ERL_LIBS` and OTP
+  pub deps: Vec<String>,
+
+  /// Warn when a file is included more than once into the same module
+  pub warn_duplicate_include: bool,
+
+  /// OTP release the code is compiled for, the value of `?OTP_RELEASE`
+  pub otp_release: Option<u32>,
+
+  /// Do not print the preprocessing progress and the preprocessed directives to the standard
+  /// error. Set by the command line unless `--verbose` is given.
+  pub quiet: bool,
 }
 
 /// Wrap compiler options with refcounted box
@@ -24,6 +40,14 @@ impl CompilerOptsImpl {
   /// Default value for max errors limit. Will try to stop compilation when this count is reached.
   pub const MAX_ERRORS_PER_MODULE: usize = 20;
 
+  /// Default value for `?OTP_RELEASE`, if not set in the project file
+  pub const OTP_RELEASE: u32 = 25;
+
+  /// The OTP release the code is compiled for, or the default
+  pub fn get_otp_release(&self) -> u32 {
+    self.otp_release.unwrap_or(Self::OTP_RELEASE)
+  }
+
   /// Given self (read-only) and other opts (read-only) combine them into self+other
   pub fn overlay(&self, other: &CompilerOptsImpl) -> Self {
     let mut result: CompilerOptsImpl = self.clone();
@@ -43,15 +67,51 @@ impl CompilerOptsImpl {
     //   panic!("Can't lock scopes for merging")
     // };
     result.scope = new_scope;
+
+    if other.otp_root.is_some() {
+      result.otp_root = other.otp_root.clone();
+    }
+    if other.otp_release.is_some() {
+      result.otp_release = other.otp_release;
+    }
+    result.warn_duplicate_include |= other.warn_duplicate_include;
+    result.quiet |= other.quiet;
+    for dep in other.deps.iter() {
+      if !result.deps.contains(dep) {
+        result.deps.push(dep.clone());
+      }
+    }
     result
   }
 
+  /// Build options from command line `-I DIR` and `-D NAME[=VALUE]` arguments, to be overlaid
+  /// over the project options.
+  pub fn new_from_command_line(include_paths: &[String], defines: &[String]) -> Self {
+    Self {
+      include_paths: include_paths.to_vec(),
+      scope: PreprocessorDefinesMap::new_from_config_lines(defines),
+      max_errors_per_module: Self::MAX_ERRORS_PER_MODULE,
+      otp_root: None,
+      deps: Vec::default(),
+      warn_duplicate_include: false,
+      otp_release: None,
+      quiet: false,
+    }
+  }
+
   pub(crate) fn new_from_opts(opts: SerializableCompilerOpts) -> Self {
     let self_default = Self::default();
     Self {
       include_paths: opts.include_paths.unwrap_or(self_default.include_paths),
       scope: PreprocessorDefinesMap::new_from_config(opts.defines, &self_default.scope),
       max_errors_per_module: Self::MAX_ERRORS_PER_MODULE,
+      otp_root: opts.otp_root,
+      deps: opts.deps.unwrap_or(self_default.deps),
+      warn_duplicate_include: opts
+        .warn_duplicate_include
+        .unwrap_or(self_default.warn_duplicate_include),
+      otp_release: opts.otp_release,
+      quiet: false,
     }
   }
 
@@ -69,6 +129,11 @@ impl Default for CompilerOptsImpl {
       include_paths: Default::default(),
       scope: Default::default(),
       max_errors_per_module: Self::MAX_ERRORS_PER_MODULE,
+      otp_root: None,
+      deps: Default::default(),
+      warn_duplicate_include: false,
+      otp_release: None,
+      quiet: false,
     }
   }
 }
@@ -13,4 +13,13 @@ pub struct SerializableCompilerOpts {
   pub include_paths: Option<Vec<String>>,
   /// Defaults to empty list. Preprocessor defs in form of "NAME" or "NAME=VALUE"
   pub defines: Option<Vec<String>>,
+  /// OTP installation directory, its `lib/` is searched for `-include_lib()` applications
+  pub otp_root: Option<String>,
+  /// Defaults to empty. Directories containing dependency applications, like `deps` or
+  /// `_build/default/lib`, searched for `-include_lib()` applications
+  pub deps: Option<Vec<String>>,
+  /// Defaults to false. Warn when a file is included more than once into the same module
+  pub warn_duplicate_include: Option<bool>,
+  /// Defaults to 25. The OTP release the code is compiled for, the value of `?OTP_RELEASE`
+  pub otp_release: Option<u32>,
 }
@@ -9,6 +9,7 @@ pub mod module;
 pub mod project_impl;
 pub mod project_include;
 pub mod project_inputs;
+pub mod xref;
 
 /// Wrapper for shared access
 pub type ErlProject = Arc<ErlProjectImpl>;
@@ -1,7 +1,9 @@
 //! Defines an Erlang module ready to be compiled
 
+pub mod module_fns;
 pub mod module_impl;
 pub mod module_parse;
+pub mod module_types;
 pub mod module_verify;
 pub mod preprocess;
 pub mod scope;
@@ -0,0 +1,198 @@
+//! Resolves calls to functions: local, `-import`ed, auto-imported BIFs and remote `module:fun()`
+
+use crate::erl_syntax::erl_ast::ast_iter::IterableAstNodeT;
+use crate::erl_syntax::erl_ast::node_impl::AstNodeType;
+use crate::erl_syntax::erl_ast::AstNode;
+use crate::erl_syntax::erl_error::ErlError;
+use crate::error::ic_error::IcResult;
+use crate::project::module::module_impl::{ErlModule, ErlModuleImpl};
+use crate::project::module::scope::scope_impl::{Scope, ScopeImpl};
+use crate::source_loc::SourceLoc;
+use crate::typing::bif::BifTable;
+use crate::typing::erl_type::ErlType;
+use crate::typing::type_error::TypeError;
+use libironclad_util::mfarity::MFArity;
+use std::collections::BTreeSet;
+
+/// Compile option, which exports every function of the module
+const EXPORT_ALL: &str = "export_all";
+
+impl ErlModuleImpl {
+  /// Check whether a `-compile(Option)` or `-compile([Option, ...])` attribute sets the option
+  pub fn has_compile_option(&self, option: &str) -> bool {
+    let attrs = match self.root_scope.attributes.get(&"compile".to_string()) {
+      Some(attrs) => attrs,
+      None => return false,
+    };
+    let is_option = |expr: &AstNode| expr.get_atom_text().is_some_and(|a| a == option);
+    (0..attrs.len())
+      .filter_map(|index| attrs.get(index).expr)
+      .any(|expr| match &expr.content {
+        AstNodeType::List { elements, .. } => elements.iter().any(is_option),
+        _ => is_option(&expr),
+      })
+  }
+
+  /// Whether the local function is listed in `-export`, or the module is compiled with
+  /// `export_all`
+  pub fn is_exported(&self, fn_name: &MFArity) -> bool {
+    self.root_scope.exports.contains(fn_name) || self.has_compile_option(EXPORT_ALL)
+  }
+
+  /// Find the `-import` for a local function name. Returns the imported name with the module.
+  pub fn find_import(&self, fn_name: &MFArity) -> Option<MFArity> {
+    if let Ok(r_imports) = self.root_scope.imports.collection.read() {
+      r_imports
+        .iter()
+        .find(|imported| imported.name == fn_name.name && imported.arity == fn_name.arity)
+        .cloned()
+    } else {
+      panic!("Can't lock module imports for reading")
+    }
+  }
+
+  /// Find the type of a function called from this module. A local name is a function of this
+  /// module (its `-spec` or the synthesized type), then an `-import`ed function, then an
+  /// auto-imported BIF. A remote function, also `?MODULE:fun()`, is looked up in the project, and
+  /// must be defined and exported. Returns `None` for functions of modules which are not loaded,
+  /// and BIFs without a spec.
+  pub(crate) fn find_function_type(
+    module: &ErlModule,
+    location: &SourceLoc,
+    scope: &Scope,
+    fn_name: &MFArity,
+  ) -> IcResult<Option<ErlType>> {
+    let local_name = MFArity::new_local(&fn_name.name, fn_name.arity);
+    match &fn_name.module {
+      Some(m) if *m != module.get_name() => {
+        Self::find_remote_function_type(module, location, fn_name)
+      }
+      Some(_) => {
+        if !module.root_scope.fn_defs.contains(&local_name) {
+          ErlError::type_error(
+            location.clone(),
+            TypeError::FunctionNotFound { mfa: fn_name.clone() },
+          )
+        } else if !module.is_exported(&local_name) {
+          ErlError::type_error(
+            location.clone(),
+            TypeError::FunctionNotExported { mfa: fn_name.clone() },
+          )
+        } else {
+          Self::find_local_function_type(module, scope, &local_name)
+        }
+      }
+      None => {
+        if module.root_scope.fn_defs.contains(&local_name) {
+          return Self::find_local_function_type(module, scope, &local_name);
+        }
+        if let Some(imported) = module.find_import(&local_name) {
+          return Self::find_remote_function_type(module, location, &imported);
+        }
+        if BifTable::erlang().is_bif(&local_name) {
+          return Ok(BifTable::erlang().find_spec(&local_name));
+        }
+        ErlError::type_error(location.clone(), TypeError::FunctionNotFound { mfa: local_name })
+      }
+    }
+  }
+
+  /// Type of a function defined in this module: its `-spec`, or the synthesized type
+  fn find_local_function_type(
+    module: &ErlModule,
+    scope: &Scope,
+    local_name: &MFArity,
+  ) -> IcResult<Option<ErlType>> {
+    if let Some(spec) = module.root_scope.fn_specs.get(local_name) {
+      return Ok(Some(spec));
+    }
+    match module.root_scope.fn_defs.get(local_name) {
+      Some(fndef) => Ok(Some(fndef.as_fn_def().synthesize_function_type(module, scope)?)),
+      None => Ok(None),
+    }
+  }
+
+  /// Find the type of `module:fun/arity` in the project. Calls to the `erlang` module, which is not
+  /// a part of the project, use the BIF table. The type synthesized for a function without a spec
+  /// is stored in its module, usually when that module is checked before its callers.
+  fn find_remote_function_type(
+    module: &ErlModule,
+    location: &SourceLoc,
+    fn_name: &MFArity,
+  ) -> IcResult<Option<ErlType>> {
+    let module_name = fn_name.module.clone().unwrap_or_default();
+    let other_module = match module.root_scope.project.modules.get(&module_name) {
+      Some(m) => m,
+      None => return Ok(BifTable::erlang().find_spec(fn_name)),
+    };
+    let local_name = MFArity::new_local(&fn_name.name, fn_name.arity);
+    let fndef = match other_module.root_scope.fn_defs.get(&local_name) {
+      Some(fndef) => fndef,
+      None => {
+        return ErlError::type_error(
+          location.clone(),
+          TypeError::FunctionNotFound { mfa: fn_name.clone() },
+        )
+      }
+    };
+    if !other_module.is_exported(&local_name) {
+      return ErlError::type_error(
+        location.clone(),
+        TypeError::FunctionNotExported { mfa: fn_name.clone() },
+      );
+    }
+    if let Some(spec) = other_module.root_scope.fn_specs.get(&local_name) {
+      return Ok(Some(spec));
+    }
+    if let Some(fn_type) = other_module.root_scope.fn_types.get(&local_name) {
+      return Ok(Some(fn_type));
+    }
+    let other_scope = ScopeImpl::new_root_scope(fn_name.to_string());
+    let fn_type = fndef
+      .as_fn_def()
+      .synthesize_function_type(&other_module, &other_scope)?;
+    other_module
+      .root_scope
+      .fn_types
+      .add(local_name, fn_type.clone());
+    Ok(Some(fn_type))
+  }
+
+  /// Names of other modules, which this module calls, refers to with `fun m:f/a`, or imports from
+  pub fn called_modules(&self) -> BTreeSet<String> {
+    let mut result = BTreeSet::new();
+    Self::collect_called_modules(&self.ast.borrow(), &mut result);
+    if let Ok(r_imports) = self.root_scope.imports.collection.read() {
+      result.extend(
+        r_imports
+          .iter()
+          .filter_map(|imported| imported.module.clone()),
+      );
+    } else {
+      panic!("Can't lock module imports for reading")
+    }
+    result.remove(&self.get_name());
+    result
+  }
+
+  /// Recursive descend into AST collecting the module names of remote calls and fun references
+  fn collect_called_modules(ast: &AstNode, result: &mut BTreeSet<String>) {
+    let remote_name = match &ast.content {
+      AstNodeType::Apply(app) => app
+        .target
+        .as_mfarity(app.args.len())
+        .and_then(|mfa| mfa.module),
+      AstNodeType::FnRef { mfa } | AstNodeType::MFA { mfarity: mfa } => mfa.module.clone(),
+      _ => None,
+    };
+    if let Some(m) = remote_name {
+      result.insert(m);
+    }
+
+    if let Some(children) = ast.children() {
+      for c in children {
+        Self::collect_called_modules(&c, result)
+      }
+    }
+  }
+}
@@ -9,15 +9,19 @@ use crate::erl_syntax::parsers::token_stream::token::Token;
 use crate::erl_syntax::parsers::token_stream::token_type::TokenType;
 use crate::erl_syntax::parsers::token_stream::tokenizer::tokenize_source;
 use crate::error::ic_error::IcResult;
+use crate::error::ic_error_trait::IcError;
 use crate::project::compiler_opts::{CompilerOpts, CompilerOptsImpl};
 use crate::project::module::scope::root_scope::RootScope;
 use crate::project::ErlProject;
+use crate::source_loc::SourceLoc;
+use libironclad_util::rw_hashmap::RwHashMap;
 use libironclad_util::rw_vec::RwVec;
 use libironclad_util::source_file::{SourceFile, SourceFileImpl};
 use nom::Finish;
 use std::cell::RefCell;
 use std::fmt;
 use std::fmt::Debug;
+use std::path::PathBuf;
 use std::ptr::null;
 use std::sync::{Arc, RwLock};
 
@@ -42,8 +46,15 @@ pub struct ErlModuleImpl {
   pub errors: RwVec<ErlError>,
   /// Warnings which can accumulate but do not block the processing
   pub warnings: RwVec<ErlError>,
-  // /// Collection of included files to prevent circular imports
-  // pub included_files: RwHashSet<OsString>,
+  /// The module file and the included files, which produced tokens for this module. Used to map
+  /// token positions back to the file locations.
+  pub source_files: RwVec<SourceFile>,
+  /// Files being included, each with the location of its `-include` directive. The innermost
+  /// include is the last. Used to search includes relative to the including file, and to detect
+  /// circular includes.
+  pub include_stack: RwVec<(PathBuf, SourceLoc)>,
+  /// Every file included by this module, with the location where it was first included
+  pub included_files: RwHashMap<PathBuf, SourceLoc>,
 }
 
 /// Wraps module into runtime-lockable refcount
@@ -59,7 +70,9 @@ impl Default for ErlModuleImpl {
       root_scope: RootScope::default(),
       errors: RwVec::with_capacity(CompilerOptsImpl::MAX_ERRORS_PER_MODULE * 110 / 100),
       warnings: RwVec::default(),
-      // included_files: Default::default(),
+      source_files: RwVec::default(),
+      include_stack: RwVec::default(),
+      included_files: RwHashMap::default(),
     }
   }
 }
@@ -87,7 +100,8 @@ impl ErlModuleImpl {
   }
 
   /// Given project, module and the source file, break it into tokens, and interpret the
-  /// preprocessor directives using module root scope.
+  /// preprocessor directives using module root scope. Included files are tokenized with the same
+  /// module, which must have been set up with `setup_preprocessor` once before.
   pub fn tokenize(
     project: &ErlProject,
     module: &ErlModule,
@@ -96,6 +110,7 @@ impl ErlModuleImpl {
     //----------------------
     // Stage 1 tokenize the input
     //----------------------
+    module.source_files.push(src_file.clone());
     let mut tokens = ErlModuleImpl::tokenize_helper(project, src_file.clone(), tokenize_source)?;
 
     // Inject a mandatory EOL if the stream doesn't end with one
@@ -107,7 +122,6 @@ impl ErlModuleImpl {
     // Stage 2 preprocessor: handle ifdefs, defines, includes etc
     // tokenize includes and paste in the token stream too
     //----------------------
-    module.setup_preprocessor();
     let tokens_preprocessed =
       ErlModuleImpl::preprocess_interpret(src_file, project, module, tokens)?;
     module.verify_preprocessed_integrity()?;
@@ -130,6 +144,21 @@ impl ErlModuleImpl {
     Ok(forms)
   }
 
+  /// Find the module file or an included file, whose text contains the pointer
+  pub fn find_source_file(&self, ptr: *const u8) -> Option<SourceFile> {
+    if ptr.is_null() {
+      return None;
+    }
+    if self.source_file.offset_of(ptr).is_some() {
+      return Some(self.source_file.clone());
+    }
+    if let Ok(r_files) = self.source_files.data.read() {
+      r_files.iter().find(|f| f.offset_of(ptr).is_some()).cloned()
+    } else {
+      panic!("Can't lock module source files for reading")
+    }
+  }
+
   /// Adds an error to vector of errors. Returns false when error list is full and the calling code
   /// should attempt to stop.
   pub fn add_error(&self, err: ErlError) -> bool {
@@ -137,6 +166,23 @@ impl ErlModuleImpl {
     self.errors.len() < self.compiler_options.max_errors_per_module
   }
 
+  /// Adds an error, returned while checking a function, to vector of errors. The same error found
+  /// again by a later check is not repeated. Returns false when error list is full.
+  pub fn add_ic_error(&self, err: IcError) -> bool {
+    let new_err = ErlError::from_ic_error(&err);
+    let is_repeated = if let Ok(r_errors) = self.errors.data.read() {
+      r_errors
+        .iter()
+        .any(|e| e.msg == new_err.msg && e.loc.to_string() == new_err.loc.to_string())
+    } else {
+      panic!("Can't lock module errors collection for reading")
+    };
+    if is_repeated {
+      return self.errors.len() < self.compiler_options.max_errors_per_module;
+    }
+    self.add_error(new_err)
+  }
+
   /// Adds an warning to vector of warnings.
   pub fn add_warning(&self, err: ErlError) {
     self.warnings.push(err);
@@ -168,7 +214,7 @@ impl ErlModuleImpl {
     self.errors.len() > 0
   }
 
-  /// Print errors accumulated for this module
+  /// Print errors accumulated for this module, then the warnings prefixed with `warning:`
   pub fn print_errors(&self) {
     if let Ok(r_errors) = self.errors.data.read() {
       for err in r_errors.iter() {
@@ -180,7 +226,7 @@ impl ErlModuleImpl {
 
     if let Ok(r_warnings) = self.warnings.data.read() {
       for wrn in r_warnings.iter() {
-        println!("{}", wrn);
+        println!("warning: {}", wrn);
       }
     } else {
       panic!("Can't lock module warnings collection for printing")
@@ -12,6 +12,7 @@ use crate::erl_syntax::parsers::token_stream::token::format_tok_stream;
 use crate::error::ic_error::IcResult;
 use crate::project::compiler_opts::CompilerOpts;
 use crate::project::module::module_impl::{ErlModule, ErlModuleImpl};
+use crate::project::module::scope::root_scope::RootScopeImpl;
 use crate::project::ErlProject;
 use libironclad_util::source_file::SourceFile;
 use nom::Finish;
@@ -28,16 +29,21 @@ impl ErlModuleImpl {
   where
     T: Fn(ParserInput) -> ParserResult<AstNode>,
   {
-    println!("{}", project);
-
+    // Root scope refers to the project to find other modules, for remote types and calls
+    let root_scope = RootScopeImpl {
+      project: project.clone(),
+      ..RootScopeImpl::default()
+    };
     let mut module_impl = ErlModuleImpl {
       source_file: src_file.clone(),
+      root_scope: root_scope.into(),
       ..ErlModuleImpl::default()
     };
     if let Some(o) = compiler_options {
       module_impl.compiler_options = o;
     }
     let module: ErlModule = module_impl.into();
+    module.setup_preprocessor();
     let tokens = ErlModuleImpl::tokenize(project, &module, &src_file)?;
     // println!("TOKENS {}", format_tok_stream(&tokens, tokens.len()));
 
@@ -0,0 +1,231 @@
+//! Resolves references to user-defined types, local and remote `module:type()`
+
+use crate::erl_syntax::erl_error::ErlError;
+use crate::error::ic_error::IcResult;
+use crate::project::module::module_impl::ErlModuleImpl;
+use crate::source_loc::SourceLoc;
+use crate::typing::erl_type::{ErlType, ErlTypeImpl};
+use crate::typing::record_field_type::RecordFieldType;
+use crate::typing::subtyping::SubtypeChecker;
+use crate::typing::type_error::TypeError;
+use crate::typing::user_type::UserType;
+use libironclad_util::mfarity::MFArity;
+use std::ops::Deref;
+use std::sync::Arc;
+
+impl ErlModuleImpl {
+  /// Whether the type name refers to a type defined in another module
+  fn is_remote_type_name(&self, name: &MFArity) -> bool {
+    match &name.module {
+      Some(m) => *m != self.get_name(),
+      None => false,
+    }
+  }
+
+  /// Report a type which is not found. If the type name exists with other arities, the error is
+  /// an arity mismatch.
+  fn type_not_found<T>(
+    defining_module: &ErlModuleImpl,
+    location: &SourceLoc,
+    name: &MFArity,
+  ) -> IcResult<T> {
+    let mut other_arities =
+      if let Ok(r_types) = defining_module.root_scope.user_types.collection.read() {
+        r_types
+          .keys()
+          .filter(|type_name| type_name.name == name.name)
+          .map(|type_name| type_name.arity)
+          .collect::<Vec<_>>()
+      } else {
+        panic!("Can't lock module types for reading")
+      };
+    if other_arities.is_empty() {
+      return ErlError::type_error(
+        location.clone(),
+        TypeError::TypeNotFound { name: name.clone() },
+      );
+    }
+    other_arities.sort_unstable();
+    let arities_str: Vec<String> = other_arities.iter().map(|a| a.to_string()).collect();
+    let msg = format!(
+      "{} is used, but the type is defined with arity {}",
+      name,
+      arities_str.join(", ")
+    );
+    ErlError::type_error(location.clone(), TypeError::TypeArityMismatch { msg })
+  }
+
+  /// Find a user type definition. Remote types are looked up in the project, and must be listed in
+  /// `-export_type` of their module. Returns `None` for remote types of modules not loaded.
+  pub fn find_user_type(&self, location: &SourceLoc, name: &MFArity) -> IcResult<Option<UserType>> {
+    let local_name = MFArity::new_local(&name.name, name.arity);
+
+    if !self.is_remote_type_name(name) {
+      return match self.root_scope.user_types.get(&local_name) {
+        Some(user_type) => Ok(Some(user_type)),
+        None => Self::type_not_found(self, location, name),
+      };
+    }
+
+    let module_name = name.module.clone().unwrap_or_default();
+    let other_module = match self.root_scope.project.modules.get(&module_name) {
+      Some(m) => m,
+      None => return Ok(None),
+    };
+    if !other_module.root_scope.exported_types.contains(&local_name) {
+      return ErlError::type_error(
+        location.clone(),
+        TypeError::TypeNotExported { name: name.clone() },
+      );
+    }
+    match other_module.root_scope.user_types.get(&local_name) {
+      Some(user_type) => Ok(Some(user_type)),
+      None => Self::type_not_found(&other_module, location, name),
+    }
+  }
+
+  /// Replace a user-defined type reference with its definition, where the type arguments are
+  /// substituted for the type parameters. Types of this module are always transparent. Remote
+  /// `-opaque` types, and types of modules not loaded, stay abstract. Only the outer type is
+  /// expanded: user types nested in the definition are expanded when they are visited, which
+  /// allows recursive types. A type which is an alias of itself is an error.
+  pub fn resolve_user_type(&self, location: &SourceLoc, ty: &ErlType) -> IcResult<ErlType> {
+    let mut visited: Vec<MFArity> = Vec::new();
+    let mut ty = ty.clone();
+    loop {
+      let (name, args) = match ty.deref() {
+        ErlTypeImpl::UserDefinedType { name, args } => (name, args),
+        _ => return Ok(ty),
+      };
+      if visited.contains(name) {
+        return ErlError::type_error(
+          location.clone(),
+          TypeError::RecursiveType { name: name.clone() },
+        );
+      }
+      let expanded = match self.find_user_type(location, name)? {
+        Some(user_type) if user_type.is_opaque() && self.is_remote_type_name(name) => {
+          return Ok(ty)
+        }
+        Some(user_type) => user_type.instantiate(args),
+        None => return Ok(ty),
+      };
+      visited.push(name.clone());
+      ty = expanded;
+    }
+  }
+
+  /// Build a record type for `#tag{}` from the record definition, where the pinned fields have
+  /// their pinned types. Fields without a type are `any()`.
+  pub fn resolve_record_ref(
+    &self,
+    location: &SourceLoc,
+    tag: &str,
+    pins: &[RecordFieldType],
+  ) -> IcResult<ErlType> {
+    let record_def = match self.root_scope.record_defs.get(&tag.to_string()) {
+      Some(record_def) => record_def,
+      None => {
+        return ErlError::type_error(
+          location.clone(),
+          TypeError::RecordNotFound { tag: tag.to_string() },
+        )
+      }
+    };
+    if let Some(pin) = pins.iter().find(|pin| {
+      !record_def
+        .fields
+        .iter()
+        .any(|field| field.field_tag == pin.name)
+    }) {
+      let msg = format!("#{} has no field {}", tag, pin.name);
+      return ErlError::type_error(location.clone(), TypeError::RecordFieldNotFound { msg });
+    }
+    let fields = record_def
+      .fields
+      .iter()
+      .map(|field| {
+        let pinned = pins.iter().find(|pin| pin.name == field.field_tag);
+        let field_type = match (pinned, &field.type_ascription) {
+          (Some(pin), _) => pin.ty.clone(),
+          (None, Some(ascription)) => ascription.clone(),
+          (None, None) => ErlTypeImpl::any(),
+        };
+        Arc::new(RecordFieldType::new(field.field_tag.clone(), field_type))
+      })
+      .collect();
+    Ok(ErlTypeImpl::Record { tag: tag.to_string(), fields }.into())
+  }
+
+  /// Checks whether `sub_ty` is a subtype of `super_ty`, where user-defined types and record
+  /// references at any depth are replaced with their definitions
+  pub fn is_subtype(&self, sub_ty: &ErlTypeImpl, super_ty: &ErlTypeImpl) -> bool {
+    SubtypeChecker::is_subtype_in_module(self, sub_ty, super_ty)
+  }
+
+  /// If `ty` is a remote opaque type, return its name
+  fn as_remote_opaque(&self, location: &SourceLoc, ty: &ErlType) -> IcResult<Option<MFArity>> {
+    if let ErlTypeImpl::UserDefinedType { name, .. } = ty.deref() {
+      if self.is_remote_type_name(name) {
+        if let Some(user_type) = self.find_user_type(location, name)? {
+          if user_type.is_opaque() {
+            return Ok(Some(name.clone()));
+          }
+        }
+      }
+    }
+    Ok(None)
+  }
+
+  /// If `ty` is a nominal type, return its name
+  fn as_nominal(&self, location: &SourceLoc, ty: &ErlType) -> IcResult<Option<MFArity>> {
+    if let ErlTypeImpl::UserDefinedType { name, .. } = ty.deref() {
+      if let Some(user_type) = self.find_user_type(location, name)? {
+        if user_type.is_nominal() {
+          return Ok(Some(name.clone()));
+        }
+      }
+    }
+    Ok(None)
+  }
+
+  /// Check that a value of type `actual` can be used where `expected` is required, without looking
+  /// inside of opaque types of other modules, and without mixing different nominal types.
+  pub fn check_opaque_use(
+    &self,
+    location: &SourceLoc,
+    expected: &ErlType,
+    actual: &ErlType,
+  ) -> IcResult<()> {
+    if expected == actual || expected.is_any() || actual.is_none() {
+      return Ok(());
+    }
+
+    if let Some(name) = self.as_remote_opaque(location, expected)? {
+      let msg = format!(
+        "value of type {} is used as {}, which can only be created in module {}",
+        actual,
+        name,
+        name.module.clone().unwrap_or_default()
+      );
+      return ErlError::type_error(location.clone(), TypeError::OpaqueType { msg });
+    }
+    if let Some(name) = self.as_remote_opaque(location, actual)? {
+      let msg = format!(
+        "structure of {} is private to module {}, it can't be used as {}",
+        name,
+        name.module.clone().unwrap_or_default(),
+        expected
+      );
+      return ErlError::type_error(location.clone(), TypeError::OpaqueType { msg });
+    }
+
+    if let (Some(expected_name), Some(actual_name)) =
+      (self.as_nominal(location, expected)?, self.as_nominal(location, actual)?)
+    {
+      let msg = format!("nominal types {} and {} are not compatible", expected_name, actual_name);
+      return ErlError::type_error(location.clone(), TypeError::OpaqueType { msg });
+    }
+    Ok(())
+  }
+}
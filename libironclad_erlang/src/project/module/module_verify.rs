@@ -7,8 +7,8 @@ use crate::error::ic_error::IcResult;
 use crate::project::module::module_impl::ErlModuleImpl;
 
 impl ErlModuleImpl {
-  /// Check that specs and exports match the fn defs
-  /// TODO: Check that func specs match func defs
+  /// Check that exports match the fn defs. Specs are checked against the fn defs after parsing
+  /// by `SpecCheck::verify_module`.
   ///  TODO: Check that exports all exist as funs
   pub fn verify_preprocessed_integrity(&self) -> IcResult<()> {
     Ok(())
@@ -26,6 +26,9 @@ impl ErlModuleImpl {
           for arg in c.args.iter() {
             AstNodeImpl::verify_expr_is_matchexpr(arg)?;
           }
+          if let Some(guard) = &c.guard_expr {
+            AstNodeImpl::verify_expr_is_guard(guard)?;
+          }
           self.verify_parsed(&c.body)?;
         }
       }
@@ -125,12 +128,24 @@ impl ErlModuleImpl {
         self.verify_parsed(left)?;
         self.verify_parsed(right)?;
       }
+      AstNodeType::MapComprehension { key, value, generators } => {
+        self.verify_parsed(key)?;
+        self.verify_parsed(value)?;
+        for g in generators.iter() {
+          self.verify_parsed(g)?;
+        }
+      }
+      AstNodeType::MapComprehensionGenerator { key, value, right } => {
+        AstNodeImpl::verify_expr_is_matchexpr(key)?;
+        AstNodeImpl::verify_expr_is_matchexpr(value)?;
+        self.verify_parsed(right)?;
+      }
       AstNodeType::IfStatement { clauses } => {
         for c in clauses.iter() {
           self.verify_parsed(&c.cond)?;
           self.verify_parsed(&c.body)?;
         }
-        // TODO: Coverage analysis, must end with true or cover all cases?
+        // Coverage of the conditions is checked later with the types, see `CoverageCheck`
       }
       AstNodeType::BeginEnd { exprs } => {
         for e in exprs.iter() {
@@ -158,6 +173,37 @@ impl ErlModuleImpl {
           self.verify_parsed(&c.body)?;
         }
       }
+      AstNodeType::Receive { clauses, after } => {
+        for c in clauses.iter() {
+          AstNodeImpl::verify_expr_is_matchexpr(&c.pattern)?;
+          if let Some(guard) = &c.guard {
+            AstNodeImpl::verify_expr_is_guard(guard)?;
+          }
+          self.verify_parsed(&c.body)?;
+        }
+        if let Some(a) = after {
+          self.verify_parsed(&a.timeout)?;
+          self.verify_parsed(&a.body)?;
+        }
+      }
+      AstNodeType::MaybeExpr { exprs, else_clauses } => {
+        for e in exprs.iter() {
+          self.verify_parsed(e)?;
+        }
+        if let Some(ec) = else_clauses {
+          for c in ec.iter() {
+            AstNodeImpl::verify_expr_is_matchexpr(&c.pattern)?;
+            if let Some(guard) = &c.guard {
+              AstNodeImpl::verify_expr_is_guard(guard)?;
+            }
+            self.verify_parsed(&c.body)?;
+          }
+        }
+      }
+      AstNodeType::MaybeMatch { pattern, expr } => {
+        AstNodeImpl::verify_expr_is_matchexpr(pattern)?;
+        self.verify_parsed(expr)?;
+      }
       AstNodeType::CClause(_, _) => {
         unreachable!("Node must not occur in the wild: {:?}", &ast.content);
       }
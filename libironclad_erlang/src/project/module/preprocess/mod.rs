@@ -1,5 +1,6 @@
 //! Preprocessing support for `ErlModule`
 
+use crate::erl_syntax::erl_ast::node_impl::AstNodeType;
 use crate::erl_syntax::erl_ast::AstNode;
 use crate::erl_syntax::erl_error::ErlError;
 use crate::erl_syntax::literal_bool::LiteralBool;
@@ -12,12 +13,16 @@ use crate::erl_syntax::preprocessor::pp_node::pp_type::PreprocessorNodeType;
 use crate::erl_syntax::preprocessor::pp_node::PreprocessorNode;
 use crate::error::ic_error::{IcResult, IroncladError};
 use crate::project::module::module_impl::{ErlModule, ErlModuleImpl};
-use crate::project::module::preprocess::pp_macro_substitution::substitute_macro_invocations;
+use crate::project::module::preprocess::pp_macro_substitution::{
+  substitute_macro_invocations, substitute_macro_invocations_in_code,
+};
+use crate::project::module::preprocess::pp_tok_stream::TokenStream;
+use crate::project::module::scope::root_scope::{FEATURE_MAYBE_EXPR, KNOWN_FEATURES};
 use crate::project::ErlProject;
 use crate::record_def::RecordDefinition;
 use crate::source_loc::SourceLoc;
 use crate::typing::erl_type::ErlType;
-use ::function_name::named;
+use crate::typing::user_type::{UserTypeImpl, UserTypeKind};
 use libironclad_util::mfarity::MFArity;
 use libironclad_util::source_file::SourceFile;
 use pp_state::PreprocessState;
@@ -113,43 +118,141 @@ fn on_export_type(state: &mut PreprocessState, type_arities: &[MFArity]) {
   })
 }
 
-fn on_import(state: &mut PreprocessState, module_name: &str, fun_arities: &[MFArity]) {
+fn on_import(
+  state: &mut PreprocessState,
+  ppnode: &PreprocessorNode,
+  module_name: &str,
+  fun_arities: &[MFArity],
+) {
+  let root_scope = &state.module.root_scope;
   fun_arities.iter().for_each(|fun_arity| {
-    state
-      .module
-      .root_scope
-      .imports
-      .add(fun_arity.clone_with_module(module_name))
+    let imported = fun_arity.clone_with_module(module_name);
+    root_scope
+      .import_locations
+      .add(imported.clone(), ppnode.location.clone());
+    root_scope.imports.add(imported)
   })
 }
 
-fn on_new_type(state: &mut PreprocessState, name: &str, vars: &[String], ty: ErlType) {
+fn on_new_type(
+  state: &mut PreprocessState,
+  ppnode: &PreprocessorNode,
+  kind: UserTypeKind,
+  name: &str,
+  vars: &[String],
+  ty: ErlType,
+) {
   let key = MFArity::new_local(name, vars.len());
-  state.module.root_scope.user_types.add(key, ty)
+  let root_scope = &state.module.root_scope;
+  root_scope
+    .user_type_locations
+    .add(key.clone(), ppnode.location.clone());
+  root_scope
+    .user_types
+    .add(key, UserTypeImpl::new(kind, vars.to_vec(), ty))
 }
 
-fn on_new_record(state: &mut PreprocessState, tag: &str, fields: &[RecordField]) {
+fn on_new_record(
+  state: &mut PreprocessState,
+  ppnode: &PreprocessorNode,
+  tag: &str,
+  fields: &[RecordField],
+) {
   let r_def = RecordDefinition { tag: tag.to_string(), fields: fields.into() }.into();
-  state
-    .module
-    .root_scope
-    .record_defs
-    .add(tag.to_string(), r_def)
+  let root_scope = &state.module.root_scope;
+  root_scope
+    .record_def_locations
+    .add(tag.to_string(), ppnode.location.clone());
+  root_scope.record_defs.add(tag.to_string(), r_def)
 }
 
-fn on_fn_spec(state: &mut PreprocessState, funarity: &MFArity, spec: &ErlType) {
+fn on_fn_spec(
+  state: &mut PreprocessState,
+  ppnode: &PreprocessorNode,
+  funarity: &MFArity,
+  spec: &ErlType,
+) {
+  let root_scope = &state.module.root_scope;
+  root_scope.fn_specs.add(funarity.clone(), spec.clone());
+  root_scope
+    .fn_spec_locations
+    .add(funarity.clone(), ppnode.location.clone())
+}
+
+fn on_attr(
+  state: &mut PreprocessState,
+  ppnode: &PreprocessorNode,
+  tag: &str,
+  term: &Option<AstNode>,
+) {
+  if tag == "feature" {
+    on_feature(state, ppnode, term);
+  }
+  state.module.root_scope.add_attr(tag, term.clone())
+}
+
+/// Handle `-feature(NAME, enable | disable).` attribute, toggling a language feature
+fn on_feature(state: &mut PreprocessState, ppnode: &PreprocessorNode, term: &Option<AstNode>) {
+  let feature_and_action = match term {
+    Some(t) => match &t.content {
+      AstNodeType::Tuple { elements } if elements.len() == 2 => {
+        Some((elements[0].clone(), elements[1].clone()))
+      }
+      _ => None,
+    },
+    None => None,
+  };
+  let msg = match feature_and_action {
+    Some((feature, action)) if feature.is_atom() => {
+      let feature_name = feature.as_atom();
+      if !KNOWN_FEATURES.contains(&feature_name) {
+        format!("Unknown feature '{}' in -feature() attribute", feature_name)
+      } else if action.is_atom_of("enable") {
+        state
+          .module
+          .root_scope
+          .enabled_features
+          .add(feature_name.to_string());
+        return;
+      } else if action.is_atom_of("disable") {
+        state
+          .module
+          .root_scope
+          .enabled_features
+          .remove(&feature_name.to_string());
+        return;
+      } else {
+        format!("-feature({}, ...) expects 'enable' or 'disable'", feature_name)
+      }
+    }
+    _ => "-feature() attribute expects a feature name and 'enable' or 'disable'".to_string(),
+  };
   state
     .module
-    .root_scope
-    .fn_specs
-    .add(funarity.clone(), spec.clone())
+    .add_error(ErlError::preprocessor_error(ppnode.location.clone(), msg));
 }
 
-fn on_attr(state: &mut PreprocessState, tag: &str, term: &Option<AstNode>) {
-  state.module.root_scope.add_attr(tag, term.clone())
+/// `maybe` is a keyword only while the `maybe_expr` feature is enabled, otherwise it is an atom
+fn demote_disabled_keywords(state: &PreprocessState, tokens: &[Token]) -> Vec<Token> {
+  let maybe_enabled = state
+    .module
+    .root_scope
+    .is_feature_enabled(FEATURE_MAYBE_EXPR);
+  tokens
+    .iter()
+    .map(|t| {
+      if !maybe_enabled && t.is_keyword(Keyword::Maybe) {
+        Token {
+          content: TokenType::Atom("maybe".to_string()),
+          ..t.clone()
+        }
+      } else {
+        t.clone()
+      }
+    })
+    .collect()
 }
 
-#[named]
 fn on_if(state: &mut PreprocessState, ppnode: &PreprocessorNode, cond: &AstNode) {
   match cond.walk_boolean_litexpr() {
     LiteralBool::False => state.begin_section(ppnode.clone(), false),
@@ -157,10 +260,9 @@ fn on_if(state: &mut PreprocessState, ppnode: &PreprocessorNode, cond: &AstNode)
     LiteralBool::NotABoolean => {
       let msg =
         "-if() or elif() condition does not evaluate to a compile-time boolean.".to_string();
-      state.module.add_error(ErlError::preprocessor_error(
-        SourceLoc::unimplemented(file!(), function_name!()),
-        msg,
-      ));
+      state
+        .module
+        .add_error(ErlError::preprocessor_error(ppnode.location.clone(), msg));
     }
   }
 }
@@ -175,16 +277,14 @@ fn on_if_not_def(state: &mut PreprocessState, ppnode: &PreprocessorNode, macro_n
   state.begin_section(ppnode.clone(), is_not_def);
 }
 
-#[named]
-fn on_else(state: &mut PreprocessState) {
+fn on_else(state: &mut PreprocessState, ppnode: &PreprocessorNode) {
   if let Some(section) = state.section.last_mut() {
     if section.else_encountered {
       // Can only encounter -else once, otherwise an error is raised
       let msg = "-else() encountered after another -else().".to_string();
-      state.module.add_error(ErlError::preprocessor_error(
-        SourceLoc::unimplemented(file!(), function_name!()),
-        msg,
-      ));
+      state
+        .module
+        .add_error(ErlError::preprocessor_error(ppnode.location.clone(), msg));
     } else {
       section.else_encountered = true;
       section.condition = !section.condition;
@@ -192,69 +292,98 @@ fn on_else(state: &mut PreprocessState) {
   } else {
     let msg =
       "-else() encountered without a matching -if(), ifdef(), -ifndef() or -elif().".to_string();
-    state.module.add_error(ErlError::preprocessor_error(
-      SourceLoc::unimplemented(file!(), function_name!()),
-      msg,
-    ));
+    state
+      .module
+      .add_error(ErlError::preprocessor_error(ppnode.location.clone(), msg));
   }
 }
 
-#[named]
-fn on_endif(state: &mut PreprocessState) {
+fn on_endif(state: &mut PreprocessState, ppnode: &PreprocessorNode) {
   if let Some(_section) = state.section.pop() {
     // all good
   } else {
     let msg =
       "-endif() encountered without a matching -if, ifdef, -ifndef, -elif or -else.".to_string();
-    state.module.add_error(ErlError::preprocessor_error(
-      SourceLoc::unimplemented(file!(), function_name!()),
-      msg,
-    ));
+    state
+      .module
+      .add_error(ErlError::preprocessor_error(ppnode.location.clone(), msg));
   }
 }
 
 /// Pop last section; Invert the condition in it and push back
-#[named]
-fn on_else_if(state: &mut PreprocessState, cond: &AstNode) {
+fn on_else_if(state: &mut PreprocessState, ppnode: &PreprocessorNode, cond: &AstNode) {
   if let Some(section) = state.section.pop() {
     // Open a new -IF section
     on_if(state, &section.ppnode, cond);
   } else {
     let msg = "-elif() encountered without a matching -if, ifdef, -ifndef or -elif.".to_string();
-    state.module.add_error(ErlError::preprocessor_error(
-      SourceLoc::unimplemented(file!(), function_name!()),
-      msg,
-    ));
+    state
+      .module
+      .add_error(ErlError::preprocessor_error(ppnode.location.clone(), msg));
   }
 }
 
+/// Tokenize and preprocess an included file. Including a file which is already being included
+/// is reported with the include chain, and the file is skipped. With the `warn_duplicate_include`
+/// option, including the same file again is reported as a warning.
 fn generic_include(
   state: &mut PreprocessState,
-  _ppnode: PreprocessorNode,
+  ppnode: PreprocessorNode,
   found_path: &Path,
 ) -> IcResult<Vec<Token>> {
-  // let found_path_oss = found_path.as_os_str().to_os_string();
-
-  // if state.module.included_files.contains(&found_path_oss) {
-  //   // Circular include detected
-  //   let msg = format!(
-  //     "Circular include detected {} from {}",
-  //     found_path_oss.to_string_lossy(),
-  //     state.module.source_file.file_name.to_string_lossy()
-  //   );
-  //   return Err(Box::new(ErlError::preprocessor_error(ppnode.location.clone(), msg)));
-  // }
-
   let src_file = state
     .project
     .file_cache
     .get_or_load(&found_path)
     .map_err(|e| IroncladError::from(e))?;
+  let module = state.module.clone();
+  let include_stack = module.include_stack.clone_contents();
+
+  // File cache paths are canonical, the module path might be not
+  let module_path = &module.source_file.file_name;
+  let is_module_file = module_path
+    .canonicalize()
+    .is_ok_and(|p| p == src_file.file_name);
+  if is_module_file
+    || include_stack
+      .iter()
+      .any(|(file, _)| *file == src_file.file_name)
+  {
+    let mut msg =
+      format!("Circular include of {}, include chain:", src_file.file_name.to_string_lossy());
+    for (file, location) in include_stack.iter() {
+      msg.push_str(&format!("\n  {}: includes {}", location, file.to_string_lossy()));
+    }
+    msg.push_str(&format!(
+      "\n  {}: includes {}",
+      ppnode.location,
+      src_file.file_name.to_string_lossy()
+    ));
+    module.add_error(ErlError::preprocessor_error(ppnode.location.clone(), msg));
+    return Ok(Vec::new());
+  }
 
-  // // update included modules collection as to prevent circular includes
-  // state.module.included_files.add(found_path_oss);
+  match module.included_files.get(&src_file.file_name) {
+    Some(first_location) if module.compiler_options.warn_duplicate_include => {
+      let msg = format!(
+        "File {} is included again, first included at {}",
+        src_file.file_name.to_string_lossy(),
+        first_location
+      );
+      module.add_warning(ErlError::preprocessor_error(ppnode.location.clone(), msg));
+    }
+    Some(_) => {}
+    None => module
+      .included_files
+      .add(src_file.file_name.clone(), ppnode.location.clone()),
+  }
 
-  ErlModuleImpl::tokenize(&state.project, &state.module, &src_file)
+  module
+    .include_stack
+    .push((src_file.file_name.clone(), ppnode.location.clone()));
+  let result = ErlModuleImpl::tokenize(&state.project, &module, &src_file);
+  module.include_stack.pop();
+  result
 }
 
 /// Handle `-include(Path)` preprocessor directive
@@ -264,25 +393,30 @@ fn on_include(
   ppnode: PreprocessorNode,
 ) -> IcResult<Vec<Token>> {
   let literal_path = PathBuf::from(path);
-  let found_path = state
-    .project
-    .find_include(ppnode.location.clone(), &literal_path, None)?;
+  let includers = state.includer_chain();
+  let found_path =
+    state
+      .project
+      .find_include(ppnode.location.clone(), &literal_path, &includers)?;
   generic_include(state, ppnode, &found_path)
 }
 
+/// Handle `-include_lib(Path)` preprocessor directive, where the first path component can be an
+/// application name
 fn on_include_lib(
   state: &mut PreprocessState,
   path: &str,
   ppnode: PreprocessorNode,
 ) -> IcResult<Vec<Token>> {
   let literal_path = PathBuf::from(path);
-  let found_path = state
-    .project
-    .find_include(ppnode.location.clone(), &literal_path, None)?;
+  let includers = state.includer_chain();
+  let found_path =
+    state
+      .project
+      .find_include_lib(ppnode.location.clone(), &literal_path, &includers)?;
   generic_include(state, ppnode, &found_path)
 }
 
-#[named]
 fn preprocess_handle_ppnode(
   input_tokens: &mut Vec<Token>,
   ppnode: PreprocessorNode,
@@ -295,7 +429,8 @@ fn preprocess_handle_ppnode(
     // Set module name (can be done only once)
     //------------------
     PreprocessorNodeType::ModuleName { name } if active => {
-      ErlModuleImpl::set_name(&state.module, name.as_str())
+      ErlModuleImpl::set_name(&state.module, name.as_str());
+      state.module.add_module_name_macros();
     }
 
     //------------------
@@ -317,31 +452,36 @@ fn preprocess_handle_ppnode(
     // Failure on demand
     //------------------
     PreprocessorNodeType::Error(e) if active => {
-      state.module.add_error(ErlError::preprocessor_error(
-        SourceLoc::unimplemented(file!(), function_name!()),
-        e.clone(),
-      ));
+      state
+        .module
+        .add_error(ErlError::preprocessor_error(ppnode.location.clone(), e.clone()));
     }
-    PreprocessorNodeType::Warning(w) if active => state.module.add_warning(
-      ErlError::preprocessor_error(SourceLoc::unimplemented(file!(), function_name!()), w.clone()),
-    ),
+    PreprocessorNodeType::Warning(w) if active => state
+      .module
+      .add_warning(ErlError::preprocessor_error(ppnode.location.clone(), w.clone())),
 
     //------------------
     // Populate module scope with stuff
     //------------------
-    PreprocessorNodeType::Attr { tag, term } if active => on_attr(state, tag.as_str(), term),
+    PreprocessorNodeType::Attr { tag, term } if active => {
+      on_attr(state, &ppnode, tag.as_str(), term)
+    }
     PreprocessorNodeType::Export { fun_arities } if active => on_export(state, fun_arities),
     PreprocessorNodeType::ExportType { type_arities } if active => {
       on_export_type(state, type_arities)
     }
     PreprocessorNodeType::Import { module: module_name, fun_arities } if active => {
-      on_import(state, module_name.as_str(), fun_arities)
+      on_import(state, &ppnode, module_name.as_str(), fun_arities)
     }
-    PreprocessorNodeType::NewType { name, vars, ty } if active => {
-      on_new_type(state, name.as_str(), vars, ty.clone())
+    PreprocessorNodeType::NewType { kind, name, vars, ty } if active => {
+      on_new_type(state, &ppnode, *kind, name.as_str(), vars, ty.clone())
+    }
+    PreprocessorNodeType::NewRecord { tag, fields } if active => {
+      on_new_record(state, &ppnode, tag, fields)
+    }
+    PreprocessorNodeType::FnSpec { funarity, spec } if active => {
+      on_fn_spec(state, &ppnode, funarity, spec)
     }
-    PreprocessorNodeType::NewRecord { tag, fields } if active => on_new_record(state, tag, fields),
-    PreprocessorNodeType::FnSpec { funarity, spec } if active => on_fn_spec(state, funarity, spec),
 
     //------------------
     // Macro define and undefine
@@ -361,9 +501,9 @@ fn preprocess_handle_ppnode(
     }
 
     // Elseif, Else and Endif do not check the `active` condition, and are always processed
-    PreprocessorNodeType::ElseIf { cond } => on_else_if(state, cond),
-    PreprocessorNodeType::Else => on_else(state),
-    PreprocessorNodeType::Endif => on_endif(state),
+    PreprocessorNodeType::ElseIf { cond } => on_else_if(state, &ppnode, cond),
+    PreprocessorNodeType::Else => on_else(state, &ppnode),
+    PreprocessorNodeType::Endif => on_endif(state, &ppnode),
     _ => {
       // println!("Section is not active for: {}", ppnode);
     }
@@ -380,43 +520,84 @@ fn line_begins_with_preprocessor_or_attr(line: &[Token]) -> bool {
     && (line[1].is_atom() || line[1].is_keyword(Keyword::Else) || line[1].is_keyword(Keyword::If))
 }
 
+/// Check whether the line is a `-define(...)` directive
+fn line_is_define_directive(line: &[Token]) -> bool {
+  line.len() > 2 && line[0].is_tok(TokenType::Minus) && line[1].is_atom_of("define")
+}
+
 /// Final checks for whether preprocessing was successful:
 /// * Unmatched #if/#endif
-#[named]
 fn final_state_check(state: &mut PreprocessState) {
   // Check for if/ifdef/else without a matching endif
   if let Some(last_sec) = state.section.last() {
     let msg =
       format!("A preprocessor section does not have a matching -endif: {}", last_sec.ppnode);
-    state.module.add_error(ErlError::preprocessor_error(
-      SourceLoc::unimplemented(file!(), function_name!()),
-      msg,
-    ));
+    state
+      .module
+      .add_error(ErlError::preprocessor_error(last_sec.ppnode.location.clone(), msg));
   }
 }
 
 impl ErlModuleImpl {
-  /// Add predefined symbols for current module
+  /// Add predefined symbols for current module. Called once per module before tokenizing, so
+  /// that an `-undef` is not reverted by the following `-include`.
   pub fn setup_preprocessor(&self) {
-    let add0 = |name: &str, value: &[Token]| {
-      self.root_scope.defines.add(
-        MFArity::new_local(name, 0),
-        PreprocessorDefineImpl::new(name.to_string(), &[], value),
-      );
-    };
-    add0("COMPILER_VSN", &[Token::new_small(42)]);
-    add0(
+    // Defines from the project file and the command line `-D` options
+    for (name_arity, pdef) in self.compiler_options.scope.data.iter() {
+      self
+        .root_scope
+        .defines
+        .add(name_arity.clone(), pdef.clone());
+    }
+
+    self.add_predefined_macro("COMPILER_VSN", &[Token::new_small(42)]);
+    // ?FILE and ?LINE values depend on where they are used, and are substituted in
+    // `pp_macro_substitution`; the definitions here make them visible to `-ifdef()`
+    self.add_predefined_macro(
       "FILE",
       &[Token::new_string(
         self.source_file.file_name.to_string_lossy().to_string(),
       )],
     );
-    add0("MODULE", &[Token::new_atom(self.name.read().unwrap().clone())])
+    self.add_predefined_macro("LINE", &[Token::new_small(0)]);
+
+    let otp_release = self.compiler_options.get_otp_release();
+    self.add_predefined_macro("OTP_RELEASE", &[Token::new_small(otp_release as i64)]);
+    self.add_predefined_macro("MACHINE", &[Token::new_atom("BEAM".to_string())]);
+    self.add_predefined_macro("BEAM", &[Token::new_atom("true".to_string())]);
+    if otp_release >= 25 {
+      // ?FEATURE_AVAILABLE(F) and ?FEATURE_ENABLED(F) are also substituted in `pp_macro_substitution`
+      for name in ["FEATURE_AVAILABLE", "FEATURE_ENABLED"] {
+        self.root_scope.defines.add(
+          MFArity::new_local(name, 1),
+          PreprocessorDefineImpl::new(name.to_string(), &["Feature".to_string()], &[]),
+        );
+      }
+    }
+
+    // Included files are tokenized after -module() is seen
+    if !self.name.read().unwrap().is_empty() {
+      self.add_module_name_macros();
+    }
+  }
+
+  /// Add `?MODULE` and `?MODULE_STRING`, once the module name is known from `-module()`
+  pub(crate) fn add_module_name_macros(&self) {
+    let name = self.get_name();
+    self.add_predefined_macro("MODULE_STRING", &[Token::new_string(name.clone())]);
+    self.add_predefined_macro("MODULE", &[Token::new_atom(name)]);
+  }
+
+  /// Add a predefined macro without arguments
+  fn add_predefined_macro(&self, name: &str, value: &[Token]) {
+    self.root_scope.defines.add(
+      MFArity::new_local(name, 0),
+      PreprocessorDefineImpl::new(name.to_string(), &[], value),
+    );
   }
 
   /// Filter through the tokens array and produce a new token array with preprocessor directives
   /// eliminated, files included and macros substituted.
-  #[named]
   pub fn preprocess_interpret(
     source_file: &SourceFile,
     project: &ErlProject,
@@ -430,16 +611,25 @@ impl ErlModuleImpl {
       if state.too_many_errors {
         break;
       }
-      print!(
-        "\rProgress: {:.02}% - {} ",
-        state.itr.progress(),
-        source_file.file_name.to_string_lossy()
-      );
+      if !module.compiler_options.quiet {
+        eprint!(
+          "\rProgress: {:.02}% - {} ",
+          state.itr.progress(),
+          source_file.file_name.to_string_lossy()
+        );
+      }
 
       if line_begins_with_preprocessor_or_attr(&line) {
         let line2 = expand_till_directive_end(line, &mut state);
-        let line3 = substitute_macro_invocations(original_input, line2, &mut state);
-        println!("LINE {}", format_tok_stream(line3.as_slice(), line3.as_slice().len()));
+        // Macros in a -define body are expanded where the macro is used, not where it is defined
+        let line3 = if line_is_define_directive(line2) {
+          TokenStream::new_borrowed(line2)
+        } else {
+          substitute_macro_invocations(line2, &mut state)
+        };
+        if !module.compiler_options.quiet {
+          eprintln!("LINE {}", format_tok_stream(line3.as_slice(), line3.as_slice().len()));
+        }
 
         let (tail, ppnode) = line3.parse_as_preprocessor(original_input, state.module.clone());
 
@@ -449,10 +639,7 @@ impl ErlModuleImpl {
           let msg = format!(
             "Not all input consumed while parsing a preprocessor directive or a module attribute:\n{}",
             format_tok_stream(tail.tokens, 100));
-          module.add_error(ErlError::preprocessor_error(
-            SourceLoc::unimplemented(file!(), function_name!()),
-            msg,
-          ));
+          module.add_error(ErlError::preprocessor_error(SourceLoc::new(&tail), msg));
         }
         preprocess_handle_ppnode(&mut tokens, ppnode, &mut state)?;
       } else {
@@ -461,11 +648,12 @@ impl ErlModuleImpl {
           let line2 = expand_till_directive_start(line, &mut state);
 
           // Substitute macro invocations in the line with their content
-          let line3 = substitute_macro_invocations(original_input, line2, &mut state);
+          let line3 = substitute_macro_invocations_in_code(line2, &mut state);
           // println!("{}", format_tok_stream(line3.as_slice(), line3.as_slice().len()));
 
           // Copy the line contents to result.
-          state.result.extend(line3.as_slice().iter().cloned())
+          let line4 = demote_disabled_keywords(&state, line3.as_slice());
+          state.result.extend(line4)
         }
       }
     }
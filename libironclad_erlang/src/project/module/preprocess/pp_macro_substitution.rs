@@ -1,37 +1,73 @@
 //! Code for processing a line of tokens and pasting macro values instead of macro invocations.
 
 use crate::erl_syntax::erl_error::ErlError;
-use crate::erl_syntax::parsers::misc::panicking_parser_error_reporter;
 use crate::erl_syntax::parsers::parser_input::ParserInput;
+use crate::erl_syntax::parsers::token_stream::keyword::Keyword;
 use crate::erl_syntax::parsers::token_stream::token::Token;
 use crate::erl_syntax::parsers::token_stream::token_type::TokenType;
-use crate::erl_syntax::preprocessor::parsers::parse_pp::parse_macro_invocation_args;
-use crate::erl_syntax::preprocessor::pp_define::{PreprocessorDefine, PreprocessorDefineImpl};
+use crate::erl_syntax::preprocessor::pp_define::PreprocessorDefine;
 use crate::exit_codes::erl_fatal_error;
 use crate::project::module::module_impl::ErlModule;
 use crate::project::module::preprocess::pp_state::PreprocessState;
 use crate::project::module::preprocess::pp_tok_stream::TokenStream;
+use crate::project::module::scope::root_scope::KNOWN_FEATURES;
 use crate::source_loc::SourceLoc;
 use ::function_name::named;
 use libironclad_util::mfarity::MFArity;
-use nom::combinator::recognize;
-use nom::Finish;
 
 fn has_any_macro_invocations(line: &[Token]) -> bool {
   line.iter().any(|t| t.is_macro_invocation())
 }
 
+/// Source text of a token as it was written. Tokens which did not come from the source text (i.e.
+/// created by the preprocessor) are printed instead.
+fn token_source_text(module: &ErlModule, t: &Token) -> String {
+  if let Some(file) = module.find_source_file(t.offset) {
+    if let (Some(start), Some(end)) = (file.offset_of(t.offset), file.offset_of(t.end)) {
+      if !t.end.is_null() && start <= end {
+        return file.text[start..end].to_string();
+      }
+    }
+  }
+  format!("{}", t.content).trim().to_string()
+}
+
+/// Produce the text for `??Arg`: source text of the argument tokens separated by single spaces,
+/// same as `epp` does.
+fn stringify_arg(module: &ErlModule, arg: &[Token]) -> String {
+  arg
+    .iter()
+    .filter(|t| !t.is_eol())
+    .map(|t| token_source_text(module, t))
+    .collect::<Vec<String>>()
+    .join(" ")
+}
+
 /// For all tokens in `pdef.tokens` paste them into the `output`.
 /// If a token is a `Variable(s)` token, then try look up its name in the macro args list, and if
-/// found, paste the value from `args[]` into the output.
-fn paste_tokens(output: &mut Vec<Token>, pdef: &PreprocessorDefine, args: &[Vec<Token>]) {
+/// found, paste the value from `args[]` into the output. A `??Variable` token is replaced with a
+/// string containing the source text of the argument as it was written, from `raw_args[]`.
+fn paste_tokens(
+  module: &ErlModule,
+  output: &mut Vec<Token>,
+  pdef: &PreprocessorDefine,
+  args: &[Vec<Token>],
+  raw_args: &[Vec<Token>],
+) {
+  let find_arg = |var: &String| pdef.args.iter().position(|arg_name| arg_name == var);
+
   for t in pdef.tokens.iter() {
     match &t.content {
       TokenType::Variable(var) => {
-        if let Some(arg_index) = pdef.args.iter().position(|arg_name| arg_name == var) {
-          let arg = &args[arg_index];
-          // TODO: Macro invocation inside a macro body
-          output.extend(arg.iter().cloned());
+        if let Some(arg_index) = find_arg(var) {
+          output.extend(args[arg_index].iter().cloned());
+        } else {
+          output.push(t.clone());
+        }
+      }
+      TokenType::MacroStringifyArg(var) => {
+        if let Some(arg_index) = find_arg(var) {
+          output.push(Token::new_string(stringify_arg(module, &raw_args[arg_index])));
         } else {
           output.push(t.clone());
         }
@@ -41,67 +77,230 @@ fn paste_tokens(output: &mut Vec<Token>, pdef: &PreprocessorDefine, args: &[Vec<
   }
 }
 
+/// A macro invocation in the source being expanded, including the macros invoked from its body
+struct MacroExpansion {
+  /// Names of the macros whose bodies are currently being expanded, an invocation of any of them
+  /// is an error
+  macros: Vec<String>,
+  /// The macro invocation token in the source, `?FILE` and `?LINE` refer to its position
+  invoked_at: Token,
+}
+
+/// Report an error at the macro invocation `tokens[index]`
+fn macro_error(state: &PreprocessState, tokens: &[Token], index: usize, msg: String) {
+  let location =
+    SourceLoc::new_span(&ParserInput::new_slice(state.module.clone(), &tokens[index..=index]));
+  state
+    .module
+    .add_error(ErlError::preprocessor_error(location, msg));
+}
+
+/// Split the invocation arguments following `tokens[index]`, reporting an error if they are not
+/// terminated.
+fn invocation_args(
+  state: &PreprocessState,
+  tokens: &[Token],
+  index: usize,
+  macro_name: &str,
+) -> (Vec<Vec<Token>>, usize) {
+  match split_invocation_args(&tokens[index + 1..]) {
+    Some(args_and_span) => args_and_span,
+    None => {
+      let msg = format!("Unterminated argument list of the macro invocation ?{}", macro_name);
+      macro_error(state, tokens, index, msg);
+      (Vec::default(), 0)
+    }
+  }
+}
+
+/// Substitute macros predefined by the compiler whose values depend on where they are used.
+/// Returns the index of the next input token, or `None` if `macro_name` is not such a macro.
+fn paste_predefined_macro(
+  macro_name: &str,
+  tokens: &[Token],
+  index: usize,
+  output: &mut Vec<Token>,
+  state: &PreprocessState,
+  expansion: &MacroExpansion,
+) -> Option<usize> {
+  let module = &state.module;
+  let invoked_in = module.find_source_file(expansion.invoked_at.offset);
+  let token = match macro_name {
+    "LINE" => {
+      let line = invoked_in
+        .and_then(|file| {
+          let offset = file.offset_of(expansion.invoked_at.offset)?;
+          Some(file.line_col(offset).0)
+        })
+        .unwrap_or_default();
+      Token::new_small(line as i64)
+    }
+    "FILE" => Token::new_string(invoked_in?.file_name.to_string_lossy().to_string()),
+    "FUNCTION_NAME" | "FUNCTION_ARITY" => match &state.function {
+      Some(function) if macro_name == "FUNCTION_NAME" => Token::new_atom(function.name.clone()),
+      Some(function) => Token::new_small(function.arity as i64),
+      None => {
+        let msg = format!("?{} can only be used within a function body", macro_name);
+        macro_error(state, tokens, index, msg);
+        return Some(index + 1);
+      }
+    },
+    "FEATURE_AVAILABLE" | "FEATURE_ENABLED" if module.root_scope.is_defined(macro_name) => {
+      let (args, args_span) = invocation_args(state, tokens, index, macro_name);
+      let feature = match args.as_slice() {
+        [arg] if arg.len() == 1 => match &arg[0].content {
+          TokenType::Atom(feature) => feature.as_str(),
+          _ => "",
+        },
+        _ => "",
+      };
+      if feature.is_empty() {
+        let msg = format!("?{}() expects a feature name", macro_name);
+        macro_error(state, tokens, index, msg);
+        return Some(index + args_span + 1);
+      }
+      let value = if macro_name == "FEATURE_AVAILABLE" {
+        KNOWN_FEATURES.contains(&feature)
+      } else {
+        module.root_scope.is_feature_enabled(feature)
+      };
+      output.push(Token::new_atom(value.to_string()));
+      return Some(index + args_span + 1);
+    }
+    _ => return None,
+  };
+  output.push(token);
+  Some(index + 1)
+}
+
 #[named]
 #[inline]
-fn lookup_and_paste_macro<'a>(
+fn lookup_and_paste_macro(
   macro_name: &String,
-  tokens: &'a [Token],
+  tokens: &[Token],
   index: usize,
   output: &mut Vec<Token>,
-  original_input: &str,
-  state: &mut PreprocessState<'a>,
+  state: &mut PreprocessState,
+  expansion: &mut MacroExpansion,
 ) -> usize {
-  // Parse once to get the actual arguments grouped by the commas
-  // and parse twice to get the actual span of tokens affected
-  let (args, args_span) =
-    parse_as_invocation_params(original_input, state.module.clone(), &tokens[index + 1..]);
+  let (args, args_span) = invocation_args(state, tokens, index, macro_name);
 
-  // Look up the macro definition
-  let key = MFArity::new_local(&macro_name, args.len());
+  // Look up the macro definition. Like `epp` does, if there is no definition with matching arity,
+  // a macro without arguments is used and the parenthesized tokens are left for the caller.
+  let defines = &state.module.root_scope.defines;
+  let (pdef, args, args_span) =
+    if let Some(pdef) = defines.get(&MFArity::new_local(macro_name, args.len())) {
+      (pdef, args, args_span)
+    } else if let Some(pdef) = defines.get(&MFArity::new_local(macro_name, 0)) {
+      (pdef, Vec::default(), 0)
+    } else {
+      erl_fatal_error(ErlError::preprocessor_error(
+        SourceLoc::unimplemented(file!(), function_name!()),
+        format!("Invocation of an undefined macro: {}", macro_name),
+      ));
+      return index + args_span + 1;
+    };
 
-  if let Some(pdef) = state.module.root_scope.defines.get(&key) {
-    // Insert macro body and replace any macro variables with content
-    paste_tokens(output, &pdef, &args);
-  } else {
-    erl_fatal_error(ErlError::preprocessor_error(
-      SourceLoc::unimplemented(file!(), function_name!()),
-      format!("Invocation of an undefined macro: {}", macro_name),
-    ));
+  // Arguments are expanded before they are pasted, so that `?M(?M(X))` is not taken for recursion
+  let expanded_args: Vec<Vec<Token>> = args
+    .iter()
+    .map(|arg| {
+      if has_any_macro_invocations(arg) {
+        expand_macros(arg, state, expansion)
+      } else {
+        arg.clone()
+      }
+    })
+    .collect();
+
+  // Insert macro body and replace any macro variables with content
+  let mut body = Vec::with_capacity(pdef.tokens.len());
+  paste_tokens(&state.module, &mut body, &pdef, &expanded_args, &args);
+
+  // Macro invocations in the body are expanded after pasting, while this macro is marked as being
+  // expanded to catch self-recursive macros
+  if has_any_macro_invocations(&body) {
+    expansion.macros.push(macro_name.clone());
+    body = expand_macros(&body, state, expansion);
+    expansion.macros.pop();
   }
+  output.extend(body);
 
   // Skip input tokens consumed by parsing the arguments
   index + args_span + 1
 }
 
+/// Expand one macro invocation `tokens[index]` into the `output`.
+/// Returns the index of the next input token.
+fn expand_invocation(
+  macro_name: &String,
+  tokens: &[Token],
+  index: usize,
+  output: &mut Vec<Token>,
+  state: &mut PreprocessState,
+  expansion: &mut MacroExpansion,
+) -> usize {
+  if let Some(next_index) =
+    paste_predefined_macro(macro_name, tokens, index, output, state, expansion)
+  {
+    next_index
+  } else if expansion.macros.contains(macro_name) {
+    let msg = format!(
+      "Recursive macro invocation: ?{} (while expanding ?{})",
+      macro_name,
+      expansion.macros.join(", ?")
+    );
+    macro_error(state, tokens, index, msg);
+    index + 1
+  } else {
+    lookup_and_paste_macro(macro_name, tokens, index, output, state, expansion)
+  }
+}
+
 /// Given an input line of tokens, replace macro invocations with their actual body content.
 /// Also substitute the macro variables.
 /// Returns a wrapper struct with either original or substituted tokens.
 pub(crate) fn substitute_macro_invocations<'a>(
-  original_input: &str,
   tokens: &'a [Token],
-  state: &mut PreprocessState<'a>,
+  state: &mut PreprocessState,
+) -> TokenStream<'a> {
+  substitute_in_line(tokens, state, false)
+}
+
+/// Same as `substitute_macro_invocations` for a line of code, which also tracks the function being
+/// defined for `?FUNCTION_NAME` and `?FUNCTION_ARITY`.
+pub(crate) fn substitute_macro_invocations_in_code<'a>(
+  tokens: &'a [Token],
+  state: &mut PreprocessState,
+) -> TokenStream<'a> {
+  substitute_in_line(tokens, state, true)
+}
+
+fn substitute_in_line<'a>(
+  tokens: &'a [Token],
+  state: &mut PreprocessState,
+  track_functions: bool,
 ) -> TokenStream<'a> {
   if !has_any_macro_invocations(tokens) {
+    if track_functions {
+      (0..tokens.len()).for_each(|index| track_function_form(state, tokens, index));
+    }
     // no changes, no macro invocations
     return TokenStream::new_borrowed(tokens);
   }
 
   let mut output = Vec::with_capacity(tokens.len());
   let mut index = 0usize;
-  let max_index = tokens.len();
 
-  while index < max_index {
+  while index < tokens.len() {
+    if track_functions {
+      track_function_form(state, tokens, index);
+    }
     let t = &tokens[index];
 
     if let TokenType::MacroInvocation(macro_name) = &t.content {
-      if macro_name == "LINE" {
-        let pdef = PreprocessorDefineImpl::new("LINE".to_string(), &[], &[Token::new_small(0)]);
-        paste_tokens(&mut output, &pdef, &[]);
-        index += 1;
-      } else {
-        index =
-          lookup_and_paste_macro(macro_name, tokens, index, &mut output, original_input, state);
-      }
+      let mut expansion = MacroExpansion { macros: Vec::new(), invoked_at: t.clone() };
+      index = expand_invocation(macro_name, tokens, index, &mut output, state, &mut expansion);
     } else {
       output.push(t.clone());
       index += 1;
@@ -111,27 +310,163 @@ pub(crate) fn substitute_macro_invocations<'a>(
   TokenStream::new_owned(output)
 }
 
-/// Invoke parser producing a list of expressions? separated by commas
-/// Return value: The tokens of arguments, grouped by the separating commas, and the span of the
-///               arguments list (used to skip the length of tokens)
-fn parse_as_invocation_params(
-  original_input: &str,
-  module: ErlModule,
+/// Replace macro invocations in `tokens`, which are a macro body or macro arguments, with the
+/// macro bodies, recursively.
+fn expand_macros(
   tokens: &[Token],
-) -> (Vec<Vec<Token>>, usize) {
-  let parser_input = ParserInput::new_slice(module.clone(), tokens);
-  let (_tail, nodes_as_tokens) = panicking_parser_error_reporter(
-    original_input,
-    parser_input.clone(),
-    parse_macro_invocation_args(parser_input).finish(),
-    false,
-  );
-
-  // Assume this cannot fail, if previous failed it should panic or something
-  let parser_input2 = ParserInput::new_slice(module, tokens);
-  let (_tail2, recognized_span) = recognize(parse_macro_invocation_args)(parser_input2)
-    .finish()
-    .unwrap();
-
-  (nodes_as_tokens, recognized_span.tokens.len())
+  state: &mut PreprocessState,
+  expansion: &mut MacroExpansion,
+) -> Vec<Token> {
+  let mut output = Vec::with_capacity(tokens.len());
+  let mut index = 0usize;
+
+  while index < tokens.len() {
+    let t = &tokens[index];
+
+    if let TokenType::MacroInvocation(macro_name) = &t.content {
+      index = expand_invocation(macro_name, tokens, index, &mut output, state, expansion);
+    } else {
+      output.push(t.clone());
+      index += 1;
+    }
+  }
+
+  output
+}
+
+/// Whether the period `tokens[index]` ends a form: it is followed by whitespace, a comment or the
+/// end of input, otherwise it is a part of an expression, like in `R#rec.field`.
+fn is_form_end(module: &ErlModule, tokens: &[Token], index: usize) -> bool {
+  let t = &tokens[index];
+  if let Some(file) = module.find_source_file(t.end) {
+    if let Some(end) = file.offset_of(t.end) {
+      return file.text[end..]
+        .chars()
+        .next()
+        .is_none_or(|c| c.is_whitespace() || c == '%');
+    }
+  }
+  tokens.get(index + 1).is_none_or(|next| next.is_eol())
+}
+
+/// Update the function being defined, seeing `tokens[index]` in the code. A form which begins
+/// with `atom(...)` is a function definition, and it ends with a period.
+fn track_function_form(state: &mut PreprocessState, tokens: &[Token], index: usize) {
+  let t = &tokens[index];
+  if t.is_eol() {
+    return;
+  }
+  if t.is_tok(TokenType::Period) {
+    if is_form_end(&state.module, tokens, index) {
+      state.form_start = true;
+      state.function = None;
+    }
+    return;
+  }
+  if !state.form_start {
+    return;
+  }
+
+  state.form_start = false;
+  if let TokenType::Atom(name) = &t.content {
+    let head = &tokens[index + 1..];
+    if head.first().is_some_and(|t| t.is_tok(TokenType::ParOpen)) {
+      if let Some((args, _)) = split_invocation_args(head) {
+        state.function = Some(MFArity::new_local(name, args.len()));
+      }
+    }
+  }
+}
+
+/// Whether the token closes a bracket or a block, expected or not
+fn is_closing_token(t: &Token) -> bool {
+  matches!(
+    t.content,
+    TokenType::ParClose
+      | TokenType::SquareClose
+      | TokenType::CurlyClose
+      | TokenType::DoubleAngleClose
+      | TokenType::Keyword(Keyword::End)
+  )
+}
+
+/// For a token opening a bracket or a block, return the token expected to close it. The `tokens`
+/// begin with the token being checked.
+fn closing_token_for(tokens: &[Token]) -> Option<TokenType> {
+  match &tokens[0].content {
+    TokenType::ParOpen => Some(TokenType::ParClose),
+    TokenType::SquareOpen => Some(TokenType::SquareClose),
+    TokenType::CurlyOpen => Some(TokenType::CurlyClose),
+    TokenType::DoubleAngleOpen => Some(TokenType::DoubleAngleClose),
+    TokenType::Keyword(Keyword::Begin)
+    | TokenType::Keyword(Keyword::Case)
+    | TokenType::Keyword(Keyword::If)
+    | TokenType::Keyword(Keyword::Maybe)
+    | TokenType::Keyword(Keyword::Receive)
+    | TokenType::Keyword(Keyword::Try) => Some(TokenType::Keyword(Keyword::End)),
+    // Only `fun (...) -> ... end` and `fun Name(...) -> ... end` have a body, not `fun f/1`
+    TokenType::Keyword(Keyword::Fun) => {
+      let is_par_open = |i: usize| tokens.get(i).is_some_and(|t| t.is_tok(TokenType::ParOpen));
+      let is_named = tokens
+        .get(1)
+        .is_some_and(|t| matches!(t.content, TokenType::Variable(_)));
+      if is_par_open(1) || (is_named && is_par_open(2)) {
+        Some(TokenType::Keyword(Keyword::End))
+      } else {
+        None
+      }
+    }
+    _ => None,
+  }
+}
+
+/// Split the tokens following a macro invocation `?NAME` into the arguments, same as `epp` does:
+/// arguments are separated by commas which are not nested inside of brackets or `begin ... end`
+/// style blocks. The arguments are not parsed, because they can contain other macro invocations.
+/// If the invocation is not followed by an opening parenthesis, there are no arguments.
+/// Return value: The tokens of arguments, grouped by the separating commas, and the span of the
+///               arguments list (used to skip the length of tokens), or `None` if the closing
+///               parenthesis was not found.
+fn split_invocation_args(tokens: &[Token]) -> Option<(Vec<Vec<Token>>, usize)> {
+  if !tokens.first().is_some_and(|t| t.is_tok(TokenType::ParOpen)) {
+    return Some((Vec::default(), 0));
+  }
+
+  let mut args = Vec::new();
+  let mut arg = Vec::new();
+  let mut expected_closing = vec![TokenType::ParClose];
+
+  for index in 1..tokens.len() {
+    let t = &tokens[index];
+
+    if expected_closing.len() == 1 && t.is_tok(TokenType::Comma) {
+      args.push(std::mem::take(&mut arg));
+      continue;
+    }
+
+    if is_closing_token(t) {
+      let expected = expected_closing.pop()?;
+      let matches_expected = match expected {
+        TokenType::Keyword(_) => t.is_keyword(Keyword::End),
+        other => t.is_tok(other),
+      };
+      if !matches_expected {
+        return None;
+      }
+      if expected_closing.is_empty() {
+        // `?M()` has no args, and `?M(A)` has one
+        if !arg.is_empty() || !args.is_empty() {
+          args.push(arg);
+        }
+        return Some((args, index + 1));
+      }
+    } else if let Some(closing) = closing_token_for(&tokens[index..]) {
+      expected_closing.push(closing);
+    }
+
+    if !t.is_eol() {
+      arg.push(t.clone());
+    }
+  }
+  None
 }
@@ -6,6 +6,8 @@ use crate::erl_syntax::preprocessor::pp_node::PreprocessorNode;
 use crate::project::module::module_impl::ErlModule;
 use crate::project::module::preprocess::pp_section::PreprocessorSection;
 use crate::project::ErlProject;
+use libironclad_util::mfarity::MFArity;
+use std::path::PathBuf;
 
 /// Stores the state of preprocessor directives interpretation.
 pub(crate) struct PreprocessState<'a> {
@@ -21,6 +23,11 @@ pub(crate) struct PreprocessState<'a> {
   pub(crate) section: Vec<PreprocessorSection>,
   /// Input iterator of Tokens
   pub(crate) itr: TokenLinesIter<'a>,
+  /// True when the next token in the code begins a new form (a function, for example)
+  pub(crate) form_start: bool,
+  /// The function whose body is being preprocessed, gives values for `?FUNCTION_NAME` and
+  /// `?FUNCTION_ARITY`
+  pub(crate) function: Option<MFArity>,
 }
 
 impl<'a> PreprocessState<'a> {
@@ -37,9 +44,26 @@ impl<'a> PreprocessState<'a> {
       itr: TokenLinesIter::new(input_tokens),
       too_many_errors: false,
       section: Vec::default(),
+      form_start: true,
+      function: None,
     }
   }
 
+  /// The file being preprocessed, then the file which included it, and so on. The module file is
+  /// the last.
+  pub(crate) fn includer_chain(&self) -> Vec<PathBuf> {
+    let mut result: Vec<PathBuf> = self
+      .module
+      .include_stack
+      .clone_contents()
+      .into_iter()
+      .rev()
+      .map(|(file, _)| file)
+      .collect();
+    result.push(self.module.source_file.file_name.clone());
+    result
+  }
+
   /// Insert tokens vector contents at the current preprocessing state. The vector might be
   /// reallocated, so also update the `itr` iterator. This is used to include files.
   pub(crate) fn paste_tokens(&self, paste_into: &mut Vec<Token>, mut tokens: Vec<Token>) {
@@ -8,13 +8,21 @@ use crate::project::module::scope::mod_attr::ModuleAttributes;
 use crate::project::project_impl::ErlProjectImpl;
 use crate::project::ErlProject;
 use crate::record_def::RecordDefinition;
+use crate::source_loc::SourceLoc;
 use crate::typing::erl_type::ErlType;
+use crate::typing::user_type::UserType;
 use libironclad_util::io::file_cache::FileCache;
 use libironclad_util::mfarity::MFArity;
 use libironclad_util::rw_hashmap::RwHashMap;
 use libironclad_util::rw_hashset::RwHashSet;
 use std::sync::Arc;
 
+/// Language feature which enables `maybe ... end` blocks (EEP-49)
+pub const FEATURE_MAYBE_EXPR: &str = "maybe_expr";
+
+/// Language features which can be toggled with `-feature(NAME, enable | disable).`
+pub const KNOWN_FEATURES: &[&str] = &[FEATURE_MAYBE_EXPR];
+
 /// Implements module root scope, stuff available directly from the module root
 #[derive(Debug)]
 pub struct RootScopeImpl {
@@ -26,13 +34,27 @@ pub struct RootScopeImpl {
   pub project: ErlProject,
   /// Contains definitions, added by `-spec` attribute
   pub fn_specs: RwHashMap<MFArity, ErlType>,
-  /// Contains `-type NAME() ...` definitions for new types
-  pub user_types: RwHashMap<MFArity, ErlType>,
+  /// Where the `-spec` attributes were found, to report spec errors
+  pub fn_spec_locations: RwHashMap<MFArity, SourceLoc>,
+  /// Contains `-type NAME() ...` definitions for new types, also `-opaque` and `-nominal`
+  pub user_types: RwHashMap<MFArity, UserType>,
+  /// Where the user types were defined, to report unused types
+  pub user_type_locations: RwHashMap<MFArity, SourceLoc>,
   /// Functions can only be found on the module root scope (but technically can be created in the
   /// other internal scopes too)
   pub fn_defs: RwHashMap<MFArity, AstNode>,
+  /// Synthesized function types, stored when the module is checked, so that the calls from other
+  /// modules do not synthesize them again
+  pub fn_types: RwHashMap<MFArity, ErlType>,
+  /// Functions which are being synthesized now, with the type assumed for the recursive calls to
+  /// them, while the synthesis iterates to a fixpoint
+  pub fn_synth_in_progress: RwHashMap<MFArity, ErlType>,
+  /// Functions being synthesized, which were called recursively during the current iteration
+  pub fn_synth_recursive: RwHashSet<MFArity>,
   /// Collection of record definitions
   pub record_defs: RwHashMap<String, Arc<RecordDefinition>>,
+  /// Where the records were defined, to report unused records
+  pub record_def_locations: RwHashMap<String, SourceLoc>,
   /// Collection of all custom attributes coming in form of `- <TAG> ( <EXPR> ).` tag is key in this
   /// collection and not unique.
   pub attributes: RwHashMap<String, Arc<ModuleAttributes>>,
@@ -42,6 +64,10 @@ pub struct RootScopeImpl {
   pub exported_types: RwHashSet<MFArity>,
   /// Imported function names keyed by the MFArity
   pub imports: RwHashSet<MFArity>,
+  /// Where the functions were imported, to report unused imports
+  pub import_locations: RwHashMap<MFArity, SourceLoc>,
+  /// Language features enabled with `-feature(NAME, enable).`
+  pub enabled_features: RwHashSet<String>,
 }
 
 /// Alias type for `Arc<>`
@@ -54,13 +80,21 @@ impl Default for RootScopeImpl {
       file_cache: FileCache::default(),
       project: ErlProjectImpl::default().into(),
       fn_specs: RwHashMap::default(),
+      fn_spec_locations: RwHashMap::default(),
       user_types: RwHashMap::default(),
+      user_type_locations: RwHashMap::default(),
+      fn_types: RwHashMap::default(),
+      fn_synth_in_progress: RwHashMap::default(),
+      fn_synth_recursive: RwHashSet::default(),
       fn_defs: RwHashMap::default(),
       record_defs: RwHashMap::default(),
+      record_def_locations: RwHashMap::default(),
       attributes: RwHashMap::default(),
       exports: RwHashSet::default(),
       exported_types: RwHashSet::default(),
       imports: RwHashSet::default(),
+      import_locations: RwHashMap::default(),
+      enabled_features: RwHashSet::default(),
     }
   }
 }
@@ -96,10 +130,12 @@ impl RootScopeImpl {
     }
   }
 
-  /// Recursive descend into AST saving FnDef nodes
+  /// Recursive descend into AST saving FnDef nodes. FnDef nodes inside a function are lambdas,
+  /// and are not functions of the module.
   pub fn update_from_ast(&self, ast: &AstNode) {
     if let AstNodeType::FnDef(fndef) = &ast.content {
       self.fn_defs.add(fndef.funarity.clone(), ast.clone());
+      return;
     }
 
     if let Some(children) = ast.children() {
@@ -109,6 +145,11 @@ impl RootScopeImpl {
     }
   }
 
+  /// Check whether a language feature was enabled with `-feature(NAME, enable).`
+  pub fn is_feature_enabled(&self, feature: &str) -> bool {
+    self.enabled_features.contains(&feature.to_string())
+  }
+
   /// Check if preprocessor definition of any arity exists in the scope
   pub fn is_defined(&self, name: &str) -> bool {
     if let Ok(r_defines) = self.defines.collection.read() {
@@ -1,6 +1,9 @@
 //! Scope for module
 
+use crate::erl_syntax::erl_ast::node_impl::AstNodeImpl;
+use crate::erl_syntax::erl_ast::AstNode;
 use crate::erl_syntax::node::erl_var::ErlVar;
+use crate::error::ic_error::IcResult;
 use crate::typing::erl_type::ErlType;
 use libironclad_util::rw_hashmap::RwHashMap;
 use std::collections::HashMap;
@@ -73,6 +76,18 @@ impl ScopeImpl {
     .into()
   }
 
+  /// Create a copy of the scope with variables bound by the pattern added. Bound variables have
+  /// the type `any()`.
+  pub(crate) fn new_with_pattern_vars(
+    scope: &Scope,
+    name: &str,
+    pattern: &AstNode,
+  ) -> IcResult<Scope> {
+    let mut variables = scope.variables.collection.read().unwrap().clone();
+    AstNodeImpl::extract_variables(pattern, &mut variables)?;
+    Ok(Self::new(name.to_string(), scope.parent_scope.clone(), variables))
+  }
+
   // /// Return new copy of Scope with a new variable added
   // #[allow(dead_code)]
   // pub(crate) fn add_clone(&self, var_name: &str) -> Scope {
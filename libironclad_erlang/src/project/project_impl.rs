@@ -75,6 +75,23 @@ impl ErlProjectImpl {
     Ok(())
   }
 
+  /// Use the given files as project inputs instead of searching the input directories.
+  /// Used when the files are given on the command line.
+  pub fn set_input_files(&self, files: &[PathBuf]) -> IroncladResult<()> {
+    let mut file_set: HashSet<PathBuf> = HashSet::with_capacity(files.len());
+    let mut file_list = Vec::new();
+
+    for path in files {
+      Self::maybe_add_path(&mut file_set, &mut file_list, path.clone())?;
+    }
+
+    self
+      .project_inputs
+      .input_paths
+      .replace(file_list.iter().cloned());
+    Ok(())
+  }
+
   /// Check exclusions in the Self.input. Hashset is used to check for duplicates. Add to Vec.
   fn maybe_add_path(
     file_set: &mut HashSet<PathBuf>,
@@ -100,6 +117,44 @@ impl ErlProjectImpl {
     self.modules.add(m_name, module.clone())
   }
 
+  /// Loaded modules ordered so that the modules which are called come before their callers, and
+  /// can be type checked first. Modules calling each other in a cycle are ordered by name.
+  pub fn modules_in_dependency_order(&self) -> Vec<ErlModule> {
+    let mut names = if let Ok(r_modules) = self.modules.collection.read() {
+      r_modules.keys().cloned().collect::<Vec<_>>()
+    } else {
+      panic!("Can't lock project modules for reading")
+    };
+    names.sort();
+
+    let mut visited = HashSet::new();
+    let mut result = Vec::with_capacity(names.len());
+    for name in names.iter() {
+      self.visit_module_dependencies(name, &mut visited, &mut result);
+    }
+    result
+  }
+
+  /// Depth-first visit of the modules called by the module, then the module itself
+  fn visit_module_dependencies(
+    &self,
+    name: &str,
+    visited: &mut HashSet<String>,
+    result: &mut Vec<ErlModule>,
+  ) {
+    if !visited.insert(name.to_string()) {
+      return;
+    }
+    let module = match self.modules.get(&name.to_string()) {
+      Some(m) => m,
+      None => return, // not a module of this project
+    };
+    for dependency in module.called_modules().iter() {
+      self.visit_module_dependencies(dependency, visited, result);
+    }
+    result.push(module);
+  }
+
   /// Retrieve a source file from the file cache, load if necessary
   pub fn get_source_file(&self, path: &Path) -> IcResult<SourceFile> {
     self
@@ -2,13 +2,16 @@
 use crate::error::ic_error::{IcResult, IroncladError};
 use crate::project::project_impl::ErlProjectImpl;
 use crate::source_loc::SourceLoc;
-use std::path::{Path, PathBuf};
+use std::cmp::Ordering;
+use std::path::{Component, Path, PathBuf};
+
+/// Environment variable with extra library directories, separated like `PATH`
+const ERL_LIBS: &str = "ERL_LIBS";
 
 impl ErlProjectImpl {
   fn find_include_in(sample: &Path, try_dirs: &[String]) -> Option<PathBuf> {
     for dir in try_dirs {
       let try_path = Path::new(&dir).join(sample);
-      println!("Trying path: {}", try_path.to_string_lossy());
       if try_path.exists() {
         return Some(try_path);
       }
@@ -28,56 +31,183 @@ impl ErlProjectImpl {
   /// If os:getenv(VAR) returns false, $VAR is left as is.
   ///
   /// If the filename File is absolute (possibly after variable substitution), the include file
-  /// with that name is included. Otherwise, the specified file is searched for in the following
-  /// directories, and in this order:
+  /// with that name is included. Otherwise, the specified file is searched for in the directories
+  /// returned by `include_search_dirs`.
   ///
-  /// * The current working directory
-  /// * The directory where the module is being compiled
-  /// * The directories given by the include option
+  /// `includers` are the file containing the directive, then the file which included it, and so
+  /// on up to the module file, which is the last.
   pub(crate) fn find_include(
     &self,
     location: SourceLoc,
     find_file: &Path,
-    from_file: Option<PathBuf>,
+    includers: &[PathBuf],
   ) -> IcResult<PathBuf> {
+    let find_file = Self::expand_path_var(find_file);
     if find_file.is_absolute() {
-      return Ok(find_file.to_path_buf());
+      if find_file.exists() {
+        return Ok(find_file);
+      }
+    } else if let Some(try_loc) =
+      Self::find_include_in(&find_file, &self.include_search_dirs(includers))
+    {
+      return Ok(try_loc);
     }
-    // TODO: Support $VAR components in paths
+    IroncladError::file_not_found(location, &find_file, "searching for an -include() path")
+  }
 
-    // Try current working directory
-    if let Some(try_loc) = Self::find_include_in(find_file, &[".".to_string()]) {
-      return Ok(try_loc);
+  /// Substitute `$VAR` in the first path component with the value of the environment variable
+  /// `VAR`. If the variable is not set, the path is left as is.
+  fn expand_path_var(path: &Path) -> PathBuf {
+    let mut components = path.components();
+    if let Some(Component::Normal(first)) = components.next() {
+      if let Some(var_name) = first.to_str().and_then(|s| s.strip_prefix('$')) {
+        if let Some(value) = std::env::var_os(var_name) {
+          return PathBuf::from(value).join(components.as_path());
+        }
+      }
     }
+    path.to_path_buf()
+  }
 
-    // Try current compiled file directory
-    if let Some(current_source) = &from_file {
-      let current_source_dir = current_source
-        .parent()
-        .unwrap()
-        .to_string_lossy()
-        .to_string();
-      if let Some(try_loc) = Self::find_include_in(find_file, &[current_source_dir]) {
-        return Ok(try_loc);
+  /// Directories to search for a relative include path, in the order used by `erlc`:
+  ///
+  /// * The directory of the file containing the directive. Directories of the files which
+  ///   included that file are not searched.
+  /// * The current working directory
+  /// * The directory of the module source file
+  /// * The directories given by the include option for the module file, then for the project
+  fn include_search_dirs(&self, includers: &[PathBuf]) -> Vec<String> {
+    let dir_of = |file: &PathBuf| match file.parent() {
+      Some(dir) if !dir.as_os_str().is_empty() => dir.to_string_lossy().to_string(),
+      _ => ".".to_string(),
+    };
+    let mut result: Vec<String> = includers.first().map(dir_of).into_iter().collect();
+    result.push(".".to_string());
+    if let Some(module_file) = includers.last() {
+      result.push(dir_of(module_file));
+      if let Some(opts_per_file) = self.project_inputs.compiler_opts_per_file.get(module_file) {
+        result.extend(opts_per_file.include_paths.iter().cloned());
       }
     }
+    result.extend(
+      self
+        .project_inputs
+        .compiler_opts
+        .include_paths
+        .iter()
+        .cloned(),
+    );
 
-    // Try find in local search paths for file
-    if let Some(from_file1) = &from_file {
-      if let Some(opts_per_file) = self.project_inputs.compiler_opts_per_file.get(from_file1) {
-        if let Some(try_loc) = Self::find_include_in(find_file, &opts_per_file.include_paths) {
-          return Ok(try_loc);
+    let mut unique = Vec::with_capacity(result.len());
+    for dir in result {
+      if !unique.contains(&dir) {
+        unique.push(dir);
+      }
+    }
+    unique
+  }
+
+  /// Check include paths to find the file for `-include_lib(File)`.
+  /// First the file is searched like for `-include`. If it is not found, the first path component
+  /// (possibly after variable substitution) is assumed to be the name of an application, and the rest of the path is searched in the
+  /// directory of that application.
+  ///
+  /// Application directories are looked up in the library directories: the project `deps`, then
+  /// the directories listed in `ERL_LIBS`, then `<otp_root>/lib`. A library directory can contain
+  /// the application as `app` or as a versioned `app-1.2.3`, the highest version is used.
+  pub(crate) fn find_include_lib(
+    &self,
+    location: SourceLoc,
+    find_file: &Path,
+    includers: &[PathBuf],
+  ) -> IcResult<PathBuf> {
+    let find_file = Self::expand_path_var(find_file);
+    if let Ok(found) = self.find_include(location.clone(), &find_file, includers) {
+      return Ok(found);
+    }
+
+    let mut components = find_file.components();
+    if let Some(Component::Normal(app_name)) = components.next() {
+      let app_name = app_name.to_string_lossy();
+      let in_app_path = components.as_path();
+      for lib_dir in self.library_dirs(includers.last().map(PathBuf::as_path)) {
+        if let Some(app_dir) = Self::find_app_dir(&lib_dir, &app_name) {
+          let try_path = app_dir.join(in_app_path);
+          if try_path.exists() {
+            return Ok(try_path);
+          }
         }
       }
     }
+    IroncladError::file_not_found(location, &find_file, "searching for an -include_lib() path")
+  }
 
-    // Try find in global search paths for project
-    if let Some(try_glob) =
-      Self::find_include_in(find_file, &self.project_inputs.compiler_opts.include_paths)
-    {
-      Ok(try_glob)
-    } else {
-      IroncladError::file_not_found(location, find_file, "searching for an -include() path")
+  /// Directories which contain applications, in the search order: project `deps`, `ERL_LIBS`,
+  /// and `lib` in the OTP root
+  fn library_dirs(&self, from_file: Option<&Path>) -> Vec<PathBuf> {
+    let opts = match from_file {
+      Some(path) => self.get_compiler_options_for(path),
+      None => self.project_inputs.compiler_opts.clone(),
+    };
+    let mut result: Vec<PathBuf> = opts.deps.iter().map(PathBuf::from).collect();
+    if let Some(erl_libs) = std::env::var_os(ERL_LIBS) {
+      result.extend(std::env::split_paths(&erl_libs).filter(|p| !p.as_os_str().is_empty()));
+    }
+    if let Some(otp_root) = &opts.otp_root {
+      result.push(Path::new(otp_root).join("lib"));
+    }
+    result
+  }
+
+  /// Find the directory of the application in the library directory. It is named either `app`
+  /// or `app-VERSION`. If there are several, the highest version is chosen, and the directory
+  /// without a version is the lowest.
+  fn find_app_dir(lib_dir: &Path, app_name: &str) -> Option<PathBuf> {
+    let entries = std::fs::read_dir(lib_dir).ok()?;
+    let mut best: Option<(Vec<String>, PathBuf)> = None;
+    for entry in entries.flatten() {
+      let path = entry.path();
+      if !path.is_dir() {
+        continue;
+      }
+      let dir_name = entry.file_name().to_string_lossy().to_string();
+      let version = if dir_name == app_name {
+        Vec::new()
+      } else {
+        match dir_name
+          .strip_prefix(app_name)
+          .and_then(|rest| rest.strip_prefix('-'))
+        {
+          Some(v) if v.starts_with(|c: char| c.is_ascii_digit()) => {
+            v.split(['.', '-']).map(str::to_string).collect()
+          }
+          _ => continue,
+        }
+      };
+      let is_better = match &best {
+        Some((best_version, _)) => {
+          Self::compare_versions(&version, best_version) == Ordering::Greater
+        }
+        None => true,
+      };
+      if is_better {
+        best = Some((version, path));
+      }
+    }
+    best.map(|(_, path)| path)
+  }
+
+  /// Compare application versions part by part, numeric parts are compared as numbers
+  fn compare_versions(a: &[String], b: &[String]) -> Ordering {
+    for (a_part, b_part) in a.iter().zip(b.iter()) {
+      let ordering = match (a_part.parse::<u64>(), b_part.parse::<u64>()) {
+        (Ok(a_num), Ok(b_num)) => a_num.cmp(&b_num),
+        _ => a_part.cmp(b_part),
+      };
+      if ordering != Ordering::Equal {
+        return ordering;
+      }
     }
+    a.len().cmp(&b.len())
   }
 }
@@ -0,0 +1,499 @@
+//! Cross-reference analysis of the whole project, like OTP `xref`: calls to undefined functions,
+//! and functions, types, records and imports which are never used.
+//! The report is printed one finding per line, with tab separated fields, for scripts and CI.
+
+use crate::erl_syntax::erl_ast::ast_iter::IterableAstNodeT;
+use crate::erl_syntax::erl_ast::node_impl::AstNodeType;
+use crate::erl_syntax::erl_ast::AstNode;
+use crate::project::module::module_impl::ErlModule;
+use crate::project::project_impl::ErlProjectImpl;
+use crate::source_loc::SourceLoc;
+use crate::typing::bif::{BifTable, ERLANG_MODULE};
+use crate::typing::erl_type::{ErlType, ErlTypeImpl};
+use libironclad_util::mfarity::MFArity;
+use std::collections::HashSet;
+use std::fmt::{Display, Formatter};
+use std::ops::Deref;
+
+/// Kind of a problem found by the cross-reference analysis
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Ord, PartialOrd)]
+pub enum XrefKind {
+  /// A call to a local function which is not defined, imported or a BIF, or to a remote function
+  /// which its module does not define or export
+  UndefinedCall,
+  /// An exported function, which no module of the project calls
+  UnusedExport,
+  /// A function, which is not exported and never called
+  UnusedFunction,
+  /// A `-type`, which is not exported and never used
+  UnusedType,
+  /// A `-record`, which is never used
+  UnusedRecord,
+  /// An `-import`ed function, which is never called
+  UnusedImport,
+}
+
+impl Display for XrefKind {
+  fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+    match self {
+      XrefKind::UndefinedCall => write!(f, "undefined_call"),
+      XrefKind::UnusedExport => write!(f, "unused_export"),
+      XrefKind::UnusedFunction => write!(f, "unused_function"),
+      XrefKind::UnusedType => write!(f, "unused_type"),
+      XrefKind::UnusedRecord => write!(f, "unused_record"),
+      XrefKind::UnusedImport => write!(f, "unused_import"),
+    }
+  }
+}
+
+/// One problem found by the cross-reference analysis
+#[derive(Debug, Clone)]
+pub struct XrefFinding {
+  /// What is wrong
+  pub kind: XrefKind,
+  /// The module where the problem was found
+  pub module: String,
+  /// The function `mod:fun/arity`, the type `name/arity`, or the record `#tag`
+  pub name: String,
+  /// Where the call or the definition is
+  pub location: SourceLoc,
+}
+
+impl Display for XrefFinding {
+  /// Prints `kind <TAB> module <TAB> name <TAB> file:line:column`, or `-` for unknown location
+  fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+    write!(f, "{}\t{}\t{}\t", self.kind, self.module, self.name)?;
+    match (&self.location, self.location.line_col()) {
+      (SourceLoc::Span { file, .. }, Some(((line, col), _))) => {
+        write!(f, "{}:{}:{}", file.file_name.to_string_lossy(), line, col)
+      }
+      _ => write!(f, "-"),
+    }
+  }
+}
+
+/// Result of the cross-reference analysis of a project
+#[derive(Debug, Default)]
+pub struct XrefReport {
+  /// Problems found, ordered by module, kind and name
+  pub findings: Vec<XrefFinding>,
+}
+
+impl Display for XrefReport {
+  fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+    for finding in self.findings.iter() {
+      writeln!(f, "{}", finding)?;
+    }
+    Ok(())
+  }
+}
+
+impl XrefReport {
+  /// Whether no problems were found
+  pub fn is_empty(&self) -> bool {
+    self.findings.is_empty()
+  }
+
+  /// Names of the findings of one kind, for example `mod:fun/1`
+  pub fn names_of_kind(&self, kind: XrefKind) -> Vec<String> {
+    self
+      .findings
+      .iter()
+      .filter(|finding| finding.kind == kind)
+      .map(|finding| finding.name.clone())
+      .collect()
+  }
+}
+
+/// A call or a function reference found in the code
+struct XrefCall {
+  /// The function which contains the call, `None` for record field initializers
+  caller: Option<MFArity>,
+  /// The called function as written, module is `None` for local calls
+  callee: MFArity,
+  /// Where the call is
+  location: SourceLoc,
+}
+
+/// Everything a module refers to
+#[derive(Default)]
+struct XrefUses {
+  /// Calls and function references
+  calls: Vec<XrefCall>,
+  /// Types referred to, with the module name
+  types: HashSet<MFArity>,
+  /// Record tags used in this module
+  records: HashSet<String>,
+}
+
+impl XrefUses {
+  /// Collect the uses in the functions, specs, types and records of a module
+  fn collect(module: &ErlModule) -> Self {
+    let mut uses = Self::default();
+    let module_name = module.get_name();
+    let root_scope = &module.root_scope;
+
+    if let Ok(r_fn_defs) = root_scope.fn_defs.collection.read() {
+      for (fn_name, ast) in r_fn_defs.iter() {
+        uses.visit_ast(&module_name, &Some(fn_name.clone()), ast);
+      }
+    } else {
+      panic!("Can't lock module functions for reading")
+    }
+    if let Ok(r_specs) = root_scope.fn_specs.collection.read() {
+      for spec in r_specs.values() {
+        uses.visit_type(&module_name, spec);
+      }
+    } else {
+      panic!("Can't lock module specs for reading")
+    }
+    if let Ok(r_types) = root_scope.user_types.collection.read() {
+      for (type_name, user_type) in r_types.iter() {
+        let mut type_uses = Self::default();
+        type_uses.visit_type(&module_name, &user_type.ty);
+        // A recursive type does not use itself
+        type_uses
+          .types
+          .remove(&type_name.clone_with_module(&module_name));
+        uses.types.extend(type_uses.types);
+        uses.records.extend(type_uses.records);
+      }
+    } else {
+      panic!("Can't lock module types for reading")
+    }
+    if let Ok(r_records) = root_scope.record_defs.collection.read() {
+      for record_def in r_records.values() {
+        for field in record_def.fields.iter() {
+          if let Some(init) = &field.initializer {
+            uses.visit_ast(&module_name, &None, init);
+          }
+          if let Some(ty) = &field.type_ascription {
+            uses.visit_type(&module_name, ty);
+          }
+        }
+      }
+    } else {
+      panic!("Can't lock module records for reading")
+    }
+    uses
+  }
+
+  /// Recursive descend into AST collecting calls, records and types
+  fn visit_ast(&mut self, module_name: &str, caller: &Option<MFArity>, ast: &AstNode) {
+    let mut add_call = |callee: MFArity| {
+      self.calls.push(XrefCall {
+        caller: caller.clone(),
+        callee,
+        location: ast.location.clone(),
+      })
+    };
+    match &ast.content {
+      AstNodeType::Apply(app) => {
+        if let Some(callee) = app.target.as_mfarity(app.args.len()) {
+          add_call(callee)
+        }
+      }
+      AstNodeType::FnRef { mfa } | AstNodeType::MFA { mfarity: mfa } => add_call(mfa.clone()),
+      AstNodeType::RecordBuilder { tag, .. } | AstNodeType::RecordField { tag, .. } => {
+        self.records.insert(tag.clone());
+      }
+      AstNodeType::Type { ty } => self.visit_type(module_name, ty),
+      _ => {}
+    }
+
+    if let Some(children) = ast.children() {
+      for c in children {
+        self.visit_ast(module_name, caller, &c)
+      }
+    }
+  }
+
+  /// Recursive descend into a type collecting user types and records
+  fn visit_type(&mut self, module_name: &str, ty: &ErlType) {
+    match ty.deref() {
+      ErlTypeImpl::UserDefinedType { name, args } => {
+        let qualified = match &name.module {
+          Some(_) => name.clone(),
+          None => name.clone_with_module(module_name),
+        };
+        self.types.insert(qualified);
+        for arg in args.iter() {
+          self.visit_type(module_name, &arg.ty);
+        }
+      }
+      ErlTypeImpl::RecordRef { tag, pins } => {
+        self.records.insert(tag.clone());
+        for pin in pins.iter() {
+          self.visit_type(module_name, &pin.ty);
+        }
+      }
+      ErlTypeImpl::Record { fields, .. } => {
+        for field in fields.iter() {
+          self.visit_type(module_name, &field.ty);
+        }
+      }
+      ErlTypeImpl::Tuple { elements } | ErlTypeImpl::StronglyTypedList { elements, tail: None } => {
+        for el in elements.iter() {
+          self.visit_type(module_name, el);
+        }
+      }
+      ErlTypeImpl::StronglyTypedList { elements, tail: Some(tail) } => {
+        for el in elements.iter() {
+          self.visit_type(module_name, el);
+        }
+        self.visit_type(module_name, tail);
+      }
+      ErlTypeImpl::List { elements, tail, .. } => {
+        self.visit_type(module_name, elements);
+        if let Some(t) = tail {
+          self.visit_type(module_name, t);
+        }
+      }
+      ErlTypeImpl::Map { members } => {
+        for m in members.iter() {
+          self.visit_type(module_name, &m.key);
+          self.visit_type(module_name, &m.value);
+        }
+      }
+      ErlTypeImpl::Fn(fn_type) => {
+        for clause in fn_type.clauses().iter() {
+          for arg in clause.args.iter() {
+            self.visit_type(module_name, &arg.ty);
+          }
+          self.visit_type(module_name, &clause.ret_type.ty);
+        }
+      }
+      ErlTypeImpl::Union(u) => {
+        for member in u.types.iter() {
+          self.visit_type(module_name, member);
+        }
+      }
+      ErlTypeImpl::Typevar(tv) => self.visit_type(module_name, &tv.ty),
+      _ => {}
+    }
+  }
+}
+
+impl ErlProjectImpl {
+  /// Run the cross-reference analysis on all loaded modules. Calls to modules which are not a part
+  /// of the project are not checked. Types and records which come from included files are not
+  /// reported as unused, because other modules including the same file may use them.
+  pub fn xref(&self) -> XrefReport {
+    let modules = self.modules_in_dependency_order();
+    let module_uses: Vec<XrefUses> = modules.iter().map(XrefUses::collect).collect();
+
+    let mut findings = Vec::new();
+    // Functions called from another function, or from another module, with the module name
+    let mut called: HashSet<MFArity> = HashSet::new();
+    let mut used_types: HashSet<MFArity> = HashSet::new();
+
+    for (module, uses) in modules.iter().zip(module_uses.iter()) {
+      let module_name = module.get_name();
+      let mut used_imports = HashSet::new();
+
+      for call in uses.calls.iter() {
+        let target = match self.xref_resolve_call(module, &call.callee, &mut used_imports) {
+          Some(target) => target,
+          None => {
+            findings.push(XrefFinding {
+              kind: XrefKind::UndefinedCall,
+              module: module_name.clone(),
+              name: call.callee.to_string(),
+              location: call.location.clone(),
+            });
+            continue;
+          }
+        };
+        let is_self_call = call.caller.as_ref().is_some_and(|caller| {
+          target.module.as_ref() == Some(&module_name)
+            && caller.name == target.name
+            && caller.arity == target.arity
+        });
+        if !is_self_call {
+          called.insert(target);
+        }
+      }
+      used_types.extend(uses.types.iter().cloned());
+
+      Self::xref_unused_imports(module, &used_imports, &mut findings);
+      Self::xref_unused_records(module, &uses.records, &mut findings);
+    }
+
+    for module in modules.iter() {
+      Self::xref_unused_functions(module, &called, &mut findings);
+      Self::xref_unused_types(module, &used_types, &mut findings);
+    }
+
+    findings.sort_by(|a, b| (&a.module, a.kind, &a.name).cmp(&(&b.module, b.kind, &b.name)));
+    XrefReport { findings }
+  }
+
+  /// Find which function a call refers to, with the module name. Returns `None` if the function
+  /// is not defined, or is a remote function which is not exported. Calls to modules which are not
+  /// loaded are always accepted.
+  fn xref_resolve_call(
+    &self,
+    module: &ErlModule,
+    callee: &MFArity,
+    used_imports: &mut HashSet<MFArity>,
+  ) -> Option<MFArity> {
+    let module_name = module.get_name();
+    let local_name = MFArity::new_local(&callee.name, callee.arity);
+    let remote_name = match &callee.module {
+      Some(m) if *m != module_name => callee.clone(),
+      _ => {
+        if module.root_scope.fn_defs.contains(&local_name) {
+          return Some(local_name.clone_with_module(&module_name));
+        }
+        match module.find_import(&local_name) {
+          Some(imported) if callee.module.is_none() => {
+            used_imports.insert(imported.clone());
+            imported
+          }
+          _ if callee.module.is_none() && BifTable::erlang().is_bif(&local_name) => {
+            return Some(local_name.clone_with_module(ERLANG_MODULE));
+          }
+          _ => return None,
+        }
+      }
+    };
+
+    let other_name = remote_name.module.clone().unwrap_or_default();
+    match self.modules.get(&other_name) {
+      Some(other_module) => {
+        let other_local = MFArity::new_local(&remote_name.name, remote_name.arity);
+        let is_defined = other_module.root_scope.fn_defs.contains(&other_local)
+          && other_module.is_exported(&other_local);
+        is_defined.then_some(remote_name)
+      }
+      None => Some(remote_name),
+    }
+  }
+
+  /// Whether the definition is in the module file, and not in an included file
+  fn xref_is_in_module_file(module: &ErlModule, location: &SourceLoc) -> bool {
+    match location {
+      SourceLoc::Span { file, .. } => file.file_name == module.source_file.file_name,
+      SourceLoc::None => true,
+    }
+  }
+
+  /// Report exported functions never called by other modules, and local functions never called
+  fn xref_unused_functions(
+    module: &ErlModule,
+    called: &HashSet<MFArity>,
+    findings: &mut Vec<XrefFinding>,
+  ) {
+    let module_name = module.get_name();
+    if let Ok(r_fn_defs) = module.root_scope.fn_defs.collection.read() {
+      for (fn_name, ast) in r_fn_defs.iter() {
+        let qualified = fn_name.clone_with_module(&module_name);
+        if called.contains(&qualified) {
+          continue;
+        }
+        let kind = if module.root_scope.exports.contains(fn_name) {
+          XrefKind::UnusedExport
+        } else if module.is_exported(fn_name) {
+          continue; // exported with `export_all`
+        } else {
+          XrefKind::UnusedFunction
+        };
+        findings.push(XrefFinding {
+          kind,
+          module: module_name.clone(),
+          name: qualified.to_string(),
+          location: ast.location.clone(),
+        });
+      }
+    } else {
+      panic!("Can't lock module functions for reading")
+    }
+  }
+
+  /// Report types which are not exported and not used anywhere
+  fn xref_unused_types(
+    module: &ErlModule,
+    used_types: &HashSet<MFArity>,
+    findings: &mut Vec<XrefFinding>,
+  ) {
+    let module_name = module.get_name();
+    if let Ok(r_types) = module.root_scope.user_types.collection.read() {
+      for type_name in r_types.keys() {
+        let location = module
+          .root_scope
+          .user_type_locations
+          .get(type_name)
+          .unwrap_or(SourceLoc::None);
+        if used_types.contains(&type_name.clone_with_module(&module_name))
+          || module.root_scope.exported_types.contains(type_name)
+          || !Self::xref_is_in_module_file(module, &location)
+        {
+          continue;
+        }
+        findings.push(XrefFinding {
+          kind: XrefKind::UnusedType,
+          module: module_name.clone(),
+          name: type_name.to_string(),
+          location,
+        });
+      }
+    } else {
+      panic!("Can't lock module types for reading")
+    }
+  }
+
+  /// Report records never used in the module
+  fn xref_unused_records(
+    module: &ErlModule,
+    used_records: &HashSet<String>,
+    findings: &mut Vec<XrefFinding>,
+  ) {
+    if let Ok(r_records) = module.root_scope.record_defs.collection.read() {
+      for tag in r_records.keys() {
+        let location = module
+          .root_scope
+          .record_def_locations
+          .get(tag)
+          .unwrap_or(SourceLoc::None);
+        if used_records.contains(tag) || !Self::xref_is_in_module_file(module, &location) {
+          continue;
+        }
+        findings.push(XrefFinding {
+          kind: XrefKind::UnusedRecord,
+          module: module.get_name(),
+          name: format!("#{}", tag),
+          location,
+        });
+      }
+    } else {
+      panic!("Can't lock module records for reading")
+    }
+  }
+
+  /// Report imported functions never called in the module
+  fn xref_unused_imports(
+    module: &ErlModule,
+    used_imports: &HashSet<MFArity>,
+    findings: &mut Vec<XrefFinding>,
+  ) {
+    if let Ok(r_imports) = module.root_scope.imports.collection.read() {
+      for imported in r_imports
+        .iter()
+        .filter(|imported| !used_imports.contains(imported))
+      {
+        findings.push(XrefFinding {
+          kind: XrefKind::UnusedImport,
+          module: module.get_name(),
+          name: imported.to_string(),
+          location: module
+            .root_scope
+            .import_locations
+            .get(imported)
+            .unwrap_or(SourceLoc::None),
+        });
+      }
+    } else {
+      panic!("Can't lock module imports for reading")
+    }
+  }
+}
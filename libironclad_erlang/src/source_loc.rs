@@ -1,16 +1,23 @@
 //! Source file locations for printing and reporting to the user
 use crate::erl_syntax::parsers::parser_input::ParserInput;
+use crate::erl_syntax::parsers::token_stream::token::Token;
+use crate::project::module::module_impl::ErlModule;
+use libironclad_util::source_file::SourceFile;
 use std::fmt::Formatter;
 
-/// Source code span with start and end
-#[derive(Clone, Debug)]
+/// Source code span with start and end. Offsets are stored as `u32` to keep `AstNodeImpl` small.
+#[derive(Clone)]
 pub enum SourceLoc {
   /// We do not know the location, or do not care
   None,
-  /// Offset in the input string
-  Offset {
-    /// Start of the input
-    start: *const u8,
+  /// A byte range in a source file
+  Span {
+    /// The file containing the span (a module or an included file)
+    file: SourceFile,
+    /// Byte offset of the first character
+    start: u32,
+    /// Byte offset after the last character
+    end: u32,
   },
 }
 
@@ -22,9 +29,75 @@ impl SourceLoc {
     SourceLoc::None
   }
 
-  /// Create an absolute pointer from an input position. Use this to determine source location later.
+  /// Location of the first token in the input. Use this to point at where a parser has started.
   pub(crate) fn new(input: &ParserInput) -> Self {
-    Self::Offset { start: input.tokens.iter().next().unwrap().offset }
+    match input.tokens.iter().find(|t| !t.is_eol()) {
+      Some(tok) => Self::from_tokens(&input.module, tok, tok),
+      None => SourceLoc::None,
+    }
+  }
+
+  /// Location spanning all tokens in the input, use with the output of `nom::combinator::consumed`.
+  /// Tokens pasted from a macro body point into the macro definition, which is earlier in the file
+  /// or in another file, so the span ends at the last token following the first one in its file.
+  pub(crate) fn new_span(consumed_input: &ParserInput) -> Self {
+    let from_source = |t: &&Token| !t.offset.is_null() && !t.is_eol();
+    let first = match consumed_input.tokens.iter().find(from_source) {
+      Some(first) => first,
+      None => return SourceLoc::None,
+    };
+    let first_file = consumed_input.module.find_source_file(first.offset);
+    let follows_first = |t: &&Token| {
+      t.offset >= first.offset
+        && first_file
+          .as_ref()
+          .is_some_and(|file| file.offset_of(t.offset).is_some())
+    };
+    let last = consumed_input
+      .tokens
+      .iter()
+      .rev()
+      .filter(from_source)
+      .find(follows_first)
+      .unwrap_or(first);
+    Self::from_tokens(&consumed_input.module, first, last)
+  }
+
+  /// Build a span from the start of `first` to the end of `last`. If the tokens come from different
+  /// files (i.e. a macro was pasted from an include), the span covers only the `first` token.
+  fn from_tokens(module: &ErlModule, first: &Token, last: &Token) -> Self {
+    let file = match module.find_source_file(first.offset) {
+      Some(f) => f,
+      None => return SourceLoc::None,
+    };
+    let start = file.offset_of(first.offset).unwrap_or_default();
+    let token_end = |tok: &Token| {
+      if tok.end.is_null() {
+        file.offset_of(tok.offset)
+      } else {
+        file.offset_of(tok.end)
+      }
+    };
+    let end = token_end(last)
+      .or_else(|| token_end(first))
+      .unwrap_or(start)
+      .max(start);
+    SourceLoc::Span { file, start: start as u32, end: end as u32 }
+  }
+
+  /// Return 1-based line and column for the span start and end
+  pub fn line_col(&self) -> Option<((usize, usize), (usize, usize))> {
+    match self {
+      SourceLoc::None => None,
+      SourceLoc::Span { file, start, end } => {
+        Some((file.line_col(*start as usize), file.line_col(*end as usize)))
+      }
+    }
+  }
+
+  /// Check whether location is known
+  pub fn is_none(&self) -> bool {
+    matches!(self, SourceLoc::None)
   }
 }
 
@@ -32,7 +105,25 @@ impl std::fmt::Display for SourceLoc {
   fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
     match self {
       SourceLoc::None => write!(f, "<No info>"),
-      SourceLoc::Offset { start } => write!(f, "SourceLoc[{:x}]", *start as usize),
+      SourceLoc::Span { file, start, end } => {
+        let (start_line, start_col) = file.line_col(*start as usize);
+        let (end_line, end_col) = file.line_col(*end as usize);
+        write!(
+          f,
+          "{}:{}:{}-{}:{}",
+          file.file_name.to_string_lossy(),
+          start_line,
+          start_col,
+          end_line,
+          end_col
+        )
+      }
     }
   }
 }
+
+impl std::fmt::Debug for SourceLoc {
+  fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+    write!(f, "SourceLoc[{}]", self)
+  }
+}
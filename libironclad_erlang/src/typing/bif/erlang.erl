@@ -0,0 +1,150 @@
+%% Types of the built-in functions of the `erlang` module. They are used for the calls to
+%% `erlang:Name(...)` and to the auto-imported functions like `length(L)`. The specs follow OTP
+%% sources, simplified where the type checker can not use the details.
+-module(erlang).
+-vsn("OTP-26").
+
+%% Functions which can be called without the `erlang:` prefix. Other functions in the table are
+%% only known as `erlang:Name(...)`.
+-auto_import([
+  abs/1, apply/2, apply/3, atom_to_binary/1, atom_to_binary/2, atom_to_list/1, binary_part/2,
+  binary_part/3, binary_to_atom/1, binary_to_atom/2, binary_to_existing_atom/1, binary_to_float/1,
+  binary_to_integer/1, binary_to_list/1, binary_to_term/1, bit_size/1, byte_size/1, ceil/1,
+  element/2, erase/1, error/1, error/2, exit/1, float/1, float_to_binary/1, float_to_list/1,
+  floor/1, get/1, halt/0, halt/1, hd/1, integer_to_binary/1, integer_to_list/1, integer_to_list/2,
+  iolist_size/1, iolist_to_binary/1, is_atom/1, is_binary/1, is_bitstring/1, is_boolean/1,
+  is_float/1, is_function/1, is_function/2, is_integer/1, is_list/1, is_map/1, is_map_key/2,
+  is_number/1, is_pid/1, is_port/1, is_process_alive/1, is_record/2, is_record/3, is_reference/1,
+  is_tuple/1, length/1, link/1, list_to_atom/1, list_to_binary/1, list_to_existing_atom/1,
+  list_to_float/1, list_to_integer/1, list_to_pid/1, list_to_tuple/1, make_ref/0, map_get/2,
+  map_size/1, max/2, min/2, monitor/2, node/0, node/1, process_flag/2, put/2, register/2,
+  registered/0, round/1, self/0, setelement/3, size/1, spawn/1, spawn/2, spawn/3, spawn/4,
+  spawn_link/1, spawn_link/3, term_to_binary/1, throw/1, tl/1, trunc/1, tuple_size/1,
+  tuple_to_list/1, unlink/1, whereis/1
+]).
+
+%% Functions which are allowed in guard expressions
+-guard_bifs([
+  abs/1, binary_part/2, binary_part/3, bit_size/1, byte_size/1, ceil/1, element/2, float/1,
+  floor/1, hd/1, is_map_key/2, length/1, map_get/2, map_size/1, max/2, min/2, node/0, node/1,
+  round/1, self/0, size/1, tl/1, trunc/1, tuple_size/1,
+  is_atom/1, is_binary/1, is_bitstring/1, is_boolean/1, is_float/1, is_function/1,
+  is_function/2, is_integer/1, is_list/1, is_map/1, is_number/1, is_pid/1, is_port/1,
+  is_record/2, is_record/3, is_reference/1, is_tuple/1
+]).
+
+%% Numbers
+-spec abs(integer()) -> non_neg_integer(); (float()) -> float().
+-spec ceil(number()) -> integer().
+-spec float(number()) -> float().
+-spec floor(number()) -> integer().
+-spec round(number()) -> integer().
+-spec trunc(number()) -> integer().
+-spec max(term(), term()) -> term().
+-spec min(term(), term()) -> term().
+-spec phash2(term()) -> non_neg_integer().
+-spec phash2(term(), pos_integer()) -> non_neg_integer().
+
+%% Type tests
+-spec is_atom(term()) -> boolean().
+-spec is_binary(term()) -> boolean().
+-spec is_bitstring(term()) -> boolean().
+-spec is_boolean(term()) -> boolean().
+-spec is_float(term()) -> boolean().
+-spec is_function(term()) -> boolean().
+-spec is_function(term(), arity()) -> boolean().
+-spec is_integer(term()) -> boolean().
+-spec is_list(term()) -> boolean().
+-spec is_map(term()) -> boolean().
+-spec is_number(term()) -> boolean().
+-spec is_pid(term()) -> boolean().
+-spec is_port(term()) -> boolean().
+-spec is_record(term(), atom()) -> boolean().
+-spec is_record(term(), atom(), non_neg_integer()) -> boolean().
+-spec is_reference(term()) -> boolean().
+-spec is_tuple(term()) -> boolean().
+
+%% Lists, tuples and maps
+-spec hd(nonempty_maybe_improper_list()) -> term().
+-spec tl(nonempty_maybe_improper_list()) -> term().
+-spec length(list()) -> non_neg_integer().
+-spec element(pos_integer(), tuple()) -> term().
+-spec setelement(pos_integer(), tuple(), term()) -> tuple().
+-spec tuple_size(tuple()) -> non_neg_integer().
+-spec size(tuple() | binary()) -> non_neg_integer().
+-spec list_to_tuple(list()) -> tuple().
+-spec tuple_to_list(tuple()) -> list().
+-spec is_map_key(term(), map()) -> boolean().
+-spec map_get(term(), map()) -> term().
+-spec map_size(map()) -> non_neg_integer().
+
+%% Binaries
+-spec bit_size(bitstring()) -> non_neg_integer().
+-spec byte_size(bitstring()) -> non_neg_integer().
+-spec binary_part(binary(), {non_neg_integer(), integer()}) -> binary().
+-spec binary_part(binary(), non_neg_integer(), integer()) -> binary().
+-spec iolist_size(iodata()) -> non_neg_integer().
+-spec iolist_to_binary(iodata()) -> binary().
+-spec term_to_binary(term()) -> binary().
+-spec binary_to_term(binary()) -> term().
+
+%% Conversions
+-spec atom_to_binary(atom()) -> binary().
+-spec atom_to_binary(atom(), latin1 | unicode | utf8) -> binary().
+-spec atom_to_list(atom()) -> string().
+-spec binary_to_atom(binary()) -> atom().
+-spec binary_to_atom(binary(), latin1 | unicode | utf8) -> atom().
+-spec binary_to_existing_atom(binary()) -> atom().
+-spec binary_to_float(binary()) -> float().
+-spec binary_to_integer(binary()) -> integer().
+-spec binary_to_list(binary()) -> list(byte()).
+-spec float_to_binary(float()) -> binary().
+-spec float_to_list(float()) -> string().
+-spec integer_to_binary(integer()) -> binary().
+-spec integer_to_list(integer()) -> string().
+-spec integer_to_list(integer(), 2..36) -> string().
+-spec list_to_atom(string()) -> atom().
+-spec list_to_binary(iolist()) -> binary().
+-spec list_to_existing_atom(string()) -> atom().
+-spec list_to_float(string()) -> float().
+-spec list_to_integer(string()) -> integer().
+-spec list_to_pid(string()) -> pid().
+
+%% Processes
+-spec self() -> pid().
+-spec node() -> node().
+-spec node(pid() | port() | reference()) -> node().
+-spec spawn(function()) -> pid().
+-spec spawn(node(), function()) -> pid().
+-spec spawn(module(), atom(), list()) -> pid().
+-spec spawn(node(), module(), atom(), list()) -> pid().
+-spec spawn_link(function()) -> pid().
+-spec spawn_link(module(), atom(), list()) -> pid().
+-spec send(pid() | port() | atom() | {atom(), node()}, term()) -> term().
+-spec link(pid() | port()) -> true.
+-spec unlink(pid() | port()) -> true.
+-spec monitor(process, pid() | atom()) -> reference().
+-spec make_ref() -> reference().
+-spec register(atom(), pid() | port()) -> true.
+-spec registered() -> list(atom()).
+-spec whereis(atom()) -> pid() | port() | undefined.
+-spec is_process_alive(pid()) -> boolean().
+-spec process_flag(trap_exit, boolean()) -> boolean().
+-spec get(term()) -> term().
+-spec put(term(), term()) -> term().
+-spec erase(term()) -> term().
+
+%% Control flow
+-spec apply(function(), list()) -> term().
+-spec apply(module(), atom(), list()) -> term().
+-spec error(term()) -> no_return().
+-spec error(term(), list() | none) -> no_return().
+-spec exit(term()) -> no_return().
+-spec throw(term()) -> no_return().
+-spec halt() -> no_return().
+-spec halt(non_neg_integer() | string() | abort) -> no_return().
+
+%% System
+-spec monotonic_time() -> integer().
+-spec system_time() -> integer().
+-spec garbage_collect() -> true.
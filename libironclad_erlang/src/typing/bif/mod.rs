@@ -0,0 +1,166 @@
+//! Types of the built-in functions of the `erlang` module, which of them are auto-imported, and
+//! which are allowed in guards. The table is bundled as Erlang source with `-spec` attributes, and
+//! is parsed on first use.
+
+use crate::erl_syntax::erl_ast::node_impl::AstNodeType;
+use crate::erl_syntax::erl_ast::AstNode;
+use crate::erl_syntax::erl_op::ErlBinaryOp;
+use crate::literal::Literal;
+use crate::project::compiler_opts::CompilerOptsImpl;
+use crate::project::module::module_impl::ErlModuleImpl;
+use crate::project::project_impl::ErlProjectImpl;
+use crate::typing::erl_type::ErlType;
+use lazy_static::lazy_static;
+use libironclad_util::mfarity::MFArity;
+use libironclad_util::source_file::SourceFileImpl;
+use std::collections::{HashMap, HashSet};
+use std::ops::Deref;
+use std::path::PathBuf;
+
+/// Name of the module, which contains the built-in functions
+pub const ERLANG_MODULE: &str = "erlang";
+
+/// Source of the `erlang` BIF table
+const ERLANG_BIFS_SOURCE: &str = include_str!("erlang.erl");
+
+lazy_static! {
+  static ref ERLANG_BIFS: BifTable = BifTable::parse("erlang.erl", ERLANG_BIFS_SOURCE);
+}
+
+/// Specs for the built-in functions of a module
+pub struct BifTable {
+  /// The OTP version the table was written for, from the `-vsn` attribute
+  version: String,
+  /// Function types from the `-spec` attributes, keyed by the local name and arity
+  specs: HashMap<MFArity, ErlType>,
+  /// Functions listed in the `-auto_import` attribute, can be called without the module name
+  auto_imports: HashSet<MFArity>,
+  /// Functions listed in the `-guard_bifs` attribute
+  guard_bifs: HashSet<MFArity>,
+}
+
+impl BifTable {
+  /// Access the table for the `erlang` module
+  pub fn erlang() -> &'static BifTable {
+    &ERLANG_BIFS
+  }
+
+  /// The OTP version which the table describes
+  pub fn version(&self) -> &str {
+    &self.version
+  }
+
+  /// Whether the function name refers to the `erlang` module, either as `erlang:name` or as
+  /// a local name which is auto-imported
+  pub fn is_erlang_fn(&self, mfa: &MFArity) -> bool {
+    match &mfa.module {
+      Some(m) => m == ERLANG_MODULE,
+      None => self.auto_imports.contains(mfa),
+    }
+  }
+
+  /// Find the function type for a call to `erlang:name/arity` or to an auto-imported local
+  /// `name/arity`
+  pub fn find_spec(&self, mfa: &MFArity) -> Option<ErlType> {
+    if !self.is_erlang_fn(mfa) {
+      return None;
+    }
+    self
+      .specs
+      .get(&MFArity::new_local(&mfa.name, mfa.arity))
+      .cloned()
+  }
+
+  /// Whether the table knows the function, either by its spec or as a guard BIF
+  pub fn is_bif(&self, mfa: &MFArity) -> bool {
+    self.find_spec(mfa).is_some() || self.is_guard_bif(mfa)
+  }
+
+  /// Whether the function can be called from a guard expression
+  pub fn is_guard_bif(&self, mfa: &MFArity) -> bool {
+    self.is_erlang_fn(mfa)
+      && self
+        .guard_bifs
+        .contains(&MFArity::new_local(&mfa.name, mfa.arity))
+  }
+
+  /// Parse the bundled table, without printing the preprocessor progress. The table is a part of
+  /// the compiler, so errors in it are fatal.
+  fn parse(filename: &str, source: &str) -> Self {
+    let project = ErlProjectImpl::default().into();
+    let source_file = SourceFileImpl::new(&PathBuf::from(filename), source.to_string());
+    let compiler_opts = CompilerOptsImpl { quiet: true, ..CompilerOptsImpl::default() };
+    let module =
+      ErlModuleImpl::from_module_source(&project, &source_file, Some(compiler_opts.into()))
+        .unwrap_or_else(|e| panic!("Can't parse the BIF table {}: {}", filename, e));
+
+    let specs = if let Ok(r_specs) = module.root_scope.fn_specs.collection.read() {
+      r_specs.clone()
+    } else {
+      panic!("Can't lock BIF table specs for reading")
+    };
+    let version = Self::attribute_exprs(&module, "vsn")
+      .iter()
+      .find_map(|expr| match &expr.content {
+        AstNodeType::Lit { value, .. } => match value.deref() {
+          Literal::String(s) => Some(s.to_string()),
+          _ => None,
+        },
+        _ => None,
+      })
+      .unwrap_or_default();
+    let auto_imports = Self::parse_funarity_attribute(filename, &module, "auto_import");
+    let guard_bifs = Self::parse_funarity_attribute(filename, &module, "guard_bifs");
+    Self { version, specs, auto_imports, guard_bifs }
+  }
+
+  /// Expressions of all module attributes with the tag
+  fn attribute_exprs(module: &ErlModuleImpl, tag: &str) -> Vec<AstNode> {
+    match module.root_scope.attributes.get(&tag.to_string()) {
+      Some(attrs) => (0..attrs.len())
+        .filter_map(|index| attrs.get(index).expr)
+        .collect(),
+      None => Vec::default(),
+    }
+  }
+
+  /// Functions listed in all module attributes with the tag
+  fn parse_funarity_attribute(
+    filename: &str,
+    module: &ErlModuleImpl,
+    tag: &str,
+  ) -> HashSet<MFArity> {
+    Self::attribute_exprs(module, tag)
+      .iter()
+      .flat_map(|expr| Self::parse_funarity_list(filename, tag, expr))
+      .collect()
+  }
+
+  /// A list of `[name/arity, ...]` parses as an expression of atoms divided by integers
+  fn parse_funarity_list(filename: &str, tag: &str, expr: &AstNode) -> Vec<MFArity> {
+    let elements = match &expr.content {
+      AstNodeType::List { elements, .. } => elements,
+      _ => panic!("{}: -{} must be a list, got {}", filename, tag, expr),
+    };
+    elements
+      .iter()
+      .map(|element| match &element.content {
+        AstNodeType::BinaryOp { binop_expr } if binop_expr.operator == ErlBinaryOp::Div => {
+          let name = binop_expr.left.get_atom_text();
+          let arity = match &binop_expr.right.content {
+            AstNodeType::Lit { value, .. } => match value.deref() {
+              Literal::Integer(i) => i.as_usize(),
+              _ => None,
+            },
+            _ => None,
+          };
+          match (name, arity) {
+            (Some(name), Some(arity)) => MFArity::new_local(&name, arity),
+            _ => panic!("{}: expected name/arity, got {}", filename, element),
+          }
+        }
+        _ => panic!("{}: expected name/arity, got {}", filename, element),
+      })
+      .collect()
+  }
+}
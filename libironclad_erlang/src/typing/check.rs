@@ -5,7 +5,7 @@ use crate::erl_syntax::erl_error::ErlError;
 use crate::error::ic_error::IcResult;
 use crate::project::module::module_impl::ErlModule;
 use crate::project::module::scope::scope_impl::Scope;
-use crate::typing::erl_type::ErlTypeImpl;
+use crate::typing::erl_type::{ErlType, ErlTypeImpl};
 use crate::typing::type_error::TypeError;
 
 /// Contains type checking code
@@ -23,12 +23,18 @@ impl TypeCheck {
   ) -> IcResult<bool> {
     let synthesized_ty = ast.synthesize(module, scope)?;
 
+    // Remote opaque types are abstract, other user types are replaced with their definitions
+    let expected_arc: ErlType = expected_ty.clone().into();
+    module.check_opaque_use(&ast.location, &expected_arc, &synthesized_ty)?;
+    let expected_ty = &*module.resolve_user_type(&ast.location, &expected_arc)?;
+    let synthesized_ty = module.resolve_user_type(&ast.location, &synthesized_ty)?;
+
     println!(
       "Checking AST type vs expected type\n\tAst: {}\n\tSynth type: {}\n\tExpected: {}",
       ast, synthesized_ty, expected_ty
     );
 
-    if !synthesized_ty.is_subtype_of(expected_ty) {
+    if !module.is_subtype(&synthesized_ty, expected_ty) {
       let type_err = TypeError::ExpectedType {
         expected_type: format!("{}", expected_ty),
         actual_type: format!("{}", synthesized_ty),
@@ -0,0 +1,497 @@
+//! Checks clauses of functions, `case`, `if`, `receive` and `try ... of` for values which no clause
+//! matches, and for clauses which can never run
+
+use crate::erl_syntax::erl_ast::ast_iter::IterableAstNodeT;
+use crate::erl_syntax::erl_ast::node_impl::{AstNodeImpl, AstNodeType};
+use crate::erl_syntax::erl_ast::AstNode;
+use crate::erl_syntax::erl_error::ErlError;
+use crate::erl_syntax::erl_op::ErlBinaryOp;
+use crate::erl_syntax::node::erl_case_clause::ErlCaseClause;
+use crate::erl_syntax::node::erl_fn_clause::ErlFnClause;
+use crate::erl_syntax::node::erl_fn_def::ErlFnDef;
+use crate::erl_syntax::node::erl_if_clause::ErlIfClause;
+use crate::error::ic_error::IcResult;
+use crate::project::module::module_impl::ErlModule;
+use crate::project::module::scope::scope_impl::{Scope, ScopeImpl};
+use crate::source_loc::SourceLoc;
+use crate::typing::erl_type::{ErlType, ErlTypeImpl};
+use crate::typing::fn_clause_type::FnClauseType;
+use crate::typing::spec_check::SpecCheck;
+use crate::typing::type_error::TypeError;
+use crate::typing::type_narrow::TypeNarrow;
+use std::collections::{HashMap, HashSet};
+use std::fmt::Display;
+use std::ops::Deref;
+
+/// Contains the clause coverage checking pass
+pub struct CoverageCheck {}
+
+/// Values which are not yet matched by the clauses, as the clauses are visited in order
+struct PatternSpace {
+  /// All values which can come to the clauses
+  domain: ErlType,
+  /// Whether the domain is known, otherwise the unmatched values are not reported
+  is_known_domain: bool,
+  /// Values not matched by the clauses visited so far
+  remaining: ErlType,
+  /// Becomes false when a clause matched values which could not be computed exactly (because of
+  /// a complex guard or pattern), then `remaining` is wider than the truly unmatched values.
+  is_exact: bool,
+}
+
+impl PatternSpace {
+  fn new(domain: ErlType, is_known_domain: bool) -> Self {
+    Self {
+      remaining: domain.clone(),
+      domain,
+      is_known_domain,
+      is_exact: true,
+    }
+  }
+
+  /// Check that a clause pattern can match some values, and report if it can't
+  fn check_reachable(
+    &self,
+    module: &ErlModule,
+    location: &SourceLoc,
+    pattern: &dyn Display,
+    ty: &ErlType,
+  ) {
+    let msg = if !self.domain.is_overlapping(ty) {
+      format!("{} can never match {}", pattern, self.domain)
+    } else if !self.remaining.is_overlapping(ty) {
+      format!("{} is shadowed by earlier clauses", pattern)
+    } else {
+      return;
+    };
+    let type_err = TypeError::RedundantClause { msg };
+    module.add_warning(ErlError::new_type_error(location.clone(), type_err));
+  }
+
+  /// Remove the values matched by a clause, `None` if they are not known exactly
+  fn subtract(&mut self, module: &ErlModule, matched: Option<ErlType>) {
+    match matched {
+      Some(ty) => self.remaining = self.remaining.subtract(&ty, module),
+      None => self.is_exact = false,
+    }
+  }
+
+  /// Values surely not matched by any clause, if the domain is known
+  fn unmatched(&self) -> Option<ErlType> {
+    if self.is_known_domain && self.is_exact && !self.remaining.is_none() {
+      Some(self.remaining.clone())
+    } else {
+      None
+    }
+  }
+}
+
+impl CoverageCheck {
+  /// Check clauses of every function in the module. Functions with a `-spec` are also checked for
+  /// values allowed by the spec which no function clause matches. Problems found are added to the
+  /// module warnings. An error while checking one function is added to the module errors, and the
+  /// check continues with the next function.
+  pub fn verify_module(module: &ErlModule) -> IcResult<()> {
+    let mut fn_defs = if let Ok(r_defs) = module.root_scope.fn_defs.collection.read() {
+      r_defs
+        .iter()
+        .map(|(mfa, fn_def)| (mfa.clone(), fn_def.clone()))
+        .collect::<Vec<_>>()
+    } else {
+      panic!("Can't lock module functions for reading")
+    };
+    fn_defs.sort_by_key(|(mfa, _)| mfa.to_string());
+
+    for (mfa, fndef_ast) in fn_defs.iter() {
+      let spec = module.root_scope.fn_specs.get(mfa);
+      if let Err(err) = Self::verify_fn_def(module, fndef_ast.as_fn_def(), spec.as_ref()) {
+        module.add_ic_error(err);
+      }
+    }
+    Ok(())
+  }
+
+  /// Union of the spec clause arguments, as tuples of all arguments
+  fn spec_domain(
+    module: &ErlModule,
+    fndef: &ErlFnDef,
+    spec_clauses: &[FnClauseType],
+  ) -> IcResult<ErlType> {
+    let mut spec_tuples = Vec::with_capacity(spec_clauses.len());
+    for spec_clause in spec_clauses.iter() {
+      let mut args = Vec::with_capacity(fndef.funarity.arity);
+      for arg in spec_clause.args.iter() {
+        args.push(module.resolve_user_type(&fndef.location, &arg.ty)?);
+      }
+      spec_tuples.push(ErlTypeImpl::new_tuple_move(args));
+    }
+    Ok(ErlTypeImpl::new_union(&spec_tuples))
+  }
+
+  /// Function arguments are checked as a tuple of all arguments, the domain is the union of the
+  /// spec clause arguments, or any values if there is no spec. A spec which can not be resolved is
+  /// reported by the spec check, here the function is checked as if it had no spec.
+  fn verify_fn_def(module: &ErlModule, fndef: &ErlFnDef, spec: Option<&ErlType>) -> IcResult<()> {
+    let arity = fndef.funarity.arity;
+    let spec_domain = match spec.map(|s| s.deref()) {
+      Some(ErlTypeImpl::Fn(fn_type)) => Self::spec_domain(module, fndef, fn_type.clauses()).ok(),
+      _ => None,
+    };
+    let mut space = match spec_domain {
+      Some(domain) => PatternSpace::new(domain, true),
+      None => {
+        PatternSpace::new(ErlTypeImpl::new_tuple_move(vec![ErlTypeImpl::any(); arity]), false)
+      }
+    };
+
+    for fn_clause in fndef.clauses.iter() {
+      let location = SpecCheck::clause_location(fndef, fn_clause);
+      let arg_types = Self::tuple_elements(&space.remaining, arity);
+      let mut pattern_types = Vec::with_capacity(arity);
+      for arg in fn_clause.args.iter() {
+        pattern_types.push(arg.synthesize(module, &fn_clause.scope)?);
+      }
+      let pattern_ty = ErlTypeImpl::new_tuple_move(pattern_types);
+
+      // Patterns not matching the spec are reported by the spec check
+      if space.domain.is_overlapping(&pattern_ty) {
+        space.check_reachable(module, &location, fn_clause, &pattern_ty);
+      }
+      space.subtract(module, Self::fn_clause_coverage(module, fn_clause, &arg_types)?);
+
+      let body_scope = fn_clause.narrowed_body_scope(module, &arg_types)?;
+      Self::verify_expr(module, &body_scope, &fn_clause.body)?;
+    }
+
+    if let Some(unmatched) = space.unmatched() {
+      let example = match Self::tuple_elements(&unmatched, arity).as_slice() {
+        [single] => single.clone(),
+        _ => unmatched,
+      };
+      let msg = format!("{} has no clause matching {}", fndef.funarity, example);
+      let type_err = TypeError::NonExhaustive { msg };
+      module.add_warning(ErlError::new_type_error(fndef.location.clone(), type_err));
+    }
+    Ok(())
+  }
+
+  /// Values matched by a function clause, as a tuple of all arguments
+  fn fn_clause_coverage(
+    module: &ErlModule,
+    fn_clause: &ErlFnClause,
+    arg_types: &[ErlType],
+  ) -> IcResult<Option<ErlType>> {
+    if !TypeNarrow::is_exact_pattern(None, &fn_clause.args) {
+      return Ok(None);
+    }
+    if let Some(guard) = &fn_clause.guard_expr {
+      let mut pattern_vars = HashMap::new();
+      for arg in fn_clause.args.iter() {
+        AstNodeImpl::extract_variables(arg, &mut pattern_vars)?;
+      }
+      let args_scope = fn_clause.args_scope(module, arg_types)?;
+      match Self::exact_guard_vars(module, &args_scope, guard)? {
+        Some(vars) if vars.iter().all(|v| pattern_vars.contains_key(v)) => {}
+        _ => return Ok(None),
+      }
+    }
+    let body_scope = fn_clause.narrowed_body_scope(module, arg_types)?;
+    let mut matched = Vec::with_capacity(fn_clause.args.len());
+    for arg in fn_clause.args.iter() {
+      matched.push(arg.synthesize(module, &body_scope)?);
+    }
+    Ok(Some(ErlTypeImpl::new_tuple_move(matched)))
+  }
+
+  /// Split a tuple type (or an union of tuples) into the element types, union of all tuples for
+  /// every position.
+  fn tuple_elements(ty: &ErlType, arity: usize) -> Vec<ErlType> {
+    match ty.deref() {
+      ErlTypeImpl::Tuple { elements } if elements.len() == arity => elements.clone(),
+      ErlTypeImpl::Union(u) => {
+        let members: Vec<Vec<ErlType>> = u
+          .types
+          .iter()
+          .map(|t| Self::tuple_elements(t, arity))
+          .collect();
+        (0..arity)
+          .map(|i| {
+            let types: Vec<ErlType> = members.iter().map(|m| m[i].clone()).collect();
+            ErlTypeImpl::new_union(&types)
+          })
+          .collect()
+      }
+      _ if ty.is_none() => vec![ErlTypeImpl::none(); arity],
+      _ => vec![ErlTypeImpl::any(); arity],
+    }
+  }
+
+  /// Walk the expression and check all nested clause constructs
+  fn verify_expr(module: &ErlModule, scope: &Scope, expr: &AstNode) -> IcResult<()> {
+    match &expr.content {
+      AstNodeType::CaseExpr { expr: scrutinee, clauses } => {
+        Self::verify_expr(module, scope, scrutinee)?;
+        Self::verify_case_clauses(module, scope, &expr.location, "case", Some(scrutinee), clauses)
+      }
+      AstNodeType::Receive { clauses, after } => {
+        Self::verify_case_clauses(module, scope, &expr.location, "receive", None, clauses)?;
+        if let Some(after) = after {
+          Self::verify_expr(module, scope, &after.timeout)?;
+          Self::verify_expr(module, scope, &after.body)?;
+        }
+        Ok(())
+      }
+      AstNodeType::TryCatch { body, of_branches, catch_clauses } => {
+        Self::verify_expr(module, scope, body)?;
+        if let Some(of_branches) = of_branches {
+          Self::verify_case_clauses(module, scope, &expr.location, "try", Some(body), of_branches)?;
+        }
+        for catch_clause in catch_clauses.iter() {
+          let mut catch_scope = scope.clone();
+          let exc = &catch_clause.exc_pattern;
+          for pattern in [Some(&exc.class), Some(&exc.error), exc.stack.as_ref()]
+            .into_iter()
+            .flatten()
+          {
+            catch_scope = ScopeImpl::new_with_pattern_vars(&catch_scope, "catch clause", pattern)?;
+          }
+          Self::verify_expr(module, &catch_scope, &catch_clause.body)?;
+        }
+        Ok(())
+      }
+      AstNodeType::IfStatement { clauses } => {
+        Self::verify_if(module, scope, &expr.location, clauses)
+      }
+      _ => {
+        for child in expr.children().unwrap_or_default().iter() {
+          Self::verify_expr(module, scope, child)?;
+        }
+        Ok(())
+      }
+    }
+  }
+
+  /// Match clauses against the values of `scrutinee`. Without a scrutinee (in `receive`) any value
+  /// can arrive, and only redundant clauses are reported.
+  fn verify_case_clauses(
+    module: &ErlModule,
+    scope: &Scope,
+    location: &SourceLoc,
+    kind: &str,
+    scrutinee: Option<&AstNode>,
+    clauses: &[ErlCaseClause],
+  ) -> IcResult<()> {
+    // Types are not known everywhere yet, so an expression failing to synthesize can be anything
+    let domain = scrutinee
+      .and_then(|s| s.synthesize(module, scope).ok())
+      .unwrap_or_else(ErlTypeImpl::any);
+    let is_known_domain = !domain.is_any() && !domain.is_none();
+    let mut space = PatternSpace::new(domain, is_known_domain);
+
+    for clause in clauses.iter() {
+      let base_scope = ScopeImpl::new_with_pattern_vars(scope, "case clause", &clause.pattern)?;
+      let pattern_ty = clause.pattern.synthesize(module, &base_scope)?;
+      space.check_reachable(module, &clause.pattern.location, &clause.pattern, &pattern_ty);
+
+      let pattern_scope = clause.pattern_scope(module, scope, scrutinee, &space.remaining)?;
+      let body_scope = match &pattern_scope {
+        Some(pattern_scope) => {
+          let matched =
+            Self::case_clause_coverage(module, scope, pattern_scope, scrutinee, clause)?;
+          space.subtract(module, matched);
+          clause.narrow_by_guard(module, pattern_scope)?
+        }
+        None => base_scope,
+      };
+      Self::verify_expr(module, &body_scope, &clause.body)?;
+    }
+
+    if let Some(unmatched) = space.unmatched() {
+      let msg = format!("{} has no clause matching {}", kind, unmatched);
+      let type_err = TypeError::NonExhaustive { msg };
+      module.add_warning(ErlError::new_type_error(location.clone(), type_err));
+    }
+    Ok(())
+  }
+
+  /// Values matched by a case clause, given the enclosing scope and the scope with pattern
+  /// variables
+  fn case_clause_coverage(
+    module: &ErlModule,
+    scope: &Scope,
+    pattern_scope: &Scope,
+    scrutinee: Option<&AstNode>,
+    clause: &ErlCaseClause,
+  ) -> IcResult<Option<ErlType>> {
+    if !TypeNarrow::is_exact_pattern(Some(scope), std::slice::from_ref(&clause.pattern)) {
+      return Ok(None);
+    }
+    let scrutinee_var = scrutinee.and_then(|s| match &s.content {
+      AstNodeType::Var(v) => Some(v.name.clone()),
+      _ => None,
+    });
+    if let Some(guard) = &clause.guard {
+      let mut allowed_vars = HashMap::new();
+      AstNodeImpl::extract_variables(&clause.pattern, &mut allowed_vars)?;
+      if let Some(var_name) = &scrutinee_var {
+        allowed_vars.insert(var_name.clone(), ErlTypeImpl::any());
+      }
+      match Self::exact_guard_vars(module, pattern_scope, guard)? {
+        Some(vars) if vars.iter().all(|v| allowed_vars.contains_key(v)) => {}
+        _ => return Ok(None),
+      }
+    }
+    let clause_scope = clause.narrow_by_guard(module, pattern_scope)?;
+    let matched = clause.pattern.synthesize(module, &clause_scope)?;
+    Ok(Some(match scrutinee_var.and_then(|v| clause_scope.variables.get(&v)) {
+      Some(scrutinee_ty) => matched.narrow(&scrutinee_ty),
+      None => matched,
+    }))
+  }
+
+  /// An `if` is covered by a `true` clause, or when all conditions test one variable exactly, the
+  /// values of this variable are checked.
+  fn verify_if(
+    module: &ErlModule,
+    scope: &Scope,
+    location: &SourceLoc,
+    clauses: &[ErlIfClause],
+  ) -> IcResult<()> {
+    let mut has_true_clause = false;
+    for clause in clauses.iter() {
+      if has_true_clause {
+        let msg = format!("{} is shadowed by an earlier true clause", clause.cond);
+        let type_err = TypeError::RedundantClause { msg };
+        module.add_warning(ErlError::new_type_error(clause.cond.location.clone(), type_err));
+      }
+      has_true_clause |= clause.cond.is_atom_of("true");
+      let body_scope = TypeNarrow::narrow_scope_by_guard(module, scope, &clause.cond)?;
+      Self::verify_expr(module, &body_scope, &clause.body)?;
+    }
+    if has_true_clause {
+      return Ok(());
+    }
+
+    // All conditions must test the same variable
+    let mut subject: Option<String> = None;
+    for clause in clauses.iter() {
+      match Self::exact_guard_vars(module, scope, &clause.cond)? {
+        Some(vars) if vars.len() == 1 => {
+          let var_name = vars.into_iter().next().unwrap();
+          if subject.get_or_insert_with(|| var_name.clone()) != &var_name {
+            return Ok(());
+          }
+        }
+        _ => return Ok(()),
+      }
+    }
+    let subject = match subject {
+      Some(s) => s,
+      None => return Ok(()),
+    };
+    let domain = scope
+      .variables
+      .get(&subject)
+      .unwrap_or_else(ErlTypeImpl::any);
+    let is_known_domain = !domain.is_any() && !domain.is_none();
+    let mut space = PatternSpace::new(domain, is_known_domain);
+    for clause in clauses.iter() {
+      let clause_scope = TypeNarrow::bind_var(scope, &subject, &space.remaining);
+      let narrowed = TypeNarrow::narrow_scope_by_guard(module, &clause_scope, &clause.cond)?;
+      let matched = narrowed
+        .variables
+        .get(&subject)
+        .unwrap_or_else(ErlTypeImpl::any);
+      if matched.is_none() {
+        let msg = format!("{} can never be true", clause.cond);
+        let type_err = TypeError::RedundantClause { msg };
+        module.add_warning(ErlError::new_type_error(clause.cond.location.clone(), type_err));
+      }
+      space.subtract(module, Some(matched));
+    }
+    if let Some(unmatched) = space.unmatched() {
+      let msg = format!("if has no clause for {} :: {}", subject, unmatched);
+      let type_err = TypeError::NonExhaustive { msg };
+      module.add_warning(ErlError::new_type_error(location.clone(), type_err));
+    }
+    Ok(())
+  }
+
+  /// Check if the guard narrows the variables exactly, so that the narrowed types contain only the
+  /// values which pass the guard. Returns the tested variables, or `None` if the guard is not exact.
+  fn exact_guard_vars(
+    module: &ErlModule,
+    scope: &Scope,
+    guard: &AstNode,
+  ) -> IcResult<Option<HashSet<String>>> {
+    let single_var = |node: &AstNode| match &node.content {
+      AstNodeType::Var(v) => Some(HashSet::from([v.name.clone()])),
+      _ => None,
+    };
+    let result = match &guard.content {
+      _ if guard.is_atom_of("true") => Some(HashSet::new()),
+      AstNodeType::Apply(apply) => match TypeNarrow::erlang_fn_name(&apply.target) {
+        Some(name) if apply.args.len() == 1 && TypeNarrow::type_test_bif(&name).is_some() => {
+          single_var(&apply.args[0])
+        }
+        _ => None,
+      },
+      AstNodeType::BinaryOp { binop_expr } => {
+        let (left, right) = (&binop_expr.left, &binop_expr.right);
+        match binop_expr.operator {
+          ErlBinaryOp::Comma | ErlBinaryOp::AndAlso | ErlBinaryOp::And => {
+            // The right side sees the variables narrowed by the left side
+            let left_vars = Self::exact_guard_vars(module, scope, left)?;
+            let right_scope = TypeNarrow::narrow_scope_by_guard(module, scope, left)?;
+            let right_vars = Self::exact_guard_vars(module, &right_scope, right)?;
+            left_vars
+              .zip(right_vars)
+              .map(|(l, r)| l.union(&r).cloned().collect())
+          }
+          ErlBinaryOp::Semicolon | ErlBinaryOp::OrElse | ErlBinaryOp::Or => {
+            // Union of both sides is exact only for the same variable
+            let left_vars = Self::exact_guard_vars(module, scope, left)?;
+            let right_vars = Self::exact_guard_vars(module, scope, right)?;
+            match (left_vars, right_vars) {
+              (Some(l), Some(r)) if l.is_empty() || r.is_empty() => Some(HashSet::new()),
+              (Some(l), Some(r)) if l.len() == 1 && l == r => Some(l),
+              _ => None,
+            }
+          }
+          op => Self::exact_comparison_vars(module, scope, op, left, right)?,
+        }
+      }
+      _ => None,
+    };
+    Ok(result)
+  }
+
+  /// Comparison of a variable with a literal is exact for `=:=`, and for ordering comparisons if
+  /// the variable is known to be an integer.
+  fn exact_comparison_vars(
+    module: &ErlModule,
+    scope: &Scope,
+    op: ErlBinaryOp,
+    left: &AstNode,
+    right: &AstNode,
+  ) -> IcResult<Option<HashSet<String>>> {
+    let (var_name, value) = match (&left.content, &right.content) {
+      (AstNodeType::Var(v), AstNodeType::Lit { .. }) => (&v.name, right),
+      (AstNodeType::Lit { .. }, AstNodeType::Var(v)) => (&v.name, left),
+      _ => return Ok(None),
+    };
+    let is_exact = match op {
+      ErlBinaryOp::HardEq => true,
+      ErlBinaryOp::Less | ErlBinaryOp::LessEq | ErlBinaryOp::Greater | ErlBinaryOp::GreaterEq => {
+        let var_ty = scope
+          .variables
+          .get(var_name)
+          .unwrap_or_else(ErlTypeImpl::any);
+        var_ty.is_subtype_of(&ErlTypeImpl::integer())
+          && value.synthesize(module, scope)?.is_integer()
+      }
+      _ => false,
+    };
+    Ok(if is_exact { Some(HashSet::from([var_name.clone()])) } else { None })
+  }
+}
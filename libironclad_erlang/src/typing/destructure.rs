@@ -0,0 +1,449 @@
+//! Destructuring of a type with a pattern: finds the types of the variables bound by the pattern
+//! when a value of the given type is matched against it.
+
+use crate::erl_syntax::erl_ast::node_impl::AstNodeType;
+use crate::erl_syntax::erl_ast::AstNode;
+use crate::erl_syntax::erl_error::ErlError;
+use crate::erl_syntax::erl_op::ErlBinaryOp;
+use crate::erl_syntax::node::erl_binary_element::{
+  BinaryElement, TypeSpecifier, ValueSignedness, ValueType, ValueWidth,
+};
+use crate::erl_syntax::node::erl_map::MapBuilderMember;
+use crate::erl_syntax::node::erl_record::RecordBuilderMember;
+use crate::error::ic_error::IcResult;
+use crate::project::module::module_impl::ErlModule;
+use crate::project::module::scope::scope_impl::{Scope, ScopeImpl};
+use crate::source_loc::SourceLoc;
+use crate::typing::erl_integer::ErlInteger;
+use crate::typing::erl_type::{ErlType, ErlTypeImpl};
+use crate::typing::record_field_type::RecordFieldType;
+use crate::typing::type_error::TypeError;
+use std::collections::HashMap;
+use std::ops::Deref;
+
+/// Result of matching a type against a pattern
+pub struct Destructure {
+  /// The values of the matched type which can match the pattern, `none()` if the pattern can
+  /// never match
+  pub matched: ErlType,
+  /// Types of the variables bound by the pattern
+  pub bindings: HashMap<String, ErlType>,
+}
+
+impl Destructure {
+  /// Match a value of type `ty` against the `pattern`. Variables already bound in `scope` are
+  /// compared with the value, and narrow it. A pattern which can never match produces `none()` for
+  /// the matched type and for the bound variables.
+  pub(crate) fn new(
+    module: &ErlModule,
+    scope: &Scope,
+    pattern: &AstNode,
+    ty: &ErlType,
+  ) -> IcResult<Self> {
+    let mut result = Self {
+      matched: ErlTypeImpl::none(),
+      bindings: HashMap::new(),
+    };
+    result.matched = result.destructure(module, scope, pattern, ty)?;
+    if result.matched.is_none() {
+      for var_type in result.bindings.values_mut() {
+        *var_type = ErlTypeImpl::none();
+      }
+    }
+    Ok(result)
+  }
+
+  /// Match `Pattern = Expr`, where the expression has type `ty`. Returns the scope with the pattern
+  /// variables bound, and the type of the match expression. A pattern which can never match is
+  /// a type error.
+  pub(crate) fn bind_match(
+    module: &ErlModule,
+    scope: &Scope,
+    location: &SourceLoc,
+    pattern: &AstNode,
+    ty: &ErlType,
+  ) -> IcResult<(Scope, ErlType)> {
+    let destructure = Self::new(module, scope, pattern, ty)?;
+    if destructure.matched.is_none() {
+      let msg = format!("{} can never match a value of type {}", pattern, ty);
+      return ErlError::type_error(location.clone(), TypeError::PatternNeverMatches { msg });
+    }
+    let match_scope = destructure.bind(scope, "match", pattern)?;
+    Ok((match_scope, destructure.matched))
+  }
+
+  /// Create a copy of the scope with the pattern variables added, with their destructured types
+  pub(crate) fn bind(&self, scope: &Scope, name: &str, pattern: &AstNode) -> IcResult<Scope> {
+    let pattern_scope = ScopeImpl::new_with_pattern_vars(scope, name, pattern)?;
+    let mut variables = pattern_scope.variables.collection.read().unwrap().clone();
+    for (var_name, var_type) in self.bindings.iter() {
+      variables.insert(var_name.clone(), var_type.clone());
+    }
+    Ok(ScopeImpl::new(name.to_string(), scope.parent_scope.clone(), variables))
+  }
+
+  /// Match `ty` against the `pattern`, record the bound variables, and return the narrowed type
+  fn destructure(
+    &mut self,
+    module: &ErlModule,
+    scope: &Scope,
+    pattern: &AstNode,
+    ty: &ErlType,
+  ) -> IcResult<ErlType> {
+    let ty = Self::resolve(module, &pattern.location, ty)?;
+    match &pattern.content {
+      AstNodeType::Var(v) if v.name == "_" => Ok(ty),
+      AstNodeType::Var(v) => {
+        let var_type = match self.bindings.get(&v.name) {
+          Some(bound) => bound.narrow(&ty),
+          None => match scope.variables.get(&v.name) {
+            Some(bound) => bound.narrow(&ty),
+            None => ty,
+          },
+        };
+        self.bindings.insert(v.name.clone(), var_type.clone());
+        Ok(var_type)
+      }
+      AstNodeType::Lit { value } => Ok(ty.narrow(&ErlTypeImpl::new_singleton(value))),
+      AstNodeType::Tuple { elements } => self.destructure_tuple(module, scope, elements, &ty),
+      AstNodeType::List { elements, tail } => {
+        self.destructure_list(module, scope, elements, tail, &ty)
+      }
+      AstNodeType::MapBuilder { base: None, members } => {
+        self.destructure_map(module, scope, members, &ty)
+      }
+      AstNodeType::RecordBuilder { base: None, tag, members } => {
+        self.destructure_record(module, scope, tag, members, &ty)
+      }
+      AstNodeType::BinaryExpr { elements } => self.destructure_binary(module, scope, elements, &ty),
+      AstNodeType::BinaryOp { binop_expr } => match binop_expr.operator {
+        // Both sides of `Pattern1 = Pattern2` match the same value
+        ErlBinaryOp::Match => {
+          let left = self.destructure(module, scope, &binop_expr.left, &ty)?;
+          self.destructure(module, scope, &binop_expr.right, &left)
+        }
+        // A string prefix `"abc" ++ Tail`, the tail is a part of the same list
+        ErlBinaryOp::ListAppend => {
+          let list_ty = ty.narrow(&ErlTypeImpl::any_list());
+          self.destructure(module, scope, &binop_expr.right, &list_ty)?;
+          Ok(list_ty)
+        }
+        _ => Ok(ty),
+      },
+      // Other expressions allowed in patterns are constant, and are not checked
+      _ => Ok(ty),
+    }
+  }
+
+  /// Replace user-defined types and record references with their definitions
+  fn resolve(module: &ErlModule, location: &SourceLoc, ty: &ErlType) -> IcResult<ErlType> {
+    match ty.deref() {
+      ErlTypeImpl::Typevar(tv) => Self::resolve(module, location, &tv.ty),
+      ErlTypeImpl::UserDefinedType { .. } => module.resolve_user_type(location, ty),
+      ErlTypeImpl::RecordRef { tag, pins } => module.resolve_record_ref(location, tag, pins),
+      ErlTypeImpl::Union(u) => {
+        let members: IcResult<Vec<ErlType>> = u
+          .types
+          .iter()
+          .map(|t| Self::resolve(module, location, t))
+          .collect();
+        Ok(ErlTypeImpl::new_union(&members?))
+      }
+      _ => Ok(ty.clone()),
+    }
+  }
+
+  fn destructure_tuple(
+    &mut self,
+    module: &ErlModule,
+    scope: &Scope,
+    elements: &[AstNode],
+    ty: &ErlType,
+  ) -> IcResult<ErlType> {
+    let element_types = match Self::tuple_elements(ty, elements.len()) {
+      Some(types) => types,
+      None => return Ok(ErlTypeImpl::none()),
+    };
+    let mut matched = Vec::with_capacity(elements.len());
+    for (el, el_type) in elements.iter().zip(element_types.iter()) {
+      matched.push(self.destructure(module, scope, el, el_type)?);
+    }
+    if matched.iter().any(|t| t.is_none()) {
+      return Ok(ErlTypeImpl::none());
+    }
+    Ok(ErlTypeImpl::new_tuple_move(matched))
+  }
+
+  /// Types of the elements, if the type can contain tuples of the given size
+  fn tuple_elements(ty: &ErlType, arity: usize) -> Option<Vec<ErlType>> {
+    match ty.deref() {
+      ErlTypeImpl::Any | ErlTypeImpl::AnyTuple | ErlTypeImpl::UserDefinedType { .. } => {
+        Some(vec![ErlTypeImpl::any(); arity])
+      }
+      ErlTypeImpl::Tuple { elements } if elements.len() == arity => Some(elements.clone()),
+      ErlTypeImpl::Record { tag, fields } if fields.len() + 1 == arity => {
+        let mut elements = vec![ErlTypeImpl::new_atom(tag)];
+        elements.extend(fields.iter().map(|f| f.ty.clone()));
+        Some(elements)
+      }
+      ErlTypeImpl::Union(u) => {
+        let member_elements: Vec<Vec<ErlType>> = u
+          .types
+          .iter()
+          .filter_map(|t| Self::tuple_elements(t, arity))
+          .collect();
+        if member_elements.is_empty() {
+          return None;
+        }
+        let elements = (0..arity)
+          .map(|index| {
+            let types: Vec<ErlType> = member_elements.iter().map(|m| m[index].clone()).collect();
+            ErlTypeImpl::new_union(&types)
+          })
+          .collect();
+        Some(elements)
+      }
+      _ => None,
+    }
+  }
+
+  fn destructure_list(
+    &mut self,
+    module: &ErlModule,
+    scope: &Scope,
+    elements: &[AstNode],
+    tail: &Option<AstNode>,
+    ty: &ErlType,
+  ) -> IcResult<ErlType> {
+    let mut remaining = ty.clone();
+    let mut matched = Vec::with_capacity(elements.len());
+    for el in elements.iter() {
+      let (head_type, tail_type) = match Self::split_cons(&remaining) {
+        Some(cons) => cons,
+        None => return Ok(ErlTypeImpl::none()),
+      };
+      matched.push(self.destructure(module, scope, el, &head_type)?);
+      remaining = tail_type;
+    }
+    let matched_tail = match tail {
+      Some(tail_pattern) => Some(self.destructure(module, scope, tail_pattern, &remaining)?),
+      None if remaining.is_overlapping(&ErlTypeImpl::nil()) => None,
+      None => return Ok(ErlTypeImpl::none()),
+    };
+    if matched
+      .iter()
+      .chain(matched_tail.iter())
+      .any(|t| t.is_none())
+    {
+      return Ok(ErlTypeImpl::none());
+    }
+    if matched.is_empty() {
+      return Ok(matched_tail.unwrap_or_else(ErlTypeImpl::nil));
+    }
+    Ok(ErlTypeImpl::StronglyTypedList { elements: matched, tail: matched_tail }.into())
+  }
+
+  /// Types of the head and the tail, if the type can contain non-empty lists
+  fn split_cons(ty: &ErlType) -> Option<(ErlType, ErlType)> {
+    match ty.deref() {
+      ErlTypeImpl::Any | ErlTypeImpl::UserDefinedType { .. } => {
+        Some((ErlTypeImpl::any(), ErlTypeImpl::any()))
+      }
+      ErlTypeImpl::AnyList => Some((ErlTypeImpl::any(), ErlTypeImpl::any_list())),
+      ErlTypeImpl::List { elements, tail, .. } => {
+        let rest: ErlType = ErlTypeImpl::List {
+          elements: elements.clone(),
+          tail: tail.clone(),
+          is_non_empty: false,
+        }
+        .into();
+        // The last cons cell of an improper list holds the tail
+        let tail_type = match tail {
+          Some(t) => ErlTypeImpl::new_union(&[rest, t.clone()]),
+          None => rest,
+        };
+        Some((elements.clone(), tail_type))
+      }
+      ErlTypeImpl::StronglyTypedList { elements, tail } => match elements.split_first() {
+        Some((head, [])) => Some((head.clone(), tail.clone().unwrap_or_else(ErlTypeImpl::nil))),
+        Some((head, rest)) => {
+          let rest_type =
+            ErlTypeImpl::StronglyTypedList { elements: rest.to_vec(), tail: tail.clone() };
+          Some((head.clone(), rest_type.into()))
+        }
+        None => tail.as_ref().and_then(Self::split_cons),
+      },
+      ErlTypeImpl::Union(u) => {
+        let conses: Vec<(ErlType, ErlType)> = u.types.iter().filter_map(Self::split_cons).collect();
+        if conses.is_empty() {
+          return None;
+        }
+        let heads: Vec<ErlType> = conses.iter().map(|(h, _)| h.clone()).collect();
+        let tails: Vec<ErlType> = conses.iter().map(|(_, t)| t.clone()).collect();
+        Some((ErlTypeImpl::new_union(&heads), ErlTypeImpl::new_union(&tails)))
+      }
+      _ => None,
+    }
+  }
+
+  fn destructure_map(
+    &mut self,
+    module: &ErlModule,
+    scope: &Scope,
+    members: &[MapBuilderMember],
+    ty: &ErlType,
+  ) -> IcResult<ErlType> {
+    let map_type = ty.narrow(&ErlTypeImpl::any_map());
+    if map_type.is_none() {
+      return Ok(map_type);
+    }
+    for member in members.iter() {
+      let key_type = member.key.synthesize(module, scope)?;
+      match Self::map_value_type(&map_type, &key_type) {
+        Some(value_type) => {
+          if self
+            .destructure(module, scope, &member.expr, &value_type)?
+            .is_none()
+          {
+            return Ok(ErlTypeImpl::none());
+          }
+        }
+        None => return Ok(ErlTypeImpl::none()),
+      }
+    }
+    Ok(map_type)
+  }
+
+  /// Type of the values stored under the key, if the map type can contain the key
+  fn map_value_type(ty: &ErlType, key_type: &ErlType) -> Option<ErlType> {
+    match ty.deref() {
+      ErlTypeImpl::Any | ErlTypeImpl::AnyMap | ErlTypeImpl::UserDefinedType { .. } => {
+        Some(ErlTypeImpl::any())
+      }
+      ErlTypeImpl::Map { members } => {
+        let values: Vec<ErlType> = members
+          .iter()
+          .filter(|m| m.key.is_overlapping(key_type))
+          .map(|m| m.value.clone())
+          .collect();
+        if values.is_empty() {
+          None
+        } else {
+          Some(ErlTypeImpl::new_union(&values))
+        }
+      }
+      ErlTypeImpl::Union(u) => {
+        let values: Vec<ErlType> = u
+          .types
+          .iter()
+          .filter_map(|t| Self::map_value_type(t, key_type))
+          .collect();
+        if values.is_empty() {
+          None
+        } else {
+          Some(ErlTypeImpl::new_union(&values))
+        }
+      }
+      _ => None,
+    }
+  }
+
+  fn destructure_record(
+    &mut self,
+    module: &ErlModule,
+    scope: &Scope,
+    tag: &str,
+    members: &[RecordBuilderMember],
+    ty: &ErlType,
+  ) -> IcResult<ErlType> {
+    let record_def = match module.root_scope.record_defs.get(&tag.to_string()) {
+      Some(rd) => rd,
+      None => {
+        // Undefined records are reported elsewhere, the fields are not known
+        for member in members.iter() {
+          self.destructure(module, scope, &member.expr, &ErlTypeImpl::any())?;
+        }
+        return Ok(ty.clone());
+      }
+    };
+    let element_types = match Self::tuple_elements(ty, record_def.fields.len() + 1) {
+      Some(types) if types[0].is_overlapping(&ErlTypeImpl::new_atom(tag)) => types,
+      _ => return Ok(ErlTypeImpl::none()),
+    };
+
+    let mut pins = Vec::with_capacity(members.len());
+    for member in members.iter() {
+      let field_index = record_def
+        .fields
+        .iter()
+        .position(|f| f.field_tag == member.field);
+      let field_type = match field_index {
+        Some(index) => {
+          let stored = &element_types[index + 1];
+          match &record_def.fields[index].type_ascription {
+            Some(ascription) => stored.narrow(ascription),
+            None => stored.clone(),
+          }
+        }
+        None => ErlTypeImpl::any(),
+      };
+      let matched = self.destructure(module, scope, &member.expr, &field_type)?;
+      if matched.is_none() {
+        return Ok(ErlTypeImpl::none());
+      }
+      pins.push(RecordFieldType::new(member.field.clone(), matched));
+    }
+    Ok(ErlTypeImpl::new_record_ref(tag.to_string(), pins))
+  }
+
+  fn destructure_binary(
+    &mut self,
+    module: &ErlModule,
+    scope: &Scope,
+    elements: &[BinaryElement],
+    ty: &ErlType,
+  ) -> IcResult<ErlType> {
+    let binary_type = ty.narrow(&ErlTypeImpl::any_binary());
+    if binary_type.is_none() {
+      return Ok(binary_type);
+    }
+    for el in elements.iter() {
+      let el_type = Self::binary_element_type(el);
+      if self
+        .destructure(module, scope, &el.value, &el_type)?
+        .is_none()
+      {
+        return Ok(ErlTypeImpl::none());
+      }
+    }
+    Ok(binary_type)
+  }
+
+  /// Type of the values read from a binary, depending on the type specifiers
+  fn binary_element_type(el: &BinaryElement) -> ErlType {
+    let value_type = el.type_specs.iter().find_map(|spec| match spec {
+      TypeSpecifier::Type(t) => Some(t.clone()),
+      _ => None,
+    });
+    let is_signed = el
+      .type_specs
+      .iter()
+      .any(|spec| matches!(spec, TypeSpecifier::Signedness(ValueSignedness::Signed)));
+    match value_type.unwrap_or(ValueType::Integer) {
+      ValueType::Integer if is_signed => ErlTypeImpl::integer(),
+      ValueType::Integer => {
+        let max_value = match el.width {
+          ValueWidth::Literal(bits) if bits < 63 => Some(ErlInteger::Small((1i64 << bits) - 1)),
+          ValueWidth::DefaultWidth => Some(ErlInteger::Small(255)),
+          _ => None,
+        };
+        ErlTypeImpl::new_range_checked(Some(ErlInteger::Small(0)), max_value)
+      }
+      ValueType::Float => ErlTypeImpl::float(),
+      ValueType::Bytes | ValueType::Bitstring => ErlTypeImpl::any_binary(),
+      ValueType::Utf8 | ValueType::Utf16 | ValueType::Utf32 => {
+        ErlTypeImpl::new_range(Some(ErlInteger::Small(0)), Some(ErlInteger::Small(0x10FFFF)))
+      }
+    }
+  }
+}
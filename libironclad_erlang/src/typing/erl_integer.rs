@@ -37,6 +37,22 @@ impl ErlInteger {
     }
   }
 
+  /// Creates from a bigint, choosing the small representation if the value fits
+  pub(crate) fn from_bigint(big: BigInt) -> Self {
+    match big.to_i64() {
+      Some(small) => Small(small),
+      None => Big(big),
+    }
+  }
+
+  /// Convert to a bigint, for arithmetic
+  pub(crate) fn to_bigint(&self) -> BigInt {
+    match self {
+      Small(small) => BigInt::from_i64(*small).unwrap(),
+      Big(big) => big.clone(),
+    }
+  }
+
   /// True if zero or greater
   pub(crate) fn is_non_negative(&self) -> bool {
     match self {
@@ -45,6 +61,20 @@ impl ErlInteger {
     }
   }
 
+  /// True if below zero
+  pub(crate) fn is_negative(&self) -> bool {
+    !self.is_non_negative()
+  }
+
+  /// Absolute value
+  pub(crate) fn abs(&self) -> Self {
+    if self.is_negative() {
+      -self.clone()
+    } else {
+      self.clone()
+    }
+  }
+
   /// Convert to an usize
   pub fn as_usize(&self) -> Option<usize> {
     match self {
@@ -71,6 +101,73 @@ impl std::fmt::Display for ErlInteger {
   }
 }
 
+impl std::ops::Add for ErlInteger {
+  type Output = ErlInteger;
+
+  fn add(self, other: Self) -> Self::Output {
+    if let (Small(a), Small(b)) = (&self, &other) {
+      if let Some(sum) = a.checked_add(*b) {
+        return Small(sum);
+      }
+    }
+    Self::from_bigint(self.to_bigint() + other.to_bigint())
+  }
+}
+
+impl std::ops::Sub for ErlInteger {
+  type Output = ErlInteger;
+
+  fn sub(self, other: Self) -> Self::Output {
+    if let (Small(a), Small(b)) = (&self, &other) {
+      if let Some(diff) = a.checked_sub(*b) {
+        return Small(diff);
+      }
+    }
+    Self::from_bigint(self.to_bigint() - other.to_bigint())
+  }
+}
+
+impl std::ops::Mul for ErlInteger {
+  type Output = ErlInteger;
+
+  fn mul(self, other: Self) -> Self::Output {
+    if let (Small(a), Small(b)) = (&self, &other) {
+      if let Some(product) = a.checked_mul(*b) {
+        return Small(product);
+      }
+    }
+    Self::from_bigint(self.to_bigint() * other.to_bigint())
+  }
+}
+
+/// Integer division, rounds towards zero like Erlang `div` does
+impl std::ops::Div for ErlInteger {
+  type Output = ErlInteger;
+
+  fn div(self, other: Self) -> Self::Output {
+    if let (Small(a), Small(b)) = (&self, &other) {
+      if let Some(quotient) = a.checked_div(*b) {
+        return Small(quotient);
+      }
+    }
+    Self::from_bigint(self.to_bigint() / other.to_bigint())
+  }
+}
+
+impl std::ops::Neg for ErlInteger {
+  type Output = ErlInteger;
+
+  fn neg(self) -> Self::Output {
+    match self {
+      Small(small) => match small.checked_neg() {
+        Some(negated) => Small(negated),
+        None => Big(-BigInt::from_i64(small).unwrap()),
+      },
+      Big(big) => Big(-big),
+    }
+  }
+}
+
 impl PartialEq<Self> for ErlInteger {
   fn eq(&self, other: &Self) -> bool {
     match self {
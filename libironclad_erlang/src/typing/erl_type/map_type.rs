@@ -10,10 +10,25 @@ pub struct MapMemberType {
   pub key: ErlType,
   /// The corresponding value type
   pub value: ErlType,
+  /// The key is always present (`Key := Value`), otherwise it is optional (`Key => Value`)
+  pub mandatory: bool,
+}
+
+impl MapMemberType {
+  /// Create a member for a key which is always present `Key := Value`
+  pub fn new_mandatory(key: ErlType, value: ErlType) -> Self {
+    Self { key, value, mandatory: true }
+  }
+
+  /// Create a member for a key which may be absent `Key => Value`
+  pub fn new_optional(key: ErlType, value: ErlType) -> Self {
+    Self { key, value, mandatory: false }
+  }
 }
 
 impl Display for MapMemberType {
   fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-    write!(f, "{} => {}", self.key, self.value)
+    let op = if self.mandatory { ":=" } else { "=>" };
+    write!(f, "{} {} {}", self.key, op, self.value)
   }
 }
@@ -36,12 +36,13 @@ pub enum ErlTypeImpl {
   Float,
   /// Any integer or big integer
   Integer,
-  /// Defines integer range A..B, cannot be single element or empty
+  /// Defines integer range A..B, cannot be single element or empty.
+  /// A missing bound means the range is not limited on that side, like in `pos_integer()`.
   IntegerRange {
-    /// First value of the range
-    from: ErlInteger,
-    /// Last value of the range
-    to: ErlInteger,
+    /// First value of the range, or `None` if not limited from below
+    from: Option<ErlInteger>,
+    /// Last value of the range, or `None` if not limited from above
+    to: Option<ErlInteger>,
   },
 
   /// Any tuple of any size
@@ -1,6 +1,7 @@
 //! Contains is_* checks
 use crate::literal::Literal;
 use crate::typing::erl_type::ErlTypeImpl;
+use crate::typing::overlap::OverlapChecker;
 use crate::typing::subtyping::SubtypeChecker;
 use std::ops::Deref;
 
@@ -10,6 +11,11 @@ impl ErlTypeImpl {
     SubtypeChecker::is_subtype(self, other)
   }
 
+  /// Shortcut to the overlap checker, true if some values belong to both types
+  pub fn is_overlapping(&self, other: &ErlTypeImpl) -> bool {
+    OverlapChecker::is_overlapping(self, other)
+  }
+
   /// Checks whether type is an atom
   pub fn is_atom(&self) -> bool {
     match self {
@@ -134,7 +140,6 @@ impl ErlTypeImpl {
   }
 
   /// Checks whether a type is callable
-  #[allow(dead_code)]
   pub(crate) fn is_function(&self) -> bool {
     matches!(
       self,
@@ -148,6 +153,27 @@ impl ErlTypeImpl {
     matches!(self, ErlTypeImpl::Any)
   }
 
+  /// True if the type is any(), or has any() in its elements, members or fields
+  pub(crate) fn contains_any(&self) -> bool {
+    match self {
+      ErlTypeImpl::Any => true,
+      ErlTypeImpl::Typevar(tv) => tv.ty.contains_any(),
+      ErlTypeImpl::Union(u) => u.types.iter().any(|t| t.contains_any()),
+      ErlTypeImpl::Tuple { elements } => elements.iter().any(|t| t.contains_any()),
+      ErlTypeImpl::List { elements, tail, .. } => {
+        elements.contains_any() || tail.as_ref().is_some_and(|t| t.contains_any())
+      }
+      ErlTypeImpl::StronglyTypedList { elements, tail } => {
+        elements.iter().any(|t| t.contains_any()) || tail.as_ref().is_some_and(|t| t.contains_any())
+      }
+      ErlTypeImpl::Map { members } => members
+        .iter()
+        .any(|m| m.key.contains_any() || m.value.contains_any()),
+      ErlTypeImpl::Record { fields, .. } => fields.iter().any(|f| f.ty.contains_any()),
+      _ => false,
+    }
+  }
+
   /// True if type is none() or union of no types
   pub(crate) fn is_none(&self) -> bool {
     match self {
@@ -99,7 +99,6 @@ impl ErlTypeImpl {
   }
 
   /// Create a new union but do not normalize
-  #[allow(dead_code)]
   pub(crate) fn new_union_skip_normalize(types: &[ErlType]) -> ErlType {
     match types.len() {
       0 => ErlTypeImpl::none(),
@@ -134,41 +133,133 @@ impl ErlTypeImpl {
     ErlTypeImpl::RecordRef { tag, pins }.into()
   }
 
-  /// Construct a new integer range
-  pub(crate) fn new_range(a: ErlInteger, b: ErlInteger) -> ErlType {
+  /// Construct a new integer range, a missing bound means the range is open on that side
+  pub(crate) fn new_range(a: Option<ErlInteger>, b: Option<ErlInteger>) -> ErlType {
     ErlTypeImpl::IntegerRange { from: a, to: b }.into()
   }
 
-  /// Try match type name and arity vs known basic types
+  /// Construct a new integer range with both bounds known
+  fn new_small_range(a: i64, b: i64) -> ErlType {
+    Self::new_range(Some(ErlInteger::Small(a)), Some(ErlInteger::Small(b)))
+  }
+
+  /// Creates a list type which can have an improper tail `tail` or a `[]`
+  fn new_maybe_improper_list(elements: ErlType, tail: ErlType, is_non_empty: bool) -> ErlType {
+    let tail = Self::new_union(&[tail, Self::nil()]);
+    ErlTypeImpl::List { elements, tail: Some(tail), is_non_empty }.into()
+  }
+
+  /// `iolist()` is a recursive type `maybe_improper_list(byte() | binary() | iolist(),
+  /// binary() | [])`, the nested `iolist()` is approximated by `list()`
+  fn new_iolist() -> ErlType {
+    let elements = Self::new_union(&[
+      Self::new_small_range(0, 255),
+      Self::any_binary(),
+      Self::any_list(),
+    ]);
+    Self::new_maybe_improper_list(elements, Self::any_binary(), false)
+  }
+
+  /// Built-in types of zero arity, see the "Types and their Syntax" table in the Erlang Reference
+  /// Manual. Returns `None` if the name is not a built-in type.
+  fn builtin_type_arity0(type_name: &str) -> Option<ErlType> {
+    let non_neg_integer = || Self::new_range(Some(ErlInteger::Small(0)), None);
+    let char_range = || Self::new_small_range(0, 0x10ffff);
+    let result = match type_name {
+      "any" | "term" | "dynamic" => Self::any(),
+      "none" | "no_return" => Self::none(),
+
+      "number" => Self::number(),
+      "integer" => Self::integer(),
+      "float" => Self::float(),
+      "non_neg_integer" => non_neg_integer(),
+      "pos_integer" => Self::new_range(Some(ErlInteger::Small(1)), None),
+      "neg_integer" => Self::new_range(None, Some(ErlInteger::Small(-1))),
+      "byte" => Self::new_small_range(0, 255),
+      "char" => char_range(),
+      "arity" => Self::new_small_range(0, 255),
+
+      "atom" | "module" | "node" => Self::atom(),
+      "boolean" => Self::boolean(),
+
+      "list" => Self::any_list(),
+      "nonempty_list" => Self::list_of(Self::any(), true),
+      "maybe_improper_list" => Self::new_maybe_improper_list(Self::any(), Self::any(), false),
+      "nonempty_maybe_improper_list" => {
+        Self::new_maybe_improper_list(Self::any(), Self::any(), true)
+      }
+      "nil" => Self::nil(),
+      "string" => Self::list_of(char_range(), false),
+      "nonempty_string" => Self::list_of(char_range(), true),
+      "iolist" => Self::new_iolist(),
+      "iodata" => Self::new_union(&[Self::new_iolist(), Self::any_binary()]),
+
+      "tuple" => Self::any_tuple(),
+      "mfa" => Self::new_tuple(&[Self::atom(), Self::atom(), Self::new_small_range(0, 255)]),
+      "map" => Self::any_map(),
+
+      // Binaries and bitstrings are not distinguished yet
+      "binary" | "bitstring" | "nonempty_binary" | "nonempty_bitstring" => Self::any_binary(),
+
+      "fun" | "function" => Self::any_fun(),
+
+      "pid" => Self::pid(),
+      "port" => Self::port(),
+      "reference" => Self::reference(),
+      "identifier" => Self::new_union(&[Self::pid(), Self::port(), Self::reference()]),
+      "timeout" => Self::new_union(&[non_neg_integer(), Self::new_atom("infinity")]),
+      _ => return None,
+    };
+    Some(result)
+  }
+
+  /// Built-in types which take type arguments. Returns `None` if the name and arity is not a
+  /// built-in type.
+  fn builtin_type_with_args(type_name: &str, args: &[Typevar]) -> Option<ErlType> {
+    let result = match (type_name, args) {
+      ("list", [elem]) => Self::list_of(elem.ty.clone(), false),
+      ("nonempty_list", [elem]) => Self::list_of(elem.ty.clone(), true),
+      ("maybe_improper_list", [elem, tail]) => {
+        Self::new_maybe_improper_list(elem.ty.clone(), tail.ty.clone(), false)
+      }
+      ("nonempty_maybe_improper_list", [elem, tail]) => {
+        Self::new_maybe_improper_list(elem.ty.clone(), tail.ty.clone(), true)
+      }
+      ("nonempty_improper_list", [elem, tail]) => ErlTypeImpl::List {
+        elements: elem.ty.clone(),
+        tail: Some(tail.ty.clone()),
+        is_non_empty: true,
+      }
+      .into(),
+      ("map", [key, value]) => Self::new_map(vec![MapMemberType::new_optional(
+        key.ty.clone(),
+        value.ty.clone(),
+      )]),
+      _ => return None,
+    };
+    Some(result)
+  }
+
+  /// Try match type name and arity vs known basic types. Types with a module name other than
+  /// `erlang` are never built-in.
   pub(crate) fn from_name(
     maybe_module: Option<String>,
     type_name: String,
     args: &[Typevar],
   ) -> ErlType {
-    #[allow(clippy::single_match)]
-    match args.len() {
-      0 => match type_name.as_ref() {
-        "any" => return ErlTypeImpl::any(),
-        "none" => return ErlTypeImpl::none(),
-
-        "number" => return ErlTypeImpl::number(),
-        "integer" => return ErlTypeImpl::integer(),
-        "float" => return ErlTypeImpl::float(),
-
-        "atom" => return ErlTypeImpl::atom(),
-        "boolean" => return ErlTypeImpl::boolean(),
-
-        "list" => return ErlTypeImpl::any_list(),
-        "nil" => return ErlTypeImpl::nil(),
-
-        "tuple" => return ErlTypeImpl::any_tuple(),
-
-        "pid" => return ErlTypeImpl::pid(),
-        "port" => return ErlTypeImpl::port(),
-        "reference" => return ErlTypeImpl::reference(),
-        _ => {}
-      },
-      _ => {}
+    let is_builtin_module = match &maybe_module {
+      None => true,
+      Some(m) => m == "erlang",
+    };
+    if is_builtin_module {
+      let builtin = if args.is_empty() {
+        Self::builtin_type_arity0(&type_name)
+      } else {
+        Self::builtin_type_with_args(&type_name, args)
+      };
+      if let Some(ty) = builtin {
+        return ty;
+      }
     }
     // We were not able to find a basic type of that name and arity
     UserDefinedType {
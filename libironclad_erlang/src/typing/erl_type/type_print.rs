@@ -1,5 +1,6 @@
 //! Display code for printing types
 
+use crate::typing::erl_integer::ErlInteger;
 use crate::typing::erl_type::ErlTypeImpl;
 use libironclad_util::pretty::Pretty;
 use std::fmt::Formatter;
@@ -15,7 +16,15 @@ impl std::fmt::Display for ErlTypeImpl {
       ErlTypeImpl::Number => write!(f, "number()"),
       ErlTypeImpl::Float => write!(f, "float()"),
       ErlTypeImpl::Integer => write!(f, "integer()"),
-      ErlTypeImpl::IntegerRange { from, to } => write!(f, "{}..{}", from, to),
+      ErlTypeImpl::IntegerRange { from, to } => match (from, to) {
+        (Some(a), Some(b)) => write!(f, "{}..{}", a, b),
+        (Some(ErlInteger::Small(0)), None) => write!(f, "non_neg_integer()"),
+        (Some(ErlInteger::Small(1)), None) => write!(f, "pos_integer()"),
+        (None, Some(ErlInteger::Small(-1))) => write!(f, "neg_integer()"),
+        (Some(a), None) => write!(f, "{}..", a),
+        (None, Some(b)) => write!(f, "..{}", b),
+        (None, None) => write!(f, "integer()"),
+      },
       ErlTypeImpl::AnyTuple => write!(f, "tuple()"),
       ErlTypeImpl::Tuple { elements } => Pretty::display_curly_list(elements.iter(), f),
       ErlTypeImpl::Record { tag, fields } => {
@@ -52,7 +61,12 @@ impl std::fmt::Display for ErlTypeImpl {
         write!(f, "}}")
       }
       ErlTypeImpl::AnyBinary => write!(f, "binary()"),
-      ErlTypeImpl::Binary { .. } => unimplemented!("Display type for binary"),
+      ErlTypeImpl::Binary { head, tail } => match (head, tail) {
+        (None, None) => write!(f, "<<>>"),
+        (Some(h), None) => write!(f, "<<_:{}>>", h.0),
+        (None, Some(t)) => write!(f, "<<_:_*{}>>", t.0),
+        (Some(h), Some(t)) => write!(f, "<<_:{}, _:_*{}>>", h.0, t.0),
+      },
       ErlTypeImpl::AnyFn => write!(f, "function()"),
       ErlTypeImpl::Fn(fntype) => {
         write!(f, "fun ").unwrap();
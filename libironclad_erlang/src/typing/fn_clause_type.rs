@@ -55,13 +55,14 @@ impl FnClauseType {
       && self.ret_type.ty.is_subtype_of(&super_clause.ret_type.ty)
   }
 
-  /// Check whether argument list can be passed to this clause
+  /// Check whether argument list can be passed to this clause, i.e. some values of every argument
+  /// type are accepted by the clause
   #[allow(dead_code)]
   pub(crate) fn can_accept_args(&self, args: &[ErlType]) -> bool {
     self
       .args
       .iter()
       .zip(args.iter())
-      .all(|(in_arg, my_arg)| my_arg.is_subtype_of(&in_arg.ty))
+      .all(|(in_arg, my_arg)| my_arg.is_overlapping(&in_arg.ty))
   }
 }
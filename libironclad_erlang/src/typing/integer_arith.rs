@@ -0,0 +1,380 @@
+//! Interval arithmetic on integer types, to synthesize precise result types for the arithmetic
+//! operators, and widening of types, to keep the synthesis of recursive functions finite.
+
+use crate::erl_syntax::erl_op::{ErlBinaryOp, ErlUnaryOp};
+use crate::literal::Literal;
+use crate::typing::erl_integer::ErlInteger;
+use crate::typing::erl_type::{ErlType, ErlTypeImpl};
+use crate::typing::fn_clause_type::FnClauseType;
+use crate::typing::fn_type::FnType;
+use crate::typing::typevar::Typevar;
+use std::ops::Deref;
+
+/// An integer interval `from..to`, a missing bound means that the interval is not limited on that
+/// side
+#[derive(Clone, Debug, PartialEq)]
+struct Interval {
+  from: Option<ErlInteger>,
+  to: Option<ErlInteger>,
+}
+
+impl Interval {
+  fn new(from: Option<ErlInteger>, to: Option<ErlInteger>) -> Self {
+    Self { from, to }
+  }
+
+  fn exact(n: ErlInteger) -> Self {
+    Self::new(Some(n.clone()), Some(n))
+  }
+
+  fn zero() -> ErlInteger {
+    ErlInteger::Small(0)
+  }
+
+  /// Interval covering all values of an integer type, or `None` for other types
+  fn from_type(ty: &ErlTypeImpl) -> Option<Self> {
+    match ty {
+      ErlTypeImpl::Integer => Some(Self::new(None, None)),
+      ErlTypeImpl::IntegerRange { from, to } => Some(Self::new(from.clone(), to.clone())),
+      ErlTypeImpl::Singleton { val } => match val.deref() {
+        Literal::Integer(n) => Some(Self::exact(n.clone())),
+        _ => None,
+      },
+      ErlTypeImpl::Typevar(tv) => Self::from_type(&tv.ty),
+      ErlTypeImpl::Union(u) => {
+        let mut result: Option<Self> = None;
+        for member in u.types.iter() {
+          let member_interval = Self::from_type(member)?;
+          result = Some(match result {
+            Some(r) => r.hull(&member_interval),
+            None => member_interval,
+          });
+        }
+        result
+      }
+      _ => None,
+    }
+  }
+
+  /// Split the type into an interval covering its integer members, and the other members
+  fn split(ty: &ErlType) -> (Option<Self>, Vec<ErlType>) {
+    let members = match ty.deref() {
+      ErlTypeImpl::Union(u) => u.types.clone(),
+      _ => vec![ty.clone()],
+    };
+    let mut interval: Option<Self> = None;
+    let mut rest = Vec::new();
+    for member in members.into_iter() {
+      match Self::from_type(&member) {
+        Some(member_interval) => {
+          interval = Some(match interval {
+            Some(i) => i.hull(&member_interval),
+            None => member_interval,
+          })
+        }
+        None => rest.push(member),
+      }
+    }
+    (interval, rest)
+  }
+
+  fn to_type(&self) -> ErlType {
+    ErlTypeImpl::new_range_checked(self.from.clone(), self.to.clone())
+  }
+
+  fn is_zero(&self) -> bool {
+    self.from == Some(Self::zero()) && self.to == Some(Self::zero())
+  }
+
+  fn is_non_negative(&self) -> bool {
+    self.from.as_ref().is_some_and(|f| f.is_non_negative())
+  }
+
+  fn is_non_positive(&self) -> bool {
+    self.to.as_ref().is_some_and(|t| *t <= Self::zero())
+  }
+
+  /// Smallest interval containing both intervals
+  fn hull(&self, other: &Self) -> Self {
+    let from = match (&self.from, &other.from) {
+      (Some(a), Some(b)) => Some(if a < b { a.clone() } else { b.clone() }),
+      _ => None,
+    };
+    let to = match (&self.to, &other.to) {
+      (Some(a), Some(b)) => Some(if a > b { a.clone() } else { b.clone() }),
+      _ => None,
+    };
+    Self::new(from, to)
+  }
+
+  /// Interval with the given bounds, from a non-empty list of the candidate values
+  fn from_candidates(candidates: Vec<ErlInteger>) -> Self {
+    let mut from = candidates[0].clone();
+    let mut to = candidates[0].clone();
+    for c in candidates.into_iter().skip(1) {
+      if c < from {
+        from = c;
+      } else if c > to {
+        to = c;
+      }
+    }
+    Self::new(Some(from), Some(to))
+  }
+
+  fn add(&self, other: &Self) -> Self {
+    let add_bounds = |a: &Option<ErlInteger>, b: &Option<ErlInteger>| match (a, b) {
+      (Some(a), Some(b)) => Some(a.clone() + b.clone()),
+      _ => None,
+    };
+    Self::new(add_bounds(&self.from, &other.from), add_bounds(&self.to, &other.to))
+  }
+
+  fn neg(&self) -> Self {
+    Self::new(self.to.clone().map(|t| -t), self.from.clone().map(|f| -f))
+  }
+
+  fn sub(&self, other: &Self) -> Self {
+    self.add(&other.neg())
+  }
+
+  /// Bitwise not is `-X - 1`
+  fn bnot(&self) -> Self {
+    self.neg().add(&Self::exact(ErlInteger::Small(-1)))
+  }
+
+  fn mul(&self, other: &Self) -> Self {
+    if self.is_zero() || other.is_zero() {
+      return Self::exact(Self::zero());
+    }
+    match (&self.from, &self.to, &other.from, &other.to) {
+      (Some(a), Some(b), Some(c), Some(d)) => Self::from_candidates(vec![
+        a.clone() * c.clone(),
+        a.clone() * d.clone(),
+        b.clone() * c.clone(),
+        b.clone() * d.clone(),
+      ]),
+      (Some(a), to, Some(c), other_to) if self.is_non_negative() && other.is_non_negative() => {
+        let new_to = match (to, other_to) {
+          (Some(b), Some(d)) => Some(b.clone() * d.clone()),
+          _ => None,
+        };
+        Self::new(Some(a.clone() * c.clone()), new_to)
+      }
+      _ => Self::new(None, None),
+    }
+  }
+
+  /// Integer division rounding towards zero. Division by zero does not produce a value, so only
+  /// the positive and the negative parts of the divisor are used. Returns `None` if the divisor can
+  /// only be zero.
+  fn div(&self, divisor: &Self) -> Option<Self> {
+    let one = ErlInteger::Small(1);
+    let positive = match &divisor.to {
+      Some(to) if *to < one => None,
+      _ => {
+        let from = match &divisor.from {
+          Some(from) if *from > one => from.clone(),
+          _ => one.clone(),
+        };
+        Some(Self::new(Some(from), divisor.to.clone()))
+      }
+    };
+    let negative = match &divisor.from {
+      Some(from) if *from > -one.clone() => None,
+      _ => {
+        let to = match &divisor.to {
+          Some(to) if *to < -one.clone() => to.clone(),
+          _ => -one.clone(),
+        };
+        Some(Self::new(divisor.from.clone(), Some(to)))
+      }
+    };
+    let quotients: Vec<Self> = [positive, negative]
+      .into_iter()
+      .flatten()
+      .map(|d| self.div_same_sign(&d))
+      .collect();
+    quotients.into_iter().reduce(|a, b| a.hull(&b))
+  }
+
+  /// Division by a divisor which is either all positive or all negative. The quotient is monotonic
+  /// in each argument, so its extremes are found at the corners. An unlimited divisor bound gives
+  /// a zero quotient.
+  fn div_same_sign(&self, divisor: &Self) -> Self {
+    match (&self.from, &self.to) {
+      (Some(a), Some(b)) => {
+        let mut candidates = Vec::with_capacity(4);
+        for x in [a, b] {
+          for y in [&divisor.from, &divisor.to] {
+            match y {
+              Some(y) => candidates.push(x.clone() / y.clone()),
+              None => candidates.push(Self::zero()),
+            }
+          }
+        }
+        Self::from_candidates(candidates)
+      }
+      _ => {
+        // Only the sign of the quotient is known
+        let is_divisor_positive = divisor.is_non_negative();
+        let is_non_negative = if is_divisor_positive {
+          self.is_non_negative()
+        } else {
+          self.is_non_positive()
+        };
+        let is_non_positive = if is_divisor_positive {
+          self.is_non_positive()
+        } else {
+          self.is_non_negative()
+        };
+        Self::new(is_non_negative.then(Self::zero), is_non_positive.then(Self::zero))
+      }
+    }
+  }
+
+  /// Remainder has the sign of the dividend, and is smaller than the divisor by absolute value.
+  /// Returns `None` if the divisor can only be zero.
+  fn rem(&self, divisor: &Self) -> Option<Self> {
+    if divisor.is_zero() {
+      return None;
+    }
+    let max_abs = match (&divisor.from, &divisor.to) {
+      (Some(a), Some(b)) => {
+        let (a, b) = (a.abs(), b.abs());
+        Some(if a > b { a } else { b } - ErlInteger::Small(1))
+      }
+      _ => None,
+    };
+    let from = if self.is_non_negative() {
+      Some(Self::zero())
+    } else {
+      match (max_abs.clone().map(|m| -m), &self.from) {
+        (Some(a), Some(b)) => Some(if a > *b { a } else { b.clone() }),
+        (a, b) => a.or_else(|| b.clone()),
+      }
+    };
+    let to = if self.is_non_positive() {
+      Some(Self::zero())
+    } else {
+      match (max_abs, &self.to) {
+        (Some(a), Some(b)) => Some(if a < *b { a } else { b.clone() }),
+        (a, b) => a.or_else(|| b.clone()),
+      }
+    };
+    Some(Self::new(from, to))
+  }
+
+  /// Bitwise and of two non-negative values is not greater than either of them
+  fn band(&self, other: &Self) -> Self {
+    if !self.is_non_negative() && !other.is_non_negative() {
+      return Self::new(None, None);
+    }
+    let to = match (&self.to, &other.to) {
+      (Some(a), Some(b)) if self.is_non_negative() && other.is_non_negative() => {
+        Some(if a < b { a.clone() } else { b.clone() })
+      }
+      (Some(a), _) if self.is_non_negative() => Some(a.clone()),
+      (_, Some(b)) if other.is_non_negative() => Some(b.clone()),
+      _ => None,
+    };
+    Self::new(Some(Self::zero()), to)
+  }
+
+  /// Bounds which grew since the previous step become unlimited
+  fn widen(&self, next: &Self) -> Self {
+    let from = match (&self.from, &next.from) {
+      (Some(a), Some(b)) if a <= b => Some(a.clone()),
+      _ => None,
+    };
+    let to = match (&self.to, &next.to) {
+      (Some(a), Some(b)) if b <= a => Some(a.clone()),
+      _ => None,
+    };
+    Self::new(from, to)
+  }
+}
+
+/// Hosts the arithmetic operators type synthesis over integer intervals
+pub struct IntegerArith {}
+
+impl IntegerArith {
+  /// Result type of an arithmetic operator `+`, `-`, `*`, `div`, `rem` or `band`. Integer operands
+  /// produce an integer range, other numbers produce `integer()` or `float()`, and operands which
+  /// can't be numbers produce `none()`, because the operation will crash.
+  pub(crate) fn synthesize_binop(op: ErlBinaryOp, left: &ErlType, right: &ErlType) -> ErlType {
+    let number = ErlTypeImpl::number();
+    if !left.is_overlapping(&number) || !right.is_overlapping(&number) {
+      return ErlTypeImpl::none();
+    }
+    if let (Some(a), Some(b)) = (Interval::from_type(left), Interval::from_type(right)) {
+      let result = match op {
+        ErlBinaryOp::Add => Some(a.add(&b)),
+        ErlBinaryOp::Sub => Some(a.sub(&b)),
+        ErlBinaryOp::Mul => Some(a.mul(&b)),
+        ErlBinaryOp::IntegerDiv => a.div(&b),
+        ErlBinaryOp::Remainder => a.rem(&b),
+        ErlBinaryOp::BinaryAnd => Some(a.band(&b)),
+        _ => unreachable!("Not an integer arithmetic operator: {}", op),
+      };
+      return result.map_or_else(ErlTypeImpl::none, |r| r.to_type());
+    }
+    match op {
+      ErlBinaryOp::IntegerDiv | ErlBinaryOp::Remainder | ErlBinaryOp::BinaryAnd => {
+        ErlTypeImpl::integer()
+      }
+      _ if left.is_supertype_of_integer() && right.is_supertype_of_integer() => {
+        ErlTypeImpl::integer()
+      }
+      _ => ErlTypeImpl::float(),
+    }
+  }
+
+  /// Result type of an unary arithmetic operator `-`, `+` or `bnot`
+  pub(crate) fn synthesize_unop(op: &ErlUnaryOp, operand: &ErlType) -> ErlType {
+    if !operand.is_overlapping(&ErlTypeImpl::number()) {
+      return ErlTypeImpl::none();
+    }
+    match (op, Interval::from_type(operand)) {
+      (ErlUnaryOp::Negative, Some(interval)) => interval.neg().to_type(),
+      (ErlUnaryOp::BinaryNot, Some(interval)) => interval.bnot().to_type(),
+      (ErlUnaryOp::BinaryNot, None) => ErlTypeImpl::integer(),
+      _ => operand.clone(),
+    }
+  }
+}
+
+impl ErlTypeImpl {
+  /// Combine the type from the previous step of a fixpoint iteration with the `next` one, so that
+  /// the iteration is finite: the integer bounds which grew become unlimited. For function types,
+  /// the clause return types are widened.
+  pub(crate) fn widen(self: &ErlType, next: &ErlType) -> ErlType {
+    if let (ErlTypeImpl::Fn(prev_fn), ErlTypeImpl::Fn(next_fn)) = (self.deref(), next.deref()) {
+      if prev_fn.clauses().len() == next_fn.clauses().len() {
+        let clauses: Vec<FnClauseType> = prev_fn
+          .clauses()
+          .iter()
+          .zip(next_fn.clauses().iter())
+          .map(|(prev, next)| {
+            let ret_ty = prev.ret_ty().widen(next.ret_ty());
+            FnClauseType::new(next.args.clone(), Typevar::from_erltype(&ret_ty))
+          })
+          .collect();
+        return ErlTypeImpl::Fn(FnType::new(next_fn.arity(), &clauses).into()).into();
+      }
+    }
+    if next.is_subtype_of(self) {
+      return self.clone();
+    }
+    let (prev_interval, mut members) = Interval::split(self);
+    let (next_interval, next_rest) = Interval::split(next);
+    members.extend(next_rest);
+    let interval = match (prev_interval, next_interval) {
+      (Some(prev), Some(next)) => Some(prev.widen(&next)),
+      (prev, next) => prev.or(next),
+    };
+    if let Some(i) = interval {
+      members.push(i.to_type());
+    }
+    ErlTypeImpl::new_union(&members)
+  }
+}
@@ -1,15 +1,23 @@
 //! Contains types and typecheck related code
+pub mod bif;
 pub mod check;
+pub mod coverage;
+pub mod destructure;
 pub mod erl_integer;
 pub mod erl_type;
 pub mod extract_vars;
 pub mod fn_clause_type;
 pub mod fn_type;
+pub mod integer_arith;
+pub mod overlap;
 pub mod prefab;
 pub mod record_field_type;
+pub mod spec_check;
 pub mod subtyping;
 pub mod type_error;
 pub mod type_narrow;
+pub mod type_subst;
 pub mod type_synth;
 pub mod type_union;
 pub mod typevar;
+pub mod user_type;
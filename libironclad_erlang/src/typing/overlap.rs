@@ -0,0 +1,96 @@
+//! Checks whether two types have some values in common
+
+use crate::typing::erl_integer::ErlInteger;
+use crate::typing::erl_type::ErlTypeImpl;
+
+/// Hosts code to check whether two types intersect
+pub struct OverlapChecker {}
+
+impl OverlapChecker {
+  /// Checks whether some value can belong to both `a` and `b`. Types which can't be checked, like
+  /// user-defined types and record references, are assumed to overlap with everything.
+  pub(crate) fn is_overlapping(a: &ErlTypeImpl, b: &ErlTypeImpl) -> bool {
+    match (a, b) {
+      (ErlTypeImpl::Typevar(tv), _) => Self::is_overlapping(&tv.ty, b),
+      (_, ErlTypeImpl::Typevar(tv)) => Self::is_overlapping(a, &tv.ty),
+      (ErlTypeImpl::Union(u), _) => u.types.iter().any(|t| Self::is_overlapping(t, b)),
+      (_, ErlTypeImpl::Union(u)) => u.types.iter().any(|t| Self::is_overlapping(a, t)),
+      _ if a.is_none() || b.is_none() => false,
+      _ if a.is_subtype_of(b) || b.is_subtype_of(a) => true,
+
+      (ErlTypeImpl::UserDefinedType { .. }, _)
+      | (_, ErlTypeImpl::UserDefinedType { .. })
+      | (ErlTypeImpl::RecordRef { .. }, _)
+      | (_, ErlTypeImpl::RecordRef { .. }) => true,
+
+      (
+        ErlTypeImpl::IntegerRange { from: a_from, to: a_to },
+        ErlTypeImpl::IntegerRange { from, to },
+      ) => Self::is_bound_below(a_from, to) && Self::is_bound_below(from, a_to),
+      (ErlTypeImpl::Tuple { elements: a_elements }, ErlTypeImpl::Tuple { elements }) => {
+        a_elements.len() == elements.len()
+          && a_elements
+            .iter()
+            .zip(elements.iter())
+            .all(|(a_el, b_el)| Self::is_overlapping(a_el, b_el))
+      }
+      (ErlTypeImpl::Record { tag, fields }, ErlTypeImpl::Tuple { elements })
+      | (ErlTypeImpl::Tuple { elements }, ErlTypeImpl::Record { tag, fields }) => {
+        fields.len() + 1 == elements.len()
+          && Self::is_overlapping(&ErlTypeImpl::new_atom(tag), &elements[0])
+          && fields
+            .iter()
+            .zip(elements[1..].iter())
+            .all(|(field, el)| Self::is_overlapping(&field.ty, el))
+      }
+
+      // Two lists which can be empty always share the `[]`
+      (
+        ErlTypeImpl::List {
+          elements: a_elements, is_non_empty: a_non_empty, ..
+        },
+        ErlTypeImpl::List { elements, is_non_empty, .. },
+      ) => !(*a_non_empty && *is_non_empty) || Self::is_overlapping(a_elements, elements),
+      (
+        ErlTypeImpl::List { elements, is_non_empty, .. },
+        ErlTypeImpl::StronglyTypedList { elements: typed, .. },
+      )
+      | (
+        ErlTypeImpl::StronglyTypedList { elements: typed, .. },
+        ErlTypeImpl::List { elements, is_non_empty, .. },
+      ) => {
+        if typed.is_empty() {
+          !is_non_empty
+        } else {
+          typed.iter().all(|t| Self::is_overlapping(t, elements))
+        }
+      }
+      (
+        ErlTypeImpl::StronglyTypedList { elements: a_elements, .. },
+        ErlTypeImpl::StronglyTypedList { elements, .. },
+      ) => {
+        a_elements.len() == elements.len()
+          && a_elements
+            .iter()
+            .zip(elements.iter())
+            .all(|(a_el, b_el)| Self::is_overlapping(a_el, b_el))
+      }
+      (ErlTypeImpl::List { is_non_empty, .. }, ErlTypeImpl::Nil)
+      | (ErlTypeImpl::Nil, ErlTypeImpl::List { is_non_empty, .. }) => !is_non_empty,
+
+      // Maps and binaries are not compared by their contents yet
+      (ErlTypeImpl::Map { .. }, ErlTypeImpl::Map { .. }) => true,
+      (ErlTypeImpl::Binary { .. }, ErlTypeImpl::Binary { .. }) => true,
+      _ if a.is_function() && b.is_function() => true,
+      _ => false,
+    }
+  }
+
+  /// For two range bounds checks `low <= high`, missing bounds are infinite
+  fn is_bound_below(low: &Option<ErlInteger>, high: &Option<ErlInteger>) -> bool {
+    match (low, high) {
+      (Some(l), Some(h)) => l <= h,
+      _ => true,
+    }
+  }
+}
@@ -12,6 +12,7 @@ lazy_static! {
   static ref PF_TYPE_BOOLEAN: ErlType = ErlTypeImpl::Boolean.into();
   static ref PF_TYPE_ANY_FUN: ErlType = ErlTypeImpl::AnyFn.into();
   static ref PF_TYPE_ANY_TUPLE: ErlType = ErlTypeImpl::AnyTuple.into();
+  static ref PF_TYPE_ANY_MAP: ErlType = ErlTypeImpl::AnyMap.into();
   static ref PF_TYPE_ANY_BINARY: ErlType = ErlTypeImpl::AnyBinary.into();
   static ref PF_TYPE_ANYLIST: ErlType = ErlTypeImpl::AnyList.into();
   static ref PF_TYPE_NIL: ErlType = ErlTypeImpl::Nil.into();
@@ -54,7 +55,6 @@ impl ErlTypeImpl {
   }
 
   /// Return a cloned instance of fun()-type
-  #[allow(dead_code)]
   pub(crate) fn any_fun() -> ErlType {
     PF_TYPE_ANY_FUN.clone()
   }
@@ -62,8 +62,11 @@ impl ErlTypeImpl {
   pub fn any_tuple() -> ErlType {
     PF_TYPE_ANY_TUPLE.clone()
   }
+  /// Return a cloned instance of map()-type
+  pub fn any_map() -> ErlType {
+    PF_TYPE_ANY_MAP.clone()
+  }
   /// Return a cloned instance of binary()-type
-  #[allow(dead_code)]
   pub(crate) fn any_binary() -> ErlType {
     PF_TYPE_ANY_BINARY.clone()
   }
@@ -0,0 +1,168 @@
+//! Checks function definitions against their `-spec` attributes
+
+use crate::erl_syntax::erl_error::ErlError;
+use crate::erl_syntax::node::erl_fn_clause::ErlFnClause;
+use crate::erl_syntax::node::erl_fn_def::ErlFnDef;
+use crate::error::ic_error::IcResult;
+use crate::project::module::module_impl::ErlModule;
+use crate::source_loc::SourceLoc;
+use crate::typing::erl_type::{ErlType, ErlTypeImpl};
+use crate::typing::fn_clause_type::FnClauseType;
+use crate::typing::type_error::TypeError;
+use libironclad_util::mfarity::MFArity;
+use std::ops::Deref;
+
+/// Contains the spec checking pass
+pub struct SpecCheck {}
+
+impl SpecCheck {
+  /// Compare every `-spec` in the module with its function definition. Problems found are added
+  /// to the module errors (specs without functions, arity mismatches, return type mismatches) and
+  /// warnings (clauses which can never match the spec). An error while checking one function,
+  /// like a spec using an undefined type, is added to the module errors, and the check continues
+  /// with the next spec.
+  pub fn verify_module(module: &ErlModule) -> IcResult<()> {
+    let mut specs = if let Ok(r_specs) = module.root_scope.fn_specs.collection.read() {
+      r_specs
+        .iter()
+        .map(|(mfa, spec)| (mfa.clone(), spec.clone()))
+        .collect::<Vec<_>>()
+    } else {
+      panic!("Can't lock module specs for reading")
+    };
+    specs.sort_by_key(|(mfa, _)| mfa.to_string());
+
+    for (mfa, spec) in specs.iter() {
+      let spec_location = module
+        .root_scope
+        .fn_spec_locations
+        .get(mfa)
+        .unwrap_or(SourceLoc::None);
+      match (module.root_scope.fn_defs.get(mfa), spec.deref()) {
+        (Some(fndef_ast), ErlTypeImpl::Fn(fn_type)) => {
+          if let Err(err) = Self::verify_fn_def(module, fndef_ast.as_fn_def(), fn_type.clauses()) {
+            module.add_ic_error(err);
+          }
+        }
+        (Some(_), other) => {
+          let msg = format!("spec for {} is not a function type: {}", mfa, other);
+          let type_err = TypeError::InvalidSpec { msg };
+          module.add_error(ErlError::new_type_error(spec_location, type_err));
+        }
+        (None, _) => Self::report_missing_fn(module, &spec_location, mfa),
+      }
+    }
+    Ok(())
+  }
+
+  /// The spec has no function with the same arity, see if other arities exist
+  fn report_missing_fn(module: &ErlModule, spec_location: &SourceLoc, mfa: &MFArity) {
+    let mut other_arities = if let Ok(r_defs) = module.root_scope.fn_defs.collection.read() {
+      r_defs
+        .keys()
+        .filter(|def_mfa| def_mfa.name == mfa.name)
+        .map(|def_mfa| def_mfa.arity)
+        .collect::<Vec<_>>()
+    } else {
+      panic!("Can't lock module functions for reading")
+    };
+    other_arities.sort_unstable();
+
+    let type_err = if other_arities.is_empty() {
+      TypeError::SpecWithoutFunction { mfa: mfa.clone() }
+    } else {
+      let arities_str: Vec<String> = other_arities.iter().map(|a| a.to_string()).collect();
+      let msg = format!(
+        "spec for {} but the function is defined with arity {}",
+        mfa,
+        arities_str.join(", ")
+      );
+      TypeError::SpecArityMismatch { msg }
+    };
+    module.add_error(ErlError::new_type_error(spec_location.clone(), type_err));
+  }
+
+  /// For each clause of the function, find the spec clauses which accept its patterns, and check
+  /// the return type against them.
+  fn verify_fn_def(
+    module: &ErlModule,
+    fndef: &ErlFnDef,
+    spec_clauses: &[FnClauseType],
+  ) -> IcResult<()> {
+    for fn_clause in fndef.clauses.iter() {
+      let location = Self::clause_location(fndef, fn_clause);
+
+      let mut arg_types = Vec::with_capacity(fn_clause.args.len());
+      for arg in fn_clause.args.iter() {
+        arg_types.push(arg.synthesize(module, &fn_clause.scope)?);
+      }
+
+      let mut matching = Vec::new();
+      for spec_clause in spec_clauses.iter() {
+        if Self::is_clause_accepting(module, &location, spec_clause, &arg_types)? {
+          matching.push(spec_clause);
+        }
+      }
+
+      if matching.is_empty() {
+        let msg = format!("{} does not match -spec {}", fn_clause, fndef.funarity);
+        let type_err = TypeError::ClauseNeverMatchesSpec { msg };
+        module.add_warning(ErlError::new_type_error(location, type_err));
+        continue;
+      }
+
+      let spec_arg_types: Vec<ErlType> = (0..fn_clause.args.len())
+        .map(|index| {
+          let types: Vec<ErlType> = matching.iter().map(|c| c.args[index].ty.clone()).collect();
+          ErlTypeImpl::new_union(&types)
+        })
+        .collect();
+      let body_scope = fn_clause.narrowed_body_scope(module, &spec_arg_types)?;
+      let ret_type = fn_clause.synthesize_clause_return_type(module, &body_scope)?;
+      let expected_types: Vec<ErlType> = matching.iter().map(|c| c.ret_ty().clone()).collect();
+      let expected =
+        module.resolve_user_type(&location, &ErlTypeImpl::new_union(&expected_types))?;
+
+      if !Self::is_return_compatible(module, &ret_type, &expected) {
+        let msg =
+          format!("{} returns {}, but the -spec allows {}", fndef.funarity, ret_type, expected);
+        let type_err = TypeError::SpecReturnMismatch { msg };
+        module.add_error(ErlError::new_type_error(fn_clause.body.location.clone(), type_err));
+      }
+    }
+    Ok(())
+  }
+
+  /// Check whether the synthesized return type fits the spec. Results of calls which can not be
+  /// typed, like calls to modules outside of the project, are `any()`. A return type containing
+  /// `any()` is only reported if none of its values can fit the spec.
+  fn is_return_compatible(module: &ErlModule, ret_type: &ErlType, expected: &ErlType) -> bool {
+    module.is_subtype(ret_type, expected)
+      || (ret_type.contains_any() && ret_type.is_overlapping(expected))
+  }
+
+  /// Check whether the spec clause can accept some values of the synthesized argument types
+  fn is_clause_accepting(
+    module: &ErlModule,
+    location: &SourceLoc,
+    spec_clause: &FnClauseType,
+    arg_types: &[ErlType],
+  ) -> IcResult<bool> {
+    for (spec_arg, arg_type) in spec_clause.args.iter().zip(arg_types.iter()) {
+      let spec_arg_type = module.resolve_user_type(location, &spec_arg.ty)?;
+      if !arg_type.is_overlapping(&spec_arg_type) {
+        return Ok(false);
+      }
+    }
+    Ok(true)
+  }
+
+  /// Function clauses do not store their location, use the first argument or the body
+  pub(crate) fn clause_location(fndef: &ErlFnDef, fn_clause: &ErlFnClause) -> SourceLoc {
+    match (fn_clause.args.first(), &fn_clause.body.location) {
+      (Some(arg), _) => arg.location.clone(),
+      (None, SourceLoc::None) => fndef.location.clone(),
+      (None, body_location) => body_location.clone(),
+    }
+  }
+}
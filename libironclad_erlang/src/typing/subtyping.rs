@@ -1,37 +1,123 @@
 //! Checks whether types are subtypes of other types
 
 use crate::literal::Literal;
+use crate::project::module::module_impl::ErlModuleImpl;
+use crate::source_loc::SourceLoc;
+use crate::typing::erl_integer::ErlInteger;
+use crate::typing::erl_type::binary_type::{BinaryTypeHeadElement, BinaryTypeTailElement};
+use crate::typing::erl_type::map_type::MapMemberType;
 use crate::typing::erl_type::{ErlType, ErlTypeImpl};
 use crate::typing::fn_clause_type::FnClauseType;
+use crate::typing::record_field_type::RecordFieldType;
+use std::cell::RefCell;
 use std::ops::Deref;
 use std::sync::Arc;
 
-/// Hosts code to check the is-subtype-of relation
-pub struct SubtypeChecker {}
+/// Hosts code to check the is-subtype-of relation. When a module is given, user-defined types and
+/// record references are replaced with their definitions from the module scope, otherwise they are
+/// only subtypes of themselves.
+pub struct SubtypeChecker<'a> {
+  /// Where to look up user-defined types and records
+  module: Option<&'a ErlModuleImpl>,
+  /// Pairs of `(sub, super)` with user-defined types or record references on either side, which
+  /// are being checked now. They are assumed to be subtypes, so that recursive types terminate.
+  assumptions: RefCell<Vec<(ErlTypeImpl, ErlTypeImpl)>>,
+}
 
-impl SubtypeChecker {
-  /// is_subtype check for `Option<Erltype>` which treats `None` as `[]`
-  pub(crate) fn is_subtype_for_list_tail(
-    sub_ty: &Option<ErlType>,
-    sup_ty: &Option<ErlType>,
+impl<'a> SubtypeChecker<'a> {
+  fn new(module: Option<&'a ErlModuleImpl>) -> Self {
+    Self { module, assumptions: RefCell::new(Vec::new()) }
+  }
+
+  /// Checks whether sub_ty is a subtype of super_ty
+  pub(crate) fn is_subtype(sub_ty: &ErlTypeImpl, super_ty: &ErlTypeImpl) -> bool {
+    SubtypeChecker::new(None).check(sub_ty, super_ty)
+  }
+
+  /// Checks whether sub_ty is a subtype of super_ty, resolving user-defined types and record
+  /// references through the module scope
+  pub(crate) fn is_subtype_in_module(
+    module: &ErlModuleImpl,
+    sub_ty: &ErlTypeImpl,
+    super_ty: &ErlTypeImpl,
   ) -> bool {
+    SubtypeChecker::new(Some(module)).check(sub_ty, super_ty)
+  }
+
+  /// is_subtype check for `Option<Erltype>` which treats `None` as `[]`
+  fn is_subtype_for_list_tail(&self, sub_ty: &Option<ErlType>, sup_ty: &Option<ErlType>) -> bool {
     let nil = Arc::new(ErlTypeImpl::Nil); // TODO: Some global NIL for all our NIL needs?
     let subt = sub_ty.clone().unwrap_or_else(|| nil.clone());
     let supt = sup_ty.clone().unwrap_or(nil);
-    Self::is_subtype(&subt, &supt)
+    self.check(&subt, &supt)
   }
 
-  /// Checks whether sub_ty is a subtype of super_ty
-  pub(crate) fn is_subtype(sub_ty: &ErlTypeImpl, super_ty: &ErlTypeImpl) -> bool {
-    match super_ty.deref() {
-      equal_supertype if equal_supertype.eq(sub_ty) => true, // equal types are mutual subtypes
-      ErlTypeImpl::Typevar(super_tv) => {
-        // unwrap typevar for sub and super into nested types and try again
-        match sub_ty.deref() {
-          ErlTypeImpl::Typevar(sub_tv) => Self::is_subtype(&sub_tv.ty, &super_tv.ty),
-          _ => Self::is_subtype(sub_ty, &super_tv.ty),
+  /// Whether the type has a definition somewhere in the module scope
+  fn is_named(ty: &ErlTypeImpl) -> bool {
+    matches!(ty, ErlTypeImpl::UserDefinedType { .. } | ErlTypeImpl::RecordRef { .. })
+  }
+
+  /// Replace a user-defined type or a record reference with its definition, if it can be found
+  fn resolve(&self, ty: &ErlTypeImpl) -> Option<ErlType> {
+    let module = self.module?;
+    match ty {
+      ErlTypeImpl::UserDefinedType { .. } => {
+        let ty: ErlType = ty.clone().into();
+        match module.resolve_user_type(&SourceLoc::None, &ty) {
+          Ok(resolved) if resolved != ty => Some(resolved),
+          _ => None,
         }
       }
+      ErlTypeImpl::RecordRef { tag, pins } => {
+        module.resolve_record_ref(&SourceLoc::None, tag, pins).ok()
+      }
+      _ => None,
+    }
+  }
+
+  /// Checks user-defined types and record references by their definitions. Returns `None` if
+  /// neither side can be resolved.
+  fn check_named(&self, sub_ty: &ErlTypeImpl, super_ty: &ErlTypeImpl) -> Option<bool> {
+    let pair = (sub_ty.clone(), super_ty.clone());
+    if self.assumptions.borrow().contains(&pair) {
+      return Some(true);
+    }
+    let sub_resolved = self.resolve(sub_ty);
+    let super_resolved = self.resolve(super_ty);
+    if sub_resolved.is_none() && super_resolved.is_none() {
+      return None;
+    }
+    let sub_ty = sub_resolved.as_deref().unwrap_or(sub_ty);
+    let super_ty = super_resolved.as_deref().unwrap_or(super_ty);
+
+    self.assumptions.borrow_mut().push(pair);
+    let result = self.check(sub_ty, super_ty);
+    self.assumptions.borrow_mut().pop();
+    Some(result)
+  }
+
+  /// Checks whether sub_ty is a subtype of super_ty
+  fn check(&self, sub_ty: &ErlTypeImpl, super_ty: &ErlTypeImpl) -> bool {
+    // none() is an empty set of values, and is contained in any type
+    if sub_ty.is_none() || sub_ty.eq(super_ty) {
+      return true;
+    }
+    if Self::is_named(sub_ty) || Self::is_named(super_ty) {
+      if let Some(result) = self.check_named(sub_ty, super_ty) {
+        return result;
+      }
+    }
+    // An union is a subtype, if all its members are subtypes
+    if let ErlTypeImpl::Union(sub_union) = sub_ty {
+      return sub_union.types.iter().all(|t| self.check(t, super_ty));
+    }
+    // unwrap typevar for sub and super into nested types and try again
+    if let ErlTypeImpl::Typevar(sub_tv) = sub_ty {
+      return self.check(&sub_tv.ty, super_ty);
+    }
+
+    match super_ty {
+      ErlTypeImpl::Typevar(super_tv) => self.check(sub_ty, &super_tv.ty),
       ErlTypeImpl::Any => true,   // any includes all subtypes
       ErlTypeImpl::None => false, // none includes nothing
       ErlTypeImpl::Atom => Self::is_subtype_of_atom(sub_ty),
@@ -39,29 +125,29 @@ impl SubtypeChecker {
       ErlTypeImpl::Number => Self::is_subtype_of_number(sub_ty),
       ErlTypeImpl::Float => Self::is_subtype_of_float(sub_ty),
       ErlTypeImpl::Integer => Self::is_subtype_of_integer(sub_ty),
-      // ErlType::IntegerRange { from, to } => { todo: only singletons and nested ranges and must be from <= n <= to }
+      ErlTypeImpl::IntegerRange { from, to } => Self::is_subtype_of_range(from, to, sub_ty),
       ErlTypeImpl::AnyTuple => Self::is_subtype_of_anytuple(sub_ty),
       ErlTypeImpl::Tuple { elements: supertype_elements } => {
-        Self::is_subtype_of_tuple(supertype_elements, sub_ty)
+        self.is_subtype_of_tuple(supertype_elements, sub_ty)
       }
-      // ErlType::Record { .. } => {}
+      ErlTypeImpl::Record { tag, fields } => self.is_subtype_of_record(tag, fields, sub_ty),
       ErlTypeImpl::AnyList => Self::is_subtype_of_anylist(sub_ty),
       ErlTypeImpl::List {
-        elements: supertype_elements, tail: supertype_tail, ..
+        elements: supertype_elements,
+        tail: supertype_tail,
+        is_non_empty: supertype_non_empty,
       } => {
-        todo!("support non-empty attribute");
-        Self::is_subtype_of_list(supertype_elements, supertype_tail, sub_ty)
+        self.is_subtype_of_list(supertype_elements, supertype_tail, *supertype_non_empty, sub_ty)
       }
       ErlTypeImpl::StronglyTypedList { elements: supertype_elements, tail: supertype_tail } => {
-        Self::is_subtype_of_strongly_typed_list(supertype_elements, supertype_tail, sub_ty)
+        self.is_subtype_of_strongly_typed_list(supertype_elements, supertype_tail, sub_ty)
       }
       ErlTypeImpl::Nil => false, // can only include other nil
 
       ErlTypeImpl::AnyMap => matches!(sub_ty, ErlTypeImpl::Map { .. }),
-      // ErlType::Map { .. } => {}
+      ErlTypeImpl::Map { members } => self.is_subtype_of_map(members, sub_ty),
       ErlTypeImpl::AnyBinary => matches!(sub_ty, ErlTypeImpl::Binary { .. }),
-      // An equal binary type can be a subtype of binary, no other matches it (checked at the top)
-      ErlTypeImpl::Binary { .. } => false,
+      ErlTypeImpl::Binary { head, tail } => Self::is_subtype_of_binary(head, tail, sub_ty),
 
       ErlTypeImpl::AnyFn => {
         matches!(sub_ty, ErlTypeImpl::Fn { .. } | ErlTypeImpl::FnRef { .. } | ErlTypeImpl::Lambda)
@@ -77,16 +163,10 @@ impl SubtypeChecker {
       ErlTypeImpl::Pid | ErlTypeImpl::Reference | ErlTypeImpl::Port => false,
 
       ErlTypeImpl::Singleton { .. } => false,
-      ErlTypeImpl::UserDefinedType { .. } => false, // can't check type inclusion for user-defined
-      ErlTypeImpl::RecordRef { .. } => false,       // can't check type inclusion for records
-
-      _ => unimplemented!(
-        "Subtype check for sub={} in super={}\nsub={:?}\nsuper={:?}",
-        sub_ty,
-        super_ty,
-        sub_ty,
-        super_ty
-      ),
+      // Any member of the union must include the subtype
+      ErlTypeImpl::Union(super_union) => super_union.types.iter().any(|t| self.check(sub_ty, t)),
+      // Definitions which were not found only include themselves (equality checked at the top)
+      ErlTypeImpl::UserDefinedType { .. } | ErlTypeImpl::RecordRef { .. } => false,
     }
   }
 
@@ -157,6 +237,41 @@ impl SubtypeChecker {
     }
   }
 
+  /// Checks whether `n` is within the range bounds, missing bound is not checked
+  fn is_in_range(from: &Option<ErlInteger>, to: &Option<ErlInteger>, n: &ErlInteger) -> bool {
+    from.as_ref().is_none_or(|a| a <= n) && to.as_ref().is_none_or(|b| n <= b)
+  }
+
+  /// Checks whether sub_ty matches an integer range `from..to` type.
+  /// A range includes integer singletons within the bounds, and other ranges which are nested.
+  fn is_subtype_of_range(
+    from: &Option<ErlInteger>,
+    to: &Option<ErlInteger>,
+    sub_ty: &ErlTypeImpl,
+  ) -> bool {
+    match sub_ty {
+      ErlTypeImpl::Integer => from.is_none() && to.is_none(),
+      ErlTypeImpl::IntegerRange { from: sub_from, to: sub_to } => {
+        let from_ok = match (from, sub_from) {
+          (None, _) => true,
+          (Some(_), None) => false,
+          (Some(a), Some(sub_a)) => a <= sub_a,
+        };
+        let to_ok = match (to, sub_to) {
+          (None, _) => true,
+          (Some(_), None) => false,
+          (Some(b), Some(sub_b)) => sub_b <= b,
+        };
+        from_ok && to_ok
+      }
+      ErlTypeImpl::Singleton { val } => match val.deref() {
+        Literal::Integer(n) => Self::is_in_range(from, to, n),
+        _ => false,
+      },
+      _ => false,
+    }
+  }
+
   /// Checks whether sub_ty matches a list() type.
   /// A list() includes any other lists() and nil []
   fn is_subtype_of_anylist(sub_ty: &ErlTypeImpl) -> bool {
@@ -167,58 +282,79 @@ impl SubtypeChecker {
   }
 
   /// Checks whether sub_ty matches a list `[supertype_elements() | supertype_tail()]` type.
+  /// A non-empty superlist does not include lists which can be empty.
   fn is_subtype_of_list(
+    &self,
     supertype_elements: &ErlTypeImpl,
     supertype_tail: &Option<ErlType>,
+    supertype_non_empty: bool,
     sub_ty: &ErlTypeImpl,
   ) -> bool {
     match sub_ty {
       // For superlist to include a sublist
-      ErlTypeImpl::List { elements: subtype_elements, tail: subtype_tail, .. } => {
-        todo!("support non-empty attribute");
-        Self::is_subtype_for_list_tail(subtype_tail, supertype_tail)
-          && subtype_elements.is_subtype_of(supertype_elements)
+      ErlTypeImpl::List {
+        elements: subtype_elements,
+        tail: subtype_tail,
+        is_non_empty: subtype_non_empty,
+      } => {
+        (*subtype_non_empty || !supertype_non_empty)
+          && self.is_subtype_for_list_tail(subtype_tail, supertype_tail)
+          && self.check(subtype_elements, supertype_elements)
       }
       // For superlist to include typed sublist
       ErlTypeImpl::StronglyTypedList { elements: subtype_elements, tail: subtype_tail } => {
-        Self::is_subtype_for_list_tail(subtype_tail, supertype_tail)
+        (!subtype_elements.is_empty() || !supertype_non_empty)
+          && self.is_subtype_for_list_tail(subtype_tail, supertype_tail)
           && subtype_elements
             .iter()
-            .all(|subt| subt.is_subtype_of(supertype_elements))
+            .all(|subt| self.check(subt, supertype_elements))
+      }
+      // A list() of anything only fits a superlist which accepts anything, and can be empty
+      ErlTypeImpl::AnyList => {
+        !supertype_non_empty
+          && supertype_tail.is_none()
+          && self.check(&ErlTypeImpl::Any, supertype_elements)
       }
-      ErlTypeImpl::Nil => true,
+      ErlTypeImpl::Nil => !supertype_non_empty,
       _ => false,
     }
   }
 
-  /// Checks whether sub_ty matches a list `[supertype_elements() | supertype_tail()]` type.
+  /// Checks whether sub_ty matches a strongly typed list of fixed length. Lists of unknown length
+  /// only fit if they can only be empty, and the typed superlist is empty too.
   fn is_subtype_of_strongly_typed_list(
+    &self,
     supertype_elements: &[ErlType],
     supertype_tail: &Option<ErlType>,
     sub_ty: &ErlTypeImpl,
   ) -> bool {
     match sub_ty {
       // For typed list to include a list
-      ErlTypeImpl::List { elements: subtype_elements, tail: subtype_tail, .. } => {
-        // Sublist type must be subtype of each superlist element
-        Self::is_subtype_for_list_tail(subtype_tail, supertype_tail)
-          && supertype_elements
-            .iter()
-            .all(|supt| subtype_elements.is_subtype_of(supt));
-        todo!("support non-empty attribute")
+      ErlTypeImpl::List {
+        elements: subtype_elements,
+        tail: subtype_tail,
+        is_non_empty: subtype_non_empty,
+      } => {
+        // A list without elements is either a `[]` or its tail
+        supertype_elements.is_empty()
+          && !subtype_non_empty
+          && subtype_elements.is_none()
+          && self.is_subtype_for_list_tail(subtype_tail, supertype_tail)
       }
       // For typed superlist to include another typed sublist
       ErlTypeImpl::StronglyTypedList { elements: subtype_elements, tail: subtype_tail } => {
         // Length must match
         // AND Each element of sublist must be subtype of corresponding element of superlist
         subtype_elements.len() == supertype_elements.len()
-          && Self::is_subtype_for_list_tail(subtype_tail, supertype_tail)
+          && self.is_subtype_for_list_tail(subtype_tail, supertype_tail)
           && subtype_elements
             .iter()
             .zip(supertype_elements.iter())
-            .all(|(subt, supert)| subt.is_subtype_of(supert))
+            .all(|(subt, supert)| self.check(subt, supert))
+      }
+      ErlTypeImpl::Nil => {
+        supertype_elements.is_empty() && self.is_subtype_for_list_tail(&None, supertype_tail)
       }
-      ErlTypeImpl::Nil => true,
       _ => false,
     }
   }
@@ -232,7 +368,7 @@ impl SubtypeChecker {
   /// Checks whether sub_ty matches a tuple(T1, T2, ...) type.
   /// A tuple(T1, T2, ...) only includes tuples of the same size, where each element is a subtype,
   /// and records of tuple-1 size, where subrecord's tag would serve as supertuple's first element
-  fn is_subtype_of_tuple(supertuple_elements: &[ErlType], sub_ty: &ErlTypeImpl) -> bool {
+  fn is_subtype_of_tuple(&self, supertuple_elements: &[ErlType], sub_ty: &ErlTypeImpl) -> bool {
     match sub_ty {
       ErlTypeImpl::Tuple { elements: subtuple_elements } => {
         // lengths must match, and each element in subtuple must be a subtype of each corresponding
@@ -241,7 +377,7 @@ impl SubtypeChecker {
           && subtuple_elements
             .iter()
             .zip(supertuple_elements.iter())
-            .all(|(subt, supert)| subt.is_subtype_of(supert))
+            .all(|(subt, supert)| self.check(subt, supert))
       }
       ErlTypeImpl::Record { tag: subrecord_tag, fields: subrecord_fields } => {
         // lengths must match counting the record tag as an element
@@ -249,16 +385,116 @@ impl SubtypeChecker {
         // remaining record fields must be subtypes of the following supertuple elements
         !supertuple_elements.is_empty()
           && subrecord_fields.len() + 1 == supertuple_elements.len()
-          && ErlTypeImpl::new_atom(subrecord_tag).is_subtype_of(&supertuple_elements[0])
+          && self.check(&ErlTypeImpl::new_atom(subrecord_tag), &supertuple_elements[0])
           && subrecord_fields
             .iter()
             .zip(supertuple_elements[1..].iter())
-            .all(|(sub_t, super_t)| sub_t.ty.is_subtype_of(super_t))
+            .all(|(sub_t, super_t)| self.check(&sub_t.ty, super_t))
+      }
+      _ => false,
+    }
+  }
+
+  /// Checks whether sub_ty matches a record `#tag{field :: T, ...}` type.
+  /// A record includes records with the same tag, where each field is a subtype, and tuples where
+  /// the first element is the tag, and the following elements are subtypes of the fields.
+  fn is_subtype_of_record(
+    &self,
+    superrecord_tag: &str,
+    superrecord_fields: &[Arc<RecordFieldType>],
+    sub_ty: &ErlTypeImpl,
+  ) -> bool {
+    match sub_ty {
+      ErlTypeImpl::Record { tag: subrecord_tag, fields: subrecord_fields } => {
+        subrecord_tag == superrecord_tag
+          && subrecord_fields.len() == superrecord_fields.len()
+          && subrecord_fields
+            .iter()
+            .zip(superrecord_fields.iter())
+            .all(|(sub_f, super_f)| {
+              sub_f.name == super_f.name && self.check(&sub_f.ty, &super_f.ty)
+            })
+      }
+      ErlTypeImpl::Tuple { elements: subtuple_elements } => {
+        subtuple_elements.len() == superrecord_fields.len() + 1
+          && self.check(&subtuple_elements[0], &ErlTypeImpl::new_atom(superrecord_tag))
+          && subtuple_elements[1..]
+            .iter()
+            .zip(superrecord_fields.iter())
+            .all(|(sub_t, super_f)| self.check(sub_t, &super_f.ty))
       }
       _ => false,
     }
   }
 
+  /// Checks whether sub_ty matches a map `#{K1 := V1, K2 => V2, ...}` type.
+  /// Every association of the submap must fit some association of the supermap, with both key and
+  /// value being subtypes, and every mandatory key of the supermap must be mandatory in the submap.
+  fn is_subtype_of_map(&self, supermap_members: &[MapMemberType], sub_ty: &ErlTypeImpl) -> bool {
+    let submap_members = match sub_ty {
+      ErlTypeImpl::Map { members } => members,
+      // A map() of anything only fits a supermap accepting any keys and values, and nothing else
+      ErlTypeImpl::AnyMap => {
+        return supermap_members.iter().all(|sup| !sup.mandatory)
+          && supermap_members.iter().any(|sup| {
+            self.check(&ErlTypeImpl::Any, &sup.key) && self.check(&ErlTypeImpl::Any, &sup.value)
+          });
+      }
+      _ => return false,
+    };
+    let all_members_fit = submap_members.iter().all(|sub| {
+      supermap_members
+        .iter()
+        .any(|sup| self.check(&sub.key, &sup.key) && self.check(&sub.value, &sup.value))
+    });
+    let all_mandatory_present = supermap_members
+      .iter()
+      .filter(|sup| sup.mandatory)
+      .all(|sup| {
+        submap_members
+          .iter()
+          .any(|sub| sub.mandatory && self.check(&sub.key, &sup.key))
+      });
+    all_members_fit && all_mandatory_present
+  }
+
+  /// Bit sizes of the binary type `<<_:Head, _:_*Tail>>` as `(Head, Tail)`, missing parts are 0
+  pub(crate) fn binary_sizes(
+    head: &Option<BinaryTypeHeadElement>,
+    tail: &Option<BinaryTypeTailElement>,
+  ) -> (usize, usize) {
+    (
+      head.as_ref().map_or(0, |BinaryTypeHeadElement(h)| *h),
+      tail.as_ref().map_or(0, |BinaryTypeTailElement(t)| *t),
+    )
+  }
+
+  /// Checks whether sub_ty matches a binary `<<_:M, _:_*N>>` type, which contains binaries of
+  /// `M + k*N` bits. A binary of `M1 + k*N1` bits is included, if `M1 >= M`, and `M1 - M` and
+  /// `N1` are divisible by `N`.
+  fn is_subtype_of_binary(
+    superbin_head: &Option<BinaryTypeHeadElement>,
+    superbin_tail: &Option<BinaryTypeTailElement>,
+    sub_ty: &ErlTypeImpl,
+  ) -> bool {
+    let (super_head, super_unit) = Self::binary_sizes(superbin_head, superbin_tail);
+    let (sub_head, sub_unit) = match sub_ty {
+      ErlTypeImpl::Binary { head, tail } => Self::binary_sizes(head, tail),
+      // Any bitstring is `<<_:_*1>>`
+      ErlTypeImpl::AnyBinary => (0, 1),
+      _ => return false,
+    };
+    if sub_head < super_head {
+      return false;
+    }
+    let extra_bits = sub_head - super_head;
+    if super_unit == 0 {
+      sub_unit == 0 && extra_bits == 0
+    } else {
+      extra_bits % super_unit == 0 && sub_unit % super_unit == 0
+    }
+  }
+
   /// Checks whether sub_ty matches a regular Erlang function type with possibly multiple clauses
   /// and multiple return types.
   fn is_subtype_of_fn(
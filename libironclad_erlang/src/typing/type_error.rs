@@ -27,6 +27,11 @@ pub enum TypeError {
     /// The MFA that's not found
     mfa: MFArity,
   },
+  /// A remote call points to a function which is not in the `-export` list of its module
+  FunctionNotExported {
+    /// The function name and arity, with the module
+    mfa: MFArity,
+  },
   /// A function call was attempted with wrong argument count
   BadArity {
     /// Message to go with the error
@@ -37,6 +42,82 @@ pub enum TypeError {
     /// Message to go with the error
     msg: String,
   },
+  /// A user-defined type is not defined in its module
+  TypeNotFound {
+    /// The type name and arity, with a module for remote types
+    name: MFArity,
+  },
+  /// A user-defined type is used with a different count of type arguments than it is defined with
+  TypeArityMismatch {
+    /// Message to go with the error
+    msg: String,
+  },
+  /// A user-defined type is defined as an alias of itself, without any structure
+  RecursiveType {
+    /// The type name and arity
+    name: MFArity,
+  },
+  /// A record reference `#tag{}` names a record which is not defined
+  RecordNotFound {
+    /// The record tag
+    tag: String,
+  },
+  /// A record reference pins a field type for a field which the record does not have
+  RecordFieldNotFound {
+    /// Message to go with the error
+    msg: String,
+  },
+  /// A remote type reference points to a type which is not in the `-export_type` list
+  TypeNotExported {
+    /// The type name and arity, with the module
+    name: MFArity,
+  },
+  /// An opaque type structure was used outside of its defining module, or two different nominal
+  /// types were mixed
+  OpaqueType {
+    /// Message to go with the error
+    msg: String,
+  },
+  /// A `-spec` attribute has no function with the same name
+  SpecWithoutFunction {
+    /// The spec name and arity
+    mfa: MFArity,
+  },
+  /// A `-spec` attribute does not describe a function
+  InvalidSpec {
+    /// Message to go with the error
+    msg: String,
+  },
+  /// A `-spec` attribute has a function with the same name but different arity
+  SpecArityMismatch {
+    /// Message to go with the error
+    msg: String,
+  },
+  /// A function clause patterns do not match any of the spec clauses
+  ClauseNeverMatchesSpec {
+    /// Message to go with the error
+    msg: String,
+  },
+  /// A function clause returns a type which is not in the spec range
+  SpecReturnMismatch {
+    /// Message to go with the error
+    msg: String,
+  },
+  /// Some values are not matched by any clause
+  NonExhaustive {
+    /// Message to go with the error, with an example of unmatched values
+    msg: String,
+  },
+  /// A clause is shadowed by the earlier clauses, or its pattern can never match
+  RedundantClause {
+    /// Message to go with the error
+    msg: String,
+  },
+  /// A `Pattern = Expr` match where no value of the expression can match the pattern
+  PatternNeverMatches {
+    /// Message to go with the error
+    msg: String,
+  },
 }
 
 impl Display for TypeError {
@@ -50,6 +131,26 @@ impl Display for TypeError {
       TypeError::BadArity { msg } => write!(f, "Bad arity: {}", msg),
       TypeError::BadArguments { msg } => write!(f, "Bad arguments: {}", msg),
       TypeError::FunctionNotFound { mfa } => write!(f, "Function not found: {}", mfa),
+      TypeError::FunctionNotExported { mfa } => write!(f, "Function is not exported: {}", mfa),
+      TypeError::TypeNotFound { name } => write!(f, "Type not found: {}", name),
+      TypeError::TypeArityMismatch { msg } => write!(f, "Type arity mismatch: {}", msg),
+      TypeError::RecursiveType { name } => {
+        write!(f, "Type is defined only through itself: {}", name)
+      }
+      TypeError::RecordNotFound { tag } => write!(f, "Record not found: #{}", tag),
+      TypeError::RecordFieldNotFound { msg } => write!(f, "Record field not found: {}", msg),
+      TypeError::TypeNotExported { name } => write!(f, "Type is not exported: {}", name),
+      TypeError::OpaqueType { msg } => write!(f, "Opaque type: {}", msg),
+      TypeError::SpecWithoutFunction { mfa } => write!(f, "Spec for undefined function: {}", mfa),
+      TypeError::InvalidSpec { msg } => write!(f, "Invalid spec: {}", msg),
+      TypeError::SpecArityMismatch { msg } => write!(f, "Spec arity mismatch: {}", msg),
+      TypeError::ClauseNeverMatchesSpec { msg } => {
+        write!(f, "Clause can never match the spec: {}", msg)
+      }
+      TypeError::SpecReturnMismatch { msg } => write!(f, "Return type mismatch: {}", msg),
+      TypeError::NonExhaustive { msg } => write!(f, "Non-exhaustive clauses: {}", msg),
+      TypeError::RedundantClause { msg } => write!(f, "Redundant clause: {}", msg),
+      TypeError::PatternNeverMatches { msg } => write!(f, "Pattern can never match: {}", msg),
     }
   }
 }
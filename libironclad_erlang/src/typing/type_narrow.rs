@@ -1,17 +1,572 @@
 //! Contains logic to narrow a wider type to a more limited type.
+//! Types are narrowed by the guards and the patterns, and the values matched by a clause are
+//! subtracted from the types seen by the following clauses.
 
-use crate::typing::erl_type::ErlTypeImpl;
+use crate::erl_syntax::erl_ast::node_impl::AstNodeType;
+use crate::erl_syntax::erl_ast::AstNode;
+use crate::erl_syntax::erl_op::ErlBinaryOp;
+use crate::erl_syntax::node::erl_callable_target::CallableTarget;
+use crate::erl_syntax::node::erl_fn_clause::ErlFnClause;
+use crate::error::ic_error::IcResult;
+use crate::literal::Literal;
+use crate::project::module::module_impl::{ErlModule, ErlModuleImpl};
+use crate::project::module::scope::scope_impl::{Scope, ScopeImpl};
+use crate::typing::erl_integer::ErlInteger;
+use crate::typing::erl_type::{ErlType, ErlTypeImpl};
+use std::collections::{HashMap, HashSet};
+use std::ops::Deref;
 
 impl ErlTypeImpl {
-  // /// Handle situation when `self` type is used somewhere.
-  // /// `Self` type containing the `used_as` type is a successful check and can return updated `self`
-  // /// type, for example to update the variable scope.
-  // pub(crate) fn narrow_type(&self, usages: &[ErlType]) -> IcResult<Option<ErlType>> {
-  //   if usages.iter().all(|usage| usage.is_subtype_of(self)) {
-  //     // compatible with all usages
-  //     return Ok(None)
-  //   } else {
-  //     ErlError::internal("type_error: incompatible".to_string())
-  //   }
-  // }
+  /// Narrow `self` to only the values which also belong to `other`. When the intersection can't be
+  /// expressed precisely, a wider type is returned.
+  pub(crate) fn narrow(self: &ErlType, other: &ErlType) -> ErlType {
+    match (self.deref(), other.deref()) {
+      (ErlTypeImpl::Typevar(tv), _) => tv.ty.narrow(other),
+      (_, ErlTypeImpl::Typevar(tv)) => self.narrow(&tv.ty),
+      _ if self.is_subtype_of(other) => self.clone(),
+      _ if other.is_subtype_of(self) => other.clone(),
+      (ErlTypeImpl::Union(u), _) => {
+        let members: Vec<ErlType> = u.types.iter().map(|t| t.narrow(other)).collect();
+        ErlTypeImpl::new_union(&members)
+      }
+      (_, ErlTypeImpl::Union(u)) => {
+        let members: Vec<ErlType> = u.types.iter().map(|t| self.narrow(t)).collect();
+        ErlTypeImpl::new_union(&members)
+      }
+      _ if !self.is_overlapping(other) => ErlTypeImpl::none(),
+
+      // Unknown types are replaced by what we learned about them
+      (ErlTypeImpl::UserDefinedType { .. } | ErlTypeImpl::RecordRef { .. }, _) => other.clone(),
+
+      (
+        ErlTypeImpl::IntegerRange { from: a_from, to: a_to },
+        ErlTypeImpl::IntegerRange { from, to },
+      ) => {
+        let new_from = Self::max_bound(a_from, from, true);
+        let new_to = Self::max_bound(a_to, to, false);
+        Self::new_range_checked(new_from, new_to)
+      }
+      (ErlTypeImpl::Tuple { elements: a_elements }, ErlTypeImpl::Tuple { elements }) => {
+        let narrowed: Vec<ErlType> = a_elements
+          .iter()
+          .zip(elements.iter())
+          .map(|(a_el, b_el)| a_el.narrow(b_el))
+          .collect();
+        if narrowed.iter().any(|t| t.is_none()) {
+          ErlTypeImpl::none()
+        } else {
+          ErlTypeImpl::new_tuple_move(narrowed)
+        }
+      }
+      (
+        ErlTypeImpl::List {
+          elements: a_elements,
+          tail,
+          is_non_empty: a_non_empty,
+        },
+        ErlTypeImpl::List { elements, is_non_empty, .. },
+      ) => ErlTypeImpl::List {
+        elements: a_elements.narrow(elements),
+        tail: tail.clone(),
+        is_non_empty: *a_non_empty || *is_non_empty,
+      }
+      .into(),
+      _ => self.clone(),
+    }
+  }
+
+  /// Remove values of `other` from `self`. When the difference can't be expressed precisely,
+  /// `self` or another wider type is returned. User-defined types and record references are
+  /// compared by their definitions in the `module`.
+  pub(crate) fn subtract(self: &ErlType, other: &ErlType, module: &ErlModuleImpl) -> ErlType {
+    match (self.deref(), other.deref()) {
+      (ErlTypeImpl::Typevar(tv), _) => tv.ty.subtract(other, module),
+      _ if module.is_subtype(self, other) => ErlTypeImpl::none(),
+      (ErlTypeImpl::Union(u), _) => {
+        let members: Vec<ErlType> = u.types.iter().map(|t| t.subtract(other, module)).collect();
+        ErlTypeImpl::new_union(&members)
+      }
+      (_, ErlTypeImpl::Union(u)) => u
+        .types
+        .iter()
+        .fold(self.clone(), |accum, t| accum.subtract(t, module)),
+      (ErlTypeImpl::Boolean, ErlTypeImpl::Singleton { val }) => match val.deref() {
+        Literal::Atom(a) if a == "true" => ErlTypeImpl::new_atom("false"),
+        Literal::Atom(a) if a == "false" => ErlTypeImpl::new_atom("true"),
+        _ => self.clone(),
+      },
+      (ErlTypeImpl::IntegerRange { from, to }, ErlTypeImpl::Singleton { val }) => match val.deref()
+      {
+        Literal::Integer(n) => {
+          Self::subtract_from_range(from, to, &Some(n.clone()), &Some(n.clone()))
+        }
+        _ => self.clone(),
+      },
+      (
+        ErlTypeImpl::IntegerRange { from, to },
+        ErlTypeImpl::IntegerRange { from: b_from, to: b_to },
+      ) => Self::subtract_from_range(from, to, b_from, b_to),
+      (ErlTypeImpl::Integer, ErlTypeImpl::IntegerRange { from: b_from, to: b_to }) => {
+        Self::subtract_from_range(&None, &None, b_from, b_to)
+      }
+      (ErlTypeImpl::Tuple { elements: a_elements }, ErlTypeImpl::Tuple { elements })
+        if a_elements.len() == elements.len() =>
+      {
+        Self::subtract_from_tuple(self, a_elements, elements, module)
+      }
+      _ => self.clone(),
+    }
+  }
+
+  /// A tuple difference can be expressed, if all elements but one are fully covered by `elements`,
+  /// then the remaining element is subtracted.
+  fn subtract_from_tuple(
+    self: &ErlType,
+    a_elements: &[ErlType],
+    elements: &[ErlType],
+    module: &ErlModuleImpl,
+  ) -> ErlType {
+    let uncovered: Vec<usize> = (0..elements.len())
+      .filter(|i| !module.is_subtype(&a_elements[*i], &elements[*i]))
+      .collect();
+    match uncovered.as_slice() {
+      [index] => {
+        let element = a_elements[*index].subtract(&elements[*index], module);
+        if element.is_none() {
+          ErlTypeImpl::none()
+        } else {
+          let mut result = a_elements.to_vec();
+          result[*index] = element;
+          ErlTypeImpl::new_tuple_move(result)
+        }
+      }
+      _ => self.clone(),
+    }
+  }
+
+  /// Pick the tighter of two range bounds. For lower bounds (`is_lower`) the larger wins, for upper
+  /// bounds the smaller wins. Missing bounds are infinite.
+  fn max_bound(
+    a: &Option<ErlInteger>,
+    b: &Option<ErlInteger>,
+    is_lower: bool,
+  ) -> Option<ErlInteger> {
+    match (a, b) {
+      (None, _) => b.clone(),
+      (_, None) => a.clone(),
+      (Some(x), Some(y)) => {
+        if (x > y) == is_lower {
+          a.clone()
+        } else {
+          b.clone()
+        }
+      }
+    }
+  }
+
+  /// Create an integer range, which might end up being empty, a single value or all integers
+  pub(crate) fn new_range_checked(from: Option<ErlInteger>, to: Option<ErlInteger>) -> ErlType {
+    match (&from, &to) {
+      (None, None) => ErlTypeImpl::integer(),
+      (Some(a), Some(b)) if a > b => ErlTypeImpl::none(),
+      (Some(a), Some(b)) if a == b => {
+        ErlTypeImpl::new_singleton(&Literal::Integer(a.clone()).into())
+      }
+      _ => ErlTypeImpl::new_range(from, to),
+    }
+  }
+
+  /// Remove range `b_from..b_to` from the range `from..to`. Only cuts from the ends of the range are
+  /// precise, a hole in the middle of the range is not represented.
+  fn subtract_from_range(
+    from: &Option<ErlInteger>,
+    to: &Option<ErlInteger>,
+    b_from: &Option<ErlInteger>,
+    b_to: &Option<ErlInteger>,
+  ) -> ErlType {
+    let one = ErlInteger::Small(1);
+    let is_disjoint = match (b_from, to, b_to, from) {
+      (Some(b_a), Some(a_b), _, _) if b_a > a_b => true,
+      (_, _, Some(b_b), Some(a_a)) if b_b < a_a => true,
+      _ => false,
+    };
+    if is_disjoint {
+      return Self::new_range_checked(from.clone(), to.clone());
+    }
+    let covers_start = match (from, b_from) {
+      (_, None) => true,
+      (None, Some(_)) => false,
+      (Some(a), Some(b)) => b <= a,
+    };
+    let covers_end = match (to, b_to) {
+      (_, None) => true,
+      (None, Some(_)) => false,
+      (Some(a), Some(b)) => a <= b,
+    };
+    match (covers_start, covers_end) {
+      (true, true) => ErlTypeImpl::none(),
+      // The start of the range is removed, new range begins after `b_to`
+      (true, false) => {
+        let new_from = b_to.as_ref().map(|b| b.clone() + one);
+        Self::new_range_checked(new_from, to.clone())
+      }
+      // The end of the range is removed, new range ends before `b_from`
+      (false, true) => {
+        let new_to = b_from.as_ref().map(|b| b.clone() - one);
+        Self::new_range_checked(from.clone(), new_to)
+      }
+      (false, false) => Self::new_range_checked(from.clone(), to.clone()),
+    }
+  }
+}
+
+/// Narrows variable types in a scope, using the knowledge from guards and patterns
+pub struct TypeNarrow {}
+
+impl TypeNarrow {
+  /// Create a copy of the scope, where variables have the types narrowed by the guard. Guards in
+  /// `guard1, guard2` all must succeed, in `guard1; guard2` only one must succeed.
+  pub(crate) fn narrow_scope_by_guard(
+    module: &ErlModule,
+    scope: &Scope,
+    guard: &AstNode,
+  ) -> IcResult<Scope> {
+    let mut variables = scope.variables.collection.read().unwrap().clone();
+    Self::narrow_by_guard(module, scope, guard, &mut variables)?;
+    Ok(ScopeImpl::new(scope.name.clone(), scope.parent_scope.clone(), variables))
+  }
+
+  /// Create a copy of the scope, where the variable has a new type
+  pub(crate) fn bind_var(scope: &Scope, var_name: &str, ty: &ErlType) -> Scope {
+    let mut variables = scope.variables.collection.read().unwrap().clone();
+    variables.insert(var_name.to_string(), ty.clone());
+    ScopeImpl::new(scope.name.clone(), scope.parent_scope.clone(), variables)
+  }
+
+  /// Update `variables` with the knowledge from the guard expression
+  fn narrow_by_guard(
+    module: &ErlModule,
+    scope: &Scope,
+    guard: &AstNode,
+    variables: &mut HashMap<String, ErlType>,
+  ) -> IcResult<()> {
+    match &guard.content {
+      AstNodeType::BinaryOp { binop_expr } => {
+        let (left, right) = (&binop_expr.left, &binop_expr.right);
+        match binop_expr.operator {
+          ErlBinaryOp::Comma | ErlBinaryOp::AndAlso | ErlBinaryOp::And => {
+            Self::narrow_by_guard(module, scope, left, variables)?;
+            Self::narrow_by_guard(module, scope, right, variables)
+          }
+          ErlBinaryOp::Semicolon | ErlBinaryOp::OrElse | ErlBinaryOp::Or => {
+            let mut left_vars = variables.clone();
+            Self::narrow_by_guard(module, scope, left, &mut left_vars)?;
+            let mut right_vars = variables.clone();
+            Self::narrow_by_guard(module, scope, right, &mut right_vars)?;
+            // Either branch could have succeeded, so the variable can have either narrowed type
+            for (name, ty) in variables.iter_mut() {
+              let left_ty = left_vars.get(name).unwrap_or(ty).clone();
+              let right_ty = right_vars.get(name).unwrap_or(ty).clone();
+              *ty = ErlTypeImpl::new_union(&[left_ty, right_ty]);
+            }
+            Ok(())
+          }
+          op => Self::narrow_by_comparison(module, scope, op, left, right, variables),
+        }
+      }
+      AstNodeType::Apply(apply) => {
+        if let Some(name) = Self::erlang_fn_name(&apply.target) {
+          Self::narrow_by_bif(&name, &apply.args, variables);
+        }
+        Ok(())
+      }
+      _ => Ok(()),
+    }
+  }
+
+  /// Narrow a variable type by applying a type to it
+  fn narrow_var(variables: &mut HashMap<String, ErlType>, var_name: &str, ty: &ErlType) {
+    let current = variables
+      .get(var_name)
+      .cloned()
+      .unwrap_or_else(ErlTypeImpl::any);
+    variables.insert(var_name.to_string(), current.narrow(ty));
+  }
+
+  /// Type guard BIFs like `is_integer(X)` narrow the type of the variable
+  fn narrow_by_bif(name: &str, args: &[AstNode], variables: &mut HashMap<String, ErlType>) {
+    let type_test = match name {
+      "is_record" => Some(ErlTypeImpl::any_tuple()),
+      _ => Self::type_test_bif(name),
+    };
+    if let (Some(ty), Some(arg)) = (type_test, args.first()) {
+      if let AstNodeType::Var(v) = &arg.content {
+        Self::narrow_var(variables, &v.name, &ty);
+      }
+      return;
+    }
+    if let ("is_map_key", [_key, map_arg]) = (name, args) {
+      if let AstNodeType::Var(v) = &map_arg.content {
+        Self::narrow_var(variables, &v.name, &ErlTypeImpl::any_map());
+      }
+    }
+  }
+
+  /// For a type test BIF like `is_integer` returns the type, which the tested value must have
+  pub(crate) fn type_test_bif(name: &str) -> Option<ErlType> {
+    match name {
+      "is_atom" => Some(ErlTypeImpl::atom()),
+      "is_boolean" => Some(ErlTypeImpl::boolean()),
+      "is_integer" => Some(ErlTypeImpl::integer()),
+      "is_float" => Some(ErlTypeImpl::float()),
+      "is_number" => Some(ErlTypeImpl::number()),
+      "is_list" => Some(ErlTypeImpl::any_list()),
+      "is_tuple" => Some(ErlTypeImpl::any_tuple()),
+      "is_map" => Some(ErlTypeImpl::any_map()),
+      "is_binary" | "is_bitstring" => Some(ErlTypeImpl::any_binary()),
+      "is_function" => Some(ErlTypeImpl::any_fun()),
+      "is_pid" => Some(ErlTypeImpl::pid()),
+      "is_port" => Some(ErlTypeImpl::port()),
+      "is_reference" => Some(ErlTypeImpl::reference()),
+      _ => None,
+    }
+  }
+
+  /// Narrow by comparing a variable with a value, like `X > 0` or `X =:= ok`, or by comparing an
+  /// `element(N, Tuple)` with a value.
+  fn narrow_by_comparison(
+    module: &ErlModule,
+    scope: &Scope,
+    op: ErlBinaryOp,
+    left: &AstNode,
+    right: &AstNode,
+    variables: &mut HashMap<String, ErlType>,
+  ) -> IcResult<()> {
+    // Put the variable (or the element call) on the left side
+    let (op, subject, value) = if Self::is_narrowable_subject(left) {
+      (op, left, right)
+    } else if Self::is_narrowable_subject(right) {
+      match Self::flip_comparison(op) {
+        Some(flipped) => (flipped, right, left),
+        None => return Ok(()),
+      }
+    } else {
+      return Ok(());
+    };
+
+    // Only literal values are useful for narrowing
+    let value_ty = value.synthesize(module, scope)?;
+    if !matches!(value_ty.deref(), ErlTypeImpl::Singleton { .. }) {
+      return Ok(());
+    }
+
+    match &subject.content {
+      AstNodeType::Var(v) => {
+        let current = variables
+          .get(&v.name)
+          .cloned()
+          .unwrap_or_else(ErlTypeImpl::any);
+        if let Some(narrowed) = Self::narrow_compared(module, &current, op, &value_ty) {
+          variables.insert(v.name.clone(), narrowed);
+        }
+      }
+      AstNodeType::Apply(apply) => {
+        if let (Some(index), AstNodeType::Var(v)) =
+          (Self::element_index(subject), &apply.args[1].content)
+        {
+          if op == ErlBinaryOp::HardEq || op == ErlBinaryOp::Eq {
+            let current = variables
+              .get(&v.name)
+              .cloned()
+              .unwrap_or_else(ErlTypeImpl::any);
+            let narrowed = Self::narrow_tuple_element(&current, index, &value_ty);
+            variables.insert(v.name.clone(), narrowed);
+          }
+        }
+      }
+      _ => {}
+    }
+    Ok(())
+  }
+
+  /// For a call target `name` or `erlang:name` returns the function name, as guard BIFs are only
+  /// found in the `erlang` module.
+  pub(crate) fn erlang_fn_name(target: &CallableTarget) -> Option<String> {
+    match target {
+      CallableTarget::MFArity(mfa) => match mfa.module.as_deref() {
+        None | Some("erlang") => Some(mfa.name.clone()),
+        Some(_) => None,
+      },
+      CallableTarget::Expr(expr) => expr.get_atom_text(),
+      CallableTarget::MFAExpression { module: None, function, .. } => function.get_atom_text(),
+      CallableTarget::MFAExpression { module: Some(module), function, .. } => {
+        if module.get_atom_text().as_deref() == Some("erlang") {
+          function.get_atom_text()
+        } else {
+          None
+        }
+      }
+    }
+  }
+
+  /// Variables and `element(N, Var)` calls can be narrowed by comparisons
+  fn is_narrowable_subject(node: &AstNode) -> bool {
+    node.is_var() || Self::element_index(node).is_some()
+  }
+
+  /// For `element(N, Var)` returns the literal index N
+  fn element_index(node: &AstNode) -> Option<usize> {
+    if let AstNodeType::Apply(apply) = &node.content {
+      let is_element = Self::erlang_fn_name(&apply.target).is_some_and(|n| n == "element");
+      if is_element && apply.args.len() == 2 && apply.args[1].is_var() {
+        if let AstNodeType::Lit { value } = &apply.args[0].content {
+          if let Literal::Integer(i) = value.deref() {
+            return i.as_usize().filter(|i| *i > 0);
+          }
+        }
+      }
+    }
+    None
+  }
+
+  /// Swap the comparison sides: `0 < X` becomes `X > 0`
+  fn flip_comparison(op: ErlBinaryOp) -> Option<ErlBinaryOp> {
+    match op {
+      ErlBinaryOp::Less => Some(ErlBinaryOp::Greater),
+      ErlBinaryOp::Greater => Some(ErlBinaryOp::Less),
+      ErlBinaryOp::LessEq => Some(ErlBinaryOp::GreaterEq),
+      ErlBinaryOp::GreaterEq => Some(ErlBinaryOp::LessEq),
+      ErlBinaryOp::Eq | ErlBinaryOp::NotEq | ErlBinaryOp::HardEq | ErlBinaryOp::HardNotEq => {
+        Some(op)
+      }
+      _ => None,
+    }
+  }
+
+  /// Type of the variable after the comparison `var OP value` succeeded
+  fn narrow_compared(
+    module: &ErlModuleImpl,
+    current: &ErlType,
+    op: ErlBinaryOp,
+    value_ty: &ErlType,
+  ) -> Option<ErlType> {
+    let n = match value_ty.deref() {
+      ErlTypeImpl::Singleton { val } => match val.deref() {
+        Literal::Integer(n) => Some(n.clone()),
+        _ => None,
+      },
+      _ => None,
+    };
+    let one = ErlInteger::Small(1);
+    match op {
+      ErlBinaryOp::HardEq => Some(current.narrow(value_ty)),
+      // `==` also compares integers with floats
+      ErlBinaryOp::Eq if n.is_none() => Some(current.narrow(value_ty)),
+      ErlBinaryOp::HardNotEq => Some(current.subtract(value_ty, module)),
+      // Ordering comparisons narrow to a range only for integers, in the term order other types
+      // can be compared with numbers too
+      _ if n.is_none() || !current.is_subtype_of(&ErlTypeImpl::integer()) => None,
+      ErlBinaryOp::Greater => {
+        Some(current.narrow(&ErlTypeImpl::new_range(n.map(|n| n + one), None)))
+      }
+      ErlBinaryOp::GreaterEq => Some(current.narrow(&ErlTypeImpl::new_range(n, None))),
+      ErlBinaryOp::Less => Some(current.narrow(&ErlTypeImpl::new_range(None, n.map(|n| n - one)))),
+      ErlBinaryOp::LessEq => Some(current.narrow(&ErlTypeImpl::new_range(None, n))),
+      _ => None,
+    }
+  }
+
+  /// After `element(index, T) =:= value` succeeded, `T` is a tuple with at least `index` elements.
+  /// Tuple types in an union are filtered by their element at `index`.
+  fn narrow_tuple_element(current: &ErlType, index: usize, value_ty: &ErlType) -> ErlType {
+    let narrow_one = |t: &ErlType| -> ErlType {
+      match t.deref() {
+        ErlTypeImpl::Tuple { elements } if elements.len() >= index => {
+          let mut elements = elements.clone();
+          elements[index - 1] = elements[index - 1].narrow(value_ty);
+          if elements[index - 1].is_none() {
+            ErlTypeImpl::none()
+          } else {
+            ErlTypeImpl::new_tuple_move(elements)
+          }
+        }
+        ErlTypeImpl::Tuple { .. } => ErlTypeImpl::none(),
+        _ => t.narrow(&ErlTypeImpl::any_tuple()),
+      }
+    };
+    match current.deref() {
+      ErlTypeImpl::Union(u) => {
+        let members: Vec<ErlType> = u.types.iter().map(narrow_one).collect();
+        ErlTypeImpl::new_union(&members)
+      }
+      _ => narrow_one(current),
+    }
+  }
+
+  /// A pattern is exact, if it matches every value of its synthesized type, so the type can be
+  /// subtracted from the following clauses. Literals and fresh variables are exact, tuples and
+  /// lists are exact if their elements are. A variable repeated in the patterns is not exact.
+  /// A variable already bound in the enclosing `scope` only matches its value, and is not exact.
+  /// Function heads pass no scope, because their variables are always fresh.
+  pub(crate) fn is_exact_pattern(scope: Option<&Scope>, patterns: &[AstNode]) -> bool {
+    let mut seen_vars = HashSet::new();
+    patterns
+      .iter()
+      .all(|p| Self::is_exact_pattern_node(scope, p, &mut seen_vars))
+  }
+
+  fn is_exact_pattern_node(
+    scope: Option<&Scope>,
+    pattern: &AstNode,
+    seen_vars: &mut HashSet<String>,
+  ) -> bool {
+    match &pattern.content {
+      AstNodeType::Lit { .. } => true,
+      AstNodeType::Var(v)
+        if v.name != "_" && scope.is_some_and(|s| s.retrieve_var_from(v).is_some()) =>
+      {
+        false
+      }
+      AstNodeType::Var(v) => v.name.starts_with('_') || seen_vars.insert(v.name.clone()),
+      AstNodeType::Tuple { elements } => elements
+        .iter()
+        .all(|el| Self::is_exact_pattern_node(scope, el, seen_vars)),
+      AstNodeType::List { elements, tail } => {
+        elements
+          .iter()
+          .all(|el| Self::is_exact_pattern_node(scope, el, seen_vars))
+          && tail
+            .as_ref()
+            .is_none_or(|t| Self::is_exact_pattern_node(scope, t, seen_vars))
+      }
+      _ => false,
+    }
+  }
+
+  /// Remove the values matched by a function clause from the argument types for the following
+  /// clauses. This is only possible if the clause has no guard, its patterns are exact, and at
+  /// most one of the arguments is not a plain variable.
+  pub(crate) fn subtract_fn_clause(
+    module: &ErlModule,
+    fn_clause: &ErlFnClause,
+    arg_types: &[ErlType],
+  ) -> IcResult<Vec<ErlType>> {
+    if fn_clause.guard_expr.is_some() || !Self::is_exact_pattern(None, &fn_clause.args) {
+      return Ok(arg_types.to_vec());
+    }
+    let refutable: Vec<usize> = (0..fn_clause.args.len())
+      .filter(|i| !fn_clause.args[*i].is_var())
+      .collect();
+    match refutable.as_slice() {
+      // All arguments are variables, nothing remains for the following clauses
+      [] => Ok(vec![ErlTypeImpl::none(); arg_types.len()]),
+      [index] => {
+        let pattern_ty = fn_clause.args[*index].synthesize(module, &fn_clause.scope)?;
+        let mut result = arg_types.to_vec();
+        result[*index] = arg_types[*index].subtract(&pattern_ty, module);
+        Ok(result)
+      }
+      _ => Ok(arg_types.to_vec()),
+    }
+  }
 }
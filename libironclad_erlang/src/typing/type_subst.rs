@@ -0,0 +1,100 @@
+//! Substitutes named type variables with types, to instantiate parametrized user types
+
+use crate::typing::erl_type::map_type::MapMemberType;
+use crate::typing::erl_type::{ErlType, ErlTypeImpl};
+use crate::typing::fn_clause_type::FnClauseType;
+use crate::typing::fn_type::FnType;
+use crate::typing::record_field_type::RecordFieldType;
+use crate::typing::typevar::Typevar;
+use std::collections::HashMap;
+use std::ops::Deref;
+use std::sync::Arc;
+
+impl ErlTypeImpl {
+  /// Replace type variables, which have a name found in `bindings`, with the bound types. Nested
+  /// user-defined types only get their arguments substituted, and are not expanded, so that
+  /// recursive types can be expanded lazily, one level at a time.
+  pub(crate) fn substitute(self: &ErlType, bindings: &HashMap<String, ErlType>) -> ErlType {
+    if bindings.is_empty() {
+      return self.clone();
+    }
+    let subst = |t: &ErlType| t.substitute(bindings);
+    let subst_all = |types: &[ErlType]| types.iter().map(subst).collect::<Vec<_>>();
+    match self.deref() {
+      ErlTypeImpl::Typevar(tv) => match tv.name.as_ref().and_then(|name| bindings.get(name)) {
+        Some(bound) => bound.clone(),
+        None => ErlTypeImpl::new_typevar(Self::substitute_typevar(tv, bindings)),
+      },
+      ErlTypeImpl::Tuple { elements } => ErlTypeImpl::new_tuple_move(subst_all(elements)),
+      ErlTypeImpl::Record { tag, fields } => ErlTypeImpl::Record {
+        tag: tag.clone(),
+        fields: fields
+          .iter()
+          .map(|f| Arc::new(RecordFieldType::new(f.name.clone(), subst(&f.ty))))
+          .collect(),
+      }
+      .into(),
+      ErlTypeImpl::List { elements, tail, is_non_empty } => ErlTypeImpl::List {
+        elements: subst(elements),
+        tail: tail.as_ref().map(subst),
+        is_non_empty: *is_non_empty,
+      }
+      .into(),
+      ErlTypeImpl::StronglyTypedList { elements, tail } => ErlTypeImpl::StronglyTypedList {
+        elements: subst_all(elements),
+        tail: tail.as_ref().map(subst),
+      }
+      .into(),
+      ErlTypeImpl::Map { members } => ErlTypeImpl::new_map(
+        members
+          .iter()
+          .map(|m| MapMemberType { key: subst(&m.key), value: subst(&m.value), ..*m })
+          .collect(),
+      ),
+      ErlTypeImpl::Fn(fn_type) => {
+        let clauses: Vec<FnClauseType> = fn_type
+          .clauses()
+          .iter()
+          .map(|clause| {
+            FnClauseType::new(
+              clause
+                .args
+                .iter()
+                .map(|arg| Self::substitute_typevar(arg, bindings))
+                .collect(),
+              Self::substitute_typevar(&clause.ret_type, bindings),
+            )
+          })
+          .collect();
+        ErlTypeImpl::Fn(FnType::new(fn_type.arity(), &clauses).into()).into()
+      }
+      ErlTypeImpl::Union(u) => ErlTypeImpl::new_union(&subst_all(&u.types)),
+      ErlTypeImpl::UserDefinedType { name, args } => ErlTypeImpl::UserDefinedType {
+        name: name.clone(),
+        args: args
+          .iter()
+          .map(|arg| Self::substitute_typevar(arg, bindings))
+          .collect(),
+      }
+      .into(),
+      ErlTypeImpl::RecordRef { tag, pins } => ErlTypeImpl::RecordRef {
+        tag: tag.clone(),
+        pins: pins
+          .iter()
+          .map(|pin| RecordFieldType::new(pin.name.clone(), subst(&pin.ty)))
+          .collect(),
+      }
+      .into(),
+      _ => self.clone(),
+    }
+  }
+
+  /// A type variable with a bound name becomes an unnamed type variable with the bound type,
+  /// otherwise its type is substituted
+  fn substitute_typevar(tv: &Typevar, bindings: &HashMap<String, ErlType>) -> Typevar {
+    match tv.name.as_ref().and_then(|name| bindings.get(name)) {
+      Some(bound) => Typevar::from_erltype(bound),
+      None => Typevar::new(tv.name.clone(), Some(tv.ty.substitute(bindings))),
+    }
+  }
+}
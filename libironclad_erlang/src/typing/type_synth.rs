@@ -1,15 +1,41 @@
 //! Synthesize a type from AST node
 
 use crate::erl_syntax::erl_ast::node_impl::AstNodeType::{
-  Apply, BinaryOp, FnDef, FnRef, List, Lit, Tuple, Var,
+  Apply, BeginEnd, BinaryComprehension, BinaryExpr, BinaryOp, CClause, CaseExpr, CommaExpr, FnDef,
+  FnRef, IfStatement, List, ListComprehension, ListComprehensionGenerator, Lit, MapBuilder,
+  MapComprehension, MapComprehensionGenerator, MaybeExpr, MaybeMatch, ModuleForms, Receive,
+  RecordBuilder, RecordField, TryCatch, Tuple, Type, UnaryOp, Var, MFA,
 };
 use crate::erl_syntax::erl_ast::node_impl::{AstNodeImpl, AstNodeType};
 use crate::erl_syntax::erl_ast::AstNode;
 use crate::erl_syntax::erl_error::ErlError;
+use crate::erl_syntax::erl_op::ErlBinaryOp;
+use crate::erl_syntax::node::erl_binary_element::{
+  BinaryElement, TypeSpecifier, ValueType, ValueWidth,
+};
+use crate::erl_syntax::node::erl_case_clause::ErlCaseClause;
+use crate::erl_syntax::node::erl_catch_clause::CatchClause;
+use crate::erl_syntax::node::erl_if_clause::ErlIfClause;
+use crate::erl_syntax::node::erl_map::MapBuilderMember;
+use crate::erl_syntax::node::erl_receive_after::ErlReceiveAfter;
+use crate::erl_syntax::node::erl_record::RecordBuilderMember;
 use crate::error::ic_error::IcResult;
-use crate::project::module::module_impl::ErlModule;
-use crate::project::module::scope::scope_impl::Scope;
+use crate::literal::Literal;
+use crate::project::module::module_impl::{ErlModule, ErlModuleImpl};
+use crate::project::module::scope::scope_impl::{Scope, ScopeImpl};
+use crate::source_loc::SourceLoc;
+use crate::typing::bif::BifTable;
+use crate::typing::destructure::Destructure;
+use crate::typing::erl_integer::ErlInteger;
+use crate::typing::erl_type::binary_type::{BinaryTypeHeadElement, BinaryTypeTailElement};
+use crate::typing::erl_type::map_type::MapMemberType;
 use crate::typing::erl_type::{ErlType, ErlTypeImpl};
+use crate::typing::record_field_type::RecordFieldType;
+use crate::typing::subtyping::SubtypeChecker;
+use crate::typing::type_narrow::TypeNarrow;
+use libironclad_util::mfarity::MFArity;
+use std::ops::Deref;
+use std::sync::Arc;
 
 impl AstNodeImpl {
   /// From AST subtree, create a type which we believe it will have, narrowest possible.
@@ -19,30 +45,325 @@ impl AstNodeImpl {
       AstNodeType::Empty { comment } => {
         unreachable!("Should not be synthesizing type from AST node: Empty({})", comment)
       }
+      ModuleForms { forms } => {
+        // Only the functions are typed, other forms have no value
+        for form in forms.iter().filter(|f| matches!(f.content, FnDef(_))) {
+          form.synthesize(module, scope)?;
+        }
+        Ok(ErlTypeImpl::any())
+      }
       FnDef(fndef) => fndef.synthesize_function_type(module, scope),
+      MFA { mfarity } => Self::synthesize_mfa_type(module, &self.location, scope, mfarity),
+      Type { ty } => Ok(ty.clone()),
+      CClause(_, clause) => clause.synthesize_body_type(module, scope),
+      FnRef { mfa, .. } if mfa.module.is_some() => {
+        Self::synthesize_mfa_type(module, &self.location, scope, mfa)
+      }
       FnRef { mfa, .. } => match module.root_scope.fn_defs.get(mfa) {
-        None => ErlError::local_function_not_found(
-          self.location.clone(),
-          mfa.clone(),
-          format!("Function reference points to a non-existent local function: {}", mfa),
-        ),
         Some(fndef) => Ok(fndef.as_fn_def().synthesize_function_type(module, scope)?),
+        None => match BifTable::erlang().find_spec(mfa) {
+          // Auto-imported BIFs can be referenced without the module name
+          Some(spec) => Ok(spec),
+          None => ErlError::local_function_not_found(
+            self.location.clone(),
+            mfa.clone(),
+            format!("Function reference points to a non-existent local function: {}", mfa),
+          ),
+        },
       },
       Apply(apply) => apply.synthesize_application_type(self.location.clone(), module, scope),
       Var(v) => match scope.variables.get(&v.name) {
-        None => {
-          println!("Var not found; Scope={:?}", &scope);
-          ErlError::variable_not_found(self.location.clone(), v.name.clone())
-        }
+        None => ErlError::variable_not_found(self.location.clone(), v.name.clone()),
         Some(val) => Ok(val),
       },
       Lit { value, .. } => Ok(ErlTypeImpl::new_singleton(value)),
       BinaryOp { binop_expr: expr, .. } => {
         expr.synthesize_binop_type(self.location.clone(), module, scope)
       }
+      UnaryOp { unop_expr } => unop_expr.synthesize_unop_type(module, scope),
+      MapBuilder { base, members } => {
+        Self::synthesize_map_builder_type(module, scope, base, members)
+      }
+      MapComprehension { key, value, generators } => {
+        Self::synthesize_map_comprehension_type(module, scope, key, value, generators)
+      }
       List { elements, tail, .. } => Self::synthesize_list_type(module, scope, elements, tail),
       Tuple { elements, .. } => Self::synthesize_tuple_type(module, scope, elements),
-      other => unimplemented!("Don't know how to synthesize type from {:?}", other),
+      CommaExpr { elements, .. } => Self::synthesize_sequence_type(module, scope, elements),
+      BeginEnd { exprs } => Self::synthesize_sequence_type(module, scope, exprs),
+      CaseExpr { expr, clauses } => Self::synthesize_case_type(module, scope, expr, clauses),
+      Receive { clauses, after } => Self::synthesize_receive_type(module, scope, clauses, after),
+      MaybeExpr { exprs, else_clauses } => {
+        Self::synthesize_maybe_type(module, scope, exprs, else_clauses)
+      }
+      MaybeMatch { expr, .. } => expr.synthesize(module, scope),
+      IfStatement { clauses } => Self::synthesize_if_type(module, scope, clauses),
+      TryCatch { body, of_branches, catch_clauses } => {
+        Self::synthesize_try_type(module, scope, body, of_branches, catch_clauses)
+      }
+      ListComprehension { expr, generators } => {
+        let body_scope = Self::bind_generators(module, scope, generators)?;
+        Ok(ErlTypeImpl::list_of(expr.synthesize(module, &body_scope)?, false))
+      }
+      BinaryComprehension { expr, generators } => {
+        let body_scope = Self::bind_generators(module, scope, generators)?;
+        expr.synthesize(module, &body_scope)?;
+        Ok(ErlTypeImpl::any_binary())
+      }
+      // A generator has no value of its own, it only binds variables for the comprehension
+      ListComprehensionGenerator { .. } | MapComprehensionGenerator { .. } => {
+        Ok(ErlTypeImpl::any())
+      }
+      RecordBuilder { base, tag, members } => {
+        Self::synthesize_record_builder_type(module, scope, base, tag, members)
+      }
+      RecordField { base, tag, field } => {
+        Self::synthesize_record_field_type(module, scope, base, tag, field)
+      }
+      BinaryExpr { elements } => Self::synthesize_binary_type(module, scope, elements),
+    }
+  }
+
+  /// Having a `fun/arity` or `mod:fun/arity` AST node, synthesize a function type. Functions of
+  /// modules not loaded, and unknown BIFs, have a function type with any arguments.
+  pub(crate) fn synthesize_mfa_type(
+    module: &ErlModule,
+    location: &SourceLoc,
+    scope: &Scope,
+    mfa: &MFArity,
+  ) -> IcResult<ErlType> {
+    match ErlModuleImpl::find_function_type(module, location, scope, mfa)? {
+      Some(fn_type) => Ok(fn_type),
+      None => Ok(ErlTypeImpl::new_fn_type_of_any_args(mfa.arity, &ErlTypeImpl::any()).into()),
+    }
+  }
+
+  /// Having a sequence of expressions (a comma expression or a `begin ... end` block), synthesize
+  /// the type of the last one. Variables bound by the `Pattern = Expr` matches are visible to the
+  /// following expressions.
+  fn synthesize_sequence_type(
+    module: &ErlModule,
+    scope: &Scope,
+    exprs: &[AstNode],
+  ) -> IcResult<ErlType> {
+    let mut body_scope = scope.clone();
+    let mut last_type = None;
+    for expr in exprs {
+      let expr_type = expr.synthesize(module, &body_scope)?;
+      body_scope = Self::bind_match_vars(module, &body_scope, expr, &expr_type)?;
+      last_type = Some(expr_type);
+    }
+    Ok(last_type.expect("Expression sequence must not be empty"))
+  }
+
+  /// After `Pattern = Expr` succeeded, the variables in the pattern are bound, with the types
+  /// destructured from the matched value. For other expressions the scope is unchanged.
+  pub(crate) fn bind_match_vars(
+    module: &ErlModule,
+    scope: &Scope,
+    expr: &AstNode,
+    expr_type: &ErlType,
+  ) -> IcResult<Scope> {
+    match &expr.content {
+      BinaryOp { binop_expr } if binop_expr.operator == ErlBinaryOp::Match => {
+        // In `A = B = Expr` both patterns are matched against the value
+        let right_scope = Self::bind_match_vars(module, scope, &binop_expr.right, expr_type)?;
+        let destructure = Destructure::new(module, &right_scope, &binop_expr.left, expr_type)?;
+        destructure.bind(&right_scope, "match", &binop_expr.left)
+      }
+      _ => Ok(scope.clone()),
+    }
+  }
+
+  /// Having an `if` AST node, synthesize a union of all clause bodies. Each body sees the
+  /// variables narrowed by its condition.
+  fn synthesize_if_type(
+    module: &ErlModule,
+    scope: &Scope,
+    clauses: &[ErlIfClause],
+  ) -> IcResult<ErlType> {
+    let mut body_types = Vec::with_capacity(clauses.len());
+    for clause in clauses {
+      let body_scope = TypeNarrow::narrow_scope_by_guard(module, scope, &clause.cond)?;
+      body_types.push(clause.body.synthesize(module, &body_scope)?);
+    }
+    Ok(ErlTypeImpl::new_union(&body_types))
+  }
+
+  /// Having a `try` AST node, synthesize a union of the body (or the `of` clauses if present) and
+  /// all `catch` clause bodies.
+  fn synthesize_try_type(
+    module: &ErlModule,
+    scope: &Scope,
+    body: &AstNode,
+    of_branches: &Option<Vec<ErlCaseClause>>,
+    catch_clauses: &[CatchClause],
+  ) -> IcResult<ErlType> {
+    let mut result_types = Vec::with_capacity(catch_clauses.len() + 1);
+    match of_branches {
+      Some(of_clauses) => {
+        result_types.push(Self::synthesize_case_type(module, scope, body, of_clauses)?)
+      }
+      None => result_types.push(body.synthesize(module, scope)?),
+    }
+    for catch_clause in catch_clauses {
+      result_types.push(catch_clause.synthesize_body_type(module, scope)?);
+    }
+    Ok(ErlTypeImpl::new_union(&result_types))
+  }
+
+  /// Create a scope for the comprehension body, with variables bound by the generators. A variable
+  /// on the left of `X <- List` gets the list element type.
+  fn bind_generators(module: &ErlModule, scope: &Scope, generators: &[AstNode]) -> IcResult<Scope> {
+    let mut body_scope = scope.clone();
+    for g in generators {
+      match &g.content {
+        ListComprehensionGenerator { left, right } => {
+          // Elements which do not match the pattern are skipped
+          let element_type = Self::list_element_type(&right.synthesize(module, &body_scope)?);
+          let destructure = Destructure::new(module, &body_scope, left, &element_type)?;
+          body_scope = destructure.bind(&body_scope, "comprehension", left)?;
+        }
+        MapComprehensionGenerator { key, value, right } => {
+          right.synthesize(module, &body_scope)?;
+          body_scope = ScopeImpl::new_with_pattern_vars(&body_scope, "comprehension", key)?;
+          body_scope = ScopeImpl::new_with_pattern_vars(&body_scope, "comprehension", value)?;
+        }
+        _ => {} // a filter condition does not bind new variables
+      }
+    }
+    Ok(body_scope)
+  }
+
+  /// Type of the list elements, or `any()` if the type is not a known list
+  fn list_element_type(list_type: &ErlType) -> ErlType {
+    match list_type.deref() {
+      ErlTypeImpl::List { elements, .. } => elements.clone(),
+      ErlTypeImpl::StronglyTypedList { elements, .. } => ErlTypeImpl::new_union(elements),
+      ErlTypeImpl::Nil => ErlTypeImpl::none(),
+      _ => ErlTypeImpl::any(),
+    }
+  }
+
+  /// Having a record builder `#tag{...}` or a record update `Base#tag{...}` AST node, synthesize
+  /// a record reference, with the fields which were set pinned to their value types.
+  fn synthesize_record_builder_type(
+    module: &ErlModule,
+    scope: &Scope,
+    base: &Option<AstNode>,
+    tag: &str,
+    members: &[RecordBuilderMember],
+  ) -> IcResult<ErlType> {
+    if let Some(b) = base {
+      b.synthesize(module, scope)?;
+    }
+    let mut pins = Vec::with_capacity(members.len());
+    for m in members {
+      pins.push(RecordFieldType::new(m.field.clone(), m.expr.synthesize(module, scope)?));
+    }
+    Ok(ErlTypeImpl::new_record_ref(tag.to_string(), pins))
+  }
+
+  /// Having a record field access `Base#tag.field` AST node, synthesize the field type from the
+  /// record definition. Without a base `#tag.field` is the field index in the record tuple.
+  fn synthesize_record_field_type(
+    module: &ErlModule,
+    scope: &Scope,
+    base: &Option<AstNode>,
+    tag: &str,
+    field: &str,
+  ) -> IcResult<ErlType> {
+    let record_def = module.root_scope.record_defs.get(&tag.to_string());
+    let field_index = record_def
+      .as_ref()
+      .and_then(|rd| rd.fields.iter().position(|f| f.field_tag == field));
+    match base {
+      None => Ok(match field_index {
+        // The record tag is the first element of the tuple
+        Some(index) => {
+          let index_lit = Literal::Integer(ErlInteger::Small(index as i64 + 2));
+          ErlTypeImpl::new_singleton(&Arc::new(index_lit))
+        }
+        None => ErlTypeImpl::new_range(Some(ErlInteger::Small(1)), None),
+      }),
+      Some(b) => {
+        b.synthesize(module, scope)?;
+        let field_type = record_def
+          .zip(field_index)
+          .and_then(|(rd, index)| rd.fields[index].type_ascription.clone());
+        Ok(field_type.unwrap_or_else(ErlTypeImpl::any))
+      }
+    }
+  }
+
+  /// Having a binary `<<...>>` AST node, synthesize a binary type `<<_:M, _:_*N>>`. The head `M`
+  /// sums the bit widths known at compile time, and the unit `N` is the common divisor of the
+  /// units of elements, whose width is only known at runtime.
+  fn synthesize_binary_type(
+    module: &ErlModule,
+    scope: &Scope,
+    elements: &[BinaryElement],
+  ) -> IcResult<ErlType> {
+    let mut head = 0;
+    let mut unit = 0;
+    for el in elements {
+      let value_type = el.value.synthesize(module, scope)?;
+      let (el_head, el_unit) = Self::binary_element_bits(el, &value_type);
+      head += el_head;
+      unit = Self::gcd(unit, el_unit);
+    }
+    Ok(ErlTypeImpl::new_binary(
+      (head > 0).then_some(BinaryTypeHeadElement(head)),
+      (unit > 0).then_some(BinaryTypeTailElement(unit)),
+    ))
+  }
+
+  /// Bit size of a binary element as `(Head, Unit)`, the element takes `Head + k*Unit` bits.
+  /// A string literal element `"abc"` is a sequence of integers, one per character.
+  fn binary_element_bits(el: &BinaryElement, value_type: &ErlType) -> (usize, usize) {
+    let mut value_kind = ValueType::Integer;
+    let mut explicit_unit = None;
+    for spec in el.type_specs.iter() {
+      match spec {
+        TypeSpecifier::Type(t) => value_kind = t.clone(),
+        TypeSpecifier::Unit(u) => explicit_unit = Some(*u),
+        _ => {}
+      }
+    }
+    let repeat = match &el.value.content {
+      Lit { value, .. } => match value.deref() {
+        Literal::String(s) => s.chars().count(),
+        _ => 1,
+      },
+      _ => 1,
+    };
+    let default_unit = if matches!(value_kind, ValueType::Bytes) { 8 } else { 1 };
+    let unit = explicit_unit.unwrap_or(default_unit);
+    let (head, tail) = match (&value_kind, &el.width) {
+      (ValueType::Utf8, _) => (8, 8),
+      (ValueType::Utf16, _) => (16, 16),
+      (ValueType::Utf32, _) => (32, 0),
+      (_, ValueWidth::Literal(w)) => (w * unit, 0),
+      (_, ValueWidth::Expr(_)) => (0, unit),
+      (ValueType::Integer, ValueWidth::DefaultWidth) => (8, 0),
+      (ValueType::Float, ValueWidth::DefaultWidth) => (64, 0),
+      // A binary or bitstring value is taken whole
+      (ValueType::Bytes | ValueType::Bitstring, ValueWidth::DefaultWidth) => {
+        match value_type.deref() {
+          ErlTypeImpl::Binary { head, tail } => SubtypeChecker::binary_sizes(head, tail),
+          _ => (0, unit),
+        }
+      }
+    };
+    (head * repeat, if repeat > 0 { tail } else { 0 })
+  }
+
+  /// Greatest common divisor of two bit units, where 0 stands for no unit
+  fn gcd(a: usize, b: usize) -> usize {
+    if b == 0 {
+      a
+    } else {
+      Self::gcd(b, a % b)
     }
   }
 
@@ -84,4 +405,141 @@ impl AstNodeImpl {
       .collect();
     Ok(ErlTypeImpl::Tuple { elements: elements? }.into())
   }
+
+  /// Having a `case` AST node, synthesize a union of all clause bodies. Each clause sees the
+  /// values of the case expression which were not matched by the previous clauses.
+  fn synthesize_case_type(
+    module: &ErlModule,
+    scope: &Scope,
+    expr: &AstNode,
+    clauses: &[ErlCaseClause],
+  ) -> IcResult<ErlType> {
+    let mut remaining = expr.synthesize(module, scope)?;
+    let mut body_types = Vec::with_capacity(clauses.len());
+    for clause in clauses {
+      body_types.push(clause.synthesize_matched_body_type(module, scope, expr, &remaining)?);
+      if let Some(matched) = clause.synthesize_matched_type(module, scope)? {
+        remaining = remaining.subtract(&matched, module);
+      }
+    }
+    Ok(ErlTypeImpl::new_union(&body_types))
+  }
+
+  /// Having a `receive` AST node, synthesize a union of all clause bodies and the `after` body.
+  fn synthesize_receive_type(
+    module: &ErlModule,
+    scope: &Scope,
+    clauses: &[ErlCaseClause],
+    after: &Option<ErlReceiveAfter>,
+  ) -> IcResult<ErlType> {
+    let mut body_types = Vec::with_capacity(clauses.len() + 1);
+    for clause in clauses {
+      body_types.push(clause.synthesize_body_type(module, scope)?);
+    }
+    if let Some(a) = after {
+      body_types.push(a.body.synthesize(module, scope)?);
+    }
+    Ok(ErlTypeImpl::new_union(&body_types))
+  }
+
+  /// Having a `maybe` AST node, synthesize a union of the last expression and the `else` clause
+  /// bodies. Without `else` the values which failed a conditional match `?=` are returned instead.
+  fn synthesize_maybe_type(
+    module: &ErlModule,
+    scope: &Scope,
+    exprs: &[AstNode],
+    else_clauses: &Option<Vec<ErlCaseClause>>,
+  ) -> IcResult<ErlType> {
+    let mut result_types = Vec::new();
+    let mut body_scope = scope.clone();
+    let mut last_type = ErlTypeImpl::none();
+
+    for expr in exprs {
+      if let MaybeMatch { pattern, expr: match_expr } = &expr.content {
+        last_type = match_expr.synthesize(module, &body_scope)?;
+        if else_clauses.is_none() {
+          result_types.push(Self::maybe_unmatched_type(module, &body_scope, pattern, &last_type)?);
+        }
+        let destructure = Destructure::new(module, &body_scope, pattern, &last_type)?;
+        body_scope = destructure.bind(&body_scope, "maybe block", pattern)?;
+      } else {
+        last_type = expr.synthesize(module, &body_scope)?;
+      }
+    }
+    result_types.push(last_type);
+
+    if let Some(ec) = else_clauses {
+      for clause in ec {
+        result_types.push(clause.synthesize_body_type(module, scope)?);
+      }
+    }
+    Ok(ErlTypeImpl::new_union(&result_types))
+  }
+
+  /// Values of `expr_type` which do not match the `pattern` in `Pattern ?= Expr`, and become the
+  /// result of a `maybe` block without `else`. Only exact patterns are subtracted, like in
+  /// `synthesize_case_type`.
+  fn maybe_unmatched_type(
+    module: &ErlModule,
+    scope: &Scope,
+    pattern: &AstNode,
+    expr_type: &ErlType,
+  ) -> IcResult<ErlType> {
+    if !TypeNarrow::is_exact_pattern(Some(scope), std::slice::from_ref(pattern)) {
+      return Ok(expr_type.clone());
+    }
+    let pattern_scope = ScopeImpl::new_with_pattern_vars(scope, "maybe block", pattern)?;
+    let matched = pattern.synthesize(module, &pattern_scope)?;
+    Ok(expr_type.subtract(&matched, module))
+  }
+
+  /// Having a map builder `#{...}` or a map update `Base#{...}` AST node, synthesize a map type.
+  /// All keys set by the builder are present in the result. If the base is not a known map type,
+  /// other keys are unknown and the result is `map()`.
+  fn synthesize_map_builder_type(
+    module: &ErlModule,
+    scope: &Scope,
+    base: &Option<AstNode>,
+    members: &[MapBuilderMember],
+  ) -> IcResult<ErlType> {
+    let mut result_members = match base {
+      None => Vec::with_capacity(members.len()),
+      Some(b) => match b.synthesize(module, scope)?.deref() {
+        ErlTypeImpl::Map { members: base_members } => base_members.clone(),
+        _ => return Ok(ErlTypeImpl::any_map()),
+      },
+    };
+
+    for m in members {
+      let member_type = MapMemberType::new_mandatory(
+        m.key.synthesize(module, scope)?,
+        m.expr.synthesize(module, scope)?,
+      );
+      match result_members
+        .iter_mut()
+        .find(|rm| rm.key == member_type.key)
+      {
+        Some(existing) => *existing = member_type,
+        None => result_members.push(member_type),
+      }
+    }
+    Ok(ErlTypeImpl::new_map(result_members))
+  }
+
+  /// Having a map comprehension `#{K => V || ...}` AST node, synthesize a map type with one
+  /// optional member, because the generators might produce no values.
+  fn synthesize_map_comprehension_type(
+    module: &ErlModule,
+    scope: &Scope,
+    key: &AstNode,
+    value: &AstNode,
+    generators: &[AstNode],
+  ) -> IcResult<ErlType> {
+    let body_scope = Self::bind_generators(module, scope, generators)?;
+    let member_type = MapMemberType::new_optional(
+      key.synthesize(module, &body_scope)?,
+      value.synthesize(module, &body_scope)?,
+    );
+    Ok(ErlTypeImpl::new_map(vec![member_type]))
+  }
 }
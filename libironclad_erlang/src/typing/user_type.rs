@@ -0,0 +1,84 @@
+//! User-defined types created with `-type`, `-opaque` and `-nominal` attributes
+
+use crate::typing::erl_type::{ErlType, ErlTypeImpl};
+use crate::typing::typevar::Typevar;
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+use std::sync::Arc;
+
+/// How the user type is visible to the code
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum UserTypeKind {
+  /// `-type` is an alias, its structure is visible everywhere
+  Type,
+  /// `-opaque` structure is only visible inside the defining module, and abstract outside it
+  Opaque,
+  /// `-nominal` structure is visible, but different nominal types are never compatible
+  Nominal,
+}
+
+impl UserTypeKind {
+  /// The attribute name which creates this kind of type
+  pub fn attr_name(&self) -> &'static str {
+    match self {
+      UserTypeKind::Type => "type",
+      UserTypeKind::Opaque => "opaque",
+      UserTypeKind::Nominal => "nominal",
+    }
+  }
+}
+
+/// A user-defined type stored in the module root scope
+#[derive(Debug)]
+pub struct UserTypeImpl {
+  /// Which attribute defined the type
+  pub kind: UserTypeKind,
+  /// Names of the type parameters `-type name(Param1, Param2, ...)`
+  pub params: Vec<String>,
+  /// The type definition, where the parameters appear as named type variables
+  pub ty: ErlType,
+}
+
+/// Wraps `UserTypeImpl` with `Arc<>`
+pub type UserType = Arc<UserTypeImpl>;
+
+impl UserTypeImpl {
+  /// Create a new user type definition
+  pub fn new(kind: UserTypeKind, params: Vec<String>, ty: ErlType) -> UserType {
+    UserTypeImpl { kind, params, ty }.into()
+  }
+
+  /// Expand the type definition for `name(Arg1, Arg2, ...)`, with the arguments substituted for
+  /// the parameters. The argument count must match the parameter count.
+  pub fn instantiate(&self, args: &[Typevar]) -> ErlType {
+    let bindings: HashMap<String, ErlType> = self
+      .params
+      .iter()
+      .zip(args.iter())
+      .map(|(param, arg)| {
+        let arg_type = match arg.name {
+          Some(_) => ErlTypeImpl::new_typevar(arg.clone()),
+          None => arg.ty.clone(),
+        };
+        (param.clone(), arg_type)
+      })
+      .collect();
+    self.ty.substitute(&bindings)
+  }
+
+  /// Whether the type structure is hidden outside of the defining module
+  pub fn is_opaque(&self) -> bool {
+    self.kind == UserTypeKind::Opaque
+  }
+
+  /// Whether the type is only compatible with itself by name
+  pub fn is_nominal(&self) -> bool {
+    self.kind == UserTypeKind::Nominal
+  }
+}
+
+impl Display for UserTypeImpl {
+  fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+    write!(f, "-{} {}", self.kind.attr_name(), self.ty)
+  }
+}
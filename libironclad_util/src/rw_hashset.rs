@@ -65,4 +65,13 @@ impl<ValType: Hash + Eq> RwHashSet<ValType> {
       panic!("Can't lock RwHashSet to insert a new one")
     }
   }
+
+  /// Removes an item from a set
+  pub fn remove(&self, item: &ValType) {
+    if let Ok(mut w_collection) = self.collection.write() {
+      w_collection.remove(item);
+    } else {
+      panic!("Can't lock RwHashSet to remove an item")
+    }
+  }
 }
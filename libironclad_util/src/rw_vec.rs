@@ -80,6 +80,15 @@ impl<ValType> RwVec<ValType> {
     }
   }
 
+  /// Remove the last item and return it
+  pub fn pop(&self) -> Option<ValType> {
+    if let Ok(mut w_data) = self.data.write() {
+      w_data.pop()
+    } else {
+      panic!("Can't lock RwVec to pop")
+    }
+  }
+
   /// Replace the contents
   pub fn replace<Iter>(&self, itr: Iter)
   where
@@ -9,7 +9,8 @@ pub struct SourceFileImpl {
   pub file_name: PathBuf,
   /// Contents of the file
   pub text: Arc<String>,
-  // line_numbers: Option<...>
+  /// Byte offsets where each line begins, the first line starts at 0
+  line_starts: Vec<usize>,
 }
 
 /// Shareable readonly source file pointer
@@ -20,6 +21,7 @@ impl SourceFileImpl {
   pub fn new(file_name: &Path, text: String) -> SourceFile {
     SourceFileImpl {
       file_name: file_name.to_path_buf(),
+      line_starts: Self::build_line_starts(&text),
       text: text.into(),
     }
     .into()
@@ -29,8 +31,40 @@ impl SourceFileImpl {
   pub fn no_file(text: &str) -> SourceFile {
     SourceFileImpl {
       file_name: PathBuf::new(),
+      line_starts: Self::build_line_starts(text),
       text: text.to_string().into(),
     }
     .into()
   }
+
+  fn build_line_starts(text: &str) -> Vec<usize> {
+    std::iter::once(0)
+      .chain(text.match_indices('\n').map(|(pos, _)| pos + 1))
+      .collect()
+  }
+
+  /// If the pointer points into this file's text, return its byte offset from the text start
+  pub fn offset_of(&self, ptr: *const u8) -> Option<usize> {
+    let start = self.text.as_ptr() as usize;
+    let ptr = ptr as usize;
+    if ptr >= start && ptr <= start + self.text.len() {
+      Some(ptr - start)
+    } else {
+      None
+    }
+  }
+
+  /// Convert a byte offset into 1-based line and column numbers. Column counts characters.
+  pub fn line_col(&self, offset: usize) -> (usize, usize) {
+    let line_index = match self.line_starts.binary_search(&offset) {
+      Ok(exact) => exact,
+      Err(next) => next.saturating_sub(1),
+    };
+    let line_start = self.line_starts.get(line_index).copied().unwrap_or(0);
+    let col = match self.text.get(line_start..offset) {
+      Some(prefix) => prefix.chars().count(),
+      None => offset - line_start,
+    };
+    (line_index + 1, col + 1)
+  }
 }